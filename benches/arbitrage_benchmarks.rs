@@ -0,0 +1,274 @@
+// benches/arbitrage_benchmarks.rs
+//
+// Hot-path benchmarks: pending-tx decoding, executor calldata encoding, local
+// AMM math, cycle search over a synthetic pool graph and revm execution on a
+// warmed fork.
+//
+// The revm group needs BENCH_WS_URL pointing at a Polygon node and is skipped
+// without it. Run with: cargo bench --bench arbitrage_benchmarks
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ethers::abi::Token;
+use ethers::prelude::*;
+use ethers::utils::id;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use polygon_mev_bot::calldata_builder::{self, CalldataEncoding, FlashEntry, Hop, RouterKind};
+use polygon_mev_bot::confidence::ConfidenceModel;
+use polygon_mev_bot::fee::FeeAmount;
+use polygon_mev_bot::flash_loan::LoanLeg;
+use polygon_mev_bot::path_constraints::PathConstraints;
+use polygon_mev_bot::pool_state::{PoolState, PoolStateManager, QUICKSWAP_ROUTER};
+use polygon_mev_bot::quickswap::{parse_quickswap_tx, QUICKSWAP_ROUTER_ABI, QUICKSWAP_ROUTER_ADDR, USDC_E, WMATIC};
+use polygon_mev_bot::retry::RetryPolicy;
+use polygon_mev_bot::rpc_budget::{self, RpcBudget};
+use polygon_mev_bot::simulation_engine::AdvancedSimulationEngine;
+use polygon_mev_bot::state_override::StateOverrides;
+use polygon_mev_bot::strategies::jit_liquidity::{parse_v3_exact_input_single, UNISWAP_V3_ROUTER_02, V3_ROUTER_SWAP_ABI};
+use polygon_mev_bot::strategies::{CyclePool, CycleSearch};
+use polygon_mev_bot::v3_ticks::{sqrt_ratio_at_tick, TickInfo, V3PoolState};
+
+// Synthetic graph: tokens, pools per token pair with the base, extra cross pairs
+const GRAPH_TOKENS: u64 = 40;
+const DEXES: u64 = 2;
+const CROSS_PAIRS: u64 = 200;
+
+fn token(n: u64) -> Address {
+    Address::from_low_u64_be(0x1000 + n)
+}
+
+fn ether(n: u64) -> U256 {
+    U256::from(n) * U256::exp10(18)
+}
+
+// Deterministic spread so every run benchmarks the same graph
+fn jitter(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407) >> 54
+}
+
+fn v2_pool(n: u64, token0: Address, token1: Address, reserve0: U256, reserve1: U256) -> PoolState {
+    PoolState {
+        address: Address::from_low_u64_be(0x10_0000 + n),
+        router: Address::from_low_u64_be(0x20_0000 + n % DEXES),
+        token0,
+        token1,
+        reserve0,
+        reserve1,
+        fee: FeeAmount::MEDIUM,
+        last_updated_block: U64::zero(),
+    }
+}
+
+fn pool_graph() -> Vec<CyclePool> {
+    let mut pools = Vec::new();
+    let mut n = 0;
+    for t in 1..GRAPH_TOKENS {
+        for _ in 0..DEXES {
+            let reserve = ether(10_000 + jitter(n));
+            pools.push(CyclePool::V2(v2_pool(n, token(0), token(t), reserve, ether(10_000))));
+            n += 1;
+        }
+    }
+    for i in 0..CROSS_PAIRS {
+        let a = 1 + jitter(i) % (GRAPH_TOKENS - 1);
+        let b = 1 + jitter(i + 7_919) % (GRAPH_TOKENS - 1);
+        if a == b {
+            continue;
+        }
+        pools.push(CyclePool::V2(v2_pool(n, token(a), token(b), ether(5_000 + jitter(n)), ether(5_000))));
+        n += 1;
+    }
+    pools
+}
+
+fn v3_pool() -> V3PoolState {
+    // Liquidity added and removed every 60 ticks around the price
+    let mut ticks = BTreeMap::new();
+    for i in 1..=50 {
+        let net = 1_000_000_000_000_000_000i128;
+        ticks.insert(-60 * i, TickInfo { liquidity_gross: net as u128, liquidity_net: net });
+        ticks.insert(60 * i, TickInfo { liquidity_gross: net as u128, liquidity_net: -net });
+    }
+    V3PoolState {
+        address: Address::from_low_u64_be(0x30_0000),
+        token0: token(0),
+        token1: token(1),
+        fee: FeeAmount::MEDIUM,
+        tick_spacing: 60,
+        sqrt_price_x96: sqrt_ratio_at_tick(0),
+        tick: 0,
+        liquidity: 50_000_000_000_000_000_000,
+        ticks,
+        range_lower: -3_000,
+        range_upper: 3_000,
+        last_updated_block: U64::zero(),
+    }
+}
+
+fn decoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    let path: Vec<Token> = [WMATIC, USDC_E, WMATIC].iter().map(|a| Token::Address(a.parse().unwrap())).collect();
+    let swap = QUICKSWAP_ROUTER_ABI
+        .function("swapExactTokensForTokens")
+        .unwrap()
+        .encode_input(&[Token::Uint(ether(100)), Token::Uint(ether(99)), Token::Array(path), Token::Address(token(9)), Token::Uint(U256::MAX)])
+        .unwrap();
+    let quickswap_tx = Transaction { to: Some(*QUICKSWAP_ROUTER_ADDR), input: swap.into(), ..Default::default() };
+    group.bench_function("quickswap_swap", |b| b.iter(|| parse_quickswap_tx(black_box(&quickswap_tx))));
+
+    // exactInputSingle behind a selfPermit, as SwapRouter02 batches them
+    let params = vec![
+        Token::Address(token(0)),
+        Token::Address(token(1)),
+        Token::Uint(500.into()),
+        Token::Address(token(9)),
+        Token::Uint(ether(100)),
+        Token::Uint(U256::zero()),
+        Token::Uint(U256::zero()),
+    ];
+    let function = V3_ROUTER_SWAP_ABI
+        .functions_by_name("exactInputSingle")
+        .unwrap()
+        .iter()
+        .find(|f| matches!(&f.inputs[0].kind, ethers::abi::ParamType::Tuple(t) if t.len() == params.len()))
+        .unwrap();
+    let leg = function.encode_input(&[Token::Tuple(params)]).unwrap();
+    let permit = [id("selfPermit(address,uint256,uint256,uint8,bytes32,bytes32)").to_vec(), vec![0u8; 192]].concat();
+    let multicall = [
+        id("multicall(bytes[])").to_vec(),
+        ethers::abi::encode(&[Token::Array(vec![Token::Bytes(permit), Token::Bytes(leg)])]),
+    ]
+    .concat();
+    let v3_tx = Transaction { to: Some(UNISWAP_V3_ROUTER_02.parse().unwrap()), input: multicall.into(), ..Default::default() };
+    group.bench_function("v3_multicall_exact_input_single", |b| b.iter(|| parse_v3_exact_input_single(black_box(&v3_tx))));
+
+    let hops: Vec<Hop> = (0..3)
+        .map(|i| Hop {
+            kind: RouterKind::UniswapV2,
+            router: token(100 + i),
+            pool: token(200 + i),
+            token_in: token(i),
+            token_out: token((i + 1) % 3),
+            fee: 3000,
+            amount_in: ether(100),
+            min_out: U256::zero(),
+        })
+        .collect();
+    let entry = FlashEntry::Balancer { loans: vec![LoanLeg::new(token(0), ether(100))] };
+    let execution = calldata_builder::encode(&entry, &hops).unwrap();
+    group.bench_function("executor_calldata", |b| b.iter(|| calldata_builder::decode(black_box(&execution))));
+
+    group.finish();
+}
+
+// Encoding time for both layouts; the gas each costs is printed once, since
+// that (not the microseconds) is what the packed layout is for
+fn calldata_encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calldata_encoding");
+    let entry = FlashEntry::Balancer { loans: vec![LoanLeg::new(token(0), ether(100))] };
+    for hop_count in [2u64, 3, 5] {
+        let hops: Vec<Hop> = (0..hop_count)
+            .map(|i| Hop {
+                kind: RouterKind::UniswapV2,
+                router: token(100 + i),
+                pool: token(200 + i),
+                token_in: token(i),
+                token_out: token((i + 1) % hop_count),
+                fee: 3000,
+                amount_in: ether(100),
+                min_out: U256::zero(),
+            })
+            .collect();
+        let abi_gas = calldata_builder::calldata_gas(&calldata_builder::encode(&entry, &hops).unwrap());
+        let packed_gas = calldata_builder::calldata_gas(&calldata_builder::encode_packed(&entry, &hops).unwrap());
+        eprintln!("{} hops: ABI calldata {} gas, packed {} gas ({} saved)", hop_count, abi_gas, packed_gas, abi_gas - packed_gas);
+        for encoding in [CalldataEncoding::Abi, CalldataEncoding::Packed] {
+            group.bench_function(format!("{:?}_{}_hops", encoding, hop_count).to_lowercase(), |b| {
+                b.iter(|| calldata_builder::encode_with(encoding, black_box(&entry), black_box(&hops)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn amm_math(c: &mut Criterion) {
+    let mut group = c.benchmark_group("amm");
+
+    let pair = v2_pool(0, token(0), token(1), ether(1_000_000), ether(2_000_000));
+    group.bench_function("v2_amount_out", |b| b.iter(|| pair.get_amount_out(black_box(token(0)), black_box(ether(1_000)))));
+
+    let pool = v3_pool();
+    group.bench_function("v3_amount_out_in_range", |b| b.iter(|| pool.amount_out(black_box(token(0)), black_box(ether(10)))));
+    // Large enough to cross a few dozen initialized ticks
+    group.bench_function("v3_amount_out_crossing_ticks", |b| b.iter(|| pool.amount_out(black_box(token(0)), black_box(ether(500)))));
+
+    group.finish();
+}
+
+fn cycle_search(c: &mut Criterion) {
+    let pools = pool_graph();
+    let mut group = c.benchmark_group("cycle_search");
+    for max_hops in [2, 3] {
+        let search = CycleSearch {
+            base_token: token(0),
+            top_n: 5,
+            max_input: ether(10_000),
+            min_profit: U256::exp10(15),
+            max_hops,
+        };
+        group.bench_function(format!("{}_pools_{}_hops", pools.len(), max_hops), |b| {
+            b.iter(|| search.run(black_box(&pools), U64::zero()))
+        });
+    }
+    group.finish();
+}
+
+fn revm_simulation(c: &mut Criterion) {
+    let url = match std::env::var("BENCH_WS_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("BENCH_WS_URL not set, skipping revm benchmarks");
+            return;
+        }
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+    let provider = Arc::new(runtime.block_on(rpc_budget::connect(&url, RpcBudget::default(), RetryPolicy::default())).unwrap());
+    let pool_state = Arc::new(PoolStateManager::new(provider.clone(), Vec::new()));
+    let engine = AdvancedSimulationEngine::new(provider, pool_state, Arc::new(ConfidenceModel::default()), PathConstraints::default());
+
+    // A WMATIC -> USDC.e swap on QuickSwap from an account funded by overrides
+    let trader = Address::from_low_u64_be(0xbeef);
+    let (wmatic, usdc): (Address, Address) = (WMATIC.parse().unwrap(), USDC_E.parse().unwrap());
+    let router: Address = QUICKSWAP_ROUTER.parse().unwrap();
+    let overrides = StateOverrides::new()
+        .with_balance(wmatic, trader, ether(1_000))
+        .with_allowance(wmatic, trader, router, U256::MAX);
+    let calldata: Bytes = QUICKSWAP_ROUTER_ABI
+        .function("swapExactTokensForTokens")
+        .unwrap()
+        .encode_input(&[
+            Token::Uint(ether(100)),
+            Token::Uint(U256::zero()),
+            Token::Array(vec![Token::Address(wmatic), Token::Address(usdc)]),
+            Token::Address(trader),
+            Token::Uint(U256::MAX),
+        ])
+        .unwrap()
+        .into();
+    let simulate = || engine.call_with_overrides(trader, router, calldata.clone(), U256::zero(), &overrides);
+
+    // Pulls every account and slot the swap touches into the shared fork, and
+    // finds WMATIC's balance and allowance slots
+    let warm = runtime.block_on(simulate()).unwrap();
+    assert!(warm.success, "warm-up swap reverted");
+
+    let mut group = c.benchmark_group("revm");
+    group.bench_function("quickswap_swap_warm_fork", |b| {
+        b.to_async(&runtime).iter(&simulate)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decoding, calldata_encoding, amm_math, cycle_search, revm_simulation);
+criterion_main!(benches);
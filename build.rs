@@ -0,0 +1,6 @@
+// build.rs
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/opportunities.proto");
+    tonic_build::compile_protos("proto/opportunities.proto")?;
+    Ok(())
+}
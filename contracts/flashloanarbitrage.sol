@@ -12,6 +12,52 @@ interface IFastLaneSender {
     function sendTransaction(bytes calldata data, uint256 targetBlock) external payable returns (bytes32);
 }
 
+interface IAavePool {
+    function flashLoan(
+        address receiverAddress,
+        address[] calldata assets,
+        uint256[] calldata amounts,
+        uint256[] calldata interestRateModes,
+        address onBehalfOf,
+        bytes calldata params,
+        uint16 referralCode
+    ) external;
+}
+
+interface IUniswapV2Router {
+    function swapExactTokensForTokens(
+        uint256 amountIn,
+        uint256 amountOutMin,
+        address[] calldata path,
+        address to,
+        uint256 deadline
+    ) external returns (uint256[] memory amounts);
+}
+
+interface ICurvePool {
+    function exchange(int128 i, int128 j, uint256 dx, uint256 minDy) external returns (uint256);
+    function exchange_underlying(int128 i, int128 j, uint256 dx, uint256 minDy) external returns (uint256);
+}
+
+// MaticX's ChildPool: mints MaticX for MATIC at the staking exchange rate
+interface IInstantStakePool {
+    function swapMaticForMaticXViaInstantPool() external payable;
+}
+
+interface IWETH {
+    function deposit() external payable;
+    function withdraw(uint256 amount) external;
+}
+
+interface IBalancerVault {
+    function flashLoan(
+        address recipient,
+        address[] memory tokens,
+        uint256[] memory amounts,
+        bytes memory userData
+    ) external;
+}
+
 contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
     ISwapRouter public immutable swapRouter;
     address public immutable WETH;
@@ -19,6 +65,15 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
     address public fastLaneSender;
     uint256 public maxDelayBlocks = 5;
     uint24 public constant DEFAULT_FEE = 3000;
+    address public constant BALANCER_VAULT = 0xBA12222222228d8Ba445958a75a0704d566BF2C8;
+    address public constant AAVE_POOL = 0x794a61358D6845594F94dc1DB02A252b5b4814aD;
+
+    // Per-hop execution kinds, matching the bot's RouterKind
+    uint8 public constant HOP_V2 = 0;
+    uint8 public constant HOP_V3 = 1;
+    uint8 public constant HOP_WRAP = 2;
+    uint8 public constant HOP_CURVE = 3;
+    uint8 public constant HOP_STAKE = 4;
 
     struct FlashCallbackData {
         address token0;
@@ -29,6 +84,17 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         address[] path;
         uint256[] amounts;
         address[] routers;
+        uint8[] kinds;
+        uint24[] fees;
+    }
+
+    // Shared by the Balancer and Aave multi-asset callbacks
+    struct LoanCallbackData {
+        address[] path;
+        uint256[] amounts;
+        address[] routers;
+        uint8[] kinds;
+        uint24[] fees;
     }
 
     struct ArbitrageOpportunity {
@@ -40,6 +106,14 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         address[] path;
         uint256[] amounts;
         address[] routers;
+        uint8[] kinds;
+        uint24[] fees;
+    }
+
+    // Most a JIT mint may pull from the contract's balance
+    struct MintCallbackData {
+        uint256 amount0Max;
+        uint256 amount1Max;
     }
 
     struct FastLaneBundle {
@@ -69,6 +143,16 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         string reason
     );
 
+    event BatchLegFailed(
+        uint256 index,
+        bytes reason
+    );
+
+    // executeGuarded's bounds, decoded by the bot's failure taxonomy
+    error ProfitTooLow(uint256 realized, uint256 minProfit);
+    error Expired(uint256 deadlineBlock, uint256 blockNumber);
+    error BatchFailed(uint256 legs);
+
     constructor(
         address _swapRouter,
         address _weth,
@@ -82,6 +166,13 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         factory = _factory;
     }
 
+    // The flash entrypoints are reached through executeGuarded's self-call, and
+    // executeGuarded through executeBatch's
+    modifier onlyOwnerOrSelf() {
+        require(msg.sender == owner() || msg.sender == address(this), "Not owner");
+        _;
+    }
+
     function setFastLaneSender(address _fastLaneSender) external onlyOwner {
         require(_fastLaneSender != address(0), "Invalid FastLane sender");
         fastLaneSender = _fastLaneSender;
@@ -100,26 +191,11 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         uint24 fee,
         address[] calldata path,
         uint256[] calldata amounts,
-        address[] calldata routers
-    ) external onlyOwner {
-        _executeFlashLoanArbitrage(token0, token1, amount0, amount1, fee, path, amounts, routers);
-    }
-
-    function _executeFlashLoanArbitrage(
-        address token0,
-        address token1,
-        uint256 amount0,
-        uint256 amount1,
-        uint24 fee,
-        address[] memory path,
-        uint256[] memory amounts,
-        address[] memory routers
-    ) internal {
-        PoolAddress.PoolKey memory poolKey = PoolAddress.getPoolKey(token0, token1, fee);
-        address poolAddress = PoolAddress.computeAddress(factory, poolKey);
-        IUniswapV3Pool pool = IUniswapV3Pool(poolAddress);
-
-        bytes memory data = abi.encode(
+        address[] calldata routers,
+        uint8[] calldata kinds,
+        uint24[] calldata fees
+    ) external onlyOwnerOrSelf {
+        _executeFlashLoanArbitrage(
             FlashCallbackData({
                 token0: token0,
                 token1: token1,
@@ -128,11 +204,63 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
                 fee: fee,
                 path: path,
                 amounts: amounts,
-                routers: routers
+                routers: routers,
+                kinds: kinds,
+                fees: fees
             })
         );
+    }
 
-        pool.flash(address(this), amount0, amount1, data);
+    // Every execution enters here. `execution` is the calldata of one of the
+    // flash entrypoints; it only runs up to `deadlineBlock` and must leave the
+    // owner at least `minProfit` of `profitToken` richer. In the public mempool
+    // a copied or front-run tx then reverts for the revert gas instead of
+    // trading at a worse price. Commit-reveal would hide the route entirely but
+    // costs a second tx and a block of delay, which a backrun can't afford.
+    function executeGuarded(
+        bytes calldata execution,
+        address profitToken,
+        uint256 minProfit,
+        uint256 deadlineBlock
+    ) external onlyOwnerOrSelf {
+        if (block.number > deadlineBlock) revert Expired(deadlineBlock, block.number);
+        uint256 balanceBefore = IERC20(profitToken).balanceOf(owner());
+
+        (bool success, bytes memory result) = address(this).call(execution);
+        if (!success) {
+            assembly {
+                revert(add(result, 32), mload(result))
+            }
+        }
+
+        uint256 balanceAfter = IERC20(profitToken).balanceOf(owner());
+        uint256 realized = balanceAfter > balanceBefore ? balanceAfter - balanceBefore : 0;
+        if (realized < minProfit) revert ProfitTooLow(realized, minProfit);
+    }
+
+    // Several independent arbitrages in one tx, each an executeGuarded call.
+    // The bot only packs legs that touch disjoint pools, so one leg reverting
+    // (front-run, price moved) doesn't invalidate the others: it is skipped and
+    // logged, and the tx only reverts when no leg succeeds.
+    function executeBatch(bytes[] calldata executions) external onlyOwner {
+        uint256 succeeded;
+        for (uint256 i = 0; i < executions.length; i++) {
+            (bool success, bytes memory result) = address(this).call(executions[i]);
+            if (success) {
+                succeeded++;
+            } else {
+                emit BatchLegFailed(i, result);
+            }
+        }
+        if (succeeded == 0) revert BatchFailed(executions.length);
+    }
+
+    function _executeFlashLoanArbitrage(FlashCallbackData memory loan) internal {
+        PoolAddress.PoolKey memory poolKey = PoolAddress.getPoolKey(loan.token0, loan.token1, loan.fee);
+        address poolAddress = PoolAddress.computeAddress(factory, poolKey);
+        IUniswapV3Pool pool = IUniswapV3Pool(poolAddress);
+
+        pool.flash(address(this), loan.amount0, loan.amount1, abi.encode(loan));
     }
 
     function uniswapV3FlashCallback(
@@ -153,7 +281,7 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         uint256 startBalance0 = IERC20(decoded.token0).balanceOf(address(this));
         uint256 startBalance1 = IERC20(decoded.token1).balanceOf(address(this));
 
-        try this.executeArbitrageInternal(decoded.path, decoded.amounts, decoded.routers) {
+        try this.executeArbitrageInternal(decoded.path, decoded.amounts, decoded.routers, decoded.kinds, decoded.fees) {
             // Success - continue with repayment
         } catch Error(string memory reason) {
             emit FlashLoanFailed(msg.sender, decoded.amount0, decoded.amount1, reason);
@@ -192,23 +320,258 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         );
     }
 
+    // Zero-fee alternative to the V3 pool flash when the Vault holds enough of the token
+    function executeBalancerFlashLoan(
+        address[] calldata tokens,
+        uint256[] calldata loanAmounts,
+        address[] calldata path,
+        uint256[] calldata amounts,
+        address[] calldata routers,
+        uint8[] calldata kinds,
+        uint24[] calldata fees
+    ) external onlyOwnerOrSelf {
+        _executeBalancerFlashLoan(
+            tokens,
+            loanAmounts,
+            LoanCallbackData({
+                path: path,
+                amounts: amounts,
+                routers: routers,
+                kinds: kinds,
+                fees: fees
+            })
+        );
+    }
+
+    function _executeBalancerFlashLoan(
+        address[] memory tokens,
+        uint256[] memory loanAmounts,
+        LoanCallbackData memory route
+    ) internal {
+        require(tokens.length == loanAmounts.length && tokens.length > 0, "Invalid loan");
+        IBalancerVault(BALANCER_VAULT).flashLoan(address(this), tokens, loanAmounts, abi.encode(route));
+    }
+
+    function receiveFlashLoan(
+        address[] memory tokens,
+        uint256[] memory loanAmounts,
+        uint256[] memory feeAmounts,
+        bytes memory userData
+    ) external {
+        require(msg.sender == BALANCER_VAULT, "Callback not from Balancer vault");
+        LoanCallbackData memory decoded = abi.decode(userData, (LoanCallbackData));
+
+        try this.executeArbitrageInternal(decoded.path, decoded.amounts, decoded.routers, decoded.kinds, decoded.fees) {
+            // Success - continue with repayment
+        } catch Error(string memory reason) {
+            emit FlashLoanFailed(msg.sender, loanAmounts[0], 0, reason);
+            revert(reason);
+        }
+
+        for (uint256 i = 0; i < tokens.length; i++) {
+            uint256 owed = loanAmounts[i] + feeAmounts[i];
+            uint256 balance = IERC20(tokens[i]).balanceOf(address(this));
+            require(balance >= owed, "Insufficient balance to repay");
+
+            // The Vault checks its balance after the callback, so repay by transfer
+            IERC20(tokens[i]).transfer(BALANCER_VAULT, owed);
+            if (balance > owed) {
+                IERC20(tokens[i]).transfer(owner(), balance - owed);
+            }
+        }
+    }
+
+    // Multi-asset loan for leg sets the Balancer Vault can't cover
+    function executeAaveFlashLoan(
+        address[] calldata assets,
+        uint256[] calldata loanAmounts,
+        address[] calldata path,
+        uint256[] calldata amounts,
+        address[] calldata routers,
+        uint8[] calldata kinds,
+        uint24[] calldata fees
+    ) external onlyOwnerOrSelf {
+        _executeAaveFlashLoan(
+            assets,
+            loanAmounts,
+            LoanCallbackData({
+                path: path,
+                amounts: amounts,
+                routers: routers,
+                kinds: kinds,
+                fees: fees
+            })
+        );
+    }
+
+    function _executeAaveFlashLoan(
+        address[] memory assets,
+        uint256[] memory loanAmounts,
+        LoanCallbackData memory route
+    ) internal {
+        require(assets.length == loanAmounts.length && assets.length > 0, "Invalid loan");
+        // Mode 0 for every asset: repay within the transaction, open no debt
+        uint256[] memory modes = new uint256[](assets.length);
+        IAavePool(AAVE_POOL).flashLoan(address(this), assets, loanAmounts, modes, address(this), abi.encode(route), 0);
+    }
+
+    function executeOperation(
+        address[] calldata assets,
+        uint256[] calldata loanAmounts,
+        uint256[] calldata premiums,
+        address initiator,
+        bytes calldata params
+    ) external returns (bool) {
+        require(msg.sender == AAVE_POOL, "Callback not from Aave pool");
+        require(initiator == address(this), "Unexpected initiator");
+        LoanCallbackData memory decoded = abi.decode(params, (LoanCallbackData));
+
+        try this.executeArbitrageInternal(decoded.path, decoded.amounts, decoded.routers, decoded.kinds, decoded.fees) {
+            // Success - continue with repayment
+        } catch Error(string memory reason) {
+            emit FlashLoanFailed(msg.sender, loanAmounts[0], 0, reason);
+            revert(reason);
+        }
+
+        for (uint256 i = 0; i < assets.length; i++) {
+            uint256 owed = loanAmounts[i] + premiums[i];
+            uint256 balance = IERC20(assets[i]).balanceOf(address(this));
+            require(balance >= owed, "Insufficient balance to repay");
+
+            // The pool pulls repayment after the callback returns
+            IERC20(assets[i]).approve(AAVE_POOL, owed);
+            if (balance > owed) {
+                IERC20(assets[i]).transfer(owner(), balance - owed);
+            }
+        }
+        return true;
+    }
+
+    // No loan: the route spends the contract's own balance of path[0]. Skips
+    // the lender's callback round trip, so it costs about half the gas. The
+    // balance must come back whole; anything above it goes to the owner, where
+    // executeGuarded measures profit.
+    function executeFromInventory(
+        address[] calldata path,
+        uint256[] calldata amounts,
+        address[] calldata routers,
+        uint8[] calldata kinds,
+        uint24[] calldata fees
+    ) external onlyOwnerOrSelf {
+        _executeFromInventory(
+            LoanCallbackData({
+                path: path,
+                amounts: amounts,
+                routers: routers,
+                kinds: kinds,
+                fees: fees
+            })
+        );
+    }
+
+    function _executeFromInventory(LoanCallbackData memory route) internal {
+        IERC20 token = IERC20(route.path[0]);
+        uint256 startBalance = token.balanceOf(address(this));
+        _executeArbitrage(route.path, route.amounts, route.routers, route.kinds, route.fees);
+        uint256 finalBalance = token.balanceOf(address(this));
+        require(finalBalance >= startBalance, "Inventory not restored");
+        if (finalBalance > startBalance) {
+            token.transfer(owner(), finalBalance - startBalance);
+        }
+    }
+
+    // Any of the entrypoints above in a compact layout, big-endian, no padding:
+    //   entry (1): 0 V3 flash, 1 Balancer, 2 Aave, 3 inventory
+    //   V3 flash: token0 (20) token1 (20) amount0 (16) amount1 (16) fee (3)
+    //   Balancer, Aave: legs (1), then per leg token (20) amount (16)
+    //   route: hops (1) path[0] (20), then per hop tokenOut (20) router (20)
+    //          kind (1) fee (3) amountIn (16)
+    // A hop costs 60 bytes instead of the ABI's 160, most of it zero padding,
+    // which is where calldata gas goes.
+    function executePacked(bytes calldata packed) external onlyOwnerOrSelf {
+        uint8 entry = uint8(packed[0]);
+        if (entry == 0) {
+            LoanCallbackData memory route = _unpackRoute(packed, 76);
+            _executeFlashLoanArbitrage(
+                FlashCallbackData({
+                    token0: address(bytes20(packed[1:21])),
+                    token1: address(bytes20(packed[21:41])),
+                    amount0: uint128(bytes16(packed[41:57])),
+                    amount1: uint128(bytes16(packed[57:73])),
+                    fee: uint24(bytes3(packed[73:76])),
+                    path: route.path,
+                    amounts: route.amounts,
+                    routers: route.routers,
+                    kinds: route.kinds,
+                    fees: route.fees
+                })
+            );
+        } else if (entry == 1 || entry == 2) {
+            uint256 legs = uint8(packed[1]);
+            address[] memory tokens = new address[](legs);
+            uint256[] memory loanAmounts = new uint256[](legs);
+            uint256 offset = 2;
+            for (uint256 i = 0; i < legs; i++) {
+                tokens[i] = address(bytes20(packed[offset:offset + 20]));
+                loanAmounts[i] = uint128(bytes16(packed[offset + 20:offset + 36]));
+                offset += 36;
+            }
+            if (entry == 1) {
+                _executeBalancerFlashLoan(tokens, loanAmounts, _unpackRoute(packed, offset));
+            } else {
+                _executeAaveFlashLoan(tokens, loanAmounts, _unpackRoute(packed, offset));
+            }
+        } else if (entry == 3) {
+            _executeFromInventory(_unpackRoute(packed, 1));
+        } else {
+            revert("Unknown packed entry");
+        }
+    }
+
+    function _unpackRoute(bytes calldata packed, uint256 offset) internal pure returns (LoanCallbackData memory route) {
+        uint256 hops = uint8(packed[offset]);
+        require(packed.length == offset + 21 + hops * 60, "Invalid packed route");
+        route.path = new address[](hops + 1);
+        route.amounts = new uint256[](hops);
+        route.routers = new address[](hops);
+        route.kinds = new uint8[](hops);
+        route.fees = new uint24[](hops);
+        route.path[0] = address(bytes20(packed[offset + 1:offset + 21]));
+        offset += 21;
+        for (uint256 i = 0; i < hops; i++) {
+            route.path[i + 1] = address(bytes20(packed[offset:offset + 20]));
+            route.routers[i] = address(bytes20(packed[offset + 20:offset + 40]));
+            route.kinds[i] = uint8(packed[offset + 40]);
+            route.fees[i] = uint24(bytes3(packed[offset + 41:offset + 44]));
+            route.amounts[i] = uint128(bytes16(packed[offset + 44:offset + 60]));
+            offset += 60;
+        }
+    }
     function executeArbitrageInternal(
         address[] memory path,
         uint256[] memory amounts,
-        address[] memory routers
+        address[] memory routers,
+        uint8[] memory kinds,
+        uint24[] memory fees
     ) external {
         require(msg.sender == address(this), "Only self-call");
-        _executeArbitrage(path, amounts, routers);
+        _executeArbitrage(path, amounts, routers, kinds, fees);
     }
 
+    // One route may mix V2-style pools, V3 pools of any fee tier, Curve pools,
+    // liquid staking mints and WMATIC wrap/unwrap hops; kinds[i] and fees[i]
+    // say how hop i runs
     function _executeArbitrage(
         address[] memory path,
         uint256[] memory amounts,
-        address[] memory routers
+        address[] memory routers,
+        uint8[] memory kinds,
+        uint24[] memory fees
     ) internal {
         require(path.length >= 2, "Invalid path");
         require(path.length == amounts.length + 1, "Invalid amounts");
         require(path.length == routers.length + 1, "Invalid routers");
+        require(path.length == kinds.length + 1 && path.length == fees.length + 1, "Invalid hop kinds");
 
         for (uint256 i = 0; i < path.length - 1; i++) {
             address tokenIn = path[i];
@@ -216,22 +579,63 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
             uint256 amountIn = amounts[i];
             address router = routers[i];
 
+            if (kinds[i] == HOP_WRAP) {
+                // The router is WMATIC itself: wrap into it, or unwrap out of it
+                if (tokenOut == router) {
+                    IWETH(router).deposit{value: amountIn}();
+                } else {
+                    IWETH(router).withdraw(amountIn);
+                }
+                continue;
+            }
+            if (kinds[i] == HOP_STAKE) {
+                // WMATIC in: unwrap, then mint the derivative at the staking rate
+                IWETH(tokenIn).withdraw(amountIn);
+                IInstantStakePool(router).swapMaticForMaticXViaInstantPool{value: amountIn}();
+                continue;
+            }
+
             // Reset and approve token spending
             IERC20(tokenIn).approve(router, 0);
             IERC20(tokenIn).approve(router, amountIn);
 
-            ISwapRouter(router).exactInputSingle(
-                ISwapRouter.ExactInputSingleParams({
-                    tokenIn: tokenIn,
-                    tokenOut: tokenOut,
-                    fee: DEFAULT_FEE,
-                    recipient: address(this),
-                    deadline: block.timestamp + 120,
-                    amountIn: amountIn,
-                    amountOutMinimum: 0,
-                    sqrtPriceLimitX96: 0
-                })
-            );
+            if (kinds[i] == HOP_V2) {
+                address[] memory pair = new address[](2);
+                pair[0] = tokenIn;
+                pair[1] = tokenOut;
+                IUniswapV2Router(router).swapExactTokensForTokens(
+                    amountIn,
+                    0,
+                    pair,
+                    address(this),
+                    block.timestamp + 120
+                );
+            } else if (kinds[i] == HOP_V3) {
+                ISwapRouter(router).exactInputSingle(
+                    ISwapRouter.ExactInputSingleParams({
+                        tokenIn: tokenIn,
+                        tokenOut: tokenOut,
+                        fee: fees[i],
+                        recipient: address(this),
+                        deadline: block.timestamp + 120,
+                        amountIn: amountIn,
+                        amountOutMinimum: 0,
+                        sqrtPriceLimitX96: 0
+                    })
+                );
+            } else if (kinds[i] == HOP_CURVE) {
+                // The router is the pool; fees[i] packs the coin indices as
+                // i << 8 | j, with bit 16 set for exchange_underlying
+                int128 from = int128(uint128((fees[i] >> 8) & 0xff));
+                int128 to = int128(uint128(fees[i] & 0xff));
+                if (fees[i] & 0x10000 != 0) {
+                    ICurvePool(router).exchange_underlying(from, to, amountIn, 0);
+                } else {
+                    ICurvePool(router).exchange(from, to, amountIn, 0);
+                }
+            } else {
+                revert("Unknown hop kind");
+            }
         }
     }
 
@@ -267,7 +671,9 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
             opportunity.fee,
             opportunity.path,
             opportunity.amounts,
-            opportunity.routers
+            opportunity.routers,
+            opportunity.kinds,
+            opportunity.fees
         );
 
         return FastLaneBundle({
@@ -276,6 +682,89 @@ contract FlashLoanArbitrage is IUniswapV3FlashCallback, Ownable {
         });
     }
 
+    // One V2 swap out of the contract's own balance, kept here: a sandwich's
+    // frontrun and backrun. The frontrun's minimum is the output the bot sized,
+    // so a pool that moved first reverts it instead of buying high
+    function swapFromInventory(
+        address router,
+        address tokenIn,
+        address tokenOut,
+        uint256 amountIn,
+        uint256 amountOutMin
+    ) external onlyOwner returns (uint256 amountOut) {
+        IERC20(tokenIn).approve(router, 0);
+        IERC20(tokenIn).approve(router, amountIn);
+        address[] memory pair = new address[](2);
+        pair[0] = tokenIn;
+        pair[1] = tokenOut;
+        uint256[] memory amounts = IUniswapV2Router(router).swapExactTokensForTokens(
+            amountIn,
+            amountOutMin,
+            pair,
+            address(this),
+            block.timestamp
+        );
+        return amounts[1];
+    }
+
+    // JIT liquidity: a position minted from the contract's own balance right
+    // before a large swap and burned right after it, keeping the fee the swap
+    // paid into its range. The bot bundles mint, swap and burn together
+    function mintJit(
+        address pool,
+        int24 tickLower,
+        int24 tickUpper,
+        uint128 liquidity,
+        uint256 amount0Max,
+        uint256 amount1Max
+    ) external onlyOwner returns (uint256 amount0, uint256 amount1) {
+        (amount0, amount1) = IUniswapV3Pool(pool).mint(
+            address(this),
+            tickLower,
+            tickUpper,
+            liquidity,
+            abi.encode(MintCallbackData({amount0Max: amount0Max, amount1Max: amount1Max}))
+        );
+    }
+
+    function uniswapV3MintCallback(
+        uint256 amount0Owed,
+        uint256 amount1Owed,
+        bytes calldata data
+    ) external {
+        IUniswapV3Pool pool = IUniswapV3Pool(msg.sender);
+        PoolAddress.PoolKey memory poolKey = PoolAddress.getPoolKey(pool.token0(), pool.token1(), pool.fee());
+        require(msg.sender == PoolAddress.computeAddress(factory, poolKey), "Callback not from expected pool");
+
+        MintCallbackData memory caps = abi.decode(data, (MintCallbackData));
+        require(amount0Owed <= caps.amount0Max && amount1Owed <= caps.amount1Max, "Mint above caps");
+        if (amount0Owed > 0) {
+            IERC20(poolKey.token0).transfer(msg.sender, amount0Owed);
+        }
+        if (amount1Owed > 0) {
+            IERC20(poolKey.token1).transfer(msg.sender, amount1Owed);
+        }
+    }
+
+    // Burned principal and the fees earned both sit in tokensOwed until collected
+    function burnJit(
+        address pool,
+        int24 tickLower,
+        int24 tickUpper,
+        uint128 liquidity,
+        address recipient
+    ) external onlyOwner returns (uint256 amount0, uint256 amount1) {
+        IUniswapV3Pool(pool).burn(tickLower, tickUpper, liquidity);
+        (uint128 collected0, uint128 collected1) = IUniswapV3Pool(pool).collect(
+            recipient,
+            tickLower,
+            tickUpper,
+            type(uint128).max,
+            type(uint128).max
+        );
+        return (collected0, collected1);
+    }
+
     function withdrawToken(address token, uint256 amount) external onlyOwner {
         require(token != address(0), "Invalid token");
         IERC20(token).transfer(owner(), amount);
@@ -0,0 +1,49 @@
+// src/abis.rs
+use ethers::prelude::*;
+use std::sync::Arc;
+
+use crate::rpc_budget::RpcTransport;
+
+// Every JSON ABI under abis/ that the bot calls, bound at compile time: a
+// missing or malformed file fails the build rather than the first call, and
+// the bindings carry the ABI as code, so nothing is parsed at runtime.
+// FastLane.json is a lone struct definition, not an ABI, and isn't bound.
+
+abigen!(
+    FlashLoanArbitrage,
+    "./abis/FlashLoanArbitrage.json",
+    event_derives(serde::Serialize, serde::Deserialize)
+);
+
+abigen!(
+    FastLaneSender,
+    "./abis/FastLaneSender.json",
+    event_derives(serde::Serialize, serde::Deserialize)
+);
+
+abigen!(IQuickswapRouter, "./abis/QuickswapRouter.json");
+
+abigen!(ISushiswapRouter, "./abis/SushiswapRouter.json");
+
+abigen!(IUniswapV3Router, "./abis/UniswapV3Router.json");
+
+/// The executor (our solver contract), as the provider stack sees it.
+pub fn executor(address: Address, provider: Arc<Provider<RpcTransport>>) -> FlashLoanArbitrage<Provider<RpcTransport>> {
+    FlashLoanArbitrage::new(address, provider)
+}
+
+pub fn fastlane_sender(address: Address, provider: Arc<Provider<RpcTransport>>) -> FastLaneSender<Provider<RpcTransport>> {
+    FastLaneSender::new(address, provider)
+}
+
+pub fn quickswap_router(address: Address, provider: Arc<Provider<RpcTransport>>) -> IQuickswapRouter<Provider<RpcTransport>> {
+    IQuickswapRouter::new(address, provider)
+}
+
+pub fn sushiswap_router(address: Address, provider: Arc<Provider<RpcTransport>>) -> ISushiswapRouter<Provider<RpcTransport>> {
+    ISushiswapRouter::new(address, provider)
+}
+
+pub fn uniswap_v3_router(address: Address, provider: Arc<Provider<RpcTransport>>) -> IUniswapV3Router<Provider<RpcTransport>> {
+    IUniswapV3Router::new(address, provider)
+}
@@ -1,35 +1,108 @@
 // src/advanced.rs
-use ethers::{
-    abi::Abi,
-    prelude::*,
-    types::{Address, Bytes, H160, H256, U256},
-};
-use revm::{
-    db::{CacheDB, EmptyDB},
-    primitives::{Bytecode, ExecutionResult, TransactTo},
-    Database, DatabaseCommit, EVM,
-};
-use std::collections::{HashMap, HashSet};
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::abis;
+use crate::bundle_scenarios::{self, Leg, LegSet, OrderingPlan, Scenario, ScenarioOutcome, SCENARIOS};
+use crate::executor::{BundleTx, Executor, OrderedBundle};
+use crate::pool_state::{PoolState, PoolStateManager};
+use crate::price_service::PriceService;
+use crate::quickswap::{parse_quickswap_tx, QuickSwapAction};
+use crate::sandwich::{self, VictimSwap};
+use crate::simulation_engine::{AdvancedSimulationEngine, BundleLeg, ExecutionCapture};
+use crate::token_safety::TokenSafety;
+use crate::rpc_budget::RpcTransport;
+
+// Normal tokens deliver the V2 quote to the wei; anything short of it is a token
+// treating our executor differently from the victim
+const MAX_DELIVERY_SHORTFALL_BPS: u64 = 10;
 
 #[derive(Debug, Clone)]
 pub struct SandwichOpportunity {
-    pub victim_tx: Transaction,
+    pub pool: PoolState,
+    // Swaps through the same pool and direction, in bundle order
+    pub victims: Vec<Transaction>,
     pub frontrun_amount: U256,
     pub backrun_amount: U256,
     pub expected_profit: U256,
     pub path: Vec<Address>,
 }
 
+/// What simulating a sandwich's orderings settled: the flags to send it with,
+/// and the gas and profit of the bundle as sent.
+#[derive(Debug, Clone, Copy)]
+struct SandwichPlan {
+    ordering: OrderingPlan,
+    frontrun_gas: u64,
+    backrun_gas: u64,
+    gas_price: U256,
+    // Net of our legs' gas
+    profit_usd: f64,
+}
+
 pub struct AdvancedArbitrage {
-    provider: Arc<Provider<Ws>>,
-    flash_loan_contract: Address,
+    provider: Arc<Provider<RpcTransport>>,
+    // Holds the inventory both legs trade and runs them
+    executor: Address,
+    // Signs our legs; the executor only takes calls from it
+    searcher: Address,
+    wrapped_native: Address,
+    pool_state: Arc<PoolStateManager>,
+    prices: Arc<PriceService>,
+    // Victims swapping through a thinner pool than this are skipped
+    min_pool_tvl_usd: f64,
+    // Largest loss any reachable partial outcome of a sandwich may take
+    max_loss_usd: f64,
+    simulation_engine: Arc<AdvancedSimulationEngine>,
+    // Tokens that short-delivered to the executor are marked unsafe here, so
+    // arbitrage skips them too
+    token_safety: Arc<TokenSafety>,
+    chain_id: u64,
 }
 
 impl AdvancedArbitrage {
-    pub fn new(provider: Arc<Provider<Ws>>, contract: Address) -> Self {
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        executor: Address,
+        searcher: Address,
+        wrapped_native: Address,
+        pool_state: Arc<PoolStateManager>,
+        prices: Arc<PriceService>,
+        min_pool_tvl_usd: f64,
+        max_loss_usd: f64,
+        simulation_engine: Arc<AdvancedSimulationEngine>,
+        token_safety: Arc<TokenSafety>,
+        chain_id: u64,
+    ) -> Self {
         Self {
             provider,
-            flash_loan_contract: contract,
+            executor,
+            searcher,
+            wrapped_native,
+            pool_state,
+            prices,
+            min_pool_tvl_usd,
+            max_loss_usd,
+            simulation_engine,
+            token_safety,
+            chain_id,
+        }
+    }
+
+    /// Whether `tx` is a swap this strategy could sandwich, before any pool lookup.
+    pub fn is_candidate(tx: &Transaction) -> bool {
+        Self::decode(tx).is_some()
+    }
+
+    /// Whether `a` and `b` swap the same way between the same two tokens, so
+    /// they can share a sandwich.
+    pub fn same_direction(a: &Transaction, b: &Transaction) -> bool {
+        match (Self::decode(a), Self::decode(b)) {
+            (Some((_, _, a)), Some((_, _, b))) => a == b,
+            _ => false,
         }
     }
 
@@ -37,88 +110,459 @@ impl AdvancedArbitrage {
         &self,
         pending_txs: Vec<Transaction>,
     ) -> Result<Vec<SandwichOpportunity>> {
-        let mut opportunities = Vec::new();
-        
+        // Swaps through the same pool in the same direction go in one sandwich:
+        // together they move the price further than any one of them
+        let mut groups: HashMap<(Address, Address), (PoolState, Vec<(Transaction, VictimSwap)>)> = HashMap::new();
         for tx in pending_txs {
-            if let Some(opportunity) = self.analyze_sandwich(&tx).await? {
+            if let Some((pool, token_in, swap)) = self.victim_swap(&tx).await {
+                groups
+                    .entry((pool.address, token_in))
+                    .or_insert_with(|| (pool, Vec::new()))
+                    .1
+                    .push((tx, swap));
+            }
+        }
+
+        let mut opportunities = Vec::new();
+        for ((_, token_in), (pool, mut victims)) in groups {
+            // Builders order by tip, so the bundle does too
+            victims.sort_by_key(|(tx, _)| std::cmp::Reverse(tx.max_priority_fee_per_gas.or(tx.gas_price)));
+            if let Some(opportunity) = self.analyze_sandwich(&pool, token_in, victims).await? {
                 opportunities.push(opportunity);
             }
         }
-        
+
         Ok(opportunities)
     }
 
-    async fn analyze_sandwich(&self, tx: &Transaction) -> Result<Option<SandwichOpportunity>> {
-        // Analyze transaction for sandwich potential
-        let impact = self.simulate_price_impact(tx).await?;
-        
-        if impact > U256::from(200) { // 2% impact threshold
-            let optimal_amounts = self.find_optimal_sandwich_amounts(tx).await?;
-            
-            return Ok(Some(SandwichOpportunity {
-                victim_tx: tx.clone(),
-                frontrun_amount: optimal_amounts.0,
-                backrun_amount: optimal_amounts.1,
-                expected_profit: optimal_amounts.2,
-                path: self.get_sandwich_path(tx).await?,
-            }));
+    // Exact-input swaps straight through one V2 pool. A multi-hop minimum covers
+    // the whole path and can't be held against one pool, and fee-on-transfer
+    // tokens don't deliver what the pool math says
+    fn decode(tx: &Transaction) -> Option<(U256, U256, [Address; 2])> {
+        let (amount_in, amount_out_min, path) = match parse_quickswap_tx(tx)? {
+            QuickSwapAction::SwapExactTokensForTokens { amount_in, amount_out_min, path, .. }
+            | QuickSwapAction::SwapExactTokensForETH { amount_in, amount_out_min, path, .. }
+            | QuickSwapAction::SwapExactETHForTokens { amount_in, amount_out_min, path, .. } => (amount_in, amount_out_min, path),
+            _ => return None,
+        };
+        match path.as_slice() {
+            [token_in, token_out] => Some((amount_in, amount_out_min, [*token_in, *token_out])),
+            _ => None,
         }
-        
-        Ok(None)
     }
 
+    async fn victim_swap(&self, tx: &Transaction) -> Option<(PoolState, Address, VictimSwap)> {
+        let (amount_in, amount_out_min, [token_in, token_out]) = Self::decode(tx)?;
+        let pool = self
+            .pool_state
+            .pools_for_pair(token_in, token_out)
+            .await
+            .into_iter()
+            .find(|p| Some(p.router) == tx.to)?;
+        Some((pool, token_in, VictimSwap { tx_hash: tx.hash, amount_in, amount_out_min }))
+    }
+
+    async fn analyze_sandwich(
+        &self,
+        pool: &PoolState,
+        token_in: Address,
+        victims: Vec<(Transaction, VictimSwap)>,
+    ) -> Result<Option<SandwichOpportunity>> {
+        let path = vec![token_in, pool.other_token(token_in)];
+        if !self.path_is_liquid(&path).await? {
+            return Ok(None);
+        }
+        let swaps: Vec<VictimSwap> = victims.iter().map(|(_, swap)| swap.clone()).collect();
+        let sizing = match sandwich::optimal(pool, token_in, &swaps) {
+            Some(sizing) => sizing,
+            None => return Ok(None),
+        };
+        if !self.delivers_in_full(&path, sizing.frontrun_in).await? {
+            return Ok(None);
+        }
+        if sizing.victims.len() > 1 {
+            debug!(pool = ?pool.address, victims = sizing.victims.len(), profit = %sizing.profit, "Multi-victim sandwich");
+        }
+
+        Ok(Some(SandwichOpportunity {
+            pool: pool.clone(),
+            victims: victims
+                .into_iter()
+                .filter(|(tx, _)| sizing.victims.contains(&tx.hash))
+                .map(|(tx, _)| tx)
+                .collect(),
+            frontrun_amount: sizing.frontrun_in,
+            backrun_amount: sizing.frontrun_out,
+            expected_profit: sizing.profit,
+            path,
+        }))
+    }
+
+    // A thin pool's price impact looks large but the profit never survives
+    // execution, so every hop needs a pool worth at least the minimum
+    async fn path_is_liquid(&self, path: &[Address]) -> Result<bool> {
+        for hop in path.windows(2) {
+            let mut deepest = 0.0f64;
+            for pool in self.pool_state.pools_for_pair(hop[0], hop[1]).await {
+                deepest = deepest.max(self.prices.pool_tvl_usd(&pool).await?.unwrap_or_default());
+            }
+            if deepest < self.min_pool_tvl_usd {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Salmonella-style tokens pay the victim in full and other recipients a
+    // fraction, so a generic round trip from a fresh address passes them. The
+    // frontrun buy is simulated paying out to our executor instead
+    async fn delivers_in_full(&self, path: &[Address], amount_in: U256) -> Result<bool> {
+        let (token_in, token_out) = match path {
+            [token_in, token_out, ..] => (*token_in, *token_out),
+            _ => return Ok(true),
+        };
+        if self.token_safety.is_unsafe(self.chain_id, token_out) {
+            return Ok(false);
+        }
+
+        let reserve_in = |pool: &PoolState| if pool.token0 == token_in { pool.reserve0 } else { pool.reserve1 };
+        let pool = match self.pool_state.pools_for_pair(token_in, token_out).await.into_iter().max_by_key(|p| reserve_in(p)) {
+            Some(pool) => pool,
+            None => return Ok(true),
+        };
+        // Any other pool holding enough of the input funds the simulated buy
+        let holder = self
+            .pool_state
+            .snapshot()
+            .await
+            .into_iter()
+            .find(|p| p.address != pool.address && p.has_token(token_in) && reserve_in(p) >= amount_in);
+        let holder = match holder {
+            Some(holder) => holder.address,
+            None => {
+                debug!("No holder of {:?} to fund the delivery check, not checked", token_in);
+                return Ok(true);
+            }
+        };
+
+        let delivery = self
+            .simulation_engine
+            .frontrun_delivery(&pool, token_in, amount_in, self.executor, holder)
+            .await?;
+        if delivery.shortfall_bps() <= MAX_DELIVERY_SHORTFALL_BPS {
+            return Ok(true);
+        }
+        warn!(
+            token = ?token_out,
+            quoted = %delivery.quoted,
+            received = %delivery.received,
+            "Token short-delivers to the executor ({} bps), not sandwiching it",
+            delivery.shortfall_bps()
+        );
+        self.token_safety.mark_unsafe(self.chain_id, token_out);
+        Ok(false)
+    }
+
+    /// Simulates the sandwich's orderings and, when every reachable one stays
+    /// within the loss bound, sends it through `executor` for `target_block`.
+    /// `None` when it isn't sent.
     pub async fn execute_sandwich_attack(
         &self,
         opportunity: &SandwichOpportunity,
-    ) -> Result<()> {
-        // Bundle frontrun, victim, and backrun transactions
-        let bundle = self.create_sandwich_bundle(opportunity).await?;
-        
-        // Send bundle to flashbots or similar service
-        self.send_bundle(bundle).await?;
-        
-        Ok(())
+        executor: &dyn Executor,
+        target_block: U64,
+    ) -> Result<Option<H256>> {
+        let bundle = match self.create_sandwich_bundle(opportunity, target_block).await? {
+            Some(bundle) => bundle,
+            None => return Ok(None),
+        };
+        let hash = executor.submit_ordered(&bundle).await?;
+        let victims: Vec<H256> = opportunity.victims.iter().map(|victim| victim.hash).collect();
+        info!(?victims, pool = ?opportunity.pool.address, "Submitted sandwich bundle {:?}", hash);
+        Ok(Some(hash))
     }
 
     async fn create_sandwich_bundle(
         &self,
         opportunity: &SandwichOpportunity,
-    ) -> Result<Vec<Bytes>> {
-        let mut bundle = Vec::new();
-        
-        // Frontrun transaction
-        let frontrun_tx = self.create_frontrun_tx(opportunity).await?;
-        bundle.push(frontrun_tx);
-        
-        // Victim transaction
-        bundle.push(opportunity.victim_tx.input.clone());
-        
-        // Backrun transaction
-        let backrun_tx = self.create_backrun_tx(opportunity).await?;
-        bundle.push(backrun_tx);
-        
-        Ok(bundle)
-    }
-
-    async fn create_frontrun_tx(
-        &self,
-        opportunity: &SandwichOpportunity,
-    ) -> Result<Bytes> {
-        // Create frontrun swap transaction
-        Ok(Bytes::from("0x"))
+        target_block: U64,
+    ) -> Result<Option<OrderedBundle>> {
+        let frontrun = self.frontrun_calldata(opportunity)?;
+        let backrun = self.backrun_calldata(opportunity)?;
+
+        let plan = match self.plan_ordering(opportunity, &frontrun, &backrun).await? {
+            Some(plan) => plan,
+            None => return Ok(None),
+        };
+        debug!(
+            can_revert = %plan.ordering.can_revert,
+            worst = plan.ordering.worst.scenario.name,
+            worst_usd = plan.ordering.worst.profit_usd,
+            "Sandwich orderings within loss bound"
+        );
+        let expected_profit = match self.native_wei(plan.profit_usd).await? {
+            Some(profit) if !profit.is_zero() => profit,
+            _ => {
+                debug!(profit_usd = plan.profit_usd, "Sandwich unprofitable after gas, not sending");
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(ordered_bundle(opportunity, &plan, frontrun, backrun, target_block, expected_profit)))
     }
 
-    async fn create_backrun_tx(
+    fn frontrun_calldata(&self, opportunity: &SandwichOpportunity) -> Result<Bytes> {
+        let (token_in, token_out) = Self::pair(opportunity)?;
+        // Exactly the sized output, so any state the sizing didn't see reverts it
+        abis::executor(self.executor, self.provider.clone())
+            .swap_from_inventory(opportunity.pool.router, token_in, token_out, opportunity.frontrun_amount, opportunity.backrun_amount)
+            .calldata()
+            .ok_or_else(|| anyhow!("Failed to encode the sandwich frontrun"))
+    }
+
+    fn backrun_calldata(&self, opportunity: &SandwichOpportunity) -> Result<Bytes> {
+        let (token_in, token_out) = Self::pair(opportunity)?;
+        // No minimum: what selling back at a loss costs is bounded by the ordering plan
+        abis::executor(self.executor, self.provider.clone())
+            .swap_from_inventory(opportunity.pool.router, token_out, token_in, opportunity.backrun_amount, U256::zero())
+            .calldata()
+            .ok_or_else(|| anyhow!("Failed to encode the sandwich backrun"))
+    }
+
+    fn pair(opportunity: &SandwichOpportunity) -> Result<(Address, Address)> {
+        match opportunity.path.as_slice() {
+            [token_in, token_out] => Ok((*token_in, *token_out)),
+            path => Err(anyhow!("Sandwich path {:?} is not one hop", path)),
+        }
+    }
+
+    // Simulates the bundle in every order and partial inclusion a block could
+    // give it, and picks the legs that may revert so that no reachable outcome
+    // loses more than the bound. Nothing on Polygon holds a validator to a
+    // relay bundle's order, so reorderings count as reachable
+    async fn plan_ordering(
         &self,
         opportunity: &SandwichOpportunity,
-    ) -> Result<Bytes> {
-        // Create backrun swap transaction
-        Ok(Bytes::from("0x"))
+        frontrun: &Bytes,
+        backrun: &Bytes,
+    ) -> Result<Option<SandwichPlan>> {
+        let (token_in, token_out) = Self::pair(opportunity)?;
+        let gas_price = self.provider.get_gas_price().await?;
+        let ours = |calldata: &Bytes| BundleLeg::Call {
+            caller: self.searcher,
+            to: self.executor,
+            calldata: calldata.clone(),
+        };
+        let (frontrun, backrun) = (ours(frontrun), ours(backrun));
+
+        let mut outcomes = Vec::with_capacity(SCENARIOS.len());
+        let (mut frontrun_gas, mut backrun_gas, mut profit_usd) = (0, 0, 0.0);
+        for scenario in SCENARIOS {
+            let (legs, kinds) = scenario_legs(scenario, &frontrun, &opportunity.victims, &backrun);
+            let sequence = self
+                .simulation_engine
+                .simulate_sequence(&legs, self.executor, &[token_in, token_out])
+                .await?;
+            let (failed, gas_used) = failed_legs(scenario, &kinds, &sequence.legs);
+
+            // What the frontrun bought and no backrun sold is marked to market
+            let priced = (
+                self.signed_usd(token_in, sequence.deltas[0]).await?,
+                self.signed_usd(token_out, sequence.deltas[1]).await?,
+                self.prices.usd_value(self.wrapped_native, gas_price * U256::from(gas_used)).await?,
+            );
+            let outcome = match priced {
+                (Some(sold), Some(held), Some(gas)) => ScenarioOutcome { scenario: *scenario, failed, profit_usd: sold + held - gas },
+                _ => {
+                    debug!(path = ?opportunity.path, "No USD prices to bound sandwich losses, not sandwiching");
+                    return Ok(None);
+                }
+            };
+            // The bundle as sent sets our legs' gas limits and what it's worth
+            if scenario.absent().is_empty() && !scenario.reordered() {
+                for (leg, capture) in kinds.iter().zip(&sequence.legs) {
+                    match leg {
+                        Leg::Frontrun => frontrun_gas = capture.gas_used,
+                        Leg::Backrun => backrun_gas = capture.gas_used,
+                        Leg::Victim => {}
+                    }
+                }
+                profit_usd = outcome.profit_usd;
+            }
+            outcomes.push(outcome);
+        }
+
+        match bundle_scenarios::plan(&outcomes, self.max_loss_usd, false) {
+            Ok(ordering) => Ok(Some(SandwichPlan { ordering, frontrun_gas, backrun_gas, gas_price, profit_usd })),
+            Err(rejected) => {
+                let victims: Vec<H256> = opportunity.victims.iter().map(|victim| victim.hash).collect();
+                info!(?victims, "Not sandwiching: {}", rejected);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn signed_usd(&self, token: Address, delta: I256) -> Result<Option<f64>> {
+        let usd = self.prices.usd_value(token, delta.unsigned_abs()).await?;
+        Ok(usd.map(|usd| if delta.is_negative() { -usd } else { usd }))
     }
 
-    async fn send_bundle(&self, bundle: Vec<Bytes>) -> Result<()> {
-        // Send bundle to MEV relay
-        // Implementation for Flashbots or similar service
-        Ok(())
+    // USD in wei of the native token, which bundles are valued in
+    async fn native_wei(&self, usd: f64) -> Result<Option<U256>> {
+        let native_usd = match self.prices.usd_price(self.wrapped_native).await? {
+            Some(price) if price > 0.0 => price,
+            _ => return Ok(None),
+        };
+        Ok(Some(U256::from((usd.max(0.0) / native_usd * 1e18) as u128)))
+    }
+}
+
+// A scenario's legs as transactions, and which sandwich leg each one is.
+// Victims run back to back and count as one leg
+fn scenario_legs(scenario: &Scenario, frontrun: &BundleLeg, victims: &[Transaction], backrun: &BundleLeg) -> (Vec<BundleLeg>, Vec<Leg>) {
+    let mut legs = Vec::new();
+    let mut kinds = Vec::new();
+    for leg in scenario.order {
+        match leg {
+            Leg::Frontrun => legs.push(frontrun.clone()),
+            Leg::Victim => legs.extend(victims.iter().cloned().map(BundleLeg::Pending)),
+            Leg::Backrun => legs.push(backrun.clone()),
+        }
+        kinds.resize(legs.len(), *leg);
+    }
+    (legs, kinds)
+}
+
+// Legs the scenario left out or that reverted (any victim failing fails the
+// victim leg), and the gas our own legs burned
+fn failed_legs(scenario: &Scenario, kinds: &[Leg], captures: &[ExecutionCapture]) -> (LegSet, u64) {
+    let mut failed = scenario.absent();
+    let mut gas_used = 0u64;
+    for (leg, capture) in kinds.iter().zip(captures) {
+        if !capture.success {
+            failed.insert(*leg);
+        }
+        if *leg != Leg::Victim {
+            gas_used += capture.gas_used;
+        }
+    }
+    (failed, gas_used)
+}
+
+// Frontrun, victims and backrun in order, each flagged with whether the bundle
+// may land with it reverted. Gas limits get headroom over the simulation; they
+// are fixed before the victims run
+fn ordered_bundle(
+    opportunity: &SandwichOpportunity,
+    plan: &SandwichPlan,
+    frontrun: Bytes,
+    backrun: Bytes,
+    target_block: U64,
+    expected_profit: U256,
+) -> OrderedBundle {
+    let mut txs = vec![BundleTx::Executor { calldata: frontrun, gas_limit: plan.frontrun_gas * 6 / 5 }];
+    txs.extend(opportunity.victims.iter().cloned().map(BundleTx::Pending));
+    txs.push(BundleTx::Executor { calldata: backrun, gas_limit: plan.backrun_gas * 6 / 5 });
+
+    let can_revert = plan.ordering.can_revert;
+    let mut flags = vec![can_revert.contains(Leg::Frontrun)];
+    flags.extend(opportunity.victims.iter().map(|_| can_revert.contains(Leg::Victim)));
+    flags.push(can_revert.contains(Leg::Backrun));
+
+    OrderedBundle {
+        txs,
+        can_revert: flags,
+        target_block,
+        gas_price: plan.gas_price,
+        expected_profit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee::FeeAmount;
+
+    fn capture(success: bool, gas_used: u64) -> ExecutionCapture {
+        ExecutionCapture { success, gas_used, logs: Vec::new() }
+    }
+
+    #[test]
+    fn simulated_scenarios_set_the_bundle_flags() {
+        let (token0, token1) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let victim = |n: u64| Transaction { hash: H256::from_low_u64_be(n), ..Default::default() };
+        let opportunity = SandwichOpportunity {
+            pool: PoolState {
+                address: Address::from_low_u64_be(3),
+                router: Address::from_low_u64_be(4),
+                token0,
+                token1,
+                reserve0: U256::exp10(24),
+                reserve1: U256::exp10(24),
+                fee: FeeAmount::MEDIUM,
+                last_updated_block: 0.into(),
+            },
+            victims: vec![victim(10), victim(11)],
+            frontrun_amount: U256::exp10(21),
+            backrun_amount: U256::exp10(21),
+            expected_profit: U256::exp10(18),
+            path: vec![token0, token1],
+        };
+        let call = |byte: u8| BundleLeg::Call {
+            caller: Address::from_low_u64_be(5),
+            to: Address::from_low_u64_be(6),
+            calldata: Bytes::from(vec![byte]),
+        };
+        let (frontrun, backrun) = (call(1), call(2));
+
+        let mut outcomes = Vec::new();
+        let (mut bundle_gas, mut frontrun_gas, mut backrun_gas) = (0, 0, 0);
+        for scenario in SCENARIOS {
+            let (legs, kinds) = scenario_legs(scenario, &frontrun, &opportunity.victims, &backrun);
+            let captures: Vec<ExecutionCapture> = kinds
+                .iter()
+                .enumerate()
+                .map(|(i, leg)| match leg {
+                    // With no frontrun there's nothing to sell back
+                    Leg::Backrun => capture(scenario.name != "no_frontrun", 100_000),
+                    Leg::Frontrun => capture(true, 120_000),
+                    // The backrun moved the price past the second victim's minimum
+                    Leg::Victim => capture(!(scenario.name == "backrun_first" && i == legs.len() - 1), 150_000),
+                })
+                .collect();
+            let (failed, gas_used) = failed_legs(scenario, &kinds, &captures);
+            if scenario.name == "bundle" {
+                bundle_gas = gas_used;
+                frontrun_gas = captures[0].gas_used;
+                backrun_gas = captures[3].gas_used;
+            }
+            let profit_usd = match scenario.name {
+                "bundle" => 40.0,
+                "victim_first" => -2.0,
+                "backrun_first" => -3.0,
+                "no_frontrun" => -0.5,
+                "no_backrun" => -60.0,
+                "no_victim" => -4.0,
+                _ => -55.0,
+            };
+            outcomes.push(ScenarioOutcome { scenario: *scenario, failed, profit_usd });
+        }
+        // Only our legs' gas is ours to pay
+        assert_eq!(bundle_gas, 220_000);
+        let backrun_first = outcomes.iter().find(|o| o.scenario.name == "backrun_first").unwrap();
+        assert!(backrun_first.failed.contains(Leg::Victim));
+
+        // Holding the frontrun's buy is unaffordable, so the backrun can't revert
+        let ordering = bundle_scenarios::plan(&outcomes, 5.0, false).unwrap();
+        let plan = SandwichPlan { ordering, frontrun_gas, backrun_gas, gas_price: U256::exp10(11), profit_usd: 40.0 };
+        let bundle = ordered_bundle(&opportunity, &plan, Bytes::from(vec![1]), Bytes::from(vec![2]), 100.into(), U256::exp10(18));
+
+        assert_eq!(bundle.txs.len(), 4);
+        assert!(matches!(bundle.txs[0], BundleTx::Executor { gas_limit: 144_000, .. }));
+        assert!(matches!(&bundle.txs[1], BundleTx::Pending(tx) if tx.hash == H256::from_low_u64_be(10)));
+        assert!(matches!(&bundle.txs[2], BundleTx::Pending(tx) if tx.hash == H256::from_low_u64_be(11)));
+        assert!(matches!(bundle.txs[3], BundleTx::Executor { gas_limit: 120_000, .. }));
+        assert_eq!(bundle.can_revert, vec![true, true, true, false]);
+        assert_eq!(plan.ordering.worst.scenario.name, "no_victim");
     }
 }
@@ -0,0 +1,351 @@
+// src/api.rs
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ethers::types::{Address, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::controls::{RuntimeControls, ThresholdUpdate, Thresholds};
+use crate::pnl::PnlAggregate;
+use crate::storage::{DailyPnl, PoolStats, Storage, StrategyStats};
+
+/// A queued opportunity, as the executor will see it.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpportunityView {
+    pub chain: &'static str,
+    pub id: String,
+    pub path: Vec<Address>,
+    pub pools: Vec<Address>,
+    pub amount_in: U256,
+    pub expected_profit: U256,
+    // Net of execution gas at the gas price when it was queued
+    pub net_profit: U256,
+    pub target_block: U64,
+    pub victim_tx: Option<H256>,
+    // Since the triggering tx or head arrived
+    pub age_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainStats {
+    pub chain: &'static str,
+    pub head: U64,
+    pub queued: usize,
+    pub in_flight: usize,
+    pub v2_pools: usize,
+    pub v3_pools: usize,
+    pub active_tokens: usize,
+    // Realized over the last 24 hours
+    pub pnl_daily: PnlAggregate,
+    pub halted: Option<String>,
+    // Pending txs dropped before classification, per spam filter rule
+    pub spam_dropped: HashMap<&'static str, u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainHealth {
+    pub chain: &'static str,
+    // Heads are arriving and the monitor isn't shutting down
+    pub healthy: bool,
+    pub head: U64,
+    pub head_age_ms: Option<u64>,
+    // A halted chain is alive, it just doesn't submit
+    pub halted: Option<String>,
+    pub shutting_down: bool,
+}
+
+/// What one chain's monitor exposes over the API.
+#[async_trait]
+pub trait ApiSource: Send + Sync {
+    fn chain(&self) -> &'static str;
+    async fn opportunities(&self) -> Vec<OpportunityView>;
+    async fn stats(&self) -> ChainStats;
+    async fn health(&self) -> ChainHealth;
+    // Where the dashboard aggregates are computed from; None without DATABASE_URL
+    fn storage(&self) -> Option<Storage>;
+    fn controls(&self) -> &RuntimeControls;
+    // Simulation results, pool reads and cached RPC responses
+    async fn clear_caches(&self);
+    // Converts and sweeps executor balances now rather than at the next interval
+    async fn sweep_treasury(&self) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlState {
+    pub chain: &'static str,
+    pub paused: Vec<String>,
+    pub thresholds: Thresholds,
+}
+
+/// One chain's slice of a dashboard aggregate.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainSeries<T> {
+    pub chain: &'static str,
+    pub data: T,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GasSpent {
+    pub total_usd: f64,
+    pub by_day: Vec<DailyGas>,
+    pub by_strategy: Vec<StrategyGas>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyGas {
+    pub day: i64,
+    pub executions: i64,
+    pub gas_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyGas {
+    pub source: String,
+    pub gas_usd: f64,
+}
+
+// ?days=N&limit=N on the dashboard endpoints
+#[derive(Debug, Deserialize)]
+struct Window {
+    days: Option<i64>,
+    limit: Option<i64>,
+}
+
+const DEFAULT_DAYS: i64 = 30;
+const DEFAULT_TOP_POOLS: i64 = 20;
+
+type Sources = Arc<Vec<Arc<dyn ApiSource>>>;
+
+/// Read-only JSON API over every chain's monitor:
+/// - `GET /opportunities`: live queued opportunities, most profitable first
+/// - `GET /stats`: per-chain counters and realized PnL
+/// - `GET /health`: 200 when every chain is healthy, 503 otherwise
+///
+/// With a `control_token`, mutating endpoints under `/control/:chain` that
+/// require `Authorization: Bearer <token>`:
+/// - `GET /control/:chain`: paused strategies and current thresholds
+/// - `POST /control/:chain/strategies/:strategy/pause` and `.../resume`
+/// - `POST /control/:chain/thresholds`: partial `ThresholdUpdate` as JSON
+/// - `POST /control/:chain/caches/clear`
+/// - `POST /control/:chain/treasury/sweep`
+///
+/// and dashboard aggregates from each chain's storage, over `?days=` (default 30):
+/// - `GET /dashboard/pnl`: realized PnL per day
+/// - `GET /dashboard/strategies`: found, submitted, included and win rate per source
+/// - `GET /dashboard/gas`: gas spent per day and per source
+/// - `GET /dashboard/pools`: most profitable pools (`?limit=`, default 20)
+pub async fn serve(addr: SocketAddr, sources: Vec<Arc<dyn ApiSource>>, control_token: Option<String>) -> Result<()> {
+    let sources: Sources = Arc::new(sources);
+    let mut app = Router::new()
+        .route("/opportunities", get(opportunities))
+        .route("/stats", get(stats))
+        .route("/health", get(health))
+        .route("/dashboard/pnl", get(dashboard_pnl))
+        .route("/dashboard/strategies", get(dashboard_strategies))
+        .route("/dashboard/gas", get(dashboard_gas))
+        .route("/dashboard/pools", get(dashboard_pools))
+        .with_state(sources.clone());
+    match control_token {
+        Some(token) => {
+            let control = Router::new()
+                .route("/control/:chain", get(control_state))
+                .route("/control/:chain/strategies/:strategy/pause", post(pause_strategy))
+                .route("/control/:chain/strategies/:strategy/resume", post(resume_strategy))
+                .route("/control/:chain/thresholds", post(update_thresholds))
+                .route("/control/:chain/caches/clear", post(clear_caches))
+                .route("/control/:chain/treasury/sweep", post(sweep_treasury))
+                .route_layer(middleware::from_fn_with_state(Arc::new(token), authorize))
+                .with_state(sources);
+            app = app.merge(control);
+        }
+        None => info!("API_CONTROL_TOKEN not set, control endpoints disabled"),
+    }
+    info!(%addr, "API listening");
+    axum::Server::bind(&addr).serve(app.into_make_service()).await?;
+    Ok(())
+}
+
+async fn opportunities(State(sources): State<Sources>) -> Json<Vec<OpportunityView>> {
+    let mut all = Vec::new();
+    for source in sources.iter() {
+        all.extend(source.opportunities().await);
+    }
+    all.sort_by(|a, b| b.net_profit.cmp(&a.net_profit));
+    Json(all)
+}
+
+async fn stats(State(sources): State<Sources>) -> Json<Vec<ChainStats>> {
+    let mut all = Vec::new();
+    for source in sources.iter() {
+        all.push(source.stats().await);
+    }
+    Json(all)
+}
+
+async fn health(State(sources): State<Sources>) -> (StatusCode, Json<Vec<ChainHealth>>) {
+    let mut all = Vec::new();
+    for source in sources.iter() {
+        all.push(source.health().await);
+    }
+    let status = if all.iter().all(|h| h.healthy) { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(all))
+}
+
+type DashboardResult<T> = Result<Json<Vec<ChainSeries<T>>>, (StatusCode, String)>;
+
+// Runs `query` against every chain that has storage
+async fn per_chain<T, F, Fut>(sources: &Sources, query: F) -> DashboardResult<T>
+where
+    F: Fn(Storage) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut all = Vec::new();
+    for source in sources.iter() {
+        let storage = match source.storage() {
+            Some(storage) => storage,
+            None => continue,
+        };
+        let chain = source.chain();
+        match query(storage).await {
+            Ok(data) => all.push(ChainSeries { chain, data }),
+            Err(e) => {
+                warn!(chain, "Dashboard query failed: {:?}", e);
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("{} storage query failed", chain)));
+            }
+        }
+    }
+    Ok(Json(all))
+}
+
+async fn dashboard_pnl(State(sources): State<Sources>, Query(window): Query<Window>) -> DashboardResult<Vec<DailyPnl>> {
+    let days = window.days.unwrap_or(DEFAULT_DAYS);
+    per_chain(&sources, |storage| async move { storage.daily_pnl(days).await }).await
+}
+
+async fn dashboard_strategies(State(sources): State<Sources>, Query(window): Query<Window>) -> DashboardResult<Vec<StrategyStats>> {
+    let days = window.days.unwrap_or(DEFAULT_DAYS);
+    per_chain(&sources, |storage| async move { storage.strategy_stats(days).await }).await
+}
+
+async fn dashboard_gas(State(sources): State<Sources>, Query(window): Query<Window>) -> DashboardResult<GasSpent> {
+    let days = window.days.unwrap_or(DEFAULT_DAYS);
+    per_chain(&sources, |storage| async move {
+        let by_day: Vec<DailyGas> = storage
+            .daily_pnl(days)
+            .await?
+            .into_iter()
+            .map(|d| DailyGas { day: d.day, executions: d.executions, gas_usd: d.gas_usd })
+            .collect();
+        let by_strategy = storage
+            .strategy_stats(days)
+            .await?
+            .into_iter()
+            .map(|s| StrategyGas { source: s.source, gas_usd: s.gas_usd })
+            .collect();
+        Ok(GasSpent {
+            total_usd: by_day.iter().map(|d| d.gas_usd).sum(),
+            by_day,
+            by_strategy,
+        })
+    })
+    .await
+}
+
+async fn dashboard_pools(State(sources): State<Sources>, Query(window): Query<Window>) -> DashboardResult<Vec<PoolStats>> {
+    let days = window.days.unwrap_or(DEFAULT_DAYS);
+    let limit = window.limit.unwrap_or(DEFAULT_TOP_POOLS);
+    per_chain(&sources, |storage| async move { storage.top_pools(days, limit).await }).await
+}
+
+type ControlResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+async fn authorize<B>(State(token): State<Arc<String>>, request: Request<B>, next: Next<B>) -> Result<Response, StatusCode> {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if constant_time_eq(presented.as_bytes(), token.as_bytes()) => Ok(next.run(request).await),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+// Compares every byte so the response time doesn't leak how much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn find_chain(sources: &Sources, chain: &str) -> Result<Arc<dyn ApiSource>, (StatusCode, String)> {
+    sources
+        .iter()
+        .find(|s| s.chain() == chain)
+        .cloned()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No chain {:?}", chain)))
+}
+
+fn control_state_of(source: &dyn ApiSource) -> ControlState {
+    ControlState {
+        chain: source.chain(),
+        paused: source.controls().paused(),
+        thresholds: source.controls().thresholds(),
+    }
+}
+
+async fn control_state(State(sources): State<Sources>, Path(chain): Path<String>) -> ControlResult<ControlState> {
+    let source = find_chain(&sources, &chain)?;
+    Ok(Json(control_state_of(source.as_ref())))
+}
+
+async fn pause_strategy(State(sources): State<Sources>, Path((chain, strategy)): Path<(String, String)>) -> ControlResult<ControlState> {
+    set_paused(&sources, &chain, &strategy, true)
+}
+
+async fn resume_strategy(State(sources): State<Sources>, Path((chain, strategy)): Path<(String, String)>) -> ControlResult<ControlState> {
+    set_paused(&sources, &chain, &strategy, false)
+}
+
+fn set_paused(sources: &Sources, chain: &str, strategy: &str, paused: bool) -> ControlResult<ControlState> {
+    let source = find_chain(sources, chain)?;
+    source
+        .controls()
+        .set_paused(strategy, paused)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(control_state_of(source.as_ref())))
+}
+
+async fn update_thresholds(
+    State(sources): State<Sources>,
+    Path(chain): Path<String>,
+    Json(update): Json<ThresholdUpdate>,
+) -> ControlResult<Thresholds> {
+    let source = find_chain(&sources, &chain)?;
+    let thresholds = source.controls().update(update).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(thresholds))
+}
+
+async fn clear_caches(State(sources): State<Sources>, Path(chain): Path<String>) -> ControlResult<ControlState> {
+    let source = find_chain(&sources, &chain)?;
+    source.clear_caches().await;
+    info!(chain = source.chain(), "Caches cleared through the control API");
+    Ok(Json(control_state_of(source.as_ref())))
+}
+
+async fn sweep_treasury(State(sources): State<Sources>, Path(chain): Path<String>) -> ControlResult<ControlState> {
+    let source = find_chain(&sources, &chain)?;
+    if let Err(e) = source.sweep_treasury().await {
+        warn!(chain = source.chain(), "Treasury sweep failed: {:?}", e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+    }
+    Ok(Json(control_state_of(source.as_ref())))
+}
@@ -0,0 +1,320 @@
+// src/atlas.rs
+use anyhow::{anyhow, Result};
+use ethers::{
+    abi::{encode, Token},
+    prelude::*,
+    utils::keccak256,
+};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::retry::{retry, transient_status, RetryPolicy};
+
+const SOLVER_OP_TYPE: &str = "SolverOperation(address from,address to,uint256 value,uint256 gas,uint256 maxFeePerGas,uint256 deadline,address solver,address control,bytes32 userOpHash,address bidToken,uint256 bidAmount,bytes data)";
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const DOMAIN_NAME: &str = "AtlasVerification";
+const DOMAIN_VERSION: &str = "1.0";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolverOperation {
+    pub from: Address,
+    // Atlas entrypoint
+    pub to: Address,
+    pub value: U256,
+    pub gas: U256,
+    pub max_fee_per_gas: U256,
+    // Block number after which the op is invalid
+    pub deadline: U256,
+    // Our solver contract
+    pub solver: Address,
+    // DAppControl the user op belongs to
+    pub control: Address,
+    pub user_op_hash: H256,
+    // Zero address bids in native MATIC
+    pub bid_token: Address,
+    pub bid_amount: U256,
+    pub data: Bytes,
+    pub signature: Bytes,
+}
+
+/// A user operation the relay is auctioning: a call from `from` into the dApp
+/// at `dapp`, guarded by `control`.
+#[derive(Debug, Clone)]
+pub struct UserOpNotice {
+    pub user_op_hash: H256,
+    pub from: Address,
+    pub dapp: Address,
+    pub control: Address,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+impl UserOpNotice {
+    /// The user's call as a pending transaction, for the analyzers that take one.
+    pub fn as_transaction(&self) -> Transaction {
+        Transaction {
+            hash: self.user_op_hash,
+            from: self.from,
+            to: Some(self.dapp),
+            value: self.value,
+            input: self.data.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AtlasConfig {
+    pub atlas: Address,
+    pub atlas_verification: Address,
+    pub chain_id: u64,
+    // Operations relay endpoint accepting solver operations
+    pub relay_url: String,
+    // The relay's solver websocket, streaming user operations up for auction
+    pub relay_ws_url: Option<String>,
+    // Share of simulated profit bid to the auctioneer, in bps
+    pub bid_share_bps: u64,
+    pub max_bid: U256,
+    pub solver_gas: U256,
+    pub deadline_blocks: u64,
+}
+
+/// Builds, signs (EIP-712) and submits Atlas solver operations.
+pub struct AtlasSolver {
+    config: AtlasConfig,
+    signer: LocalWallet,
+    solver_contract: Address,
+    http: reqwest::Client,
+    retry: RetryPolicy,
+}
+
+impl AtlasSolver {
+    pub fn new(config: AtlasConfig, signer: LocalWallet, solver_contract: Address) -> Self {
+        Self {
+            config,
+            signer,
+            solver_contract,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(2))
+                .build()
+                .expect("build http client"),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    // Bid a fixed share of simulated profit; whatever is left is ours
+    pub fn calculate_bid(&self, simulated_profit: U256) -> U256 {
+        let bid = simulated_profit * U256::from(self.config.bid_share_bps) / U256::from(10_000);
+        bid.min(self.config.max_bid)
+    }
+
+    pub async fn build_solver_op(
+        &self,
+        user_op_hash: H256,
+        control: Address,
+        data: Bytes,
+        simulated_profit: U256,
+        max_fee_per_gas: U256,
+        current_block: U64,
+    ) -> Result<SolverOperation> {
+        let mut op = SolverOperation {
+            from: self.signer.address(),
+            to: self.config.atlas,
+            value: U256::zero(),
+            gas: self.config.solver_gas,
+            max_fee_per_gas,
+            deadline: U256::from(current_block.as_u64() + self.config.deadline_blocks),
+            solver: self.solver_contract,
+            control,
+            user_op_hash,
+            bid_token: Address::zero(),
+            bid_amount: self.calculate_bid(simulated_profit),
+            data,
+            signature: Bytes::default(),
+        };
+
+        let digest = self.signing_digest(&op);
+        let signature = self.signer.sign_hash(H256::from(digest))?;
+        op.signature = Bytes::from(signature.to_vec());
+        Ok(op)
+    }
+
+    pub fn streams_user_ops(&self) -> bool {
+        self.config.relay_ws_url.is_some()
+    }
+
+    /// Forwards user operations from the relay into `sink`, reconnecting on failure.
+    pub async fn run_user_ops(&self, sink: mpsc::Sender<UserOpNotice>) {
+        let url = match &self.config.relay_ws_url {
+            Some(url) => url,
+            None => return,
+        };
+        loop {
+            match self.stream_user_ops(url, &sink).await {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!("Atlas user op feed dropped: {:?}, reconnecting", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    async fn stream_user_ops(&self, url: &str, sink: &mpsc::Sender<UserOpNotice>) -> Result<()> {
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await?;
+        let subscribe = json!({ "id": "1", "method": "subscribe", "params": { "topic": "newUserOperations" } });
+        ws.send(Message::Text(subscribe.to_string())).await?;
+        info!("Subscribed to Atlas user operations");
+
+        while let Some(message) = ws.next().await {
+            let text = match message? {
+                Message::Text(t) => t,
+                Message::Ping(p) => {
+                    ws.send(Message::Pong(p)).await?;
+                    continue;
+                }
+                Message::Close(_) => return Err(anyhow!("connection closed by relay")),
+                _ => continue,
+            };
+            let value: Value = serde_json::from_str(&text)?;
+            match Self::parse_user_op(&value) {
+                Some(op) => {
+                    if sink.send(op).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                // subscription acks and errors
+                None => debug!("Atlas relay message: {}", text),
+            }
+        }
+        Err(anyhow!("stream ended"))
+    }
+
+    fn parse_user_op(value: &Value) -> Option<UserOpNotice> {
+        let data = value.get("data")?;
+        let op = data.get("partialUserOperation")?;
+        let field = |name: &str| op.get(name).and_then(|v| v.as_str());
+        Some(UserOpNotice {
+            user_op_hash: data.get("userOpHash")?.as_str()?.parse().ok()?,
+            from: field("from")?.parse().ok()?,
+            dapp: field("dapp")?.parse().ok()?,
+            control: field("control")?.parse().ok()?,
+            value: field("value").and_then(|v| v.parse().ok()).unwrap_or_default(),
+            data: field("data")?.parse().ok()?,
+        })
+    }
+
+    pub async fn submit(&self, op: &SolverOperation) -> Result<()> {
+        let url = format!("{}/solverOperation", self.config.relay_url.trim_end_matches('/'));
+        let body = serde_json::json!({
+            "userOpHash": op.user_op_hash,
+            "solverOp": op,
+        });
+        let (url, body) = (&url, &body);
+        let response = retry(&self.retry, "solverOperation", || async move {
+            transient_status(self.http.post(url).json(body).send().await?)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Atlas relay rejected solver op ({}): {}", status, body));
+        }
+
+        info!(
+            "Submitted Atlas solver op for user op {:?} with bid {}",
+            op.user_op_hash, op.bid_amount
+        );
+        Ok(())
+    }
+
+    fn domain_separator(&self) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+            Token::FixedBytes(keccak256(DOMAIN_NAME).to_vec()),
+            Token::FixedBytes(keccak256(DOMAIN_VERSION).to_vec()),
+            Token::Uint(U256::from(self.config.chain_id)),
+            Token::Address(self.config.atlas_verification),
+        ]))
+    }
+
+    // The signature covers keccak256(data), not the raw bytes
+    fn struct_hash(op: &SolverOperation) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(SOLVER_OP_TYPE).to_vec()),
+            Token::Address(op.from),
+            Token::Address(op.to),
+            Token::Uint(op.value),
+            Token::Uint(op.gas),
+            Token::Uint(op.max_fee_per_gas),
+            Token::Uint(op.deadline),
+            Token::Address(op.solver),
+            Token::Address(op.control),
+            Token::FixedBytes(op.user_op_hash.as_bytes().to_vec()),
+            Token::Address(op.bid_token),
+            Token::Uint(op.bid_amount),
+            Token::FixedBytes(keccak256(&op.data).to_vec()),
+        ]))
+    }
+
+    fn signing_digest(&self, op: &SolverOperation) -> [u8; 32] {
+        let mut message = Vec::with_capacity(66);
+        message.extend_from_slice(&[0x19, 0x01]);
+        message.extend_from_slice(&self.domain_separator());
+        message.extend_from_slice(&Self::struct_hash(op));
+        keccak256(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generated independently from Atlas' SOLVER_TYPEHASH and EIP-712 domain,
+    // signed with the first anvil dev key
+    const TYPEHASH: &str = "57d8655e41f8b345d65e4e5aad590481b56d0ebc5b7a589653f3f46f30ba3076";
+    const DIGEST: &str = "d1a93f90f3fe0f768bc0e5c3779694bb2365aee69e15b6c744f495c7b288b63e";
+    const SIGNATURE: &str = "7b27b3d6686cb75ff4d80f76f429c4165678de100f97cab7cb02be410c2eab7004ba153f35089d7e9ef61aee1f7a735bfc3fbdf7e458bc386aef537d8297fc3c1c";
+
+    #[tokio::test]
+    async fn signs_solver_ops_as_atlas_verifies_them() {
+        let wallet: LocalWallet = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let solver = AtlasSolver::new(
+            AtlasConfig {
+                atlas: Address::repeat_byte(0x11),
+                atlas_verification: Address::repeat_byte(0x22),
+                chain_id: 137,
+                relay_url: String::new(),
+                relay_ws_url: None,
+                bid_share_bps: 1_000,
+                max_bid: U256::exp10(18),
+                solver_gas: U256::from(500_000),
+                deadline_blocks: 10,
+            },
+            wallet.clone(),
+            Address::repeat_byte(0x33),
+        );
+        let op = solver
+            .build_solver_op(
+                H256::repeat_byte(0x55),
+                Address::repeat_byte(0x44),
+                Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]),
+                U256::exp10(18),
+                U256::from(100) * U256::exp10(9),
+                U64::from(1_000),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(hex::encode(keccak256(SOLVER_OP_TYPE)), TYPEHASH);
+        assert_eq!(hex::encode(solver.signing_digest(&op)), DIGEST);
+        assert_eq!(hex::encode(&op.signature), SIGNATURE);
+    }
+}
@@ -0,0 +1,104 @@
+// src/backrun_merge.rs
+use ethers::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::call_tracer::PoolDelta;
+use crate::pool_state::PoolStateManager;
+
+/// Pools a victim moved, sorted, each with its direction (true when token0 went in).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BackrunKey(Vec<(Address, bool)>);
+
+impl BackrunKey {
+    pub fn of(deltas: &[PoolDelta]) -> Self {
+        let mut pools: Vec<_> = deltas.iter().map(|d| (d.pool, d.amount0.is_positive())).collect();
+        pools.sort();
+        pools.dedup();
+        Self(pools)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    victims: Vec<H256>,
+    // pool -> summed raw deltas of every victim in the group
+    deltas: HashMap<Address, (I256, I256)>,
+}
+
+/// The combined move of every victim sharing a key, and the victims whose
+/// queued backruns it replaces.
+#[derive(Debug, Clone)]
+pub struct MergedBackrun {
+    pub deltas: Vec<PoolDelta>,
+    pub superseded: Vec<H256>,
+}
+
+/// Groups pending victims that push the same pools the same way. Their backruns
+/// would compete for one dislocation on chain, so one backrun is sized on the
+/// combined move and the earlier ones are dropped.
+pub struct BackrunMerger {
+    pool_state: Arc<PoolStateManager>,
+    groups: Mutex<HashMap<BackrunKey, Group>>,
+}
+
+impl BackrunMerger {
+    pub fn new(pool_state: Arc<PoolStateManager>) -> Self {
+        Self {
+            pool_state,
+            groups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn merge(&self, victim: H256, deltas: &[PoolDelta]) -> MergedBackrun {
+        let mut groups = self.groups.lock().await;
+        let group = groups.entry(BackrunKey::of(deltas)).or_default();
+        if group.victims.contains(&victim) {
+            return MergedBackrun { deltas: deltas.to_vec(), superseded: Vec::new() };
+        }
+
+        let superseded = group.victims.clone();
+        group.victims.push(victim);
+        for delta in deltas {
+            let sum = group.deltas.entry(delta.pool).or_insert((I256::zero(), I256::zero()));
+            sum.0 += delta.amount0;
+            sum.1 += delta.amount1;
+        }
+        if superseded.is_empty() {
+            return MergedBackrun { deltas: deltas.to_vec(), superseded };
+        }
+
+        let mut merged = Vec::new();
+        for (pool, (amount0, amount1)) in &group.deltas {
+            merged.push(self.combined_delta(*pool, *amount0, *amount1).await);
+        }
+        debug!(victims = group.victims.len(), pools = merged.len(), "Merged backrun of {:?}", victim);
+        MergedBackrun { deltas: merged, superseded }
+    }
+
+    // Summed outputs overstate what sequential swaps pay out, so on tracked V2
+    // pools the output side is re-quoted on the summed input
+    async fn combined_delta(&self, pool: Address, amount0: I256, amount1: I256) -> PoolDelta {
+        let state = match self.pool_state.get(pool).await {
+            Some(state) => state,
+            None => return PoolDelta { pool, amount0, amount1 },
+        };
+        let (amount0, amount1) = if amount0.is_positive() {
+            let out = state.get_amount_out(state.token0, amount0.into_raw());
+            (amount0, -I256::from_raw(out))
+        } else if amount1.is_positive() {
+            let out = state.get_amount_out(state.token1, amount1.into_raw());
+            (-I256::from_raw(out), amount1)
+        } else {
+            (amount0, amount1)
+        };
+        PoolDelta { pool, amount0, amount1 }
+    }
+
+    /// Victims are only merged within a block; the next one starts clean.
+    pub async fn on_block(&self) {
+        self.groups.lock().await.clear();
+    }
+}
@@ -0,0 +1,160 @@
+// src/backtest.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::pool_state::PoolStateManager;
+use crate::shadow::tx_references_path;
+use crate::classifier::ClassifierChain;
+use crate::confidence::ConfidenceModel;
+use crate::path_constraints::PathConstraints;
+use crate::simulation_engine::AdvancedSimulationEngine;
+use crate::strategies::TriangularScanner;
+use crate::rpc_budget::RpcTransport;
+
+// Gas assumed for one flash-loan execution when estimating PnL
+const EXECUTION_GAS: u64 = 300_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestOpportunity {
+    pub block: u64,
+    pub source: String,
+    pub victim_tx: Option<H256>,
+    pub path: Vec<Address>,
+    pub amount_in: U256,
+    pub expected_profit: U256,
+    pub gas_cost: U256,
+    // false when another tx in the block already captured it
+    pub won: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub transactions_seen: usize,
+    pub transactions_decoded: usize,
+    pub opportunities: Vec<BacktestOpportunity>,
+    pub won: usize,
+    // Sum over won opportunities of expected profit minus gas, in wei of the loan token
+    pub estimated_pnl: I256,
+}
+
+/// Replays a historical block range through the decoders, simulation engine and
+/// strategies with state read at `block - 1`. Needs an archive node.
+pub struct Backtester {
+    provider: Arc<Provider<RpcTransport>>,
+    simulation_engine: AdvancedSimulationEngine,
+    classifier: ClassifierChain,
+    scanner: TriangularScanner,
+    pool_state: Arc<PoolStateManager>,
+}
+
+impl Backtester {
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        pool_state: Arc<PoolStateManager>,
+        scanner: TriangularScanner,
+        classifier: ClassifierChain,
+        constraints: PathConstraints,
+    ) -> Self {
+        Self {
+            simulation_engine: AdvancedSimulationEngine::new(
+                provider.clone(),
+                pool_state.clone(),
+                Arc::new(ConfidenceModel::default()),
+                constraints,
+            ),
+            classifier,
+            provider,
+            scanner,
+            pool_state,
+        }
+    }
+
+    pub async fn run(&self, from_block: u64, to_block: u64, tokens: &[Address]) -> Result<BacktestReport> {
+        self.pool_state.discover(tokens).await?;
+
+        let mut report = BacktestReport {
+            from_block,
+            to_block,
+            ..Default::default()
+        };
+
+        for number in from_block..=to_block {
+            let block = match self.provider.get_block_with_txs(number).await? {
+                Some(b) => b,
+                None => continue,
+            };
+            // Genesis has no parent state to replay against
+            let parent = match number.checked_sub(1) {
+                Some(parent) => U64::from(parent),
+                None => continue,
+            };
+            let gas_price = block.base_fee_per_gas.unwrap_or_default();
+            let gas_cost = gas_price * U256::from(EXECUTION_GAS);
+
+            // Pin the simulation fork to the parent too, not the live head
+            self.simulation_engine.on_new_head(parent, block.parent_hash).await;
+
+            // Opportunities at rest in the parent state; this also reloads the
+            // pool state the victim simulations below read, at the same block
+            for cycle in self.scanner.on_block(parent).await? {
+                let won = !block.transactions.iter().any(|tx| tx_references_path(tx, &cycle.path, &cycle.routers));
+                report.opportunities.push(BacktestOpportunity {
+                    block: number,
+                    source: "block_scan".to_string(),
+                    victim_tx: None,
+                    path: cycle.path,
+                    amount_in: cycle.amount_in,
+                    expected_profit: cycle.expected_profit,
+                    gas_cost,
+                    won,
+                });
+            }
+
+            // Opportunities triggered by the block's transactions
+            for tx in &block.transactions {
+                report.transactions_seen += 1;
+                if !self.classifier.classify(tx).is_swap() {
+                    continue;
+                }
+                report.transactions_decoded += 1;
+
+                let result = self.simulation_engine.simulate_multi_dex_arbitrage(tx, 3).await?;
+                if result.expected_profit <= gas_cost || result.optimal_path.is_empty() {
+                    continue;
+                }
+
+                // Anyone after the victim in the same block touching the path beat us to it
+                let position = tx.transaction_index.unwrap_or_default().as_usize();
+                let won = !block
+                    .transactions
+                    .iter()
+                    .skip(position + 1)
+                    .any(|other| tx_references_path(other, &result.optimal_path, &[]));
+
+                report.opportunities.push(BacktestOpportunity {
+                    block: number,
+                    source: "mempool".to_string(),
+                    victim_tx: Some(tx.hash),
+                    path: result.optimal_path,
+                    amount_in: U256::exp10(18),
+                    expected_profit: result.expected_profit,
+                    gas_cost,
+                    won,
+                });
+            }
+
+            info!("Backtested block {} ({} opportunities so far)", number, report.opportunities.len());
+        }
+
+        for opportunity in report.opportunities.iter().filter(|o| o.won) {
+            report.won += 1;
+            report.estimated_pnl += I256::from_raw(opportunity.expected_profit) - I256::from_raw(opportunity.gas_cost);
+        }
+        Ok(report)
+    }
+}
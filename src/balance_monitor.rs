@@ -0,0 +1,133 @@
+// src/balance_monitor.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::flash_loan::IERC20Balance;
+use crate::native::native_balance;
+use crate::rpc_budget::RpcTransport;
+
+#[derive(Debug, Clone)]
+pub struct BalanceConfig {
+    // Submissions whose worst-case gas would take the EOA below this are refused
+    pub min_eoa_balance: U256,
+    // Alert (and refill, if enabled) once the EOA drops below this
+    pub low_balance: U256,
+    // Refills top the EOA back up to this
+    pub refill_target: U256,
+    pub auto_refill: bool,
+    // Receives a JSON POST on each low-balance alert
+    pub alert_webhook: Option<String>,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        let matic = U256::exp10(18);
+        Self {
+            min_eoa_balance: matic * 5,
+            low_balance: matic * 20,
+            refill_target: matic * 50,
+            auto_refill: false,
+            alert_webhook: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Balances {
+    pub block: U64,
+    pub eoa_native: U256,
+    pub executor_tokens: HashMap<Address, U256>,
+}
+
+/// Signer MATIC and executor token balances, re-read every block.
+pub struct BalanceMonitor {
+    provider: Arc<Provider<RpcTransport>>,
+    config: BalanceConfig,
+    eoa: Address,
+    executor: Address,
+    tokens: Vec<Address>,
+    balances: Mutex<Balances>,
+    // One alert per dip below `low_balance`, not one per block
+    alerted: Mutex<bool>,
+    http: reqwest::Client,
+}
+
+impl BalanceMonitor {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, config: BalanceConfig, eoa: Address, executor: Address, tokens: Vec<Address>) -> Self {
+        Self {
+            provider,
+            config,
+            eoa,
+            executor,
+            tokens,
+            balances: Mutex::new(Balances::default()),
+            alerted: Mutex::new(false),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Refreshes balances at `block`. When the EOA first drops below `low_balance`,
+    /// alerts and, if auto-refill is on, returns how much MATIC a refill should add.
+    pub async fn on_block(&self, block: U64) -> Result<Option<U256>> {
+        let eoa_native = native_balance(&self.provider, self.eoa, Some(block)).await?;
+        let mut executor_tokens = HashMap::new();
+        for token in &self.tokens {
+            let balance = IERC20Balance::new(*token, self.provider.clone())
+                .balance_of(self.executor)
+                .block(block)
+                .call()
+                .await?;
+            executor_tokens.insert(*token, balance);
+        }
+        debug!(block = block.as_u64(), eoa_native = %eoa_native, "Balances");
+        *self.balances.lock().await = Balances { block, eoa_native, executor_tokens };
+
+        let mut alerted = self.alerted.lock().await;
+        if eoa_native >= self.config.low_balance {
+            if *alerted {
+                info!("Signer balance recovered to {}", eoa_native);
+            }
+            *alerted = false;
+            return Ok(None);
+        }
+        if *alerted {
+            return Ok(None);
+        }
+        *alerted = true;
+        self.alert(eoa_native).await;
+        Ok(self.config.auto_refill.then(|| self.config.refill_target.saturating_sub(eoa_native)))
+    }
+
+    async fn alert(&self, balance: U256) {
+        error!("Signer {:?} gas balance low: {} wei (alert below {})", self.eoa, balance, self.config.low_balance);
+        let url = match &self.config.alert_webhook {
+            Some(url) => url,
+            None => return,
+        };
+        let body = json!({
+            "alert": "low_gas_balance",
+            "signer": format!("{:?}", self.eoa),
+            "balance_wei": balance.to_string(),
+            "threshold_wei": self.config.low_balance.to_string(),
+        });
+        if let Err(e) = self.http.post(url).json(&body).send().await {
+            warn!("Failed to deliver balance alert: {:?}", e);
+        }
+    }
+
+    /// False when paying `max_gas_cost` would leave the EOA below the floor. Always
+    /// true before the first block has been read.
+    pub async fn can_afford(&self, max_gas_cost: U256) -> bool {
+        let balances = self.balances.lock().await;
+        balances.block.is_zero() || balances.eoa_native >= max_gas_cost + self.config.min_eoa_balance
+    }
+
+    pub async fn balances(&self) -> Balances {
+        self.balances.lock().await.clone()
+    }
+}
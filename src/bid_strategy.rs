@@ -0,0 +1,68 @@
+// src/bid_strategy.rs
+use ethers::types::U256;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::competition::BiddingModel;
+
+#[derive(Debug, Clone)]
+pub struct BidConfig {
+    // Hard cap on the priority fee per gas
+    pub max_tip: U256,
+    // Share of expected profit bid when we have no history, in bps
+    pub initial_k_bps: u64,
+    pub min_k_bps: u64,
+    pub max_k_bps: u64,
+    // Win rate we're willing to pay for; below it k rises, above it k falls
+    pub target_win_rate: f64,
+}
+
+impl Default for BidConfig {
+    fn default() -> Self {
+        Self {
+            max_tip: U256::from(500_000_000_000u64), // 500 gwei
+            initial_k_bps: 2_000,
+            min_k_bps: 500,
+            max_k_bps: 9_000,
+            target_win_rate: 0.5,
+        }
+    }
+}
+
+/// Sizes the priority fee as `min(max_tip, k% of expected_profit)` spread over the
+/// gas limit, with k adapting per strategy to the recent win rate.
+pub struct BidStrategy {
+    config: BidConfig,
+    model: Arc<Mutex<BiddingModel>>,
+}
+
+impl BidStrategy {
+    pub fn new(config: BidConfig, model: Arc<Mutex<BiddingModel>>) -> Self {
+        Self { config, model }
+    }
+
+    pub async fn k_bps(&self, strategy: &str) -> u64 {
+        let win_rate = match self.model.lock().await.win_rate(strategy) {
+            Some(rate) => rate,
+            None => return self.config.initial_k_bps,
+        };
+        // k moves with the miss against the target: at a 0.5 target, losing
+        // everything is 1.5x and winning everything 0.5x
+        let scale = 1.0 + (self.config.target_win_rate - win_rate);
+        let k = (self.config.initial_k_bps as f64 * scale.max(0.5)) as u64;
+        k.clamp(self.config.min_k_bps, self.config.max_k_bps)
+    }
+
+    /// Tip per gas for `expected_profit`, which must be in native wei like the tip.
+    pub async fn priority_fee(&self, strategy: &str, expected_profit: U256, gas_limit: U256) -> U256 {
+        if gas_limit.is_zero() {
+            return U256::zero();
+        }
+        let k_bps = self.k_bps(strategy).await;
+        let bribe = expected_profit * U256::from(k_bps) / U256::from(10_000);
+        let tip = (bribe / gas_limit).min(self.config.max_tip);
+        debug!(strategy, k_bps, %tip, "Sized priority fee");
+        tip
+    }
+}
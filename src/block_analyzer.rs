@@ -0,0 +1,171 @@
+// src/block_analyzer.rs
+use anyhow::Result;
+use ethers::{prelude::*, utils::keccak256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::cache::BlockLruCache;
+use crate::pool_state::PoolStateManager;
+use crate::storage::{MissedOpportunityRecord, Storage};
+use crate::token_registry::units;
+use crate::v3_ticks::V3PoolState;
+use crate::rpc_budget::RpcTransport;
+
+// Two pools more than this far apart after a block is more than both fees can explain
+const MIN_DISLOCATION_BPS: u64 = 80;
+
+#[derive(Debug, Clone, Default)]
+pub struct CoverageStats {
+    pub swaps: usize,
+    pub seen_in_mempool: usize,
+    pub missed: usize,
+}
+
+/// Decodes every V2/V3 `Swap` in a landed block and, for the pools we track,
+/// flags swaps that left a pair dislocated across pools without us capturing it.
+pub struct BlockAnalyzer {
+    provider: Arc<Provider<RpcTransport>>,
+    pool_state: Arc<PoolStateManager>,
+    storage: Option<Storage>,
+    executor: Address,
+}
+
+fn v2_swap_topic() -> H256 {
+    H256::from(keccak256("Swap(address,uint256,uint256,uint256,uint256,address)"))
+}
+
+// token1 per token0 in raw units; only ever compared between pools of one pair
+fn v2_price(reserve0: U256, reserve1: U256) -> Option<f64> {
+    if reserve0.is_zero() || reserve1.is_zero() {
+        return None;
+    }
+    Some(units(I256::from_raw(reserve1), 0) / units(I256::from_raw(reserve0), 0))
+}
+
+fn v3_price(pool: &V3PoolState) -> Option<f64> {
+    if pool.sqrt_price_x96.is_zero() || pool.liquidity == 0 {
+        return None;
+    }
+    let sqrt = units(I256::from_raw(pool.sqrt_price_x96), 0) / 2f64.powi(96);
+    Some(sqrt * sqrt)
+}
+
+impl BlockAnalyzer {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, pool_state: Arc<PoolStateManager>, storage: Option<Storage>, executor: Address) -> Self {
+        Self {
+            provider,
+            pool_state,
+            storage,
+            executor,
+        }
+    }
+
+    // Post-block price of any tracked pool, plus its pair
+    async fn pool_price(&self, pool: Address) -> Option<(Address, Address, f64)> {
+        if let Some(p) = self.pool_state.get(pool).await {
+            return v2_price(p.reserve0, p.reserve1).map(|price| (p.token0, p.token1, price));
+        }
+        let p = self.pool_state.get_v3(pool).await?;
+        v3_price(&p).map(|price| (p.token0, p.token1, price))
+    }
+
+    async fn pair_prices(&self, token0: Address, token1: Address) -> Vec<(Address, f64)> {
+        let mut prices: Vec<(Address, f64)> = self
+            .pool_state
+            .pools_for_pair(token0, token1)
+            .await
+            .into_iter()
+            .filter_map(|p| v2_price(p.reserve0, p.reserve1).map(|price| (p.address, price)))
+            .collect();
+        prices.extend(
+            self.pool_state
+                .v3_pools_for_pair(token0, token1)
+                .await
+                .iter()
+                .filter_map(|p| v3_price(p).map(|price| (p.address, price))),
+        );
+        prices
+    }
+
+    /// Expects pool state to already reflect `block`. `seen` is the mempool dedup
+    /// set, used to tag whether we had the swap before it landed.
+    pub async fn on_block(&self, block: U64, seen: &BlockLruCache<H256, ()>) -> Result<CoverageStats> {
+        let filter = Filter::new()
+            .topic0(vec![v2_swap_topic(), V3PoolState::swap_topic()])
+            .from_block(block)
+            .to_block(block);
+        let logs = self.provider.get_logs(&filter).await?;
+
+        let mut stats = CoverageStats::default();
+        // Last swap per pool decides where it ended the block
+        let mut last_swap: HashMap<Address, H256> = HashMap::new();
+        for log in &logs {
+            let tx_hash = match log.transaction_hash {
+                Some(h) => h,
+                None => continue,
+            };
+            stats.swaps += 1;
+            if seen.contains(&tx_hash) {
+                stats.seen_in_mempool += 1;
+            }
+            last_swap.insert(log.address, tx_hash);
+        }
+
+        // Anything our executor touched this block counts as captured
+        let captured: Vec<H256> = match self.provider.get_block_with_txs(block).await? {
+            Some(b) => b.transactions.iter().filter(|tx| tx.to == Some(self.executor)).map(|tx| tx.hash).collect(),
+            None => Vec::new(),
+        };
+
+        for (pool, tx_hash) in last_swap {
+            let (token0, token1, price) = match self.pool_price(pool).await {
+                Some(p) => p,
+                None => continue,
+            };
+            let widest = self
+                .pair_prices(token0, token1)
+                .await
+                .into_iter()
+                .filter(|(other, _)| *other != pool)
+                .map(|(other, other_price)| {
+                    let bps = ((price - other_price).abs() / price.min(other_price) * 10_000.0) as u64;
+                    (other, bps)
+                })
+                .max_by_key(|(_, bps)| *bps);
+
+            let (counter_pool, dislocation_bps) = match widest {
+                Some(w) if w.1 >= MIN_DISLOCATION_BPS && captured.is_empty() => w,
+                _ => continue,
+            };
+
+            let record = MissedOpportunityRecord {
+                block,
+                tx_hash,
+                pool,
+                counter_pool,
+                token0,
+                token1,
+                dislocation_bps,
+                seen_in_mempool: seen.contains(&tx_hash),
+            };
+            info!(
+                "Missed {} bps dislocation {:?} vs {:?} after {:?} (seen in mempool: {})",
+                dislocation_bps, pool, counter_pool, tx_hash, record.seen_in_mempool
+            );
+            stats.missed += 1;
+            if let Some(storage) = &self.storage {
+                storage.record_missed_opportunity(&record).await?;
+            }
+        }
+
+        debug!(
+            block = block.as_u64(),
+            swaps = stats.swaps,
+            seen_in_mempool = stats.seen_in_mempool,
+            missed = stats.missed,
+            "Block coverage"
+        );
+        Ok(stats)
+    }
+}
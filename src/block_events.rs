@@ -0,0 +1,51 @@
+// src/block_events.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::event_bus::Topic;
+use crate::rpc_budget::RpcTransport;
+
+#[derive(Debug, Clone)]
+pub struct BlockEvent {
+    pub number: U64,
+    pub hash: H256,
+    pub base_fee: Option<U256>,
+    pub gas_used: U256,
+    pub gas_limit: U256,
+    pub block: Arc<Block<TxHash>>,
+}
+
+impl BlockEvent {
+    pub fn transactions(&self) -> &[H256] {
+        &self.block.transactions
+    }
+}
+
+/// Publishes each `newHeads` block to every component that advances with the chain.
+/// Slow subscribers lag and skip blocks rather than holding up the others.
+pub async fn publish_heads(provider: Arc<Provider<RpcTransport>>, blocks: &Topic<BlockEvent>) -> Result<()> {
+    let mut heads = provider.subscribe_blocks().await?;
+    info!("Subscribed to newHeads");
+
+    while let Some(block) = heads.next().await {
+        let (number, hash) = match (block.number, block.hash) {
+            (Some(n), Some(h)) => (n, h),
+            _ => continue,
+        };
+        let event = BlockEvent {
+            number,
+            hash,
+            base_fee: block.base_fee_per_gas,
+            gas_used: block.gas_used,
+            gas_limit: block.gas_limit,
+            block: Arc::new(block),
+        };
+        // No receivers yet is fine; they pick up from the next head
+        if blocks.publish(event) == 0 {
+            debug!("No block subscribers for {}", number);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+// src/block_timing.rs
+use ethers::prelude::*;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::chain::ChainProfile;
+use crate::rpc_budget::RpcTransport;
+
+// Weight of the newest sample in the block interval and latency averages
+const SMOOTHING: f64 = 0.1;
+// Gaps longer than this many block times are stalls or reconnects, not cadence
+const MAX_INTERVAL_FACTOR: u32 = 5;
+
+/// Where the chain is in its block cadence, as of now.
+#[derive(Debug, Clone)]
+pub struct BlockTiming {
+    pub head: U64,
+    // Zero once the next block is overdue
+    pub time_to_next_block: Duration,
+    pub block_time: Duration,
+    // Bor only: the validator producing the current sprint, once looked up
+    pub proposer: Option<Address>,
+    pub sprint_blocks_left: Option<u64>,
+}
+
+struct State {
+    head: U64,
+    head_seen: Option<Instant>,
+    block_time: Duration,
+    proposer: Option<Address>,
+    // Decision to submission, averaged over recent executions
+    latency: Duration,
+}
+
+/// Tracks head arrival times (and on Bor, sprint proposers) so the scheduler
+/// knows whether a submission can still make the next block.
+pub struct BlockTimingModel {
+    provider: Arc<Provider<RpcTransport>>,
+    sprint_length: Option<u64>,
+    // Latency never assumed below this, whatever the average says
+    min_latency: Duration,
+    state: RwLock<State>,
+}
+
+impl BlockTimingModel {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, chain: &ChainProfile, min_latency: Duration) -> Self {
+        Self {
+            provider,
+            sprint_length: chain.sprint_length,
+            min_latency,
+            state: RwLock::new(State {
+                head: U64::zero(),
+                head_seen: None,
+                block_time: chain.block_time,
+                proposer: None,
+                latency: min_latency,
+            }),
+        }
+    }
+
+    pub async fn on_block(&self, number: U64) {
+        let now = Instant::now();
+        let sprint_start = {
+            let mut state = self.state.write().unwrap();
+            if let Some(seen) = state.head_seen {
+                let blocks = number.saturating_sub(state.head).as_u64().max(1) as u32;
+                let interval = now.duration_since(seen) / blocks;
+                if number > state.head && interval < state.block_time * MAX_INTERVAL_FACTOR {
+                    state.block_time = ewma(state.block_time, interval);
+                }
+            }
+            state.head = number;
+            state.head_seen = Some(now);
+            match self.sprint_length {
+                Some(length) => number.as_u64() % length == 0 || state.proposer.is_none(),
+                None => false,
+            }
+        };
+        if sprint_start {
+            let proposer = self.fetch_proposer(number).await;
+            if let Some(proposer) = proposer {
+                info!(block = %number, ?proposer, "Sprint proposer");
+            }
+            self.state.write().unwrap().proposer = proposer;
+        }
+    }
+
+    // Bor signs headers rather than setting the coinbase, so the producer comes
+    // from the node's bor namespace
+    async fn fetch_proposer(&self, number: U64) -> Option<Address> {
+        match self.provider.request::<_, Address>("bor_getAuthor", [number]).await {
+            Ok(author) => Some(author),
+            Err(e) => {
+                debug!("bor_getAuthor unavailable for {}: {:?}", number, e);
+                None
+            }
+        }
+    }
+
+    pub fn timing(&self) -> BlockTiming {
+        let state = self.state.read().unwrap();
+        let elapsed = state.head_seen.map(|seen| seen.elapsed()).unwrap_or_default();
+        BlockTiming {
+            head: state.head,
+            time_to_next_block: state.block_time.saturating_sub(elapsed),
+            block_time: state.block_time,
+            proposer: state.proposer,
+            sprint_blocks_left: self.sprint_length.map(|length| length - state.head.as_u64() % length),
+        }
+    }
+
+    /// Since the last head arrived; None before the first.
+    pub fn head_age(&self) -> Option<Duration> {
+        self.state.read().unwrap().head_seen.map(|seen| seen.elapsed())
+    }
+
+    /// Time from execution decision to the venue accepting the submission.
+    pub fn record_latency(&self, latency: Duration) {
+        let mut state = self.state.write().unwrap();
+        state.latency = ewma(state.latency, latency);
+    }
+
+    pub fn latency(&self) -> Duration {
+        self.state.read().unwrap().latency.max(self.min_latency)
+    }
+
+    /// Err with the reason when what's left of the block interval is shorter
+    /// than our simulation and submission take, so the next block would be missed.
+    pub fn check_window(&self) -> std::result::Result<BlockTiming, String> {
+        let timing = self.timing();
+        let latency = self.latency();
+        // Before the first head there's nothing to time against
+        if timing.head.is_zero() || timing.time_to_next_block >= latency {
+            return Ok(timing);
+        }
+        Err(format!(
+            "block timing: {}ms to block {}, submission takes {}ms",
+            timing.time_to_next_block.as_millis(),
+            timing.head + 1,
+            latency.as_millis()
+        ))
+    }
+}
+
+fn ewma(current: Duration, sample: Duration) -> Duration {
+    current.mul_f64(1.0 - SMOOTHING) + sample.mul_f64(SMOOTHING)
+}
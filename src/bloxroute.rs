@@ -0,0 +1,177 @@
+// src/bloxroute.rs
+use anyhow::{anyhow, Result};
+use ethers::types::{Bytes, Transaction, H256};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+use tracing::{debug, info, warn};
+
+use crate::raw_tx::decode_raw;
+use crate::retry::{retry, transient_status, RetryPolicy};
+
+pub const DEFAULT_WS_URL: &str = "wss://api.blxr.com/ws";
+pub const DEFAULT_API_URL: &str = "https://api.blxr.com";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BloxrouteStream {
+    // Txs as soon as the BDN sees them, before mempool validation
+    NewTxs,
+    // Txs accepted into a node's mempool
+    PendingTxs,
+}
+
+impl BloxrouteStream {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BloxrouteStream::NewTxs => "newTxs",
+            BloxrouteStream::PendingTxs => "pendingTxs",
+        }
+    }
+}
+
+/// bloXroute BDN client: pending tx feed over WS and submission via the Cloud API.
+#[derive(Clone)]
+pub struct BloxrouteClient {
+    ws_url: String,
+    api_url: String,
+    auth_header: String,
+    // e.g. Polygon-Mainnet
+    network: String,
+    http: reqwest::Client,
+    retry: RetryPolicy,
+}
+
+impl BloxrouteClient {
+    pub fn new(auth_header: impl Into<String>, network: impl Into<String>) -> Self {
+        Self::with_urls(auth_header, network, DEFAULT_WS_URL, DEFAULT_API_URL)
+    }
+
+    pub fn with_urls(
+        auth_header: impl Into<String>,
+        network: impl Into<String>,
+        ws_url: impl Into<String>,
+        api_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            api_url: api_url.into(),
+            auth_header: auth_header.into(),
+            network: network.into(),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(3))
+                .build()
+                .expect("build http client"),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Forwards decoded transactions into `sink`, reconnecting on failure.
+    /// Returns once the receiving side is dropped.
+    pub async fn run_feed(&self, stream: BloxrouteStream, sink: mpsc::Sender<Transaction>) {
+        loop {
+            match self.stream_once(stream, &sink).await {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!("bloXroute {} feed dropped: {:?}, reconnecting", stream.as_str(), e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    async fn stream_once(&self, stream: BloxrouteStream, sink: &mpsc::Sender<Transaction>) -> Result<()> {
+        let mut request = self.ws_url.as_str().into_client_request()?;
+        request.headers_mut().insert("Authorization", self.auth_header.parse()?);
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(request).await?;
+        let subscribe = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "subscribe",
+            "params": [stream.as_str(), { "include": ["tx_hash", "raw_tx", "tx_contents"] }],
+        });
+        ws.send(Message::Text(subscribe.to_string())).await?;
+        info!("Subscribed to bloXroute {} feed", stream.as_str());
+
+        while let Some(message) = ws.next().await {
+            let text = match message? {
+                Message::Text(t) => t,
+                Message::Ping(p) => {
+                    ws.send(Message::Pong(p)).await?;
+                    continue;
+                }
+                Message::Close(_) => return Err(anyhow!("connection closed by server")),
+                _ => continue,
+            };
+
+            let value: Value = serde_json::from_str(&text)?;
+            let result = match value.pointer("/params/result") {
+                Some(r) => r,
+                // subscription acks and errors
+                None => {
+                    debug!("bloXroute message: {}", text);
+                    continue;
+                }
+            };
+
+            if let Some(tx) = Self::parse_tx(result) {
+                if sink.send(tx).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Err(anyhow!("stream ended"))
+    }
+
+    // The signed raw tx when it's there; the JSON contents otherwise
+    fn parse_tx(result: &Value) -> Option<Transaction> {
+        if let Some(raw) = result.get("rawTx").and_then(|r| r.as_str()) {
+            match hex::decode(raw.trim_start_matches("0x")).map_err(anyhow::Error::from).and_then(|raw| decode_raw(&raw)) {
+                Ok(tx) => return Some(tx),
+                Err(e) => debug!("Falling back to bloXroute tx contents: {:?}", e),
+            }
+        }
+        let mut contents = result.get("txContents")?.clone();
+        // bloXroute puts the hash alongside rather than inside the contents
+        if contents.get("hash").is_none() {
+            contents["hash"] = result.get("txHash")?.clone();
+        }
+        serde_json::from_value(contents).ok()
+    }
+
+    /// Submit a signed raw transaction through the BDN.
+    pub async fn send_transaction(&self, raw: &Bytes) -> Result<H256> {
+        let body = json!({
+            "id": 1,
+            "method": "blxr_tx",
+            "params": {
+                "transaction": hex::encode(raw),
+                "blockchain_network": self.network,
+            },
+        });
+
+        let body = &body;
+        let response: Value = retry(&self.retry, "blxr_tx", || async move {
+            let response = self
+                .http
+                .post(&self.api_url)
+                .header("Authorization", &self.auth_header)
+                .json(body)
+                .send()
+                .await?;
+            Ok(transient_status(response)?.json().await?)
+        })
+        .await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("blxr_tx failed: {}", error));
+        }
+        let hash = response
+            .pointer("/result/txHash")
+            .and_then(|h| h.as_str())
+            .ok_or_else(|| anyhow!("blxr_tx returned no hash"))?;
+        Ok(format!("0x{}", hash.trim_start_matches("0x")).parse()?)
+    }
+}
@@ -0,0 +1,242 @@
+// src/bridge_flow.rs
+use anyhow::Result;
+use ethers::abi::{Abi, AbiParser, Token};
+use ethers::prelude::*;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::rpc_budget::RpcTransport;
+
+// Ethereum-side entrypoint of the Polygon PoS bridge
+pub const ROOT_CHAIN_MANAGER: &str = "0xA0c68C638235ee32657e8f720a23ceC1bFc77C77";
+// Across SpokePool on Polygon; relayers fill deposits from other chains here
+pub const ACROSS_SPOKE_POOL: &str = "0x9295ee1d8C5b022Be115A2AD3c30C72E34e7F096";
+
+// PoS deposits are minted on Polygon by a state sync once the L1 block is
+// final, about 20 minutes after it is mined
+const STATE_SYNC_DELAY: Duration = Duration::from_secs(20 * 60);
+// How long after arrival the recipient is still expected to move the tokens
+const INFLOW_GRACE: Duration = Duration::from_secs(10 * 60);
+
+static ROOT_CHAIN_MANAGER_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function depositFor(address,address,bytes)",
+        "function depositEtherFor(address)",
+        "function rootToChildToken(address) view returns (address)",
+    ]).expect("parse root chain manager abi")
+});
+
+// FxPortal's ERC20 tunnel (Ethereum side)
+static FX_ROOT_TUNNEL_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function deposit(address,address,uint256,bytes)",
+        "function rootToChildTokens(address) view returns (address)",
+    ]).expect("parse fx root tunnel abi")
+});
+
+static ACROSS_SPOKE_POOL_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function fillV3Relay((address,address,address,address,address,uint256,uint256,uint256,uint32,uint32,uint32,bytes),uint256)",
+    ]).expect("parse across spoke pool abi")
+});
+
+/// Tokens on their way to a recipient on this chain through a bridge.
+#[derive(Debug, Clone)]
+pub struct BridgeInflow {
+    // The token as it exists on this chain
+    pub token: Address,
+    pub amount: U256,
+    pub recipient: Address,
+    pub source: &'static str,
+    pub tx_hash: H256,
+    pub arrives: Instant,
+}
+
+/// Anticipates large token inflows from bridge traffic: PoS bridge and FxPortal
+/// deposits decoded on Ethereum (they land here after the state sync) and
+/// Across fills pending on this chain (they land with the fill). Recipients
+/// of bridged funds tend to swap them soon after arrival, so the strategy layer
+/// pre-loads the token's pools and watches the recipient's txs.
+pub struct BridgeFlowMonitor {
+    l1: Option<Arc<Provider<RpcTransport>>>,
+    root_chain_manager: Address,
+    fx_root_tunnels: Vec<Address>,
+    spoke_pool: Address,
+    // (bridge, root token) -> token here, resolved through the bridge's own mapping
+    child_tokens: RwLock<HashMap<(Address, Address), Option<Address>>>,
+    inflows: RwLock<Vec<BridgeInflow>>,
+}
+
+impl BridgeFlowMonitor {
+    pub fn new(l1: Option<Arc<Provider<RpcTransport>>>, fx_root_tunnels: Vec<Address>) -> Self {
+        Self {
+            l1,
+            root_chain_manager: ROOT_CHAIN_MANAGER.parse().unwrap(),
+            fx_root_tunnels,
+            spoke_pool: ACROSS_SPOKE_POOL.parse().unwrap(),
+            child_tokens: RwLock::new(HashMap::new()),
+            inflows: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// An Across fill pending here: the recipient holds the tokens once it lands.
+    pub fn decode_fill(&self, tx: &Transaction) -> Option<BridgeInflow> {
+        if tx.to != Some(self.spoke_pool) {
+            return None;
+        }
+        let function = ACROSS_SPOKE_POOL_ABI.function("fillV3Relay").ok()?;
+        if tx.input.get(..4)? != function.short_signature() {
+            return None;
+        }
+        let tokens = function.decode_input(&tx.input[4..]).ok()?;
+        let relay = tokens.into_iter().next()?.into_tuple()?;
+        Some(BridgeInflow {
+            token: relay.get(4)?.clone().into_address()?,
+            amount: relay.get(6)?.clone().into_uint()?,
+            recipient: relay.get(1)?.clone().into_address()?,
+            source: "across",
+            tx_hash: tx.hash,
+            arrives: Instant::now(),
+        })
+    }
+
+    /// Follows Ethereum blocks and returns PoS bridge / FxPortal deposits as
+    /// they are mined. Ends when the L1 subscription does; errors without an
+    /// L1 provider.
+    pub async fn watch_l1(&self, mut on_deposit: impl FnMut(BridgeInflow)) -> Result<()> {
+        let l1 = self.l1.clone().ok_or_else(|| anyhow::anyhow!("No L1 provider for bridge flow"))?;
+        let mut blocks = l1.subscribe_blocks().await?;
+        while let Some(head) = blocks.next().await {
+            let number = match head.number {
+                Some(number) => number,
+                None => continue,
+            };
+            let block = match l1.get_block_with_txs(number).await {
+                Ok(Some(block)) => block,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Fetching L1 block {} failed: {:?}", number, e);
+                    continue;
+                }
+            };
+            for tx in &block.transactions {
+                match self.decode_deposit(&l1, tx).await {
+                    Ok(Some(inflow)) => on_deposit(inflow),
+                    Ok(None) => {}
+                    Err(e) => debug!("Decoding L1 deposit {:?} failed: {:?}", tx.hash, e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn decode_deposit(&self, l1: &Arc<Provider<RpcTransport>>, tx: &Transaction) -> Result<Option<BridgeInflow>> {
+        let to = match tx.to {
+            Some(to) => to,
+            None => return Ok(None),
+        };
+        let selector = match tx.input.get(..4) {
+            Some(selector) => selector,
+            None => return Ok(None),
+        };
+        let (bridge, root_token, recipient, amount, source) = if to == self.root_chain_manager {
+            let deposit_for = ROOT_CHAIN_MANAGER_ABI.function("depositFor")?;
+            let deposit_ether_for = ROOT_CHAIN_MANAGER_ABI.function("depositEtherFor")?;
+            if selector == deposit_for.short_signature() {
+                let args = deposit_for.decode_input(&tx.input[4..])?;
+                // ERC20 predicate: depositData is the amount; anything else isn't fungible
+                let amount = match args.get(2).cloned().and_then(Token::into_bytes) {
+                    Some(data) if data.len() == 32 => U256::from_big_endian(&data),
+                    _ => return Ok(None),
+                };
+                (to, address_arg(&args, 1), address_arg(&args, 0), amount, "pos_bridge")
+            } else if selector == deposit_ether_for.short_signature() {
+                let args = deposit_ether_for.decode_input(&tx.input[4..])?;
+                // The manager maps ether under its own placeholder address
+                let ether: Address = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE".parse().unwrap();
+                (to, Some(ether), address_arg(&args, 0), tx.value, "pos_bridge")
+            } else {
+                return Ok(None);
+            }
+        } else if self.fx_root_tunnels.contains(&to) {
+            let deposit = FX_ROOT_TUNNEL_ABI.function("deposit")?;
+            if selector != deposit.short_signature() {
+                return Ok(None);
+            }
+            let args = deposit.decode_input(&tx.input[4..])?;
+            let amount = args.get(2).cloned().and_then(Token::into_uint).unwrap_or_default();
+            (to, address_arg(&args, 0), address_arg(&args, 1), amount, "fx_portal")
+        } else {
+            return Ok(None);
+        };
+
+        let (root_token, recipient) = match (root_token, recipient) {
+            (Some(root_token), Some(recipient)) => (root_token, recipient),
+            _ => return Ok(None),
+        };
+        let token = match self.child_token(l1, bridge, root_token).await? {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+        Ok(Some(BridgeInflow {
+            token,
+            amount,
+            recipient,
+            source,
+            tx_hash: tx.hash,
+            arrives: Instant::now() + STATE_SYNC_DELAY,
+        }))
+    }
+
+    async fn child_token(&self, l1: &Arc<Provider<RpcTransport>>, bridge: Address, root_token: Address) -> Result<Option<Address>> {
+        if let Some(token) = self.child_tokens.read().await.get(&(bridge, root_token)) {
+            return Ok(*token);
+        }
+        let (abi, getter) = if bridge == self.root_chain_manager {
+            (ROOT_CHAIN_MANAGER_ABI.clone(), "rootToChildToken")
+        } else {
+            (FX_ROOT_TUNNEL_ABI.clone(), "rootToChildTokens")
+        };
+        let child: Address = Contract::new(bridge, abi, l1.clone())
+            .method::<_, Address>(getter, root_token)?
+            .call()
+            .await?;
+        // Unmapped tokens can't be bridged, so the deposit will revert
+        let token = (!child.is_zero()).then_some(child);
+        self.child_tokens.write().await.insert((bridge, root_token), token);
+        Ok(token)
+    }
+
+    pub async fn record(&self, inflow: BridgeInflow) {
+        info!(
+            source = inflow.source,
+            token = ?inflow.token,
+            amount = %inflow.amount,
+            recipient = ?inflow.recipient,
+            eta_secs = inflow.arrives.saturating_duration_since(Instant::now()).as_secs(),
+            "Bridge inflow"
+        );
+        let mut inflows = self.inflows.write().await;
+        inflows.retain(|i| i.arrives + INFLOW_GRACE > Instant::now());
+        inflows.push(inflow);
+    }
+
+    /// The inflow `account` is about to receive or just received, if any.
+    pub async fn inflow_to(&self, account: Address) -> Option<BridgeInflow> {
+        let now = Instant::now();
+        self.inflows
+            .read()
+            .await
+            .iter()
+            .find(|i| i.recipient == account && i.arrives + INFLOW_GRACE > now)
+            .cloned()
+    }
+}
+
+fn address_arg(args: &[Token], index: usize) -> Option<Address> {
+    args.get(index).cloned().and_then(Token::into_address)
+}
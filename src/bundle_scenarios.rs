@@ -0,0 +1,206 @@
+// src/bundle_scenarios.rs
+use std::fmt;
+
+/// A leg of a sandwich bundle. All the victims of one sandwich are one leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leg {
+    Frontrun,
+    Victim,
+    Backrun,
+}
+
+impl Leg {
+    pub const ALL: [Leg; 3] = [Leg::Frontrun, Leg::Victim, Leg::Backrun];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// A set of legs, e.g. the ones a bundle may land with reverted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegSet(u8);
+
+impl LegSet {
+    pub fn contains(&self, leg: Leg) -> bool {
+        self.0 & leg.bit() != 0
+    }
+
+    pub fn insert(&mut self, leg: Leg) {
+        self.0 |= leg.bit();
+    }
+
+    pub fn is_subset(&self, other: &LegSet) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    // Every subset of the three legs
+    fn all() -> impl Iterator<Item = LegSet> {
+        (0..1u8 << Leg::ALL.len()).map(LegSet)
+    }
+}
+
+impl fmt::Display for LegSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Leg::ALL
+            .iter()
+            .filter(|leg| self.contains(**leg))
+            .map(|leg| match leg {
+                Leg::Frontrun => "frontrun",
+                Leg::Victim => "victim",
+                Leg::Backrun => "backrun",
+            })
+            .collect();
+        write!(f, "[{}]", names.join(", "))
+    }
+}
+
+/// The order a block could run some of the bundle's legs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scenario {
+    pub name: &'static str,
+    pub order: &'static [Leg],
+}
+
+impl Scenario {
+    /// Legs the block doesn't run; the bundle only lands without them if they
+    /// may revert.
+    pub fn absent(&self) -> LegSet {
+        let mut absent = LegSet::default();
+        for leg in Leg::ALL {
+            if !self.order.contains(&leg) {
+                absent.insert(leg);
+            }
+        }
+        absent
+    }
+
+    /// Whether the legs that do run are out of bundle order.
+    pub fn reordered(&self) -> bool {
+        self.order.windows(2).any(|pair| (pair[0] as u8) > (pair[1] as u8))
+    }
+}
+
+/// The bundle as sent, every ordering of it a block could produce, and every
+/// way part of it can land without the rest.
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario { name: "bundle", order: &[Leg::Frontrun, Leg::Victim, Leg::Backrun] },
+    Scenario { name: "victim_first", order: &[Leg::Victim, Leg::Frontrun, Leg::Backrun] },
+    Scenario { name: "backrun_first", order: &[Leg::Frontrun, Leg::Backrun, Leg::Victim] },
+    // The victim lands on its own, ahead of or without our frontrun
+    Scenario { name: "no_frontrun", order: &[Leg::Victim, Leg::Backrun] },
+    // We're left holding what the frontrun bought
+    Scenario { name: "no_backrun", order: &[Leg::Frontrun, Leg::Victim] },
+    Scenario { name: "no_victim", order: &[Leg::Frontrun, Leg::Backrun] },
+    Scenario { name: "frontrun_only", order: &[Leg::Frontrun] },
+];
+
+/// What one scenario did when simulated: the legs that didn't run or reverted,
+/// and our profit net of the gas our legs burned, in USD.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScenarioOutcome {
+    pub scenario: Scenario,
+    pub failed: LegSet,
+    pub profit_usd: f64,
+}
+
+impl ScenarioOutcome {
+    /// Whether a bundle sent with `can_revert` can end up like this. Relays drop
+    /// a bundle with any other leg failing; `ordered` venues also keep its order.
+    pub fn reachable(&self, can_revert: LegSet, ordered: bool) -> bool {
+        self.failed.is_subset(&can_revert) && !(ordered && self.scenario.reordered())
+    }
+}
+
+/// Flags to send a sandwich with, and the worst outcome they leave reachable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderingPlan {
+    pub can_revert: LegSet,
+    pub worst: ScenarioOutcome,
+}
+
+/// Why no flags make a sandwich safe to send.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{scenario} loses ${loss_usd:.2}, above max loss ${max_loss_usd:.2}")]
+pub struct OrderingRejected {
+    pub scenario: &'static str,
+    pub loss_usd: f64,
+    pub max_loss_usd: f64,
+}
+
+/// Picks the most legs that may revert while every outcome that makes reachable
+/// stays within `max_loss_usd`: a leg is only allowed to fail when its failing
+/// is affordable, otherwise the relay has to drop the bundle instead. Fails when
+/// even a fully atomic bundle can lose more, e.g. because the bundle itself does.
+pub fn plan(outcomes: &[ScenarioOutcome], max_loss_usd: f64, ordered: bool) -> Result<OrderingPlan, OrderingRejected> {
+    let worst_reachable = |can_revert: LegSet| {
+        outcomes
+            .iter()
+            .filter(|o| o.reachable(can_revert, ordered))
+            .min_by(|a, b| a.profit_usd.total_cmp(&b.profit_usd))
+            .copied()
+    };
+
+    let mut candidates: Vec<LegSet> = LegSet::all().collect();
+    candidates.sort_by_key(|set| std::cmp::Reverse(set.len()));
+    for can_revert in candidates {
+        if let Some(worst) = worst_reachable(can_revert) {
+            if -worst.profit_usd <= max_loss_usd {
+                return Ok(OrderingPlan { can_revert, worst });
+            }
+        }
+    }
+
+    let atomic = worst_reachable(LegSet::default());
+    Err(OrderingRejected {
+        scenario: atomic.map_or("bundle", |o| o.scenario.name),
+        loss_usd: atomic.map_or(0.0, |o| -o.profit_usd),
+        max_loss_usd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(name: &str, failed: &[Leg], profit_usd: f64) -> ScenarioOutcome {
+        let scenario = *SCENARIOS.iter().find(|s| s.name == name).unwrap();
+        let mut set = scenario.absent();
+        for leg in failed {
+            set.insert(*leg);
+        }
+        ScenarioOutcome { scenario, failed: set, profit_usd }
+    }
+
+    #[test]
+    fn allows_only_affordable_failures() {
+        let outcomes = [
+            outcome("bundle", &[], 40.0),
+            outcome("victim_first", &[], -2.0),
+            outcome("backrun_first", &[], -3.0),
+            // The backrun has nothing to sell and reverts: gas only
+            outcome("no_frontrun", &[Leg::Backrun], -0.5),
+            // Stuck holding the frontrun's buy
+            outcome("no_backrun", &[], -60.0),
+            outcome("no_victim", &[], -4.0),
+            outcome("frontrun_only", &[], -55.0),
+        ];
+
+        let plan = plan(&outcomes, 5.0, true).unwrap();
+        assert!(plan.can_revert.contains(Leg::Frontrun));
+        assert!(!plan.can_revert.contains(Leg::Backrun));
+        assert_eq!(plan.worst.scenario.name, "no_victim");
+
+        // Reorderings out of bound rule out unordered venues altogether
+        let rejected = super::plan(&outcomes, 2.5, false).unwrap_err();
+        assert_eq!(rejected.scenario, "backrun_first");
+    }
+}
@@ -0,0 +1,143 @@
+// src/cache.rs
+use ethers::types::U64;
+use lru::LruCache;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const SHARDS: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    // Pushed out by capacity
+    pub evictions: u64,
+    // Dropped by age
+    pub expirations: u64,
+    pub len: usize,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Sharded LRU whose entries also expire `ttl_blocks` after the block they were
+/// inserted in. Capacity is split evenly across shards.
+pub struct BlockLruCache<K, V> {
+    shards: Vec<Mutex<LruCache<K, (V, U64)>>>,
+    ttl_blocks: u64,
+    current_block: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> BlockLruCache<K, V> {
+    pub fn new(capacity: usize, ttl_blocks: u64) -> Self {
+        let per_shard = NonZeroUsize::new((capacity / SHARDS).max(1)).unwrap();
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::new(LruCache::new(per_shard))).collect(),
+            ttl_blocks,
+            current_block: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, key: &K) -> &Mutex<LruCache<K, (V, U64)>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % SHARDS]
+    }
+
+    fn expired(&self, inserted_at: U64) -> bool {
+        inserted_at.as_u64() + self.ttl_blocks < self.current_block.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut shard = self.shard(key).lock().unwrap();
+        let hit = match shard.get(key) {
+            Some((value, inserted_at)) if !self.expired(*inserted_at) => Some(value.clone()),
+            _ => None,
+        };
+        if hit.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
+    }
+
+    /// Presence check that leaves recency and hit/miss stats untouched.
+    pub fn contains(&self, key: &K) -> bool {
+        let shard = self.shard(key).lock().unwrap();
+        matches!(shard.peek(key), Some((_, inserted_at)) if !self.expired(*inserted_at))
+    }
+
+    /// Returns true if the key was already present and unexpired. Counts as a
+    /// lookup, so dedup sets get a meaningful hit rate.
+    pub fn insert(&self, key: K, value: V) -> bool {
+        let block = U64::from(self.current_block.load(Ordering::Relaxed));
+        let mut shard = self.shard(&key).lock().unwrap();
+        let present = matches!(shard.peek(&key), Some((_, inserted_at)) if !self.expired(*inserted_at));
+        if present {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some((evicted, _)) = shard.push(key.clone(), (value, block)) {
+            if evicted != key {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        present
+    }
+
+    /// Advances the clock and drops entries older than the TTL.
+    pub fn on_block(&self, block: U64) {
+        self.current_block.store(block.as_u64(), Ordering::Relaxed);
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            // Oldest entries sit at the LRU end, but a re-read can move an old
+            // entry forward, so scan everything
+            let stale: Vec<K> = shard
+                .iter()
+                .filter(|(_, (_, inserted_at))| self.expired(*inserted_at))
+                .map(|(k, _)| k.clone())
+                .collect();
+            for key in stale {
+                shard.pop(&key);
+                self.expirations.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().unwrap().clear();
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            len: self.shards.iter().map(|s| s.lock().unwrap().len()).sum(),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// src/call_tracer.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use ethers::types::{
+    CallFrame, GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingCallOptions,
+    GethDebugTracingOptions, GethTrace, GethTraceFrame,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::classifier::ClassifiedTx;
+use crate::pool_state::{PoolState, PoolStateManager};
+use crate::v3_ticks::V3PoolState;
+use crate::rpc_budget::RpcTransport;
+
+// transfer(address,uint256), transferFrom(address,address,uint256)
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+// UniswapV2Pair.swap(uint256,uint256,address,bytes)
+const V2_SWAP: [u8; 4] = [0x02, 0x2c, 0x0d, 0x9f];
+// UniswapV3Pool.swap(address,bool,int256,uint160,bytes)
+const V3_SWAP: [u8; 4] = [0x12, 0x8a, 0xcb, 0x08];
+
+#[derive(Debug, Clone)]
+pub struct TraceConfig {
+    // Router swaps sending at least this much MATIC are traced
+    pub min_native_value: U256,
+    // Aggregator calldata can't be decoded per venue, so trace every one
+    pub trace_aggregators: bool,
+    // debug_traceCall is expensive on most nodes; cap it per block
+    pub max_per_block: usize,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            min_native_value: U256::exp10(18) * 500,
+            trace_aggregators: true,
+            max_per_block: 20,
+        }
+    }
+}
+
+/// Net token flow into a pool, positive when the pool received the token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolDelta {
+    pub pool: Address,
+    pub amount0: I256,
+    pub amount1: I256,
+}
+
+impl PoolDelta {
+    /// From a V2 or V3 Swap event, which both carry the exact amounts moved.
+    pub fn from_swap_log(log: &Log) -> Option<Self> {
+        let topic = *log.topics.first()?;
+        let data = log.data.as_ref();
+        let word = |i: usize| data.get(i * 32..(i + 1) * 32).map(U256::from_big_endian);
+
+        if topic == PoolState::swap_topic() {
+            // amount0In, amount1In, amount0Out, amount1Out
+            let (in0, in1, out0, out1) = (word(0)?, word(1)?, word(2)?, word(3)?);
+            Some(Self {
+                pool: log.address,
+                amount0: I256::from_raw(in0) - I256::from_raw(out0),
+                amount1: I256::from_raw(in1) - I256::from_raw(out1),
+            })
+        } else if topic == V3PoolState::swap_topic() {
+            Some(Self {
+                pool: log.address,
+                amount0: I256::from_raw(word(0)?),
+                amount1: I256::from_raw(word(1)?),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VictimTrace {
+    // Every contract the call tree entered, in order
+    pub touched: Vec<Address>,
+    // Swaps on pools we track, with their exact balance changes
+    pub deltas: Vec<PoolDelta>,
+}
+
+fn word(data: &[u8], index: usize) -> Option<U256> {
+    data.get(4 + index * 32..4 + (index + 1) * 32).map(U256::from_big_endian)
+}
+
+fn word_address(data: &[u8], index: usize) -> Option<Address> {
+    data.get(4 + index * 32 + 12..4 + (index + 1) * 32).map(Address::from_slice)
+}
+
+fn frame_to(frame: &CallFrame) -> Option<Address> {
+    frame.to.as_ref().and_then(|to| to.as_address().copied())
+}
+
+/// Runs `debug_traceCall` with the callTracer on pending transactions worth a
+/// closer look, and turns the call tree into the pool deltas it would cause.
+pub struct CallTracer {
+    provider: Arc<Provider<RpcTransport>>,
+    pool_state: Arc<PoolStateManager>,
+    config: TraceConfig,
+    // (head, traces spent on it)
+    budget: Mutex<(U64, usize)>,
+}
+
+impl CallTracer {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, pool_state: Arc<PoolStateManager>, config: TraceConfig) -> Self {
+        Self {
+            provider,
+            pool_state,
+            config,
+            budget: Mutex::new((U64::zero(), 0)),
+        }
+    }
+
+    pub fn should_trace(&self, tx: &Transaction, classified: &ClassifiedTx) -> bool {
+        match classified {
+            ClassifiedTx::AggregatorSwap { .. } => self.config.trace_aggregators,
+            ClassifiedTx::RouterSwap { .. } => tx.value >= self.config.min_native_value,
+            _ => false,
+        }
+    }
+
+    /// None once this head's trace budget is spent.
+    pub async fn trace(&self, tx: &Transaction, head: U64) -> Result<Option<VictimTrace>> {
+        {
+            let mut budget = self.budget.lock().await;
+            if budget.0 != head {
+                *budget = (head, 0);
+            }
+            if budget.1 >= self.config.max_per_block {
+                return Ok(None);
+            }
+            budget.1 += 1;
+        }
+
+        let mut call = TransactionRequest::new().from(tx.from).data(tx.input.clone()).value(tx.value).gas(tx.gas);
+        if let Some(to) = tx.to {
+            call = call.to(to);
+        }
+        let options = GethDebugTracingCallOptions {
+            tracing_options: GethDebugTracingOptions {
+                tracer: Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let root = match self.provider.debug_trace_call(call, Some(head.into()), options).await? {
+            GethTrace::Known(GethTraceFrame::CallTracer(frame)) => frame,
+            other => return Err(anyhow!("Unexpected trace for {:?}: {:?}", tx.hash, other)),
+        };
+        if let Some(error) = &root.error {
+            debug!("Traced {:?} reverts ({}), ignoring", tx.hash, error);
+            return Ok(Some(VictimTrace::default()));
+        }
+
+        let mut frames = Vec::new();
+        Self::flatten(&root, &mut frames);
+        let trace = self.analyze(&frames).await;
+        debug!("Traced {:?}: {} contracts, {} tracked pool swaps", tx.hash, trace.touched.len(), trace.deltas.len());
+        Ok(Some(trace))
+    }
+
+    // Depth-first, in execution order; reverted subcalls changed nothing
+    fn flatten<'a>(frame: &'a CallFrame, out: &mut Vec<&'a CallFrame>) {
+        if frame.error.is_some() {
+            return;
+        }
+        out.push(frame);
+        for call in frame.calls.iter().flatten() {
+            Self::flatten(call, out);
+        }
+    }
+
+    async fn analyze(&self, frames: &[&CallFrame]) -> VictimTrace {
+        let mut trace = VictimTrace::default();
+        // (recipient, token) -> amount transferred in
+        let mut inflows: HashMap<(Address, Address), U256> = HashMap::new();
+
+        for frame in frames {
+            let to = match frame_to(frame) {
+                Some(to) => to,
+                None => continue,
+            };
+            if !trace.touched.contains(&to) {
+                trace.touched.push(to);
+            }
+            let input = frame.input.as_ref();
+            let selector = match input.get(..4) {
+                Some(s) => [s[0], s[1], s[2], s[3]],
+                None => continue,
+            };
+
+            match selector {
+                TRANSFER => {
+                    if let (Some(recipient), Some(amount)) = (word_address(input, 0), word(input, 1)) {
+                        *inflows.entry((recipient, to)).or_default() += amount;
+                    }
+                }
+                TRANSFER_FROM => {
+                    if let (Some(recipient), Some(amount)) = (word_address(input, 1), word(input, 2)) {
+                        *inflows.entry((recipient, to)).or_default() += amount;
+                    }
+                }
+                // V2 pools are paid before swap() and report nothing back, so the
+                // input side comes from the transfers into the pool
+                V2_SWAP => {
+                    let pool = match self.pool_state.get(to).await {
+                        Some(pool) => pool,
+                        None => continue,
+                    };
+                    let (out0, out1) = match (word(input, 0), word(input, 1)) {
+                        (Some(a), Some(b)) => (a, b),
+                        _ => continue,
+                    };
+                    let in0 = inflows.remove(&(to, pool.token0)).unwrap_or_default();
+                    let in1 = inflows.remove(&(to, pool.token1)).unwrap_or_default();
+                    trace.deltas.push(PoolDelta {
+                        pool: to,
+                        amount0: I256::from_raw(in0) - I256::from_raw(out0),
+                        amount1: I256::from_raw(in1) - I256::from_raw(out1),
+                    });
+                }
+                // V3 swap() returns the pool-side deltas directly
+                V3_SWAP => {
+                    if self.pool_state.get_v3(to).await.is_none() {
+                        continue;
+                    }
+                    let output = match &frame.output {
+                        Some(output) if output.len() >= 64 => output,
+                        _ => continue,
+                    };
+                    trace.deltas.push(PoolDelta {
+                        pool: to,
+                        amount0: I256::from_raw(U256::from_big_endian(&output[..32])),
+                        amount1: I256::from_raw(U256::from_big_endian(&output[32..64])),
+                    });
+                }
+                _ => {}
+            }
+        }
+        trace
+    }
+}
@@ -0,0 +1,532 @@
+// src/calldata_builder.rs
+use anyhow::{anyhow, Result};
+use ethers::abi::Token;
+use ethers::prelude::*;
+
+use crate::abis::FLASHLOANARBITRAGE_ABI as EXECUTOR_ABI;
+use crate::fee::FeeAmount;
+use crate::flash_loan::LoanLeg;
+use crate::native;
+
+/// How the executor runs a hop. The discriminants are the contract's HOP_* kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterKind {
+    // swapExactTokensForTokens on a V2-style router
+    UniswapV2 = 0,
+    // exactInputSingle at the hop's fee tier
+    UniswapV3 = 1,
+    // WMATIC deposit/withdraw; router and pool are the WMATIC contract
+    Wrap = 2,
+    // exchange (or exchange_underlying) on a Curve pool; the router is the pool
+    Curve = 3,
+    // Mint a liquid staking derivative from WMATIC at the staking rate; the
+    // router is the staking pool
+    Stake = 4,
+}
+
+impl RouterKind {
+    fn from_u8(kind: u8) -> Result<Self, DecodeError> {
+        match kind {
+            0 => Ok(RouterKind::UniswapV2),
+            1 => Ok(RouterKind::UniswapV3),
+            2 => Ok(RouterKind::Wrap),
+            3 => Ok(RouterKind::Curve),
+            4 => Ok(RouterKind::Stake),
+            other => Err(DecodeError::Malformed(format!("unknown hop kind {}", other))),
+        }
+    }
+}
+
+/// Why executor calldata didn't decode. Always fatal for the calldata in hand:
+/// decoding it again gives the same answer.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+    #[error("Calldata shorter than a selector")]
+    Truncated,
+    #[error("Unknown executor selector 0x{}", hex::encode(.0))]
+    UnknownSelector([u8; 4]),
+    #[error("{0} is not a flash-loan entrypoint")]
+    NotAnEntrypoint(String),
+    #[error("Malformed executor arguments: {0}")]
+    Malformed(String),
+}
+
+impl From<ethers::abi::Error> for DecodeError {
+    fn from(e: ethers::abi::Error) -> Self {
+        DecodeError::Malformed(e.to_string())
+    }
+}
+
+/// One swap of an atomic execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop {
+    pub kind: RouterKind,
+    pub router: Address,
+    pub pool: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    // Hundredths of a bip (3000 = 0.3%); for Curve, the packed coin indices
+    // (see `curve_indices`)
+    pub fee: u32,
+    pub amount_in: U256,
+    pub min_out: U256,
+}
+
+/// A Curve hop's `fee` field: coin indices `i << 8 | j`, bit 16 set when the
+/// swap goes through `exchange_underlying`.
+pub fn curve_indices(i: u8, j: u8, underlying: bool) -> u32 {
+    (i as u32) << 8 | j as u32 | if underlying { 1 << 16 } else { 0 }
+}
+
+/// The entrypoint an execution goes through, with its loan arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlashEntry {
+    // `flash` on the V3 pool for (token0, token1, fee)
+    UniswapV3 { token0: Address, token1: Address, amount0: U256, amount1: U256, fee: FeeAmount },
+    Balancer { loans: Vec<LoanLeg> },
+    Aave { loans: Vec<LoanLeg> },
+    // `executeFromInventory`: no loan, the executor spends its own balance
+    Inventory,
+}
+
+impl FlashEntry {
+    fn function(&self) -> &'static str {
+        match self {
+            FlashEntry::UniswapV3 { .. } => "executeFlashLoanArbitrage",
+            FlashEntry::Balancer { .. } => "executeBalancerFlashLoan",
+            FlashEntry::Aave { .. } => "executeAaveFlashLoan",
+            FlashEntry::Inventory => "executeFromInventory",
+        }
+    }
+
+    fn tokens(&self) -> Vec<Token> {
+        match self {
+            FlashEntry::UniswapV3 { token0, token1, amount0, amount1, fee } => vec![
+                Token::Address(*token0),
+                Token::Address(*token1),
+                Token::Uint(*amount0),
+                Token::Uint(*amount1),
+                Token::Uint((*fee).into()),
+            ],
+            FlashEntry::Balancer { loans } | FlashEntry::Aave { loans } => vec![
+                Token::Array(loans.iter().map(|l| Token::Address(l.token)).collect()),
+                Token::Array(loans.iter().map(|l| Token::Uint(l.amount)).collect()),
+            ],
+            FlashEntry::Inventory => Vec::new(),
+        }
+    }
+}
+
+/// The (path, amounts, routers, kinds, fees) arguments every flash-loan
+/// entrypoint ends with: one more token than hops, then one entry per hop. A
+/// route may mix V2 and V3 pools of any fee tier.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionPath {
+    pub path: Vec<Address>,
+    pub amounts: Vec<U256>,
+    pub routers: Vec<Address>,
+    pub kinds: Vec<RouterKind>,
+    pub fees: Vec<u32>,
+}
+
+impl ExecutionPath {
+    /// Errors unless every hop starts with the token the previous one ends with.
+    pub fn from_hops(hops: &[Hop]) -> Result<Self> {
+        let first = hops.first().ok_or_else(|| anyhow!("Execution has no hops"))?;
+        let mut path = vec![first.token_in];
+        for (i, hop) in hops.iter().enumerate() {
+            if hop.token_in != *path.last().unwrap() {
+                return Err(anyhow!("Hop {} starts with {:?}, previous hop ends with {:?}", i, hop.token_in, path.last().unwrap()));
+            }
+            path.push(hop.token_out);
+        }
+        Ok(Self {
+            path,
+            amounts: hops.iter().map(|h| h.amount_in).collect(),
+            routers: hops.iter().map(|h| h.router).collect(),
+            kinds: hops.iter().map(|h| h.kind).collect(),
+            fees: hops.iter().map(|h| h.fee).collect(),
+        })
+    }
+
+    fn tokens(&self) -> Vec<Token> {
+        vec![
+            Token::Array(self.path.iter().map(|a| Token::Address(*a)).collect()),
+            Token::Array(self.amounts.iter().map(|a| Token::Uint(*a)).collect()),
+            Token::Array(self.routers.iter().map(|a| Token::Address(*a)).collect()),
+            Token::Array(self.kinds.iter().map(|k| Token::Uint(U256::from(*k as u8))).collect()),
+            Token::Array(self.fees.iter().map(|f| Token::Uint(U256::from(*f))).collect()),
+        ]
+    }
+
+    fn from_tokens(tokens: &[Token]) -> Result<Self, DecodeError> {
+        match tokens {
+            [path, amounts, routers, kinds, fees] => Ok(Self {
+                path: addresses(path)?,
+                amounts: uints(amounts)?,
+                routers: addresses(routers)?,
+                kinds: uints(kinds)?.into_iter().map(|k| RouterKind::from_u8(k.low_u32() as u8)).collect::<Result<_, _>>()?,
+                fees: uints(fees)?.into_iter().map(|f| f.low_u32()).collect(),
+            }),
+            _ => Err(DecodeError::Malformed("expected path, amounts, routers, kinds and fees".to_string())),
+        }
+    }
+}
+
+/// Hops for a flat route without pool types, as the simulation engine produces
+/// it: `amounts` per token (or per hop), swaps through the V3 router at its
+/// default fee, native MATIC endpoints made explicit wrap hops. Pathfinders
+/// that know their pools build `Hop`s directly.
+pub fn hops_from_route(path: &[Address], amounts: &[U256], routers: &[Address]) -> Result<Vec<Hop>> {
+    let (path, amounts, routers) = native::with_wrap_hops(path, amounts, routers);
+    let hop_count = path.len().saturating_sub(1);
+    if routers.len() != hop_count || (amounts.len() != hop_count && amounts.len() != path.len()) {
+        return Err(anyhow!(
+            "Route of {} tokens has {} amounts and {} routers",
+            path.len(), amounts.len(), routers.len()
+        ));
+    }
+    let wmatic = native::wmatic();
+    Ok((0..hop_count)
+        .map(|i| Hop {
+            kind: if routers[i] == wmatic { RouterKind::Wrap } else { RouterKind::UniswapV3 },
+            router: routers[i],
+            pool: if routers[i] == wmatic { wmatic } else { Address::zero() },
+            token_in: path[i],
+            token_out: path[i + 1],
+            fee: 3000,
+            amount_in: amounts[i],
+            min_out: U256::zero(),
+        })
+        .collect())
+}
+
+/// How executor calldata is laid out. `Packed` goes through `executePacked`
+/// and drops the ABI's zero padding; `Abi` calls the entrypoints directly and
+/// stays the default until every deployed executor has `executePacked`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CalldataEncoding {
+    #[default]
+    Abi,
+    Packed,
+}
+
+// executePacked's entry byte
+const PACKED_V3: u8 = 0;
+const PACKED_BALANCER: u8 = 1;
+const PACKED_AAVE: u8 = 2;
+const PACKED_INVENTORY: u8 = 3;
+// Per hop: tokenOut (20) router (20) kind (1) fee (3) amountIn (16)
+const PACKED_HOP_LEN: usize = 60;
+
+/// Calldata for `entry` swapping through `hops`.
+pub fn encode(entry: &FlashEntry, hops: &[Hop]) -> Result<Bytes> {
+    let mut tokens = entry.tokens();
+    tokens.extend(ExecutionPath::from_hops(hops)?.tokens());
+    let function = EXECUTOR_ABI.function(entry.function())?;
+    Ok(Bytes::from(function.encode_input(&tokens)?))
+}
+
+pub fn encode_with(encoding: CalldataEncoding, entry: &FlashEntry, hops: &[Hop]) -> Result<Bytes> {
+    match encoding {
+        CalldataEncoding::Abi => encode(entry, hops),
+        CalldataEncoding::Packed => encode_packed(entry, hops),
+    }
+}
+
+/// `executePacked` calldata for `entry` swapping through `hops`; the layout
+/// is documented on the contract. Amounts must fit in 128 bits.
+pub fn encode_packed(entry: &FlashEntry, hops: &[Hop]) -> Result<Bytes> {
+    let route = ExecutionPath::from_hops(hops)?;
+    let mut packed = Vec::with_capacity(128 + hops.len() * PACKED_HOP_LEN);
+    match entry {
+        FlashEntry::UniswapV3 { token0, token1, amount0, amount1, fee } => {
+            packed.push(PACKED_V3);
+            packed.extend_from_slice(token0.as_bytes());
+            packed.extend_from_slice(token1.as_bytes());
+            packed.extend_from_slice(&uint128(*amount0)?);
+            packed.extend_from_slice(&uint128(*amount1)?);
+            packed.extend_from_slice(&fee.to_uint24_bytes());
+        }
+        FlashEntry::Balancer { loans } | FlashEntry::Aave { loans } => {
+            packed.push(if matches!(entry, FlashEntry::Balancer { .. }) { PACKED_BALANCER } else { PACKED_AAVE });
+            packed.push(u8::try_from(loans.len()).map_err(|_| anyhow!("{} loan legs don't fit the packed layout", loans.len()))?);
+            for leg in loans {
+                packed.extend_from_slice(leg.token.as_bytes());
+                packed.extend_from_slice(&uint128(leg.amount)?);
+            }
+        }
+        FlashEntry::Inventory => packed.push(PACKED_INVENTORY),
+    }
+    packed.push(u8::try_from(hops.len()).map_err(|_| anyhow!("{} hops don't fit the packed layout", hops.len()))?);
+    packed.extend_from_slice(route.path[0].as_bytes());
+    for i in 0..hops.len() {
+        if route.fees[i] >= 1 << 24 {
+            return Err(anyhow!("Hop {} fee {} doesn't fit uint24", i, route.fees[i]));
+        }
+        packed.extend_from_slice(route.path[i + 1].as_bytes());
+        packed.extend_from_slice(route.routers[i].as_bytes());
+        packed.push(route.kinds[i] as u8);
+        packed.extend_from_slice(&route.fees[i].to_be_bytes()[1..]);
+        packed.extend_from_slice(&uint128(route.amounts[i])?);
+    }
+    let function = EXECUTOR_ABI.function("executePacked")?;
+    Ok(Bytes::from(function.encode_input(&[Token::Bytes(packed)])?))
+}
+
+fn uint128(amount: U256) -> Result<[u8; 16]> {
+    if amount.bits() > 128 {
+        return Err(anyhow!("Amount {} doesn't fit the packed layout's 128 bits", amount));
+    }
+    let mut word = [0u8; 32];
+    amount.to_big_endian(&mut word);
+    Ok(word[16..].try_into().unwrap())
+}
+
+/// Intrinsic gas the calldata costs (EIP-2028): 4 per zero byte, 16 per other.
+pub fn calldata_gas(data: &[u8]) -> u64 {
+    data.iter().map(|b| if *b == 0 { 4 } else { 16 }).sum()
+}
+
+/// Inverse of `encode`, for checking and logging built calldata.
+pub fn decode(data: &[u8]) -> Result<(FlashEntry, ExecutionPath), DecodeError> {
+    let selector: [u8; 4] = data.get(..4).ok_or(DecodeError::Truncated)?.try_into().unwrap();
+    let function = EXECUTOR_ABI
+        .functions()
+        .find(|f| f.short_signature() == selector)
+        .ok_or(DecodeError::UnknownSelector(selector))?;
+    let tokens = function.decode_input(&data[4..])?;
+    if function.name == "executePacked" {
+        return match tokens.as_slice() {
+            [Token::Bytes(packed)] => decode_packed(packed),
+            _ => Err(DecodeError::Malformed("expected packed bytes".to_string())),
+        };
+    }
+    let (loan, route) = tokens.split_at(tokens.len().saturating_sub(5));
+    let entry = match (function.name.as_str(), loan) {
+        ("executeFlashLoanArbitrage", [token0, token1, amount0, amount1, fee]) => FlashEntry::UniswapV3 {
+            token0: address(token0)?,
+            token1: address(token1)?,
+            amount0: uint(amount0)?,
+            amount1: uint(amount1)?,
+            fee: FeeAmount::try_from(uint(fee)?).map_err(|e| DecodeError::Malformed(e.to_string()))?,
+        },
+        ("executeBalancerFlashLoan", [tokens, amounts]) => FlashEntry::Balancer { loans: loan_legs(tokens, amounts)? },
+        ("executeAaveFlashLoan", [assets, amounts]) => FlashEntry::Aave { loans: loan_legs(assets, amounts)? },
+        ("executeFromInventory", []) => FlashEntry::Inventory,
+        (name, _) => return Err(DecodeError::NotAnEntrypoint(name.to_string())),
+    };
+    Ok((entry, ExecutionPath::from_tokens(route)?))
+}
+
+fn decode_packed(packed: &[u8]) -> Result<(FlashEntry, ExecutionPath), DecodeError> {
+    let mut reader = PackedReader { packed, offset: 0 };
+    let entry = match reader.u8()? {
+        PACKED_V3 => FlashEntry::UniswapV3 {
+            token0: reader.address()?,
+            token1: reader.address()?,
+            amount0: reader.uint128()?,
+            amount1: reader.uint128()?,
+            fee: FeeAmount::new(reader.uint24()?).map_err(|e| DecodeError::Malformed(e.to_string()))?,
+        },
+        kind @ (PACKED_BALANCER | PACKED_AAVE) => {
+            let loans = (0..reader.u8()?)
+                .map(|_| Ok(LoanLeg::new(reader.address()?, reader.uint128()?)))
+                .collect::<Result<Vec<_>, DecodeError>>()?;
+            if kind == PACKED_BALANCER { FlashEntry::Balancer { loans } } else { FlashEntry::Aave { loans } }
+        }
+        PACKED_INVENTORY => FlashEntry::Inventory,
+        other => return Err(DecodeError::Malformed(format!("unknown packed entry {}", other))),
+    };
+
+    let hops = reader.u8()? as usize;
+    if packed.len() != reader.offset + 20 + hops * PACKED_HOP_LEN {
+        return Err(DecodeError::Malformed(format!("packed route of {} hops is {} bytes", hops, packed.len() - reader.offset)));
+    }
+    let mut route = ExecutionPath { path: vec![reader.address()?], ..Default::default() };
+    for _ in 0..hops {
+        route.path.push(reader.address()?);
+        route.routers.push(reader.address()?);
+        route.kinds.push(RouterKind::from_u8(reader.u8()?)?);
+        route.fees.push(reader.uint24()?);
+        route.amounts.push(reader.uint128()?);
+    }
+    Ok((entry, route))
+}
+
+// Big-endian fields read in order from `executePacked`'s argument
+struct PackedReader<'a> {
+    packed: &'a [u8],
+    offset: usize,
+}
+
+impl PackedReader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], DecodeError> {
+        let field = self.packed.get(self.offset..self.offset + len).ok_or_else(|| DecodeError::Malformed("packed calldata ends early".to_string()))?;
+        self.offset += len;
+        Ok(field)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uint24(&mut self) -> Result<u32, DecodeError> {
+        Ok(self.take(3)?.iter().fold(0, |fee, b| fee << 8 | *b as u32))
+    }
+
+    fn uint128(&mut self) -> Result<U256, DecodeError> {
+        Ok(U256::from_big_endian(self.take(16)?))
+    }
+
+    fn address(&mut self) -> Result<Address, DecodeError> {
+        Ok(Address::from_slice(self.take(20)?))
+    }
+}
+
+fn address(token: &Token) -> Result<Address, DecodeError> {
+    token.clone().into_address().ok_or_else(|| DecodeError::Malformed(format!("expected address, got {:?}", token)))
+}
+
+fn uint(token: &Token) -> Result<U256, DecodeError> {
+    token.clone().into_uint().ok_or_else(|| DecodeError::Malformed(format!("expected uint, got {:?}", token)))
+}
+
+fn array(token: &Token) -> Result<Vec<Token>, DecodeError> {
+    token.clone().into_array().ok_or_else(|| DecodeError::Malformed(format!("expected array, got {:?}", token)))
+}
+
+fn addresses(token: &Token) -> Result<Vec<Address>, DecodeError> {
+    array(token)?.iter().map(address).collect()
+}
+
+fn uints(token: &Token) -> Result<Vec<U256>, DecodeError> {
+    array(token)?.iter().map(uint).collect()
+}
+
+fn loan_legs(tokens: &Token, amounts: &Token) -> Result<Vec<LoanLeg>, DecodeError> {
+    Ok(addresses(tokens)?.into_iter().zip(uints(amounts)?).map(|(t, a)| LoanLeg::new(t, a)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn token(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    fn hop(token_in: u64, token_out: u64, amount_in: u64) -> Hop {
+        Hop {
+            kind: RouterKind::UniswapV3,
+            router: token(100 + token_in),
+            pool: token(200 + token_in),
+            token_in: token(token_in),
+            token_out: token(token_out),
+            fee: 500,
+            amount_in: U256::from(amount_in),
+            min_out: U256::zero(),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_entrypoint() {
+        let hops = vec![hop(1, 2, 1_000), hop(2, 3, 990), hop(3, 1, 980)];
+        let loans = vec![LoanLeg::new(token(1), U256::from(1_000))];
+        let entries = [
+            FlashEntry::UniswapV3 { token0: token(1), token1: token(2), amount0: U256::from(1_000), amount1: U256::zero(), fee: FeeAmount::LOW },
+            FlashEntry::Balancer { loans: loans.clone() },
+            FlashEntry::Aave { loans },
+            FlashEntry::Inventory,
+        ];
+        for entry in entries {
+            let (decoded, route) = decode(&encode(&entry, &hops).unwrap()).unwrap();
+            assert_eq!(decoded, entry);
+            assert_eq!(route, ExecutionPath::from_hops(&hops).unwrap());
+            assert_eq!(route.path, vec![token(1), token(2), token(3), token(1)]);
+            assert_eq!(route.amounts.len(), hops.len());
+        }
+    }
+
+    #[test]
+    fn packed_round_trips_and_costs_less_gas() {
+        let hops = vec![hop(1, 2, 1_000), Hop { kind: RouterKind::Curve, fee: curve_indices(0, 1, true), ..hop(2, 3, 990) }, hop(3, 1, 980)];
+        let loans = vec![LoanLeg::new(token(1), U256::from(1_000)), LoanLeg::new(token(2), U256::from(7))];
+        let entries = [
+            FlashEntry::UniswapV3 { token0: token(1), token1: token(2), amount0: U256::from(1_000), amount1: U256::zero(), fee: FeeAmount::LOW },
+            FlashEntry::Balancer { loans: loans.clone() },
+            FlashEntry::Aave { loans },
+            FlashEntry::Inventory,
+        ];
+        for entry in entries {
+            let packed = encode_packed(&entry, &hops).unwrap();
+            assert_eq!(decode(&packed).unwrap(), (entry.clone(), ExecutionPath::from_hops(&hops).unwrap()));
+            assert!(calldata_gas(&packed) < calldata_gas(&encode(&entry, &hops).unwrap()));
+        }
+
+        let too_big = vec![Hop { amount_in: U256::one() << 128, ..hop(1, 2, 0) }];
+        assert!(encode_packed(&FlashEntry::Inventory, &too_big).is_err());
+    }
+
+    #[test]
+    fn mixed_v2_v3_route_keeps_kinds_and_fees() {
+        let v2 = Hop { kind: RouterKind::UniswapV2, fee: 3000, ..hop(1, 2, 1_000) };
+        let v3 = Hop { fee: 100, ..hop(2, 3, 990) };
+        let hops = vec![v2, v3, hop(3, 1, 980)];
+        let entry = FlashEntry::Balancer { loans: vec![LoanLeg::new(token(1), U256::from(1_000))] };
+        let (_, route) = decode(&encode(&entry, &hops).unwrap()).unwrap();
+        assert_eq!(route.kinds, [RouterKind::UniswapV2, RouterKind::UniswapV3, RouterKind::UniswapV3]);
+        assert_eq!(route.fees, [3000, 100, 500]);
+    }
+
+    #[test]
+    fn curve_hop_carries_its_coin_indices() {
+        let curve = Hop { kind: RouterKind::Curve, fee: curve_indices(1, 2, true), ..hop(1, 2, 1_000) };
+        let hops = vec![curve, hop(2, 1, 1_001)];
+        let entry = FlashEntry::Balancer { loans: vec![LoanLeg::new(token(1), U256::from(1_000))] };
+        let (_, route) = decode(&encode(&entry, &hops).unwrap()).unwrap();
+        assert_eq!(route.kinds[0], RouterKind::Curve);
+        assert_eq!(route.fees[0], 0x1_01_02);
+    }
+
+    #[test]
+    fn rejects_disconnected_hops() {
+        assert!(ExecutionPath::from_hops(&[hop(1, 2, 1), hop(3, 1, 1)]).is_err());
+        assert!(ExecutionPath::from_hops(&[]).is_err());
+    }
+
+    #[test]
+    fn route_gets_explicit_wrap_hop() {
+        let path = [native::native_matic(), token(2), native::native_matic()];
+        let amounts = [U256::from(10), U256::from(20), U256::from(11)];
+        let hops = hops_from_route(&path, &amounts, &[token(7), token(8)]).unwrap();
+        let kinds: Vec<_> = hops.iter().map(|h| h.kind).collect();
+        assert_eq!(kinds, [RouterKind::Wrap, RouterKind::UniswapV3, RouterKind::UniswapV3, RouterKind::Wrap]);
+        assert_eq!(hops[0].amount_in, hops[1].amount_in);
+        assert!(ExecutionPath::from_hops(&hops).is_ok());
+    }
+
+    #[test]
+    fn decode_errors_say_what_is_wrong() {
+        assert_eq!(decode(&[0x12, 0x34]), Err(DecodeError::Truncated));
+        assert_eq!(decode(&[0xde, 0xad, 0xbe, 0xef]), Err(DecodeError::UnknownSelector([0xde, 0xad, 0xbe, 0xef])));
+        let selector = EXECUTOR_ABI.function("executeAaveFlashLoan").unwrap().short_signature();
+        assert!(matches!(decode(&selector), Err(DecodeError::Malformed(_))));
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_calldata_never_panics(input in prop::collection::vec(any::<u8>(), 0..1024)) {
+            let _ = decode(&input);
+        }
+
+        // Every entrypoint's selector in front of random words
+        #[test]
+        fn hostile_words_never_panic(index in 0..3usize, words in prop::collection::vec(any::<[u8; 32]>(), 0..24)) {
+            let function = ["executeFlashLoanArbitrage", "executeBalancerFlashLoan", "executeAaveFlashLoan"][index];
+            let selector = EXECUTOR_ABI.function(function).unwrap().short_signature();
+            let _ = decode(&[selector.to_vec(), words.concat()].concat());
+        }
+    }
+}
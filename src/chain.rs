@@ -0,0 +1,346 @@
+// src/chain.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::env;
+use std::env::VarError;
+use std::time::Duration;
+
+use crate::classifier::DexFamily;
+use crate::flash_loan::BALANCER_VAULT;
+use crate::native::WMATIC;
+use crate::pool_state::{QUICKSWAP_FACTORY, QUICKSWAP_ROUTER, SUSHISWAP_FACTORY, SUSHISWAP_ROUTER};
+use crate::strategies::jit_liquidity::{UNISWAP_V3_ROUTER, UNISWAP_V3_ROUTER_02};
+
+const POLYGON_USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+const POLYGON_USDT: &str = "0xc2132D05D31c914a87C6611C10748AEb04B58e8F";
+const POLYGON_WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+// Curve aave pool: DAI/USDC/USDT via exchange_underlying
+const CURVE_AAVE_POOL: &str = "0x445FE580eF8d70FF569aB36e80c647af338db351";
+const STMATIC: &str = "0x3A58a54C066FdC0f2D55FC9C89F0415C92eBf3C4";
+const STMATIC_RATE_PROVIDER: &str = "0xdEd6C522d803E35f65318a9a4d7333a22d582199";
+const MATICX: &str = "0xfa68FB4628DFF1028CFEc22b4162FCcd0d45efb6";
+const MATICX_RATE_PROVIDER: &str = "0xeE652bbF72689AA59F0B8F981c9c90e2A8Af8d8f";
+const MATICX_CHILD_POOL: &str = "0xfd225C9e6601C9d38d8F98d8731BF59eFcF8C0E3";
+
+const ZKEVM_WETH: &str = "0x4F9A0e7FD2Bf6067db6994CF12E4495Df938E6e9";
+const ZKEVM_USDC: &str = "0xA8CE8aee21bC2A48a5EF670afCc9274C7bbbC035";
+const ZKEVM_USDT: &str = "0x1E4a5963aBFD975d8c9021ce480b42188849D41d";
+const PANCAKESWAP_ZKEVM_FACTORY: &str = "0x02a84c1b3BBD7401a5f7fa98a384EBC70bB5749E";
+const PANCAKESWAP_ZKEVM_ROUTER: &str = "0x8cFe327CEc66d1C090Dd72bd0FF11d690C33a2Eb";
+
+const BASE_WETH: &str = "0x4200000000000000000000000000000000000006";
+const BASE_USDC: &str = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913";
+const BASE_USDBC: &str = "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA";
+const UNISWAP_V2_BASE_FACTORY: &str = "0x8909Dc15e40173Ff4699343b6eB8132c65e18eC6";
+const UNISWAP_V2_BASE_ROUTER: &str = "0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24";
+const SUSHISWAP_BASE_FACTORY: &str = "0x71524B4f93c58fcbF659783284E38825f0622859";
+const SUSHISWAP_BASE_ROUTER: &str = "0x6BDED42c6DA8FBf0d2bA55B2fa120C5e0c8D7891";
+
+const ARBITRUM_WETH: &str = "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1";
+const ARBITRUM_USDC: &str = "0xaf88d065e77c8cC2239327C5EDb3A432268e5831";
+const ARBITRUM_USDT: &str = "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9";
+const CAMELOT_FACTORY: &str = "0x6EcCab422D763aC031210895C81787E87B43A652";
+const CAMELOT_ROUTER: &str = "0xc873fEcbd354f5A56E00E710B90EF4201db2448d";
+
+fn address(s: &str) -> Address {
+    s.parse().unwrap()
+}
+
+#[derive(Debug, Clone)]
+pub struct Dex {
+    pub name: &'static str,
+    pub family: DexFamily,
+    // None for V3 routers; their pools are discovered separately
+    pub factory: Option<Address>,
+    pub router: Address,
+}
+
+/// A liquid staking derivative of the native token and where its exchange
+/// rate comes from.
+#[derive(Debug, Clone)]
+pub struct StakedAsset {
+    pub symbol: &'static str,
+    pub token: Address,
+    // getRate(): native per derivative, 1e18 precision
+    pub rate_provider: Address,
+    // Contracts whose FxChild messages carry the rate from L1
+    pub rate_receivers: Vec<Address>,
+    // Mints the derivative for native at the rate, if it can be done on this chain
+    pub mint_pool: Option<Address>,
+}
+
+/// Which strategies and venues run on a chain.
+#[derive(Debug, Clone)]
+pub struct StrategyToggles {
+    pub mempool_arbitrage: bool,
+    pub jit: bool,
+    pub sandwich: bool,
+    pub triangular: bool,
+    pub stable_arb: bool,
+    pub redemption_arb: bool,
+    pub fastlane: bool,
+    pub atlas: bool,
+}
+
+impl StrategyToggles {
+    fn all() -> Self {
+        Self {
+            mempool_arbitrage: true,
+            jit: true,
+            sandwich: true,
+            triangular: true,
+            stable_arb: true,
+            redemption_arb: true,
+            fastlane: true,
+            atlas: true,
+        }
+    }
+
+    // DISABLED_STRATEGIES=jit,atlas switches strategies off on top of the profile
+    fn disable(&mut self, name: &str) -> Result<()> {
+        match name {
+            "mempool" => self.mempool_arbitrage = false,
+            "jit" => self.jit = false,
+            "sandwich" => self.sandwich = false,
+            "triangular" => self.triangular = false,
+            "stable" => self.stable_arb = false,
+            "redemption" => self.redemption_arb = false,
+            "fastlane" => self.fastlane = false,
+            "atlas" => self.atlas = false,
+            other => return Err(anyhow!("Unknown strategy {:?} in DISABLED_STRATEGIES", other)),
+        }
+        Ok(())
+    }
+}
+
+/// Everything that differs between the chains the bot can run against.
+#[derive(Debug, Clone)]
+pub struct ChainProfile {
+    pub name: &'static str,
+    pub chain_id: u64,
+    // Per-chain settings are read as <PREFIX>_<KEY>, e.g. BASE_WS_URL
+    pub env_prefix: &'static str,
+    // WMATIC on PoS, WETH elsewhere; the base of every cycle
+    pub wrapped_native: Address,
+    pub usdc: Address,
+    // Discovery and balance set, wrapped native first
+    pub tokens: Vec<Address>,
+    // Symbol -> address for assets that exist on several chains, so the same
+    // pair can be looked up on each
+    pub assets: Vec<(&'static str, Address)>,
+    pub dexes: Vec<Dex>,
+    // Curve stable pools the stable arb strategy trades against
+    pub curve_pools: Vec<Address>,
+    // Liquid staking derivatives arbitraged against their exchange rate
+    pub staked_assets: Vec<StakedAsset>,
+    pub balancer_vault: Option<Address>,
+    // bloXroute's name for the network, where it has one
+    pub bloxroute_network: Option<&'static str>,
+    // Nominal block interval; the timing model refines it from observed heads
+    pub block_time: Duration,
+    // Bor: blocks each proposer produces before rotating. None off Polygon PoS
+    pub sprint_length: Option<u64>,
+    pub strategies: StrategyToggles,
+}
+
+impl ChainProfile {
+    pub fn polygon_pos() -> Self {
+        Self {
+            name: "polygon",
+            chain_id: 137,
+            env_prefix: "POLYGON",
+            wrapped_native: address(WMATIC),
+            usdc: address(POLYGON_USDC),
+            tokens: vec![address(WMATIC), address(POLYGON_USDC), address(POLYGON_USDT)],
+            assets: vec![("WETH", address(POLYGON_WETH)), ("USDC", address(POLYGON_USDC)), ("USDT", address(POLYGON_USDT))],
+            dexes: vec![
+                Dex { name: "QuickSwap", family: DexFamily::UniswapV2, factory: Some(address(QUICKSWAP_FACTORY)), router: address(QUICKSWAP_ROUTER) },
+                Dex { name: "SushiSwap", family: DexFamily::UniswapV2, factory: Some(address(SUSHISWAP_FACTORY)), router: address(SUSHISWAP_ROUTER) },
+                Dex { name: "UniswapV3", family: DexFamily::UniswapV3, factory: None, router: address(UNISWAP_V3_ROUTER) },
+                Dex { name: "UniswapV3", family: DexFamily::UniswapV3, factory: None, router: address(UNISWAP_V3_ROUTER_02) },
+            ],
+            curve_pools: vec![address(CURVE_AAVE_POOL)],
+            staked_assets: vec![
+                StakedAsset {
+                    symbol: "stMATIC",
+                    token: address(STMATIC),
+                    rate_provider: address(STMATIC_RATE_PROVIDER),
+                    rate_receivers: vec![address(STMATIC_RATE_PROVIDER)],
+                    mint_pool: None,
+                },
+                StakedAsset {
+                    symbol: "MaticX",
+                    token: address(MATICX),
+                    rate_provider: address(MATICX_RATE_PROVIDER),
+                    rate_receivers: vec![address(MATICX_RATE_PROVIDER), address(MATICX_CHILD_POOL)],
+                    mint_pool: Some(address(MATICX_CHILD_POOL)),
+                },
+            ],
+            balancer_vault: Some(address(BALANCER_VAULT)),
+            bloxroute_network: Some("Polygon-Mainnet"),
+            block_time: Duration::from_secs(2),
+            sprint_length: Some(16),
+            strategies: StrategyToggles::all(),
+        }
+    }
+
+    // No Uniswap V3 deployment, FastLane or Atlas on zkEVM
+    pub fn polygon_zkevm() -> Self {
+        Self {
+            name: "polygon-zkevm",
+            chain_id: 1101,
+            env_prefix: "POLYGON_ZKEVM",
+            wrapped_native: address(ZKEVM_WETH),
+            usdc: address(ZKEVM_USDC),
+            tokens: vec![address(ZKEVM_WETH), address(ZKEVM_USDC), address(ZKEVM_USDT)],
+            assets: vec![("WETH", address(ZKEVM_WETH)), ("USDC", address(ZKEVM_USDC)), ("USDT", address(ZKEVM_USDT))],
+            dexes: vec![
+                Dex { name: "PancakeSwap", family: DexFamily::UniswapV2, factory: Some(address(PANCAKESWAP_ZKEVM_FACTORY)), router: address(PANCAKESWAP_ZKEVM_ROUTER) },
+            ],
+            curve_pools: Vec::new(),
+            staked_assets: Vec::new(),
+            balancer_vault: Some(address(BALANCER_VAULT)),
+            bloxroute_network: None,
+            block_time: Duration::from_secs(3),
+            sprint_length: None,
+            strategies: StrategyToggles {
+                jit: false,
+                // The victim decoder only knows Polygon's QuickSwap router
+                sandwich: false,
+                fastlane: false,
+                atlas: false,
+                ..StrategyToggles::all()
+            },
+        }
+    }
+
+    // The JIT strategy is wired to Polygon's WMATIC pools; FastLane and Atlas are Polygon-only
+    pub fn base() -> Self {
+        Self {
+            name: "base",
+            chain_id: 8453,
+            env_prefix: "BASE",
+            wrapped_native: address(BASE_WETH),
+            usdc: address(BASE_USDC),
+            tokens: vec![address(BASE_WETH), address(BASE_USDC), address(BASE_USDBC)],
+            assets: vec![("WETH", address(BASE_WETH)), ("USDC", address(BASE_USDC))],
+            dexes: vec![
+                Dex { name: "UniswapV2", family: DexFamily::UniswapV2, factory: Some(address(UNISWAP_V2_BASE_FACTORY)), router: address(UNISWAP_V2_BASE_ROUTER) },
+                Dex { name: "SushiSwap", family: DexFamily::UniswapV2, factory: Some(address(SUSHISWAP_BASE_FACTORY)), router: address(SUSHISWAP_BASE_ROUTER) },
+            ],
+            curve_pools: Vec::new(),
+            staked_assets: Vec::new(),
+            balancer_vault: Some(address(BALANCER_VAULT)),
+            bloxroute_network: None,
+            block_time: Duration::from_secs(2),
+            sprint_length: None,
+            strategies: StrategyToggles {
+                jit: false,
+                sandwich: false,
+                fastlane: false,
+                atlas: false,
+                ..StrategyToggles::all()
+            },
+        }
+    }
+
+    pub fn arbitrum() -> Self {
+        Self {
+            name: "arbitrum",
+            chain_id: 42161,
+            env_prefix: "ARBITRUM",
+            wrapped_native: address(ARBITRUM_WETH),
+            usdc: address(ARBITRUM_USDC),
+            tokens: vec![address(ARBITRUM_WETH), address(ARBITRUM_USDC), address(ARBITRUM_USDT)],
+            assets: vec![("WETH", address(ARBITRUM_WETH)), ("USDC", address(ARBITRUM_USDC)), ("USDT", address(ARBITRUM_USDT))],
+            dexes: vec![
+                Dex { name: "SushiSwap", family: DexFamily::UniswapV2, factory: Some(address(SUSHISWAP_FACTORY)), router: address(SUSHISWAP_ROUTER) },
+                Dex { name: "Camelot", family: DexFamily::UniswapV2, factory: Some(address(CAMELOT_FACTORY)), router: address(CAMELOT_ROUTER) },
+                Dex { name: "UniswapV3", family: DexFamily::UniswapV3, factory: None, router: address(UNISWAP_V3_ROUTER) },
+                Dex { name: "UniswapV3", family: DexFamily::UniswapV3, factory: None, router: address(UNISWAP_V3_ROUTER_02) },
+            ],
+            curve_pools: Vec::new(),
+            staked_assets: Vec::new(),
+            balancer_vault: Some(address(BALANCER_VAULT)),
+            bloxroute_network: None,
+            block_time: Duration::from_millis(250),
+            sprint_length: None,
+            strategies: StrategyToggles {
+                jit: false,
+                sandwich: false,
+                fastlane: false,
+                atlas: false,
+                ..StrategyToggles::all()
+            },
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "polygon" | "polygon-pos" => Some(Self::polygon_pos()),
+            "polygon-zkevm" | "zkevm" => Some(Self::polygon_zkevm()),
+            "base" => Some(Self::base()),
+            "arbitrum" => Some(Self::arbitrum()),
+            _ => None,
+        }
+    }
+
+    /// CHAINS=polygon,base (or the older CHAIN) selects the profiles, default
+    /// polygon. DISABLED_STRATEGIES and <PREFIX>_DISABLED_STRATEGIES trim them.
+    pub fn from_env() -> Result<Vec<Self>> {
+        let names = env::var("CHAINS").or_else(|_| env::var("CHAIN")).unwrap_or_else(|_| "polygon".to_string());
+        let mut profiles = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let mut profile = Self::by_name(name).ok_or_else(|| anyhow!("Unknown chain {:?}", name))?;
+            for var in ["DISABLED_STRATEGIES".to_string(), format!("{}_DISABLED_STRATEGIES", profile.env_prefix)] {
+                if let Ok(disabled) = env::var(var) {
+                    for strategy in disabled.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                        profile.strategies.disable(strategy)?;
+                    }
+                }
+            }
+            profiles.push(profile);
+        }
+        if profiles.is_empty() {
+            return Err(anyhow!("No chains configured"));
+        }
+        Ok(profiles)
+    }
+
+    /// `<PREFIX>_<key>`. Polygon falls back to the bare key, which is how every
+    /// setting was named before other chains existed.
+    pub fn var(&self, key: &str) -> Result<String, VarError> {
+        env::var(format!("{}_{}", self.env_prefix, key)).or_else(|e| match self.name {
+            "polygon" => env::var(key),
+            _ => Err(e),
+        })
+    }
+
+    pub fn asset(&self, symbol: &str) -> Option<Address> {
+        self.assets.iter().find(|(s, _)| *s == symbol).map(|(_, a)| *a)
+    }
+
+    /// The router V3 hops are executed through, if the chain has Uniswap V3.
+    pub fn v3_router(&self) -> Option<Address> {
+        self.dexes.iter().find(|d| d.family == DexFamily::UniswapV3).map(|d| d.router)
+    }
+
+    /// The profile's Curve pools plus any in `<PREFIX>_CURVE_POOLS` (comma separated).
+    pub fn curve_pools(&self) -> Result<Vec<Address>> {
+        let mut pools = self.curve_pools.clone();
+        if let Ok(extra) = self.var("CURVE_POOLS") {
+            for pool in extra.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let pool: Address = pool.parse().map_err(|_| anyhow!("Invalid address {:?} in CURVE_POOLS", pool))?;
+                if !pools.contains(&pool) {
+                    pools.push(pool);
+                }
+            }
+        }
+        Ok(pools)
+    }
+
+    /// (factory, router) of every V2-style DEX, for pool discovery.
+    pub fn v2_dexes(&self) -> Vec<(Address, Address)> {
+        self.dexes.iter().filter_map(|d| d.factory.map(|f| (f, d.router))).collect()
+    }
+}
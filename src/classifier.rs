@@ -0,0 +1,401 @@
+// src/classifier.rs
+use ethers::abi::{Abi, AbiParser, Function};
+use ethers::prelude::*;
+use ethers::utils::id;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::chain::ChainProfile;
+use crate::oracle_monitor::CHAINLINK_OCR_ABI;
+use crate::strategies::multicall::swap_legs;
+
+// Swap entrypoints on the V2 routers (QuickSwap, SushiSwap)
+static V2_ROUTER_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function swapExactTokensForTokens(uint256,uint256,address[],address,uint256) returns (uint256[])",
+        "function swapExactTokensForETH(uint256,uint256,address[],address,uint256) returns (uint256[])",
+        "function swapExactETHForTokens(uint256,address[],address,uint256) returns (uint256[])",
+        "function swapTokensForExactTokens(uint256,uint256,address[],address,uint256) returns (uint256[])",
+        "function swapETHForExactTokens(uint256,address[],address,uint256) returns (uint256[])",
+        "function swapTokensForExactETH(uint256,uint256,address[],address,uint256) returns (uint256[])",
+        "function swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+        "function swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+        "function swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)",
+    ]).expect("parse v2 router abi")
+});
+
+// Swap entrypoints on SwapRouter and SwapRouter02, including the multicall wrappers
+static V3_ROUTER_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160)) returns (uint256)",
+        "function exactInputSingle((address,address,uint24,address,uint256,uint256,uint160)) returns (uint256)",
+        "function exactInput((bytes,address,uint256,uint256,uint256)) returns (uint256)",
+        "function exactInput((bytes,address,uint256,uint256)) returns (uint256)",
+        "function exactOutputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160)) returns (uint256)",
+        "function exactOutputSingle((address,address,uint24,address,uint256,uint256,uint160)) returns (uint256)",
+        "function exactOutput((bytes,address,uint256,uint256,uint256)) returns (uint256)",
+        "function exactOutput((bytes,address,uint256,uint256)) returns (uint256)",
+        "function multicall(bytes[]) returns (bytes[])",
+        "function multicall(uint256,bytes[]) returns (bytes[])",
+        "function multicall(bytes32,bytes[]) returns (bytes[])",
+    ]).expect("parse v3 router abi")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DexFamily {
+    UniswapV2,
+    UniswapV3,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SelectorEntry {
+    pub family: DexFamily,
+    pub function: &'static Function,
+}
+
+// Built once from the router ABIs so classification is a single hash lookup on
+// the selector, before any router match or ABI decoding
+static SELECTOR_INDEX: Lazy<HashMap<[u8; 4], SelectorEntry>> = Lazy::new(|| {
+    let mut index = HashMap::new();
+    for (family, abi) in [(DexFamily::UniswapV2, &*V2_ROUTER_ABI), (DexFamily::UniswapV3, &*V3_ROUTER_ABI)] {
+        for function in abi.functions() {
+            index.insert(function.short_signature(), SelectorEntry { family, function });
+        }
+    }
+    index
+});
+
+pub fn lookup_selector(input: &[u8]) -> Option<&'static SelectorEntry> {
+    SELECTOR_INDEX.get(&selector(input)?)
+}
+
+/// The first swap leg of router calldata and its entry, looking through
+/// multicall batches so a `selfPermit` + swap decodes as the swap.
+pub fn lookup_swap(input: &[u8]) -> Option<(&'static SelectorEntry, Bytes)> {
+    let entry = lookup_selector(input)?;
+    if entry.function.name != "multicall" {
+        return Some((entry, Bytes::from(input.to_vec())));
+    }
+    swap_legs(input)
+        .into_iter()
+        .find_map(|leg| lookup_selector(&leg).filter(|e| e.function.name != "multicall").map(|e| (e, leg)))
+}
+
+// Aggregators route through our pools but their calldata isn't worth decoding per venue
+const AGGREGATORS: &[(&str, &str)] = &[
+    ("0x1111111254EEB25477B68fb85Ed929f73A960582", "1inch"),
+    ("0xDEF171Fe48CF0115B1d80b88dc8eAB59176FEe57", "ParaSwap"),
+    ("0xDef1C0ded9bec7F1a1670819833240f027b25EfF", "0x"),
+    ("0x4E3288c9ca110bCC82bf38F09A7b425c095d92Bf", "Odos"),
+];
+
+pub fn aggregator_addresses() -> Vec<Address> {
+    AGGREGATORS.iter().map(|(a, _)| a.parse().unwrap()).collect()
+}
+
+pub const AAVE_V3_POOL: &str = "0x794a61358D6845594F94dc1DB02A252b5b4814aD";
+
+const LENDING_ACTIONS: &[(&str, &str)] = &[
+    ("supply(address,uint256,address,uint16)", "supply"),
+    ("withdraw(address,uint256,address)", "withdraw"),
+    ("borrow(address,uint256,uint256,uint16,address)", "borrow"),
+    ("repay(address,uint256,uint256,address)", "repay"),
+    ("liquidationCall(address,address,address,uint256,bool)", "liquidation"),
+    ("flashLoan(address,address[],uint256[],uint256[],address,bytes,uint16)", "flash_loan"),
+];
+
+// Common public-mint entrypoints; NFT drops spike gas but never touch our pools
+const NFT_MINT_SIGNATURES: &[&str] = &[
+    "mint(uint256)",
+    "mint(address,uint256)",
+    "publicMint(uint256)",
+    "safeMint(address)",
+    "mint()",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExclusionKind {
+    NftMarketplace,
+    Bridge,
+}
+
+impl ExclusionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExclusionKind::NftMarketplace => "nft_marketplace",
+            ExclusionKind::Bridge => "bridge",
+        }
+    }
+}
+
+// Marketplace sweeps and bridge calls are large batch txs that can pass the
+// swap heuristics further down (tracing, unclassified simulation) but never
+// trade on our pools, so they leave the pipeline here
+const EXCLUDED_CONTRACTS: &[(&str, &str, ExclusionKind)] = &[
+    ("0x00000000000001ad428e4906aE43D8F9852d0dD6", "Seaport 1.4", ExclusionKind::NftMarketplace),
+    ("0x00000000000000ADc04C56Bf30aC9d3c0aAF14dC", "Seaport 1.5", ExclusionKind::NftMarketplace),
+    ("0x0000000000000068F116a894984e2DB1123eB395", "Seaport 1.6", ExclusionKind::NftMarketplace),
+    ("0xC2c862322E9c97D6244a3506655DA95F05246Fd8", "Reservoir", ExclusionKind::NftMarketplace),
+    ("0x45A01E4e04F14f7A4a6702c74187c5F6222033cd", "Stargate", ExclusionKind::Bridge),
+    ("0x9295ee1d8C5b022Be115A2AD3c30C72E34e7F096", "Across", ExclusionKind::Bridge),
+    ("0x8F5BBB2BB8c2Ee94639E55d5F41de9b4839C1280", "Synapse", ExclusionKind::Bridge),
+    ("0x88DCDC47D2f83a99CF0000FDF667A468bB958a78", "cBridge", ExclusionKind::Bridge),
+];
+
+/// What a pending transaction is, as far as strategies care.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassifiedTx {
+    RouterSwap { router: Address, dex: &'static str, function: &'static str },
+    AggregatorSwap { aggregator: Address, name: &'static str },
+    OracleUpdate { aggregator: Address },
+    LendingAction { pool: Address, action: &'static str },
+    NftMint { contract: Address },
+    Excluded { contract: Address, venue: &'static str, kind: ExclusionKind },
+    Unclassified,
+}
+
+impl ClassifiedTx {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClassifiedTx::RouterSwap { .. } => "router_swap",
+            ClassifiedTx::AggregatorSwap { .. } => "aggregator_swap",
+            ClassifiedTx::OracleUpdate { .. } => "oracle_update",
+            ClassifiedTx::LendingAction { .. } => "lending_action",
+            ClassifiedTx::NftMint { .. } => "nft_mint",
+            ClassifiedTx::Excluded { .. } => "excluded",
+            ClassifiedTx::Unclassified => "unclassified",
+        }
+    }
+
+    // Anything that can move a DEX price we trade against
+    pub fn is_swap(&self) -> bool {
+        matches!(self, ClassifiedTx::RouterSwap { .. } | ClassifiedTx::AggregatorSwap { .. })
+    }
+}
+
+/// One stage of the pending-tx pipeline. Returns `None` to let the next classifier try.
+pub trait TxClassifier: Send + Sync {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx>;
+}
+
+fn selector(input: &[u8]) -> Option<[u8; 4]> {
+    input.get(..4).map(|s| [s[0], s[1], s[2], s[3]])
+}
+
+/// Known DEX router called with one of its swap selectors.
+pub struct RouterClassifier {
+    routers: HashMap<Address, (&'static str, DexFamily)>,
+}
+
+impl RouterClassifier {
+    pub fn for_chain(chain: &ChainProfile) -> Self {
+        let routers = chain.dexes.iter().map(|dex| (dex.router, (dex.name, dex.family))).collect();
+        Self { routers }
+    }
+}
+
+impl Default for RouterClassifier {
+    fn default() -> Self {
+        Self::for_chain(&ChainProfile::polygon_pos())
+    }
+}
+
+impl TxClassifier for RouterClassifier {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx> {
+        // Most pending txs fail here without touching the router map
+        let (entry, _) = lookup_swap(&tx.input)?;
+        let router = tx.to?;
+        let (dex, family) = self.routers.get(&router)?;
+        if *family != entry.family {
+            return None;
+        }
+        Some(ClassifiedTx::RouterSwap {
+            router,
+            dex,
+            function: &entry.function.name,
+        })
+    }
+}
+
+pub struct AggregatorClassifier {
+    aggregators: HashMap<Address, &'static str>,
+}
+
+impl Default for AggregatorClassifier {
+    fn default() -> Self {
+        Self {
+            aggregators: AGGREGATORS.iter().map(|(a, name)| (a.parse().unwrap(), *name)).collect(),
+        }
+    }
+}
+
+impl TxClassifier for AggregatorClassifier {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx> {
+        let aggregator = tx.to?;
+        let name = self.aggregators.get(&aggregator)?;
+        Some(ClassifiedTx::AggregatorSwap { aggregator, name })
+    }
+}
+
+/// Chainlink OCR `transmit`; the OracleMonitor decides whether it's a feed we follow.
+pub struct OracleClassifier {
+    transmit: [u8; 4],
+}
+
+impl Default for OracleClassifier {
+    fn default() -> Self {
+        Self {
+            transmit: CHAINLINK_OCR_ABI.function("transmit").expect("transmit in OCR abi").short_signature(),
+        }
+    }
+}
+
+impl TxClassifier for OracleClassifier {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx> {
+        let aggregator = tx.to?;
+        (selector(&tx.input)? == self.transmit).then_some(ClassifiedTx::OracleUpdate { aggregator })
+    }
+}
+
+pub struct LendingClassifier {
+    pool: Address,
+    actions: HashMap<[u8; 4], &'static str>,
+}
+
+impl Default for LendingClassifier {
+    fn default() -> Self {
+        Self {
+            pool: AAVE_V3_POOL.parse().unwrap(),
+            actions: LENDING_ACTIONS.iter().map(|(sig, action)| (id(sig), *action)).collect(),
+        }
+    }
+}
+
+impl TxClassifier for LendingClassifier {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx> {
+        if tx.to != Some(self.pool) {
+            return None;
+        }
+        let action = self.actions.get(&selector(&tx.input)?)?;
+        Some(ClassifiedTx::LendingAction { pool: self.pool, action })
+    }
+}
+
+pub struct NftMintClassifier {
+    selectors: Vec<[u8; 4]>,
+}
+
+impl Default for NftMintClassifier {
+    fn default() -> Self {
+        Self {
+            selectors: NFT_MINT_SIGNATURES.iter().map(|sig| id(sig)).collect(),
+        }
+    }
+}
+
+impl TxClassifier for NftMintClassifier {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx> {
+        let contract = tx.to?;
+        self.selectors
+            .contains(&selector(&tx.input)?)
+            .then_some(ClassifiedTx::NftMint { contract })
+    }
+}
+
+/// Known NFT marketplace or bridge contract, whatever the function.
+pub struct ExclusionClassifier {
+    contracts: HashMap<Address, (&'static str, ExclusionKind)>,
+}
+
+impl Default for ExclusionClassifier {
+    fn default() -> Self {
+        Self {
+            contracts: EXCLUDED_CONTRACTS.iter().map(|(a, venue, kind)| (a.parse().unwrap(), (*venue, *kind))).collect(),
+        }
+    }
+}
+
+impl TxClassifier for ExclusionClassifier {
+    fn classify(&self, tx: &Transaction) -> Option<ClassifiedTx> {
+        let contract = tx.to?;
+        let (venue, kind) = self.contracts.get(&contract)?;
+        Some(ClassifiedTx::Excluded { contract, venue, kind: *kind })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ExcludedVolume {
+    txs: u64,
+    native_value: U256,
+}
+
+/// What the exclusion classifier kept out of the pipeline, per venue, since the
+/// last report.
+#[derive(Default)]
+pub struct ExclusionStats {
+    venues: Mutex<HashMap<&'static str, (ExclusionKind, ExcludedVolume)>>,
+}
+
+impl ExclusionStats {
+    pub fn record(&self, venue: &'static str, kind: ExclusionKind, value: U256) {
+        let mut venues = self.venues.lock().unwrap();
+        let (_, volume) = venues.entry(venue).or_insert((kind, ExcludedVolume::default()));
+        volume.txs += 1;
+        volume.native_value += value;
+    }
+
+    pub fn report(&self) {
+        let venues = std::mem::take(&mut *self.venues.lock().unwrap());
+        for (venue, (kind, volume)) in venues {
+            info!(
+                venue,
+                kind = kind.name(),
+                txs = volume.txs,
+                native_value = %volume.native_value,
+                "Excluded volume"
+            );
+        }
+    }
+}
+
+/// Runs classifiers in order; the first match wins.
+pub struct ClassifierChain {
+    classifiers: Vec<Box<dyn TxClassifier>>,
+}
+
+impl Default for ClassifierChain {
+    fn default() -> Self {
+        Self::for_chain(&ChainProfile::polygon_pos())
+    }
+}
+
+impl ClassifierChain {
+    pub fn for_chain(chain: &ChainProfile) -> Self {
+        Self::new(vec![
+            // First, so exclusions cost one map lookup and nothing else
+            Box::new(ExclusionClassifier::default()),
+            Box::new(RouterClassifier::for_chain(chain)),
+            Box::new(AggregatorClassifier::default()),
+            Box::new(OracleClassifier::default()),
+            Box::new(LendingClassifier::default()),
+            Box::new(NftMintClassifier::default()),
+        ])
+    }
+
+    pub fn new(classifiers: Vec<Box<dyn TxClassifier>>) -> Self {
+        Self { classifiers }
+    }
+
+    pub fn push(&mut self, classifier: Box<dyn TxClassifier>) {
+        self.classifiers.push(classifier);
+    }
+
+    pub fn classify(&self, tx: &Transaction) -> ClassifiedTx {
+        self.classifiers
+            .iter()
+            .find_map(|c| c.classify(tx))
+            .unwrap_or(ClassifiedTx::Unclassified)
+    }
+}
@@ -0,0 +1,247 @@
+// src/competition.rs
+use anyhow::Result;
+use ethers::{prelude::*, utils::keccak256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::shadow::tx_references_path;
+use crate::storage::{CompetitorRecord, InclusionStatus, Storage};
+use crate::rpc_budget::RpcTransport;
+
+// Outcomes kept per strategy for the bidding model
+const HISTORY: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct WatchedSubmission {
+    pub opportunity_id: String,
+    pub strategy: String,
+    pub path: Vec<Address>,
+    pub routers: Vec<Address>,
+    // Tx or bundle hash returned by the submission venue
+    pub submission_hash: H256,
+    // Our executor; any tx to it in the target block is ours
+    pub executor: Address,
+    pub target_block: U64,
+    pub priority_fee: U256,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BidOutcome {
+    pub won: bool,
+    pub our_priority_fee: U256,
+    // Priority fee the winning competitor paid, when we lost to one
+    pub competitor_priority_fee: Option<U256>,
+}
+
+/// What the target blocks due this block showed: submissions that landed, and
+/// the competitors that took the ones that didn't.
+#[derive(Debug, Default)]
+pub struct BlockOutcomes {
+    pub won: Vec<WatchedSubmission>,
+    pub lost_to: Vec<CompetitorRecord>,
+}
+
+/// Per-strategy history of wins and losses, and what competitors paid to beat us.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BiddingModel {
+    outcomes: HashMap<String, VecDeque<BidOutcome>>,
+}
+
+impl BiddingModel {
+    pub fn record(&mut self, strategy: &str, outcome: BidOutcome) {
+        let history = self.outcomes.entry(strategy.to_string()).or_default();
+        if history.len() == HISTORY {
+            history.pop_front();
+        }
+        history.push_back(outcome);
+    }
+
+    pub fn win_rate(&self, strategy: &str) -> Option<f64> {
+        let history = self.outcomes.get(strategy).filter(|h| !h.is_empty())?;
+        let wins = history.iter().filter(|o| o.won).count();
+        Some(wins as f64 / history.len() as f64)
+    }
+
+    /// Priority fee that would have beaten `percentile` of the competitors we lost to.
+    pub fn winning_priority_fee(&self, strategy: &str, percentile: f64) -> Option<U256> {
+        let mut fees: Vec<U256> = self
+            .outcomes
+            .get(strategy)?
+            .iter()
+            .filter_map(|o| o.competitor_priority_fee)
+            .collect();
+        if fees.is_empty() {
+            return None;
+        }
+        fees.sort();
+        let index = ((fees.len() - 1) as f64 * percentile.clamp(0.0, 1.0)).round() as usize;
+        Some(fees[index] + 1)
+    }
+}
+
+/// Checks the target block of every submission. When ours didn't land, looks for
+/// the transaction that took the same pools and records who it was and what it paid.
+pub struct CompetitionTracker {
+    provider: Arc<Provider<RpcTransport>>,
+    storage: Option<Storage>,
+    pending: Mutex<Vec<WatchedSubmission>>,
+    model: Arc<Mutex<BiddingModel>>,
+}
+
+impl CompetitionTracker {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, storage: Option<Storage>) -> Self {
+        Self {
+            provider,
+            storage,
+            pending: Mutex::new(Vec::new()),
+            model: Arc::new(Mutex::new(BiddingModel::default())),
+        }
+    }
+
+    pub fn model(&self) -> Arc<Mutex<BiddingModel>> {
+        self.model.clone()
+    }
+
+    pub async fn watch(&self, submission: WatchedSubmission) {
+        self.pending.lock().await.push(submission);
+    }
+
+    pub async fn on_block(&self, block: U64) -> Result<BlockOutcomes> {
+        let due: Vec<WatchedSubmission> = {
+            let mut pending = self.pending.lock().await;
+            let (due, waiting) = pending.drain(..).partition(|s| s.target_block <= block);
+            *pending = waiting;
+            due
+        };
+
+        let mut outcomes = BlockOutcomes::default();
+        for submission in due {
+            // Back to pending on a failed read: the next block retries it
+            let landed = match self.provider.get_block_with_txs(submission.target_block).await {
+                Ok(Some(b)) => b,
+                Ok(None) => {
+                    self.pending.lock().await.push(submission);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Reading block {} for {} failed: {:?}", submission.target_block, submission.opportunity_id, e);
+                    self.pending.lock().await.push(submission);
+                    continue;
+                }
+            };
+
+            let won = landed
+                .transactions
+                .iter()
+                .any(|tx| tx.hash == submission.submission_hash || tx.to == Some(submission.executor));
+            if won {
+                self.model.lock().await.record(&submission.strategy, BidOutcome {
+                    won: true,
+                    our_priority_fee: submission.priority_fee,
+                    competitor_priority_fee: None,
+                });
+                outcomes.won.push(submission);
+                continue;
+            }
+
+            let competing = landed
+                .transactions
+                .iter()
+                .find(|tx| tx_references_path(tx, &submission.path, &submission.routers));
+            let record = match competing {
+                Some(tx) => match self.inspect_competitor(&submission, tx, landed.base_fee_per_gas).await {
+                    Ok(record) => Some(record),
+                    Err(e) => {
+                        warn!("Inspecting competitor {:?} failed: {:?}", tx.hash, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            self.model.lock().await.record(&submission.strategy, BidOutcome {
+                won: false,
+                our_priority_fee: submission.priority_fee,
+                competitor_priority_fee: record.as_ref().map(|r| r.priority_fee),
+            });
+
+            if let Some(storage) = &self.storage {
+                if let Err(e) = storage.update_inclusion(submission.submission_hash, InclusionStatus::Failed, None).await {
+                    warn!("Recording lost submission {:?} failed: {:?}", submission.submission_hash, e);
+                }
+                if let Some(record) = &record {
+                    if let Err(e) = storage.record_competitor(record).await {
+                        warn!("Recording competitor {:?} failed: {:?}", record.competitor, e);
+                    }
+                }
+            }
+
+            if let Some(record) = record {
+                info!(
+                    "Lost opportunity {} in block {} to {:?} (priority fee {}, profit {})",
+                    record.opportunity_id, record.block, record.competitor, record.priority_fee, record.profit
+                );
+                outcomes.lost_to.push(record);
+            }
+        }
+        Ok(outcomes)
+    }
+
+    async fn inspect_competitor(
+        &self,
+        submission: &WatchedSubmission,
+        tx: &Transaction,
+        base_fee: Option<U256>,
+    ) -> Result<CompetitorRecord> {
+        let receipt = self.provider.get_transaction_receipt(tx.hash).await?;
+        let effective_gas_price = receipt
+            .as_ref()
+            .and_then(|r| r.effective_gas_price)
+            .or(tx.gas_price)
+            .unwrap_or_default();
+        let priority_fee = effective_gas_price.saturating_sub(base_fee.unwrap_or_default());
+
+        // Profit is measured in the cycle's start token: net transfers into the
+        // sender or the contract it called
+        let profit = match (&receipt, submission.path.first()) {
+            (Some(receipt), Some(token)) => {
+                let holders = [Some(tx.from), tx.to];
+                net_token_inflow(receipt, *token, &holders)
+            }
+            _ => I256::zero(),
+        };
+
+        Ok(CompetitorRecord {
+            opportunity_id: submission.opportunity_id.clone(),
+            block: submission.target_block,
+            tx_hash: tx.hash,
+            competitor: tx.from,
+            contract: tx.to,
+            priority_fee,
+            gas_used: receipt.and_then(|r| r.gas_used).unwrap_or_default(),
+            profit,
+        })
+    }
+}
+
+fn net_token_inflow(receipt: &TransactionReceipt, token: Address, holders: &[Option<Address>]) -> I256 {
+    let transfer_topic = H256::from(keccak256("Transfer(address,address,uint256)"));
+    let is_holder = |topic: &H256| holders.iter().flatten().any(|h| H256::from(*h) == *topic);
+
+    receipt
+        .logs
+        .iter()
+        // A Transfer with any other data length isn't a standard ERC20 one
+        .filter(|log| log.address == token && log.topics.len() == 3 && log.topics[0] == transfer_topic && log.data.len() == 32)
+        .fold(I256::zero(), |net, log| {
+            let amount = I256::from_raw(U256::from_big_endian(&log.data));
+            match (is_holder(&log.topics[1]), is_holder(&log.topics[2])) {
+                (false, true) => net.saturating_add(amount),
+                (true, false) => net.saturating_sub(amount),
+                _ => net,
+            }
+        })
+}
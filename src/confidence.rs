@@ -0,0 +1,209 @@
+// src/confidence.rs
+use ethers::abi::Token;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::call_tracer::PoolDelta;
+use crate::classifier::lookup_swap;
+use crate::token_registry::units;
+
+// Fewer settled executions than this and the prior weights are kept
+const MIN_CALIBRATION_SAMPLES: usize = 50;
+const CALIBRATION_EPOCHS: usize = 200;
+const LEARNING_RATE: f64 = 0.05;
+// Slack assumed when the victim's minimum output can't be decoded (exact-output
+// swaps, aggregators), roughly a default 0.5% slippage setting
+const UNKNOWN_SLACK_PCT: f64 = 0.5;
+
+/// What a backrun's chance of landing depends on, measured at simulation time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfidenceFeatures {
+    // Our gas price over the victim's; above 1 we risk landing ahead of it
+    pub gas_ratio: f64,
+    // Other pending txs seen this block touching the same pools
+    pub competing_txs: u32,
+    // How far (in percent of its output) the victim is from its minOut revert
+    pub min_out_slack_pct: f64,
+    // Share of our recent settled submissions that were included
+    pub inclusion_rate: f64,
+}
+
+impl ConfidenceFeatures {
+    fn values(&self) -> [f64; 4] {
+        [
+            self.gas_ratio.clamp(0.0, 4.0),
+            (self.competing_txs as f64).min(10.0),
+            self.min_out_slack_pct.clamp(0.0, 10.0),
+            self.inclusion_rate.clamp(0.0, 1.0),
+        ]
+    }
+}
+
+/// Logistic regression weights, in `ConfidenceFeatures::values` order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfidenceWeights {
+    pub bias: f64,
+    pub features: [f64; 4],
+}
+
+impl Default for ConfidenceWeights {
+    // Hand-set prior: ~0.8 for a victim we don't outbid, no competition,
+    // typical slippage and a 50% inclusion history
+    fn default() -> Self {
+        Self {
+            bias: 1.6,
+            features: [-1.0, -0.5, 0.5, 0.6],
+        }
+    }
+}
+
+/// Scores simulated opportunities and learns from how past ones settled.
+pub struct ConfidenceModel {
+    weights: RwLock<ConfidenceWeights>,
+    // pool -> pending txs seen touching it since the last block
+    pending: RwLock<HashMap<Address, HashSet<H256>>>,
+    inclusion_rate: RwLock<f64>,
+}
+
+impl Default for ConfidenceModel {
+    fn default() -> Self {
+        Self {
+            weights: RwLock::new(ConfidenceWeights::default()),
+            pending: RwLock::new(HashMap::new()),
+            inclusion_rate: RwLock::new(0.5),
+        }
+    }
+}
+
+impl ConfidenceModel {
+    pub fn score(&self, features: &ConfidenceFeatures) -> f64 {
+        let weights = self.weights.read().unwrap();
+        let z = features
+            .values()
+            .iter()
+            .zip(weights.features.iter())
+            .fold(weights.bias, |z, (x, w)| z + x * w);
+        sigmoid(z)
+    }
+
+    /// Features for a victim whose simulated execution produced `swaps`. Also
+    /// registers the victim so later txs on the same pools count it.
+    pub fn features(&self, victim: &Transaction, swaps: &[PoolDelta], our_gas_price: U256) -> ConfidenceFeatures {
+        let victim_gas_price = victim.max_fee_per_gas.or(victim.gas_price).unwrap_or_default();
+        let gas_ratio = if victim_gas_price.is_zero() {
+            1.0
+        } else {
+            ratio(our_gas_price, victim_gas_price)
+        };
+
+        ConfidenceFeatures {
+            gas_ratio,
+            competing_txs: self.note_pending(victim.hash, swaps),
+            min_out_slack_pct: min_out_slack_pct(victim, swaps).unwrap_or(UNKNOWN_SLACK_PCT),
+            inclusion_rate: *self.inclusion_rate.read().unwrap(),
+        }
+    }
+
+    fn note_pending(&self, tx: H256, swaps: &[PoolDelta]) -> u32 {
+        let mut pending = self.pending.write().unwrap();
+        let mut others = HashSet::new();
+        for swap in swaps {
+            let seen = pending.entry(swap.pool).or_default();
+            others.extend(seen.iter().filter(|h| **h != tx).copied());
+            seen.insert(tx);
+        }
+        others.len() as u32
+    }
+
+    pub fn on_block(&self) {
+        self.pending.write().unwrap().clear();
+    }
+
+    pub fn set_inclusion_rate(&self, rate: f64) {
+        *self.inclusion_rate.write().unwrap() = rate;
+    }
+
+    /// Refits the weights on settled outcomes, starting from the current ones.
+    /// Returns false when there are too few samples to move off the prior.
+    pub fn calibrate(&self, samples: &[(ConfidenceFeatures, bool)]) -> bool {
+        if samples.len() < MIN_CALIBRATION_SAMPLES {
+            return false;
+        }
+        let mut weights = self.weights.read().unwrap().clone();
+        let n = samples.len() as f64;
+        for _ in 0..CALIBRATION_EPOCHS {
+            let mut bias_grad = 0.0;
+            let mut grads = [0.0; 4];
+            for (features, included) in samples {
+                let x = features.values();
+                let z = x.iter().zip(weights.features.iter()).fold(weights.bias, |z, (x, w)| z + x * w);
+                let error = sigmoid(z) - if *included { 1.0 } else { 0.0 };
+                bias_grad += error;
+                for (g, x) in grads.iter_mut().zip(x.iter()) {
+                    *g += error * x;
+                }
+            }
+            weights.bias -= LEARNING_RATE * bias_grad / n;
+            for (w, g) in weights.features.iter_mut().zip(grads.iter()) {
+                *w -= LEARNING_RATE * g / n;
+            }
+        }
+        info!(samples = samples.len(), bias = weights.bias, weights = ?weights.features, "Recalibrated confidence model");
+        *self.weights.write().unwrap() = weights;
+        true
+    }
+
+    pub fn snapshot(&self) -> ConfidenceWeights {
+        self.weights.read().unwrap().clone()
+    }
+
+    pub fn restore(&self, weights: ConfidenceWeights) {
+        *self.weights.write().unwrap() = weights;
+    }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+fn ratio(a: U256, b: U256) -> f64 {
+    units(I256::from_raw(a), 0) / units(I256::from_raw(b), 0)
+}
+
+// Realized output is what the last pool the victim swapped on paid out
+fn min_out_slack_pct(victim: &Transaction, swaps: &[PoolDelta]) -> Option<f64> {
+    let last = swaps.last()?;
+    let out = [last.amount0, last.amount1].into_iter().find(|a| a.is_negative())?.unsigned_abs();
+    let min_out = victim_min_out(victim)?;
+    if out.is_zero() {
+        return None;
+    }
+    Some(ratio(out.saturating_sub(min_out), out) * 100.0)
+}
+
+/// The minimum output an exact-input router swap will accept, if it's one.
+pub fn victim_min_out(tx: &Transaction) -> Option<U256> {
+    let (entry, input) = lookup_swap(&tx.input)?;
+    let name = entry.function.name.as_str();
+    let args = entry.function.decode_input(&input[4..]).ok()?;
+    let token = match name {
+        // (amountOutMin, path, to, deadline)
+        n if n.starts_with("swapExactETHFor") => args.first()?.clone(),
+        // (amountIn, amountOutMin, path, to, deadline)
+        n if n.starts_with("swapExact") => args.get(1)?.clone(),
+        "exactInputSingle" | "exactInput" => {
+            let params = match args.first()? {
+                Token::Tuple(params) => params,
+                _ => return None,
+            };
+            // Single swaps end in sqrtPriceLimitX96; multi-hop ends in amountOutMinimum
+            let index = if name == "exactInputSingle" { params.len().checked_sub(2)? } else { params.len() - 1 };
+            params.get(index)?.clone()
+        }
+        _ => return None,
+    };
+    token.into_uint()
+}
@@ -0,0 +1,134 @@
+// src/controls.rs
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::fastlane_integration::ProfitGuard;
+use crate::price_service::UsdPolicy;
+
+/// Detection strategies that can be paused at runtime, by their
+/// DISABLED_STRATEGIES names. Venues (fastlane, atlas) are fixed at startup.
+pub const PAUSABLE: &[&str] = &["mempool", "jit", "sandwich", "triangular", "stable", "redemption"];
+
+/// The thresholds the control API can read and change.
+#[derive(Debug, Clone, Serialize)]
+pub struct Thresholds {
+    pub min_profit_usd: f64,
+    pub max_loss_usd: f64,
+    // Share of the expected profit the executor must realize or revert, in bps;
+    // the lower, the more slippage an execution tolerates
+    pub min_profit_bps: u64,
+    pub public_min_profit_bps: u64,
+}
+
+/// A partial update; absent fields keep their value.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ThresholdUpdate {
+    pub min_profit_usd: Option<f64>,
+    pub max_loss_usd: Option<f64>,
+    pub min_profit_bps: Option<u64>,
+    pub public_min_profit_bps: Option<u64>,
+}
+
+/// Settings an operator can change while the bot runs: paused strategies and
+/// the profit thresholds, which start from the environment's values.
+pub struct RuntimeControls {
+    paused: RwLock<HashSet<String>>,
+    usd_policy: RwLock<UsdPolicy>,
+    profit_guard: RwLock<ProfitGuard>,
+    public_guard: RwLock<ProfitGuard>,
+}
+
+impl RuntimeControls {
+    pub fn new(usd_policy: UsdPolicy, profit_guard: ProfitGuard, public_guard: ProfitGuard) -> Self {
+        Self {
+            paused: RwLock::new(HashSet::new()),
+            usd_policy: RwLock::new(usd_policy),
+            profit_guard: RwLock::new(profit_guard),
+            public_guard: RwLock::new(public_guard),
+        }
+    }
+
+    pub fn usd_policy(&self) -> UsdPolicy {
+        self.usd_policy.read().unwrap().clone()
+    }
+
+    /// Bounds for private venues.
+    pub fn profit_guard(&self) -> ProfitGuard {
+        self.profit_guard.read().unwrap().clone()
+    }
+
+    /// Bounds for the public mempool, where front-running is possible.
+    pub fn public_guard(&self) -> ProfitGuard {
+        self.public_guard.read().unwrap().clone()
+    }
+
+    pub fn is_paused(&self, strategy: &str) -> bool {
+        self.paused.read().unwrap().contains(strategy)
+    }
+
+    pub fn paused(&self) -> Vec<String> {
+        let mut paused: Vec<String> = self.paused.read().unwrap().iter().cloned().collect();
+        paused.sort();
+        paused
+    }
+
+    pub fn set_paused(&self, strategy: &str, paused: bool) -> Result<()> {
+        if !PAUSABLE.contains(&strategy) {
+            return Err(anyhow!("Unknown strategy {:?}, expected one of {:?}", strategy, PAUSABLE));
+        }
+        let mut set = self.paused.write().unwrap();
+        if paused {
+            set.insert(strategy.to_string());
+        } else {
+            set.remove(strategy);
+        }
+        info!(strategy, paused, "Strategy control");
+        Ok(())
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        let policy = self.usd_policy.read().unwrap();
+        Thresholds {
+            min_profit_usd: policy.min_profit_usd,
+            max_loss_usd: policy.max_loss_usd,
+            min_profit_bps: self.profit_guard.read().unwrap().min_profit_bps,
+            public_min_profit_bps: self.public_guard.read().unwrap().min_profit_bps,
+        }
+    }
+
+    /// Applies `update` whole or not at all.
+    pub fn update(&self, update: ThresholdUpdate) -> Result<Thresholds> {
+        for usd in [update.min_profit_usd, update.max_loss_usd].into_iter().flatten() {
+            if !usd.is_finite() || usd < 0.0 {
+                return Err(anyhow!("USD thresholds must be finite and non-negative, got {}", usd));
+            }
+        }
+        for bps in [update.min_profit_bps, update.public_min_profit_bps].into_iter().flatten() {
+            if bps > 10_000 {
+                return Err(anyhow!("Profit share must be at most 10000 bps, got {}", bps));
+            }
+        }
+
+        {
+            let mut policy = self.usd_policy.write().unwrap();
+            if let Some(usd) = update.min_profit_usd {
+                policy.min_profit_usd = usd;
+            }
+            if let Some(usd) = update.max_loss_usd {
+                policy.max_loss_usd = usd;
+            }
+        }
+        if let Some(bps) = update.min_profit_bps {
+            self.profit_guard.write().unwrap().min_profit_bps = bps;
+        }
+        if let Some(bps) = update.public_min_profit_bps {
+            self.public_guard.write().unwrap().min_profit_bps = bps;
+        }
+        let thresholds = self.thresholds();
+        info!(?thresholds, "Thresholds updated");
+        Ok(thresholds)
+    }
+}
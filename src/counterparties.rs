@@ -0,0 +1,75 @@
+// src/counterparties.rs
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::storage::CompetitorRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BotSource {
+    // Listed in MEV_BOT_ADDRESSES
+    Config,
+    // Took an opportunity we submitted for
+    Competition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownBot {
+    pub address: Address,
+    pub source: BotSource,
+    // Times we lost to it
+    pub wins_against_us: u32,
+}
+
+/// Competitor bot contracts and searcher EOAs. Their pending swaps are bait or
+/// already-arbitraged flow, so they aren't treated as victims.
+pub struct CounterpartyRegistry {
+    bots: RwLock<HashMap<Address, KnownBot>>,
+}
+
+impl CounterpartyRegistry {
+    pub fn new(seed: &[Address]) -> Self {
+        let bots = seed
+            .iter()
+            .map(|address| (*address, KnownBot { address: *address, source: BotSource::Config, wins_against_us: 0 }))
+            .collect();
+        Self { bots: RwLock::new(bots) }
+    }
+
+    /// Adds the sender and the contract it called.
+    pub fn record_competitor(&self, record: &CompetitorRecord) {
+        let mut bots = self.bots.write().unwrap();
+        for address in std::iter::once(record.competitor).chain(record.contract) {
+            let bot = bots.entry(address).or_insert_with(|| {
+                info!(?address, "New MEV bot counterparty");
+                KnownBot { address, source: BotSource::Competition, wins_against_us: 0 }
+            });
+            bot.wins_against_us += 1;
+        }
+    }
+
+    /// The known bot sending or receiving `tx`, if any.
+    pub fn bot_in(&self, tx: &Transaction) -> Option<Address> {
+        let bots = self.bots.read().unwrap();
+        std::iter::once(tx.from).chain(tx.to).find(|address| bots.contains_key(address))
+    }
+
+    pub fn snapshot(&self) -> Vec<KnownBot> {
+        self.bots.read().unwrap().values().cloned().collect()
+    }
+
+    // Configured entries stay marked as such across restarts
+    pub fn restore(&self, entries: Vec<KnownBot>) {
+        let mut bots = self.bots.write().unwrap();
+        for entry in entries {
+            match bots.get_mut(&entry.address) {
+                Some(bot) => bot.wins_against_us = entry.wins_against_us,
+                None => {
+                    bots.insert(entry.address, entry);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// src/decode_coverage.rs
+use ethers::types::{Address, H256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+// Enough hashes to pull the calldata up on an explorer
+const SAMPLES_PER_SELECTOR: usize = 3;
+
+#[derive(Debug, Default)]
+struct UnknownCalls {
+    count: u64,
+    samples: Vec<H256>,
+}
+
+#[derive(Debug, Default)]
+struct RouterCalls {
+    total: u64,
+    decoded: u64,
+    unknown: HashMap<[u8; 4], UnknownCalls>,
+}
+
+/// A selector we saw sent to a router but couldn't decode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownSelector {
+    pub selector: [u8; 4],
+    pub count: u64,
+    pub samples: Vec<H256>,
+}
+
+/// How much of one router's calldata the classifier understood over a period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouterCoverage {
+    pub router: Address,
+    pub total: u64,
+    pub decoded: u64,
+    // Most frequent first
+    pub unknown: Vec<UnknownSelector>,
+}
+
+impl RouterCoverage {
+    pub fn coverage(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.decoded as f64 / self.total as f64
+    }
+}
+
+struct CoverageState {
+    since: Instant,
+    routers: HashMap<Address, RouterCalls>,
+}
+
+/// Per-router share of pending calldata the classifier decodes, with the
+/// selectors it doesn't, so ABI gaps show up as a list of what to add.
+/// Reported and reset every `interval`.
+pub struct DecodeCoverage {
+    interval: Duration,
+    // Unknown selectors listed per router in a report
+    top: usize,
+    state: Mutex<CoverageState>,
+}
+
+impl DecodeCoverage {
+    pub fn new(interval: Duration, top: usize) -> Self {
+        Self {
+            interval,
+            top,
+            state: Mutex::new(CoverageState { since: Instant::now(), routers: HashMap::new() }),
+        }
+    }
+
+    /// Counts one call to `router`. Calls without a selector (plain transfers)
+    /// aren't decodable by anyone and are left out.
+    pub fn record(&self, router: Address, input: &[u8], tx_hash: H256, decoded: bool) {
+        let selector = match input.get(..4) {
+            Some(s) => [s[0], s[1], s[2], s[3]],
+            None => return,
+        };
+        let mut state = self.state.lock().unwrap();
+        let calls = state.routers.entry(router).or_default();
+        calls.total += 1;
+        if decoded {
+            calls.decoded += 1;
+            return;
+        }
+        let unknown = calls.unknown.entry(selector).or_default();
+        unknown.count += 1;
+        if unknown.samples.len() < SAMPLES_PER_SELECTOR {
+            unknown.samples.push(tx_hash);
+        }
+    }
+
+    /// Coverage since the last report, busiest router first.
+    pub fn snapshot(&self) -> Vec<RouterCoverage> {
+        let state = self.state.lock().unwrap();
+        Self::summarize(&state.routers, self.top)
+    }
+
+    /// Logs and resets the counts once `interval` has passed since the last
+    /// report; returns what was reported.
+    pub fn report_if_due(&self) -> Option<Vec<RouterCoverage>> {
+        let routers = {
+            let mut state = self.state.lock().unwrap();
+            if state.since.elapsed() < self.interval {
+                return None;
+            }
+            state.since = Instant::now();
+            std::mem::take(&mut state.routers)
+        };
+        let report = Self::summarize(&routers, self.top);
+        for router in &report {
+            info!(
+                router = ?router.router,
+                txs = router.total,
+                decoded = router.decoded,
+                "Decode coverage {:.1}%",
+                router.coverage() * 100.0
+            );
+            for unknown in &router.unknown {
+                info!(
+                    router = ?router.router,
+                    selector = %format!("0x{}", hex::encode(unknown.selector)),
+                    count = unknown.count,
+                    samples = ?unknown.samples,
+                    "Undecoded selector"
+                );
+            }
+        }
+        Some(report)
+    }
+
+    fn summarize(routers: &HashMap<Address, RouterCalls>, top: usize) -> Vec<RouterCoverage> {
+        let mut report: Vec<RouterCoverage> = routers
+            .iter()
+            .map(|(router, calls)| {
+                let mut unknown: Vec<UnknownSelector> = calls
+                    .unknown
+                    .iter()
+                    .map(|(selector, u)| UnknownSelector { selector: *selector, count: u.count, samples: u.samples.clone() })
+                    .collect();
+                unknown.sort_by(|a, b| b.count.cmp(&a.count).then(a.selector.cmp(&b.selector)));
+                unknown.truncate(top);
+                RouterCoverage { router: *router, total: calls.total, decoded: calls.decoded, unknown }
+            })
+            .collect();
+        report.sort_by(|a, b| b.total.cmp(&a.total).then(a.router.cmp(&b.router)));
+        report
+    }
+}
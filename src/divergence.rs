@@ -0,0 +1,209 @@
+// src/divergence.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::chain::ChainProfile;
+use crate::pool_state::PoolStateManager;
+use crate::token_registry::{units, TokenRegistry};
+
+#[derive(Debug, Clone)]
+pub struct DivergenceConfig {
+    // (base, quote) symbols, looked up in each chain's asset list
+    pub pairs: Vec<(String, String)>,
+    pub alert_bps: u64,
+    pub interval: Duration,
+    // Receives a JSON POST when a pair first crosses `alert_bps`
+    pub alert_webhook: Option<String>,
+}
+
+impl Default for DivergenceConfig {
+    fn default() -> Self {
+        Self {
+            pairs: vec![("WETH".to_string(), "USDC".to_string()), ("USDT".to_string(), "USDC".to_string())],
+            alert_bps: 50,
+            interval: Duration::from_secs(30),
+            alert_webhook: None,
+        }
+    }
+}
+
+/// One chain's view of the market, taken from its monitor.
+pub struct Venue {
+    pub chain: ChainProfile,
+    pub pool_state: Arc<PoolStateManager>,
+    pub tokens: Arc<TokenRegistry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PairDivergence {
+    pub pair: String,
+    // (chain, quote per base) for every chain the pair could be priced on
+    pub prices: Vec<(&'static str, f64)>,
+    pub divergence_bps: f64,
+}
+
+/// Compares spot prices of the same pairs across chains. Analytics only: the
+/// operator decides whether to move inventory, nothing is bridged.
+pub struct DivergenceMonitor {
+    venues: Vec<Venue>,
+    config: DivergenceConfig,
+    // Pairs currently above the threshold; one alert per excursion
+    alerted: Mutex<HashSet<String>>,
+    http: reqwest::Client,
+}
+
+impl DivergenceMonitor {
+    pub fn new(venues: Vec<Venue>, config: DivergenceConfig) -> Self {
+        Self {
+            venues,
+            config,
+            alerted: Mutex::new(HashSet::new()),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Makes sure every chain tracks pools for the configured pairs. They join
+    /// the chain's pool set and are refreshed with it each block.
+    pub async fn discover(&self) -> Result<()> {
+        for venue in &self.venues {
+            for (base, quote) in &self.config.pairs {
+                if let (Some(base), Some(quote)) = (venue.chain.asset(base), venue.chain.asset(quote)) {
+                    venue.pool_state.discover(&[base, quote]).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            for (base, quote) in &self.config.pairs {
+                match self.compare(base, quote).await {
+                    Ok(Some(divergence)) => self.report(divergence).await,
+                    Ok(None) => {}
+                    Err(e) => warn!("Divergence check for {}/{} failed: {:?}", base, quote, e),
+                }
+            }
+        }
+    }
+
+    /// None unless the pair can be priced on at least two chains.
+    pub async fn compare(&self, base: &str, quote: &str) -> Result<Option<PairDivergence>> {
+        let mut prices = Vec::new();
+        for venue in &self.venues {
+            let (base_token, quote_token) = match (venue.chain.asset(base), venue.chain.asset(quote)) {
+                (Some(b), Some(q)) => (b, q),
+                _ => continue,
+            };
+            if let Some(price) = spot_price(venue, base_token, quote_token).await? {
+                prices.push((venue.chain.name, price));
+            }
+        }
+        if prices.len() < 2 {
+            return Ok(None);
+        }
+
+        let min = prices.iter().map(|(_, p)| *p).fold(f64::INFINITY, f64::min);
+        let max = prices.iter().map(|(_, p)| *p).fold(f64::NEG_INFINITY, f64::max);
+        Ok(Some(PairDivergence {
+            pair: format!("{}/{}", base, quote),
+            prices,
+            divergence_bps: (max - min) / min * 10_000.0,
+        }))
+    }
+
+    async fn report(&self, divergence: PairDivergence) {
+        info!(
+            pair = %divergence.pair,
+            divergence_bps = divergence.divergence_bps,
+            prices = ?divergence.prices,
+            "Cross-chain price divergence"
+        );
+
+        let mut alerted = self.alerted.lock().await;
+        if divergence.divergence_bps < self.config.alert_bps as f64 {
+            if alerted.remove(&divergence.pair) {
+                info!(pair = %divergence.pair, "Cross-chain divergence back under {} bps", self.config.alert_bps);
+            }
+            return;
+        }
+        if !alerted.insert(divergence.pair.clone()) {
+            return;
+        }
+        drop(alerted);
+
+        warn!(
+            pair = %divergence.pair,
+            divergence_bps = divergence.divergence_bps,
+            prices = ?divergence.prices,
+            "Cross-chain divergence above {} bps",
+            self.config.alert_bps
+        );
+        let url = match &self.config.alert_webhook {
+            Some(url) => url,
+            None => return,
+        };
+        let prices: serde_json::Map<_, _> = divergence.prices.iter().map(|(chain, price)| (chain.to_string(), json!(price))).collect();
+        let body = json!({
+            "alert": "cross_chain_divergence",
+            "pair": divergence.pair,
+            "divergence_bps": divergence.divergence_bps,
+            "threshold_bps": self.config.alert_bps,
+            "prices": prices,
+        });
+        if let Err(e) = self.http.post(url).json(&body).send().await {
+            warn!("Failed to deliver divergence alert: {:?}", e);
+        }
+    }
+}
+
+// Quote per base from the deepest V2 pool, or the most liquid V3 pool when the
+// chain has no V2 pool for the pair
+async fn spot_price(venue: &Venue, base: Address, quote: Address) -> Result<Option<f64>> {
+    let base_decimals = venue.tokens.decimals(base).await?;
+    let quote_decimals = venue.tokens.decimals(quote).await?;
+
+    let deepest = venue
+        .pool_state
+        .pools_for_pair(base, quote)
+        .await
+        .into_iter()
+        .filter(|p| !p.reserve0.is_zero() && !p.reserve1.is_zero())
+        .max_by_key(|p| if p.token0 == quote { p.reserve0 } else { p.reserve1 });
+    if let Some(pool) = deepest {
+        let (base_reserve, quote_reserve) = if pool.token0 == base {
+            (pool.reserve0, pool.reserve1)
+        } else {
+            (pool.reserve1, pool.reserve0)
+        };
+        return Ok(Some(
+            units(I256::from_raw(quote_reserve), quote_decimals) / units(I256::from_raw(base_reserve), base_decimals),
+        ));
+    }
+
+    let v3 = venue
+        .pool_state
+        .v3_pools_for_pair(base, quote)
+        .await
+        .into_iter()
+        .filter(|p| p.liquidity > 0 && !p.sqrt_price_x96.is_zero())
+        .max_by_key(|p| p.liquidity);
+    Ok(v3.map(|pool| {
+        // token1 per token0 in raw units, then adjusted for decimals
+        let sqrt = units(I256::from_raw(pool.sqrt_price_x96), 0) / 2f64.powi(96);
+        let raw = sqrt * sqrt;
+        if pool.token0 == base {
+            raw * 10f64.powi(base_decimals as i32 - quote_decimals as i32)
+        } else {
+            10f64.powi(base_decimals as i32 - quote_decimals as i32) / raw
+        }
+    }))
+}
@@ -0,0 +1,62 @@
+// src/errors.rs
+use std::error::Error;
+
+use crate::calldata_builder::DecodeError;
+use crate::executor::SubmissionError;
+use crate::inflight::InflightRejected;
+use crate::revert::ExecutionFailure;
+use crate::risk_manager::RiskRejected;
+use crate::simulation_engine::SimulationError;
+
+/// What a failure means for the opportunity that hit it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    // Node, relay or transport trouble; the same request may go through later
+    Retryable,
+    // Misconfiguration or a bug; nothing changes until someone fixes it
+    Fatal,
+    // The opportunity failed a check: it reverts, doesn't pay or breaks a limit
+    Rejected,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::Retryable => "retryable",
+            ErrorClass::Fatal => "fatal",
+            ErrorClass::Rejected => "rejected",
+        }
+    }
+}
+
+/// Classifies an error from anywhere in the pipeline by the first typed error
+/// in its chain. Untyped errors are mostly RPC failures, so they are retryable.
+pub fn classify(e: &anyhow::Error) -> ErrorClass {
+    e.chain().find_map(class_of).unwrap_or(ErrorClass::Retryable)
+}
+
+fn class_of(cause: &(dyn Error + 'static)) -> Option<ErrorClass> {
+    if let Some(e) = cause.downcast_ref::<SubmissionError>() {
+        return Some(e.class());
+    }
+    if let Some(e) = cause.downcast_ref::<SimulationError>() {
+        return Some(e.class());
+    }
+    if cause.is::<ExecutionFailure>() || cause.is::<RiskRejected>() || cause.is::<InflightRejected>() {
+        return Some(ErrorClass::Rejected);
+    }
+    if cause.is::<DecodeError>() {
+        return Some(ErrorClass::Fatal);
+    }
+    None
+}
+
+/// The classified revert behind `e`, however it was wrapped.
+pub fn execution_failure(e: &anyhow::Error) -> Option<&ExecutionFailure> {
+    e.chain().find_map(|cause| {
+        cause
+            .downcast_ref::<ExecutionFailure>()
+            .or_else(|| cause.downcast_ref::<SimulationError>().and_then(|e| e.failure()))
+            .or_else(|| cause.downcast_ref::<SubmissionError>().and_then(|e| e.failure()))
+    })
+}
@@ -0,0 +1,96 @@
+// src/event_bus.rs
+use ethers::types::{Address, Transaction, H256, U256, U64};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::block_events::BlockEvent;
+use crate::bridge_flow::BridgeInflow;
+use crate::classifier::ClassifiedTx;
+use crate::simulation_engine::SimulationResult;
+
+#[derive(Debug, Clone)]
+pub struct ClassifiedEvent {
+    pub tx_hash: H256,
+    pub class: ClassifiedTx,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpportunityFound {
+    pub id: String,
+    pub source: String,
+    pub victim_tx: Option<H256>,
+    pub path: Vec<Address>,
+    pub pools: Vec<Address>,
+    pub amount_in: U256,
+    pub expected_profit: U256,
+    pub simulation: Option<SimulationResult>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleSubmitted {
+    pub opportunity_id: String,
+    pub venue: &'static str,
+    pub hash: H256,
+    pub target_block: U64,
+    pub gas_price: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleLanded {
+    pub opportunity_id: String,
+    pub hash: H256,
+    pub block: U64,
+}
+
+/// One broadcast channel per event type, so a subscriber only sees (and only lags
+/// on) what it asked for.
+pub struct Topic<T: Clone> {
+    sender: broadcast::Sender<T>,
+}
+
+impl<T: Clone> Topic<T> {
+    fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Returns how many subscribers received it; publishing with none is not an error.
+    pub fn publish(&self, event: T) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    // Lets hot paths skip building an event nobody will read
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<T> {
+        self.sender.subscribe()
+    }
+}
+
+/// Typed events between subsystems. Producers publish without knowing who listens;
+/// storage, metrics and tests subscribe to the topics they care about.
+pub struct EventBus {
+    pub pending_txs: Topic<Arc<Transaction>>,
+    pub classified: Topic<ClassifiedEvent>,
+    pub opportunities: Topic<OpportunityFound>,
+    pub bundles_submitted: Topic<BundleSubmitted>,
+    pub bundles_landed: Topic<BundleLanded>,
+    pub blocks: Topic<BlockEvent>,
+    pub bridge_inflows: Topic<BridgeInflow>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            pending_txs: Topic::new(4096),
+            classified: Topic::new(4096),
+            opportunities: Topic::new(1024),
+            bundles_submitted: Topic::new(256),
+            bundles_landed: Topic::new(256),
+            blocks: Topic::new(64),
+            bridge_inflows: Topic::new(256),
+        }
+    }
+}
@@ -0,0 +1,464 @@
+// src/executor.rs
+use async_trait::async_trait;
+use ethers::prelude::*;
+use ethers::types::transaction::{eip2718::TypedTransaction, eip2930::{AccessList, Eip2930TransactionRequest}};
+use ethers::utils::keccak256;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::bloxroute::BloxrouteClient;
+use crate::errors::ErrorClass;
+use crate::fastlane_integration::{FastLaneBundle, FastLaneClient};
+use crate::private_rpc::{PrivateRpcClient, SubmissionPolicy, SubmissionRoute};
+use crate::relay::{BundleRequest, RelayClient};
+use crate::revert::ExecutionFailure;
+use crate::tx_tracker::{SignerClient, TxTracker};
+
+// Floor on the coinbase payment (wei) a relay simulation must show before we
+// submit, unless RELAY_MIN_COINBASE_PAYMENT overrides it: 0.001 MATIC
+pub const DEFAULT_MIN_COINBASE_PAYMENT: u64 = 1_000_000_000_000_000;
+
+/// One executor call, priced and targeted, ready for whichever venue takes it.
+#[derive(Debug, Clone)]
+pub struct ExecutionRequest {
+    pub bundle: FastLaneBundle,
+    pub gas_price: U256,
+    // In wei of the native token, so it nets against gas directly
+    pub expected_profit: U256,
+}
+
+/// One transaction of an `OrderedBundle`.
+#[derive(Debug, Clone)]
+pub enum BundleTx {
+    // An executor call we sign. The gas limit is fixed up front: a call that
+    // depends on the ones before it can't be estimated on its own
+    Executor { calldata: Bytes, gas_limit: u64 },
+    // Someone else's pending tx, included exactly as they signed it
+    Pending(Transaction),
+}
+
+/// Executor calls around other people's pending txs, in block order, for venues
+/// that land a bundle of raw transactions all or nothing.
+#[derive(Debug, Clone)]
+pub struct OrderedBundle {
+    pub txs: Vec<BundleTx>,
+    // Per tx: whether the bundle may land with it reverted
+    pub can_revert: Vec<bool>,
+    pub target_block: U64,
+    pub gas_price: U256,
+    // In wei of the native token
+    pub expected_profit: U256,
+}
+
+/// Why an execution didn't go out. Callers branch on `class`, not the message.
+#[derive(Debug, thiserror::Error)]
+pub enum SubmissionError {
+    #[error("No executor configured for {strategy} ({route:?} route)")]
+    NoExecutor { strategy: String, route: SubmissionRoute },
+    #[error("Simulated gas cost {gas_cost} exceeds expected profit {expected_profit}")]
+    Unprofitable { gas_cost: U256, expected_profit: U256 },
+    #[error(transparent)]
+    Reverted(#[from] ExecutionFailure),
+    // The venue simulated the bundle and won't take it as is
+    #[error("Bundle refused: {0}")]
+    Refused(String),
+    // Signing, transport or the venue itself; the same request may go through later
+    #[error("{stage} failed")]
+    Failed {
+        stage: &'static str,
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+impl SubmissionError {
+    /// Wraps a client error from `stage`, keeping a revert found anywhere in it.
+    pub fn at(stage: &'static str) -> impl FnOnce(anyhow::Error) -> Self {
+        move |e| match e.downcast::<ExecutionFailure>() {
+            Ok(failure) => SubmissionError::Reverted(failure),
+            Err(source) => SubmissionError::Failed { stage, source },
+        }
+    }
+
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            SubmissionError::NoExecutor { .. } => ErrorClass::Fatal,
+            SubmissionError::Unprofitable { .. } | SubmissionError::Reverted(_) | SubmissionError::Refused(_) => {
+                ErrorClass::Rejected
+            }
+            SubmissionError::Failed { .. } => ErrorClass::Retryable,
+        }
+    }
+
+    pub fn failure(&self) -> Option<&ExecutionFailure> {
+        match self {
+            SubmissionError::Reverted(failure) => Some(failure),
+            _ => None,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, SubmissionError>;
+
+/// A settlement venue. Strategies hand requests to the `ExecutorRouter` and never
+/// pick a venue themselves.
+#[async_trait]
+pub trait Executor: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn route(&self) -> SubmissionRoute;
+    async fn submit(&self, request: &ExecutionRequest) -> Result<H256>;
+
+    /// Whether `submit_ordered` does anything but refuse.
+    fn supports_ordered(&self) -> bool {
+        false
+    }
+
+    /// Submits txs that only pay off in this exact order around someone else's
+    /// pending tx. Only raw-transaction bundle venues can; the rest refuse.
+    async fn submit_ordered(&self, bundle: &OrderedBundle) -> Result<H256> {
+        let _ = bundle;
+        Err(SubmissionError::Refused(format!("{} can't bundle pending txs", self.name())))
+    }
+}
+
+/// Signs executor calls for the venues that take raw transactions.
+#[derive(Clone)]
+pub struct ExecutionSigner {
+    signer: Arc<SignerClient>,
+    fastlane: FastLaneClient,
+}
+
+impl ExecutionSigner {
+    pub fn new(signer: Arc<SignerClient>, fastlane: FastLaneClient) -> Self {
+        Self { signer, fastlane }
+    }
+
+    pub async fn sign(&self, calldata: Bytes, gas_price: U256) -> Result<Bytes> {
+        self.sign_inner(calldata, gas_price).await.map_err(SubmissionError::at("signing"))
+    }
+
+    async fn sign_inner(&self, calldata: Bytes, gas_price: U256) -> anyhow::Result<Bytes> {
+        let mut tx = self.transaction(calldata, gas_price).await;
+        self.signer.fill_transaction(&mut tx, None).await?;
+        let signature = self.signer.signer().sign_transaction(&tx).await?;
+        Ok(tx.rlp_signed(&signature))
+    }
+
+    /// The unsigned executor call, access list attached where one helps.
+    pub async fn transaction(&self, calldata: Bytes, gas_price: U256) -> TypedTransaction {
+        let request = TransactionRequest::new()
+            .from(self.signer.address())
+            .to(self.fastlane.solver_contract())
+            .data(calldata)
+            .gas_price(gas_price);
+        match self.access_list(&request).await {
+            Some(access_list) => Eip2930TransactionRequest::new(request, access_list).into(),
+            None => request.into(),
+        }
+    }
+
+    /// Signs executor calls that go out together, in order, on consecutive nonces.
+    pub async fn sign_sequence(&self, calls: &[(Bytes, u64)], gas_price: U256) -> Result<Vec<Bytes>> {
+        self.sign_sequence_inner(calls, gas_price).await.map_err(SubmissionError::at("signing"))
+    }
+
+    async fn sign_sequence_inner(&self, calls: &[(Bytes, u64)], gas_price: U256) -> anyhow::Result<Vec<Bytes>> {
+        let address = self.signer.address();
+        let nonce = self.signer.get_transaction_count(address, Some(BlockNumber::Pending.into())).await?;
+        let mut signed = Vec::with_capacity(calls.len());
+        for (i, (calldata, gas_limit)) in calls.iter().enumerate() {
+            let mut tx: TypedTransaction = TransactionRequest::new()
+                .from(address)
+                .to(self.fastlane.solver_contract())
+                .data(calldata.clone())
+                .gas(*gas_limit)
+                .gas_price(gas_price)
+                .nonce(nonce + i)
+                .into();
+            self.signer.fill_transaction(&mut tx, None).await?;
+            let signature = self.signer.signer().sign_transaction(&tx).await?;
+            signed.push(tx.rlp_signed(&signature));
+        }
+        Ok(signed)
+    }
+
+    // Warm every pool, token and router slot the execution touches (EIP-2930). Only
+    // used when the node supports eth_createAccessList and the list actually saves gas
+    async fn access_list(&self, request: &TransactionRequest) -> Option<AccessList> {
+        let tx: TypedTransaction = request.clone().into();
+        let with_list = match self.signer.create_access_list(&tx, None).await {
+            Ok(result) => result,
+            Err(e) => {
+                debug!("eth_createAccessList unavailable: {:?}", e);
+                return None;
+            }
+        };
+        let without_list = self.signer.estimate_gas(&tx, None).await.ok()?;
+
+        if with_list.access_list.0.is_empty() || with_list.gas_used >= without_list {
+            return None;
+        }
+        debug!(
+            "Access list with {} entries saves {} gas",
+            with_list.access_list.0.len(),
+            without_list - with_list.gas_used
+        );
+        Some(with_list.access_list)
+    }
+
+    // Local simulation for single-tx routes that have no eth_callBundle
+    pub async fn ensure_simulated_profit(&self, request: &ExecutionRequest) -> Result<()> {
+        let gas_used = self.fastlane
+            .simulate_bundle(&request.bundle)
+            .await
+            .map_err(SubmissionError::at("simulation"))?;
+        let gas_cost = gas_used * request.gas_price;
+        if request.expected_profit <= gas_cost {
+            return Err(SubmissionError::Unprofitable { gas_cost, expected_profit: request.expected_profit });
+        }
+        Ok(())
+    }
+}
+
+pub struct FastLaneExecutor {
+    client: FastLaneClient,
+}
+
+impl FastLaneExecutor {
+    pub fn new(client: FastLaneClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Executor for FastLaneExecutor {
+    fn name(&self) -> &'static str {
+        "fastlane"
+    }
+
+    fn route(&self) -> SubmissionRoute {
+        SubmissionRoute::Bundle
+    }
+
+    async fn submit(&self, request: &ExecutionRequest) -> Result<H256> {
+        let hash = self.client
+            .submit_bundle(request.bundle.clone())
+            .await
+            .map_err(SubmissionError::at(self.name()))?;
+        info!("Submitted FastLane bundle: {:?}", hash);
+        Ok(hash)
+    }
+}
+
+/// Signs the executor call ourselves and hands it to the relay as a one-tx bundle.
+pub struct RelayExecutor {
+    relay: RelayClient,
+    signer: ExecutionSigner,
+    min_coinbase_payment: U256,
+}
+
+impl RelayExecutor {
+    pub fn new(relay: RelayClient, signer: ExecutionSigner) -> Self {
+        Self { relay, signer, min_coinbase_payment: U256::from(DEFAULT_MIN_COINBASE_PAYMENT) }
+    }
+
+    pub fn with_min_coinbase_payment(mut self, wei: U256) -> Self {
+        self.min_coinbase_payment = wei;
+        self
+    }
+
+    // The relay simulates the bundle first; only a submittable one is sent
+    async fn send(&self, bundle: &BundleRequest) -> Result<H256> {
+        let simulation = self.relay
+            .call_bundle(&bundle.txs, bundle.block_number, BlockNumber::Latest)
+            .await
+            .map_err(SubmissionError::at("relay_simulation"))?;
+        simulation.ensure_submittable(&bundle.reverting_tx_hashes, self.min_coinbase_payment)?;
+        self.relay.send_bundle(bundle).await.map_err(SubmissionError::at(self.name()))
+    }
+}
+
+#[async_trait]
+impl Executor for RelayExecutor {
+    fn name(&self) -> &'static str {
+        "relay"
+    }
+
+    fn route(&self) -> SubmissionRoute {
+        SubmissionRoute::Bundle
+    }
+
+    fn supports_ordered(&self) -> bool {
+        true
+    }
+
+    async fn submit(&self, request: &ExecutionRequest) -> Result<H256> {
+        let bundle = BundleRequest {
+            txs: vec![self.signer.sign(request.bundle.data.clone(), request.gas_price).await?],
+            block_number: request.bundle.target_block,
+            min_timestamp: None,
+            max_timestamp: None,
+            reverting_tx_hashes: Vec::new(),
+        };
+        self.send(&bundle).await
+    }
+
+    async fn submit_ordered(&self, bundle: &OrderedBundle) -> Result<H256> {
+        let calls: Vec<(Bytes, u64)> = bundle
+            .txs
+            .iter()
+            .filter_map(|tx| match tx {
+                BundleTx::Executor { calldata, gas_limit } => Some((calldata.clone(), *gas_limit)),
+                BundleTx::Pending(_) => None,
+            })
+            .collect();
+        let mut ours = self.signer.sign_sequence(&calls, bundle.gas_price).await?.into_iter();
+
+        let mut txs = Vec::with_capacity(bundle.txs.len());
+        let mut reverting_tx_hashes = Vec::new();
+        for (tx, can_revert) in bundle.txs.iter().zip(&bundle.can_revert) {
+            let (raw, hash) = match tx {
+                BundleTx::Executor { .. } => {
+                    let raw = ours.next().expect("one signed tx per executor call");
+                    let hash = H256::from(keccak256(&raw));
+                    (raw, hash)
+                }
+                BundleTx::Pending(tx) => (tx.rlp(), tx.hash),
+            };
+            if *can_revert {
+                reverting_tx_hashes.push(hash);
+            }
+            txs.push(raw);
+        }
+        self.send(&BundleRequest {
+            txs,
+            block_number: bundle.target_block,
+            min_timestamp: None,
+            max_timestamp: None,
+            reverting_tx_hashes,
+        })
+        .await
+    }
+}
+
+pub struct PrivateRpcExecutor {
+    client: PrivateRpcClient,
+    signer: ExecutionSigner,
+}
+
+impl PrivateRpcExecutor {
+    pub fn new(client: PrivateRpcClient, signer: ExecutionSigner) -> Self {
+        Self { client, signer }
+    }
+}
+
+#[async_trait]
+impl Executor for PrivateRpcExecutor {
+    fn name(&self) -> &'static str {
+        "private_rpc"
+    }
+
+    fn route(&self) -> SubmissionRoute {
+        SubmissionRoute::Private
+    }
+
+    async fn submit(&self, request: &ExecutionRequest) -> Result<H256> {
+        self.signer.ensure_simulated_profit(request).await?;
+        let raw = self.signer.sign(request.bundle.data.clone(), request.gas_price).await?;
+        self.client
+            .send_transaction(&raw, Some(request.bundle.target_block))
+            .await
+            .map_err(SubmissionError::at(self.name()))
+    }
+}
+
+// The BDN propagates to every validator's mempool, so it's public, just faster
+pub struct BloxrouteExecutor {
+    client: BloxrouteClient,
+    signer: ExecutionSigner,
+}
+
+impl BloxrouteExecutor {
+    pub fn new(client: BloxrouteClient, signer: ExecutionSigner) -> Self {
+        Self { client, signer }
+    }
+}
+
+#[async_trait]
+impl Executor for BloxrouteExecutor {
+    fn name(&self) -> &'static str {
+        "bloxroute"
+    }
+
+    fn route(&self) -> SubmissionRoute {
+        SubmissionRoute::Public
+    }
+
+    async fn submit(&self, request: &ExecutionRequest) -> Result<H256> {
+        self.signer.ensure_simulated_profit(request).await?;
+        let raw = self.signer.sign(request.bundle.data.clone(), request.gas_price).await?;
+        self.client.send_transaction(&raw).await.map_err(SubmissionError::at(self.name()))
+    }
+}
+
+/// Plain broadcast through our own node. It sits in the public mempool, so the
+/// tracker replaces or cancels it if it stalls.
+pub struct PublicExecutor {
+    signer: ExecutionSigner,
+    tracker: Arc<TxTracker>,
+}
+
+impl PublicExecutor {
+    pub fn new(signer: ExecutionSigner, tracker: Arc<TxTracker>) -> Self {
+        Self { signer, tracker }
+    }
+}
+
+#[async_trait]
+impl Executor for PublicExecutor {
+    fn name(&self) -> &'static str {
+        "public"
+    }
+
+    fn route(&self) -> SubmissionRoute {
+        SubmissionRoute::Public
+    }
+
+    async fn submit(&self, request: &ExecutionRequest) -> Result<H256> {
+        self.signer.ensure_simulated_profit(request).await?;
+        let tx = self.signer.transaction(request.bundle.data.clone(), request.gas_price).await;
+        self.tracker.submit(tx).await.map_err(SubmissionError::at(self.name()))
+    }
+}
+
+/// Picks the venue for each strategy's executions: the first configured executor,
+/// in preference order, whose route the strategy's policy allows.
+pub struct ExecutorRouter {
+    executors: Vec<Box<dyn Executor>>,
+    policy: SubmissionPolicy,
+}
+
+impl ExecutorRouter {
+    pub fn new(executors: Vec<Box<dyn Executor>>, policy: SubmissionPolicy) -> Self {
+        Self { executors, policy }
+    }
+
+    pub fn select(&self, strategy: &str) -> Result<&dyn Executor> {
+        self.find(strategy, |_| true)
+    }
+
+    /// Like `select`, for strategies that submit `OrderedBundle`s: venues that
+    /// can't bundle pending txs are skipped.
+    pub fn select_ordered(&self, strategy: &str) -> Result<&dyn Executor> {
+        self.find(strategy, |e| e.supports_ordered())
+    }
+
+    fn find(&self, strategy: &str, capable: impl Fn(&dyn Executor) -> bool) -> Result<&dyn Executor> {
+        let route = self.policy.route_for(strategy);
+        self.executors
+            .iter()
+            .map(|e| e.as_ref())
+            .find(|e| route.allows(e.route()) && capable(*e))
+            .ok_or_else(|| SubmissionError::NoExecutor { strategy: strategy.to_string(), route })
+    }
+}
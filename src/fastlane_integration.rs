@@ -1,12 +1,18 @@
 use ethers::{
-    abi::Abi,
     prelude::*,
     types::{Address, Bytes, H256, U256},
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use anyhow::{Result, anyhow};
-use log::info;
+use tracing::info;
+
+use crate::abis;
+use crate::calldata_builder::{self, CalldataEncoding, FlashEntry};
+use crate::flash_loan::LoanSource;
+use crate::revert::{ExecutionFailure, RevertReason};
+use crate::rpc_budget::RpcTransport;
+use crate::types::ArbitrageOpportunity;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FastLaneBundle {
@@ -14,7 +20,28 @@ pub struct FastLaneBundle {
     pub target_block: U64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The on-chain bounds every execution is wrapped in (`executeGuarded`): it
+/// reverts with Expired after the deadline and with ProfitTooLow when the owner
+/// gains less than the minimum. Tighter bounds leave less for a front-runner
+/// to take but revert, and cost revert gas, on smaller price moves.
+#[derive(Debug, Clone)]
+pub struct ProfitGuard {
+    // Share of the expected profit that must reach the owner, in bps
+    pub min_profit_bps: u64,
+    // Blocks past the target the execution may still land in
+    pub deadline_blocks: u64,
+}
+
+impl Default for ProfitGuard {
+    fn default() -> Self {
+        Self {
+            min_profit_bps: 5_000,
+            deadline_blocks: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BundleStatus {
     Pending,
     Included,
@@ -24,55 +51,75 @@ pub enum BundleStatus {
 
 #[derive(Debug, Clone)]
 pub struct FastLaneClient {
-    provider: Arc<Provider<Ws>>,
+    provider: Arc<Provider<RpcTransport>>,
     fastlane_contract: Address,
     solver_contract: Address,
+    encoding: CalldataEncoding,
 }
 
 impl FastLaneClient {
-    pub fn new(provider: Arc<Provider<Ws>>, fastlane_address: Address, solver_address: Address) -> Self {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, fastlane_address: Address, solver_address: Address) -> Self {
         Self {
             provider,
             fastlane_contract: fastlane_address,
             solver_contract: solver_address,
+            encoding: CalldataEncoding::default(),
         }
     }
 
+    pub fn with_calldata_encoding(mut self, encoding: CalldataEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn solver_contract(&self) -> Address {
+        self.solver_contract
+    }
+
+    pub fn fastlane_contract(&self) -> Address {
+        self.fastlane_contract
+    }
+
+    /// Runs the solver call the way FastLane will (sender = FastLane contract) against
+    /// latest state. Errors if it reverts; otherwise returns the gas it used.
+    pub async fn simulate_bundle(&self, bundle: &FastLaneBundle) -> Result<U256> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(self.fastlane_contract)
+            .to(self.solver_contract)
+            .data(bundle.data.clone())
+            .into();
+
+        self.provider
+            .call(&tx, Some(BlockNumber::Latest.into()))
+            .await
+            .map_err(|e| ExecutionFailure::new("bundle_simulation", RevertReason::from_provider_error(&e)))?;
+        Ok(self.provider.estimate_gas(&tx, Some(BlockNumber::Latest.into())).await?)
+    }
+
+    /// Submits the bundle and returns its FastLane bundle hash.
     pub async fn submit_bundle(&self, bundle: FastLaneBundle) -> Result<H256> {
-        let contract = Contract::new(
-            self.fastlane_contract,
-            include_bytes!("../abis/FastLaneSender.json").as_ref(),
-            self.provider.clone(),
-        );
-        
-        let call = contract.method::<_, H256>(
-            "sendTransaction",
-            (bundle.data, bundle.target_block)
-        )?;
+        self.simulate_bundle(&bundle).await?;
+
+        let contract = abis::fastlane_sender(self.fastlane_contract, self.provider.clone());
+        let call = contract.send_transaction(bundle.data, U256::from(bundle.target_block.as_u64()));
+        // The hash FastLane files the bundle under, which `get_bundle_status` takes
+        let bundle_hash = H256(call.call().await?);
 
         let pending_tx = call.send().await?;
         let receipt = pending_tx.await?;
 
         match receipt {
             Some(r) => {
-                info!("FastLane bundle submitted: {:?}", r.transaction_hash);
-                Ok(r.transaction_hash)
+                info!(tx = ?r.transaction_hash, "FastLane bundle submitted: {:?}", bundle_hash);
+                Ok(bundle_hash)
             }
             None => Err(anyhow!("Failed to submit FastLane bundle"))
         }
     }
 
     pub async fn get_bundle_status(&self, bundle_hash: H256) -> Result<BundleStatus> {
-        let contract = Contract::new(
-            self.fastlane_contract,
-            include_bytes!("../abis/FastLaneSender.json").as_ref(),
-            self.provider.clone(),
-        );
-
-        let status: u8 = contract
-            .method::<_, u8>("getBundleStatus", bundle_hash)?
-            .call()
-            .await?;
+        let contract = abis::fastlane_sender(self.fastlane_contract, self.provider.clone());
+        let status = contract.get_bundle_status(bundle_hash.0).call().await?;
         
         Ok(match status {
             0 => BundleStatus::Pending,
@@ -82,40 +129,74 @@ impl FastLaneClient {
         })
     }
 
+    // Calldata for our combined solver/arbitrage contract, entering through the
+    // flash-loan entrypoint that matches the opportunity's loan source
+    fn execution_calldata(&self, opportunity: &ArbitrageOpportunity) -> Result<Bytes> {
+        let hops = if opportunity.hops.is_empty() {
+            calldata_builder::hops_from_route(&opportunity.path, &opportunity.amounts, &opportunity.routers)?
+        } else {
+            opportunity.hops.clone()
+        };
+        let entry = match opportunity.loan_source {
+            LoanSource::UniswapV3 { token0, token1, fee, .. } => {
+                // The pool lends in its own token order, whatever order the route has
+                let borrowed = |token: Address| opportunity.loans.iter().filter(|l| l.token == token).fold(U256::zero(), |sum, l| sum + l.amount);
+                FlashEntry::UniswapV3 { token0, token1, amount0: borrowed(token0), amount1: borrowed(token1), fee }
+            }
+            LoanSource::Balancer => FlashEntry::Balancer { loans: opportunity.loans.clone() },
+            LoanSource::Aave => FlashEntry::Aave { loans: opportunity.loans.clone() },
+            LoanSource::Inventory => FlashEntry::Inventory,
+        };
+        calldata_builder::encode_with(self.encoding, &entry, &hops)
+    }
+
+    /// Wraps execution calldata in the contract's deadline and minimum-profit guard.
+    pub fn guarded_calldata(&self, execution: Bytes, profit_token: Address, min_profit: U256, deadline_block: U64) -> Result<Bytes> {
+        abis::executor(self.solver_contract, self.provider.clone())
+            .execute_guarded(execution, profit_token, min_profit, U256::from(deadline_block.as_u64()))
+            .calldata()
+            .ok_or_else(|| anyhow!("Failed to encode guarded calldata"))
+    }
+
+    /// Packs guarded bundles for the same block into one `executeBatch` call.
+    pub fn batch_bundle(&self, bundles: &[FastLaneBundle]) -> Result<FastLaneBundle> {
+        let target_block = bundles
+            .iter()
+            .map(|b| b.target_block)
+            .min()
+            .ok_or_else(|| anyhow!("Empty batch"))?;
+        let executions: Vec<Bytes> = bundles.iter().map(|b| b.data.clone()).collect();
+        let data = abis::executor(self.solver_contract, self.provider.clone())
+            .execute_batch(executions)
+            .calldata()
+            .ok_or_else(|| anyhow!("Failed to encode batch calldata"))?;
+        Ok(FastLaneBundle { data, target_block })
+    }
+
     pub async fn create_arbitrage_bundle(
         &self,
         opportunity: &ArbitrageOpportunity,
         gas_price: U256,
+        guard: &ProfitGuard,
     ) -> Result<FastLaneBundle> {
+        // The block it was priced for, unless that one is already mined
         let current_block = self.provider.get_block_number().await?;
-        
-        // Create the calldata for our combined solver/arbitrage contract
-        let contract = Contract::new(
-            self.solver_contract,
-            include_bytes!("../abis/FlashLoanArbitrage.json").as_ref(),
-            self.provider.clone(),
-        );
-
-        let data = contract
-            .method::<_, Bytes>(
-                "executeFlashLoanArbitrage",
-                (
-                    opportunity.token0,
-                    opportunity.token1,
-                    opportunity.amount0,
-                    opportunity.amount1,
-                    opportunity.fee,
-                    opportunity.path.clone(),
-                    opportunity.amounts.clone(),
-                    opportunity.routers.clone(),
-                )
-            )?
-            .calldata()
-            .unwrap();
+        let target_block = match opportunity.target_block {
+            Some(block) if block > current_block => block,
+            _ => current_block + 1,
+        };
+
+        let min_profit = opportunity.expected_profit * guard.min_profit_bps / 10_000;
+        let data = self.guarded_calldata(
+            self.execution_calldata(opportunity)?,
+            opportunity.token0,
+            min_profit,
+            target_block + guard.deadline_blocks,
+        )?;
 
         Ok(FastLaneBundle {
             data,
-            target_block: current_block + 1,
+            target_block,
         })
     }
 
@@ -125,28 +206,7 @@ impl FastLaneClient {
         opportunity: &ArbitrageOpportunity,
         target_block: U64,
     ) -> Result<FastLaneBundle> {
-        let contract = Contract::new(
-            self.solver_contract,
-            include_bytes!("../abis/FlashLoanArbitrage.json").as_ref(),
-            self.provider.clone(),
-        );
-
-        let data = contract
-            .method::<_, Bytes>(
-                "executeFlashLoanArbitrage",
-                (
-                    opportunity.token0,
-                    opportunity.token1,
-                    opportunity.amount0,
-                    opportunity.amount1,
-                    opportunity.fee,
-                    opportunity.path.clone(),
-                    opportunity.amounts.clone(),
-                    opportunity.routers.clone(),
-                )
-            )?
-            .calldata()
-            .unwrap();
+        let data = self.execution_calldata(opportunity)?;
 
         Ok(FastLaneBundle {
             data,
@@ -165,16 +225,3 @@ impl FastLaneClient {
         Ok(())
     }
 }
-
-// Add ArbitrageOpportunity struct to match our contract
-#[derive(Debug, Clone)]
-pub struct ArbitrageOpportunity {
-    pub token0: Address,
-    pub token1: Address,
-    pub amount0: U256,
-    pub amount1: U256,
-    pub fee: u32,
-    pub path: Vec<Address>,
-    pub amounts: Vec<U256>,
-    pub routers: Vec<Address>,
-}
@@ -0,0 +1,159 @@
+// src/fee.rs
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 100% in hundredths of a bip
+const FEE_DENOMINATOR: u32 = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FeeError {
+    #[error("Fee {0} is not a V3 tier (100, 500, 3000 or 10000)")]
+    NotATier(u32),
+    #[error("Fee {0} bps is not below 100%")]
+    BpsOutOfRange(u32),
+    #[error("Fee {0} is not below 100% (1000000)")]
+    OutOfRange(U256),
+}
+
+/// A swap or flash-loan fee in hundredths of a bip (3000 = 0.3%), the unit V3
+/// pools, the executor's calldata and the local AMM math share. Always below
+/// 100%, so it fits the ABI's uint24.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct FeeAmount(u32);
+
+impl FeeAmount {
+    pub const ZERO: FeeAmount = FeeAmount(0);
+    pub const LOWEST: FeeAmount = FeeAmount(100);
+    pub const LOW: FeeAmount = FeeAmount(500);
+    pub const MEDIUM: FeeAmount = FeeAmount(3_000);
+    pub const HIGH: FeeAmount = FeeAmount(10_000);
+    /// The Uniswap V3 factory's enabled tiers.
+    pub const V3_TIERS: [FeeAmount; 4] = [FeeAmount::LOWEST, FeeAmount::LOW, FeeAmount::MEDIUM, FeeAmount::HIGH];
+
+    /// Any fee below 100%, for pools with dynamic fees (Algebra) and loan premiums.
+    pub fn new(pips: u32) -> Result<Self, FeeError> {
+        if pips >= FEE_DENOMINATOR {
+            return Err(FeeError::OutOfRange(U256::from(pips)));
+        }
+        Ok(Self(pips))
+    }
+
+    /// One of the V3 tiers, as `getPool` and the V3 flash entrypoint need.
+    pub fn v3(pips: u32) -> Result<Self, FeeError> {
+        Self::V3_TIERS
+            .into_iter()
+            .find(|tier| tier.0 == pips)
+            .ok_or(FeeError::NotATier(pips))
+    }
+
+    /// A V2-style pair fee in whole bps, e.g. 30 for QuickSwap's 0.3%.
+    pub fn from_bps(bps: u32) -> Result<Self, FeeError> {
+        if bps >= 10_000 {
+            return Err(FeeError::BpsOutOfRange(bps));
+        }
+        Ok(Self(bps * 100))
+    }
+
+    pub fn pips(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_v3_tier(self) -> bool {
+        Self::V3_TIERS.contains(&self)
+    }
+
+    /// The fee charged on `amount`, rounded down.
+    pub fn of(self, amount: U256) -> U256 {
+        amount * U256::from(self.0) / U256::from(FEE_DENOMINATOR)
+    }
+
+    /// `of` for amounts taken from untrusted calldata; None on overflow.
+    pub fn checked_of(self, amount: U256) -> Option<U256> {
+        Some(amount.checked_mul(U256::from(self.0))? / U256::from(FEE_DENOMINATOR))
+    }
+
+    /// The fee charged on `amount`, rounded up, as V3 pools charge flash loans
+    /// (`mulDivRoundingUp`).
+    pub fn of_rounding_up(self, amount: U256) -> U256 {
+        let product = amount * U256::from(self.0);
+        let denominator = U256::from(FEE_DENOMINATOR);
+        product / denominator + if (product % denominator).is_zero() { U256::zero() } else { U256::one() }
+    }
+
+    /// `amount` scaled to what's left after the fee, in millionths, for
+    /// constant-product math that defers the division.
+    pub fn complement(self) -> U256 {
+        U256::from(FEE_DENOMINATOR - self.0)
+    }
+
+    /// Big-endian uint24, as packed into V3 swap paths.
+    pub fn to_uint24_bytes(self) -> [u8; 3] {
+        let bytes = self.0.to_be_bytes();
+        [bytes[1], bytes[2], bytes[3]]
+    }
+}
+
+impl TryFrom<u32> for FeeAmount {
+    type Error = FeeError;
+
+    fn try_from(pips: u32) -> Result<Self, FeeError> {
+        Self::new(pips)
+    }
+}
+
+// ABI uint24 words decode to U256
+impl TryFrom<U256> for FeeAmount {
+    type Error = FeeError;
+
+    fn try_from(pips: U256) -> Result<Self, FeeError> {
+        if pips >= U256::from(FEE_DENOMINATOR) {
+            return Err(FeeError::OutOfRange(pips));
+        }
+        Ok(Self(pips.as_u32()))
+    }
+}
+
+impl From<FeeAmount> for u32 {
+    fn from(fee: FeeAmount) -> u32 {
+        fee.0
+    }
+}
+
+impl From<FeeAmount> for U256 {
+    fn from(fee: FeeAmount) -> U256 {
+        U256::from(fee.0)
+    }
+}
+
+impl fmt::Display for FeeAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_tiers_and_bps() {
+        assert_eq!(FeeAmount::v3(500), Ok(FeeAmount::LOW));
+        assert_eq!(FeeAmount::v3(2_500), Err(FeeError::NotATier(2_500)));
+        assert_eq!(FeeAmount::from_bps(30), Ok(FeeAmount::MEDIUM));
+        assert!(FeeAmount::from_bps(10_000).is_err());
+        assert!(FeeAmount::new(1_000_000).is_err());
+        assert!(FeeAmount::try_from(U256::from(1u64 << 24)).is_err());
+    }
+
+    #[test]
+    fn encodes_as_uint24() {
+        assert_eq!(FeeAmount::MEDIUM.to_uint24_bytes(), [0x00, 0x0b, 0xb8]);
+        assert_eq!(FeeAmount::new(999_999).unwrap().to_uint24_bytes(), [0x0f, 0x42, 0x3f]);
+        assert_eq!(FeeAmount::MEDIUM.of(U256::from(1_000_000)), U256::from(3_000));
+        assert_eq!(FeeAmount::LOW.of(U256::from(1_999)), U256::zero());
+        assert_eq!(FeeAmount::LOW.of_rounding_up(U256::from(1_999)), U256::one());
+        assert_eq!(FeeAmount::LOW.of_rounding_up(U256::from(2_000)), U256::one());
+    }
+}
@@ -0,0 +1,155 @@
+// src/flash_loan.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::fee::FeeAmount;
+use crate::rpc_budget::RpcTransport;
+use crate::v3_quoter::UNISWAP_V3_FACTORY;
+
+abigen!(IERC20Balance, r#"[
+    function balanceOf(address owner) external view returns (uint256)
+]"#);
+
+abigen!(IFlashPoolFactory, r#"[
+    function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address)
+]"#);
+
+pub const BALANCER_VAULT: &str = "0xBA12222222228d8Ba445958a75a0704d566BF2C8";
+// Aave V3 flash-loan premium (0.05%)
+const AAVE_PREMIUM: FeeAmount = FeeAmount::LOW;
+
+/// One asset borrowed for an execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoanLeg {
+    pub token: Address,
+    pub amount: U256,
+}
+
+impl LoanLeg {
+    pub fn new(token: Address, amount: U256) -> Self {
+        Self { token, amount }
+    }
+}
+
+/// Where the executor borrows its loan legs from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoanSource {
+    // `flash` on `pool`; token0/token1 in the pool's (sorted) order, which is
+    // the order the entrypoint takes amounts in
+    UniswapV3 { pool: Address, token0: Address, token1: Address, fee: FeeAmount },
+    // Vault `flashLoan`; the protocol flash-loan fee is 0 on Polygon
+    Balancer,
+    // Pool multi-asset `flashLoan`, for leg sets the Vault can't cover
+    Aave,
+    // No loan: the executor's own balance, for strategies in inventory mode
+    Inventory,
+}
+
+impl LoanSource {
+    pub fn fee(&self) -> FeeAmount {
+        match self {
+            LoanSource::UniswapV3 { fee, .. } => *fee,
+            LoanSource::Balancer | LoanSource::Inventory => FeeAmount::ZERO,
+            LoanSource::Aave => AAVE_PREMIUM,
+        }
+    }
+
+    /// What repaying `amount` costs on top of the principal.
+    pub fn fee_on(&self, amount: U256) -> U256 {
+        match self {
+            LoanSource::UniswapV3 { fee, .. } => fee.of_rounding_up(amount),
+            other => other.fee().of(amount),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoanSource::UniswapV3 { .. } => "uniswap_v3",
+            LoanSource::Balancer => "balancer",
+            LoanSource::Aave => "aave",
+            LoanSource::Inventory => "inventory",
+        }
+    }
+}
+
+/// Picks the cheapest source that can fund a loan: the Balancer Vault when it
+/// holds enough of every leg, otherwise the lowest-fee V3 pool holding enough
+/// of a single leg, otherwise Aave.
+pub struct LoanSourceSelector {
+    provider: Arc<Provider<RpcTransport>>,
+    // None where the chain has no Balancer deployment
+    vault: Option<Address>,
+    v3_factory: IFlashPoolFactory<Provider<RpcTransport>>,
+}
+
+impl LoanSourceSelector {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, vault: Option<Address>) -> Self {
+        Self {
+            v3_factory: IFlashPoolFactory::new(UNISWAP_V3_FACTORY.parse::<Address>().unwrap(), provider.clone()),
+            provider,
+            vault,
+        }
+    }
+
+    /// Everything the Vault holds of `token` can be flash-borrowed.
+    pub async fn balancer_liquidity(&self, token: Address) -> Result<U256> {
+        let vault = match self.vault {
+            Some(vault) => vault,
+            None => return Ok(U256::zero()),
+        };
+        Ok(IERC20Balance::new(token, self.provider.clone()).balance_of(vault).call().await?)
+    }
+
+    /// The V3 pool of `leg.token` against `pair` with the lowest fee tier that
+    /// holds at least `leg.amount`. Pools in `avoid` are skipped: a pool is
+    /// locked while it lends, so the route can't swap through it.
+    pub async fn v3_flash_pool(&self, leg: LoanLeg, pair: Address, avoid: &[Address]) -> Result<Option<LoanSource>> {
+        for fee in FeeAmount::V3_TIERS {
+            let pool = self.v3_factory.get_pool(leg.token, pair, fee.pips()).call().await?;
+            if pool == Address::zero() || avoid.contains(&pool) {
+                continue;
+            }
+            let liquidity = IERC20Balance::new(leg.token, self.provider.clone()).balance_of(pool).call().await?;
+            if liquidity >= leg.amount {
+                let (token0, token1) = if leg.token < pair { (leg.token, pair) } else { (pair, leg.token) };
+                return Ok(Some(LoanSource::UniswapV3 { pool, token0, token1, fee }));
+            }
+            debug!("V3 pool {:?} (fee {}) holds {} of {:?}, need {}", pool, fee, liquidity, leg.token, leg.amount);
+        }
+        Ok(None)
+    }
+
+    /// `pair` is the token a V3 flash pool would pair the loan token with;
+    /// `avoid` the pools the route swaps through.
+    pub async fn select(&self, legs: &[LoanLeg], pair: Address, avoid: &[Address]) -> LoanSource {
+        let mut shortfall = false;
+        for leg in legs {
+            match self.balancer_liquidity(leg.token).await {
+                Ok(liquidity) if liquidity >= leg.amount => {}
+                Ok(liquidity) => {
+                    debug!("Balancer holds {} of {:?}, need {}", liquidity, leg.token, leg.amount);
+                    shortfall = true;
+                    break;
+                }
+                Err(e) => {
+                    warn!("Balancer liquidity check failed for {:?}: {:?}", leg.token, e);
+                    shortfall = true;
+                    break;
+                }
+            }
+        }
+        if !shortfall {
+            return LoanSource::Balancer;
+        }
+        if let [leg] = legs {
+            match self.v3_flash_pool(*leg, pair, avoid).await {
+                Ok(Some(source)) => return source,
+                Ok(None) => debug!("No V3 pool can lend {} of {:?}; using Aave", leg.amount, leg.token),
+                Err(e) => warn!("V3 flash pool lookup failed for {:?}: {:?}", leg.token, e),
+            }
+        }
+        LoanSource::Aave
+    }
+}
@@ -0,0 +1,89 @@
+// src/fork_db.rs
+use ethers::prelude::*;
+use revm::{
+    primitives::{AccountInfo, Bytecode, B160, B256, U256 as rU256},
+    DatabaseRef,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::rpc_budget::RpcTransport;
+
+/// Read-only view of chain state at a fixed block, fetched lazily over RPC.
+/// Wrap it in a `CacheDB` to execute against it; writes stay in the wrapper.
+/// Clones share the fetched accounts and slots, so one instance per block can
+/// back every simulation run against that block.
+#[derive(Debug, Clone)]
+pub struct ForkDb {
+    provider: Arc<Provider<RpcTransport>>,
+    block: BlockId,
+    accounts: Arc<RwLock<HashMap<B160, AccountInfo>>>,
+    storage: Arc<RwLock<HashMap<(B160, rU256), rU256>>>,
+}
+
+impl ForkDb {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, block: impl Into<BlockId>) -> Self {
+        Self {
+            provider,
+            block: block.into(),
+            accounts: Arc::new(RwLock::new(HashMap::new())),
+            storage: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    // revm's Database traits are sync; we're always called from inside the runtime
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+    }
+}
+
+impl DatabaseRef for ForkDb {
+    type Error = ProviderError;
+
+    fn basic(&self, address: B160) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self.accounts.read().unwrap().get(&address) {
+            return Ok(Some(info.clone()));
+        }
+
+        let account = Address::from(address.0);
+        let (balance, nonce, code) = Self::block_on(async {
+            tokio::try_join!(
+                self.provider.get_balance(account, Some(self.block)),
+                self.provider.get_transaction_count(account, Some(self.block)),
+                self.provider.get_code(account, Some(self.block)),
+            )
+        })?;
+
+        let mut buf = [0u8; 32];
+        balance.to_big_endian(&mut buf);
+        let code = Bytecode::new_raw(code.0);
+        let info = AccountInfo::new(rU256::from_be_bytes(buf), nonce.as_u64(), code.hash_slow(), code);
+
+        self.accounts.write().unwrap().insert(address, info.clone());
+        Ok(Some(info))
+    }
+
+    fn code_by_hash(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // basic() always returns code inline, so revm never needs to look it up
+        Ok(Bytecode::new())
+    }
+
+    fn storage(&self, address: B160, index: rU256) -> Result<rU256, Self::Error> {
+        if let Some(value) = self.storage.read().unwrap().get(&(address, index)) {
+            return Ok(*value);
+        }
+
+        let slot = H256::from(index.to_be_bytes::<32>());
+        let value = Self::block_on(self.provider.get_storage_at(Address::from(address.0), slot, Some(self.block)))?;
+        let value = rU256::from_be_bytes(value.0);
+
+        self.storage.write().unwrap().insert((address, index), value);
+        Ok(value)
+    }
+
+    fn block_hash(&self, number: rU256) -> Result<B256, Self::Error> {
+        let number = U64::from(number.as_limbs()[0]);
+        let block = Self::block_on(self.provider.get_block(number))?;
+        Ok(block.and_then(|b| b.hash).map(|h| B256::from(h.0)).unwrap_or_default())
+    }
+}
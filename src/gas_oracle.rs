@@ -0,0 +1,50 @@
+// src/gas_oracle.rs
+use ethers::types::U256;
+use std::sync::Mutex;
+
+use crate::block_events::BlockEvent;
+
+// EIP-1559: base fee moves at most 1/8 per block toward the gas target
+const BASE_FEE_CHANGE_DENOMINATOR: u64 = 8;
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// Base fee of the head and the one it implies for the next block, updated from
+/// block events so pricing a submission doesn't cost an RPC round trip.
+#[derive(Default)]
+pub struct GasOracle {
+    fees: Mutex<(U256, U256)>,
+}
+
+impl GasOracle {
+    pub fn on_block(&self, event: &BlockEvent) {
+        let base_fee = match event.base_fee {
+            Some(fee) => fee,
+            None => return,
+        };
+        let next = Self::next_base_fee_after(base_fee, event.gas_used, event.gas_limit);
+        *self.fees.lock().unwrap() = (base_fee, next);
+    }
+
+    fn next_base_fee_after(base_fee: U256, gas_used: U256, gas_limit: U256) -> U256 {
+        let target = gas_limit / ELASTICITY_MULTIPLIER;
+        if target.is_zero() || gas_used == target {
+            return base_fee;
+        }
+        if gas_used > target {
+            let delta = base_fee * (gas_used - target) / target / BASE_FEE_CHANGE_DENOMINATOR;
+            base_fee + delta.max(U256::one())
+        } else {
+            let delta = base_fee * (target - gas_used) / target / BASE_FEE_CHANGE_DENOMINATOR;
+            base_fee.saturating_sub(delta)
+        }
+    }
+
+    /// Zero until the first block event arrives.
+    pub fn base_fee(&self) -> U256 {
+        self.fees.lock().unwrap().0
+    }
+
+    pub fn next_base_fee(&self) -> U256 {
+        self.fees.lock().unwrap().1
+    }
+}
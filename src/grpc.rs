@@ -0,0 +1,171 @@
+// src/grpc.rs
+use anyhow::Result;
+use ethers::types::{Address, H256};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::event_bus::{BundleLanded, BundleSubmitted, EventBus, OpportunityFound};
+use crate::simulation_engine::SimulationResult;
+
+pub mod proto {
+    tonic::include_proto!("mev.v1");
+}
+
+use proto::opportunity_stream_server::{OpportunityStream, OpportunityStreamServer};
+use proto::{bundle_result, BundleResult, Opportunity, SubscribeRequest};
+
+// Messages buffered per subscriber before the forwarders wait on it
+const STREAM_BUFFER: usize = 256;
+
+/// A chain whose events can be streamed.
+pub trait EventSource: Send + Sync {
+    fn chain(&self) -> &'static str;
+    fn events(&self) -> &EventBus;
+}
+
+/// Streams every chain's `OpportunityFound`, `BundleSubmitted` and
+/// `BundleLanded` events to gRPC subscribers, filtered per request.
+pub struct GrpcService {
+    sources: Vec<Arc<dyn EventSource>>,
+}
+
+impl GrpcService {
+    pub fn new(sources: Vec<Arc<dyn EventSource>>) -> Self {
+        Self { sources }
+    }
+
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        info!(%addr, "gRPC listening");
+        tonic::transport::Server::builder()
+            .add_service(OpportunityStreamServer::new(self))
+            .serve(addr)
+            .await?;
+        Ok(())
+    }
+
+    fn selected(&self, chains: &[String]) -> Vec<&Arc<dyn EventSource>> {
+        self.sources
+            .iter()
+            .filter(|s| chains.is_empty() || chains.iter().any(|c| c == s.chain()))
+            .collect()
+    }
+}
+
+#[tonic::async_trait]
+impl OpportunityStream for GrpcService {
+    type StreamOpportunitiesStream = ReceiverStream<Result<Opportunity, Status>>;
+    type StreamBundleResultsStream = ReceiverStream<Result<BundleResult, Status>>;
+
+    async fn stream_opportunities(&self, request: Request<SubscribeRequest>) -> Result<Response<Self::StreamOpportunitiesStream>, Status> {
+        let filter = request.into_inner();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        for source in self.selected(&filter.chains) {
+            let chain = source.chain();
+            let sources = filter.sources.clone();
+            forward(source.events().opportunities.subscribe(), sender.clone(), move |event: OpportunityFound| {
+                (sources.is_empty() || sources.contains(&event.source)).then(|| opportunity(chain, event))
+            });
+        }
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn stream_bundle_results(&self, request: Request<SubscribeRequest>) -> Result<Response<Self::StreamBundleResultsStream>, Status> {
+        let filter = request.into_inner();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        for source in self.selected(&filter.chains) {
+            let chain = source.chain();
+            forward(source.events().bundles_submitted.subscribe(), sender.clone(), move |event: BundleSubmitted| {
+                Some(BundleResult {
+                    opportunity_id: event.opportunity_id,
+                    chain: chain.to_string(),
+                    hash: hex_hash(event.hash),
+                    status: bundle_result::Status::Submitted as i32,
+                    venue: event.venue.to_string(),
+                    gas_price: event.gas_price.to_string(),
+                    block: event.target_block.as_u64(),
+                })
+            });
+            forward(source.events().bundles_landed.subscribe(), sender.clone(), move |event: BundleLanded| {
+                Some(BundleResult {
+                    opportunity_id: event.opportunity_id,
+                    chain: chain.to_string(),
+                    hash: hex_hash(event.hash),
+                    status: bundle_result::Status::Landed as i32,
+                    venue: String::new(),
+                    gas_price: String::new(),
+                    block: event.block.as_u64(),
+                })
+            });
+        }
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+// Relays one topic into a subscriber's stream until either side goes away.
+// A subscriber too slow for the topic misses events rather than stalling it
+fn forward<T, M>(
+    mut events: broadcast::Receiver<T>,
+    sender: mpsc::Sender<Result<M, Status>>,
+    convert: impl Fn(T) -> Option<M> + Send + 'static,
+) where
+    T: Clone + Send + 'static,
+    M: Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                _ = sender.closed() => return,
+                event = events.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("gRPC subscriber lagged, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return,
+                },
+            };
+            if let Some(message) = convert(event) {
+                if sender.send(Ok(message)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+fn opportunity(chain: &'static str, event: OpportunityFound) -> Opportunity {
+    Opportunity {
+        id: event.id,
+        chain: chain.to_string(),
+        source: event.source,
+        victim_tx: event.victim_tx.map(hex_hash),
+        path: event.path.into_iter().map(hex_address).collect(),
+        pools: event.pools.into_iter().map(hex_address).collect(),
+        amount_in: event.amount_in.to_string(),
+        expected_profit: event.expected_profit.to_string(),
+        simulation: event.simulation.map(simulation),
+    }
+}
+
+fn simulation(result: SimulationResult) -> proto::SimulationResult {
+    proto::SimulationResult {
+        price_impact: result.price_impact.to_string(),
+        expected_profit: result.expected_profit.to_string(),
+        gas_estimate: result.gas_estimate.to_string(),
+        success_probability: result.success_probability,
+        optimal_path: result.optimal_path.into_iter().map(hex_address).collect(),
+    }
+}
+
+fn hex_address(address: Address) -> String {
+    format!("{:?}", address)
+}
+
+fn hex_hash(hash: H256) -> String {
+    format!("{:?}", hash)
+}
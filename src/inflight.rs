@@ -0,0 +1,111 @@
+// src/inflight.rs
+use ethers::types::{Address, U64};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+/// Why a bundle wasn't let into flight. Clears on its own once earlier
+/// bundles land or their target block passes.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InflightRejected {
+    #[error("{limit} {strategy} bundles already in flight")]
+    Saturated { strategy: String, limit: usize },
+    #[error("bundle {other} already targets block {target_block} through the same pools")]
+    PoolConflict { other: String, target_block: U64 },
+}
+
+// A submitted bundle, holding its strategy's permit until it settles
+struct InflightBundle {
+    id: String,
+    pools: Vec<Address>,
+    target_block: U64,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Caps how many bundles each strategy has in flight, and keeps two bundles
+/// through the same pool out of the same block: whichever lands second would
+/// trade against the price the first left behind.
+pub struct InflightLimiter {
+    default_limit: usize,
+    limits: HashMap<String, usize>,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+    bundles: Mutex<Vec<InflightBundle>>,
+}
+
+impl InflightLimiter {
+    pub fn new(default_limit: usize) -> Self {
+        Self {
+            default_limit: default_limit.max(1),
+            limits: HashMap::new(),
+            semaphores: Mutex::new(HashMap::new()),
+            bundles: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_limit(mut self, strategy: &str, limit: usize) -> Self {
+        self.limits.insert(strategy.to_string(), limit.max(1));
+        self
+    }
+
+    pub fn limit(&self, strategy: &str) -> usize {
+        self.limits.get(strategy).copied().unwrap_or(self.default_limit)
+    }
+
+    /// Takes one of `strategy`'s permits for bundle `id`, unless the strategy is
+    /// at its limit or another bundle for `target_block` shares a pool. An
+    /// empty pool list can't be ruled out against anything. Held until
+    /// `release` or until `target_block` is mined.
+    pub fn reserve(&self, strategy: &str, id: &str, pools: &[Address], target_block: U64) -> Result<(), InflightRejected> {
+        let mut bundles = self.bundles.lock().unwrap();
+        if let Some(other) = bundles.iter().find(|b| {
+            b.target_block == target_block && (pools.is_empty() || b.pools.is_empty() || b.pools.iter().any(|p| pools.contains(p)))
+        }) {
+            return Err(InflightRejected::PoolConflict { other: other.id.clone(), target_block });
+        }
+
+        let limit = self.limit(strategy);
+        let semaphore = self
+            .semaphores
+            .lock()
+            .unwrap()
+            .entry(strategy.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone();
+        let permit = semaphore
+            .try_acquire_owned()
+            .map_err(|_| InflightRejected::Saturated { strategy: strategy.to_string(), limit })?;
+
+        bundles.push(InflightBundle {
+            id: id.to_string(),
+            pools: pools.to_vec(),
+            target_block,
+            _permit: permit,
+        });
+        Ok(())
+    }
+
+    pub fn release(&self, id: &str) {
+        self.bundles.lock().unwrap().retain(|b| b.id != id);
+    }
+
+    // Anything not settled by its target block can no longer land
+    pub fn on_block(&self, block: U64) {
+        let mut bundles = self.bundles.lock().unwrap();
+        let before = bundles.len();
+        bundles.retain(|b| b.target_block > block);
+        if bundles.len() < before {
+            debug!(expired = before - bundles.len(), "Released in-flight bundles past their target block");
+        }
+    }
+
+    /// Bundles currently in flight per strategy.
+    pub fn in_flight(&self) -> HashMap<String, usize> {
+        self.semaphores
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(strategy, semaphore)| (strategy.clone(), self.limit(strategy) - semaphore.available_permits()))
+            .collect()
+    }
+}
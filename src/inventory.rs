@@ -0,0 +1,191 @@
+// src/inventory.rs
+use anyhow::Result;
+use ethers::types::{Address, U256, U64};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::flash_loan::LoanLeg;
+use crate::price_service::PriceService;
+
+#[derive(Debug, Clone)]
+pub struct InventoryConfig {
+    // Opportunity sources ("mempool", "block_scan", ...) that trade from the
+    // executor's balance instead of borrowing
+    pub strategies: HashSet<String>,
+    // Every source, regardless of `strategies`
+    pub all_strategies: bool,
+    // Per-token band (USD); a balance outside it is flagged for rebalancing
+    pub min_usd: f64,
+    pub max_usd: f64,
+}
+
+impl Default for InventoryConfig {
+    fn default() -> Self {
+        Self {
+            strategies: HashSet::new(),
+            all_strategies: false,
+            min_usd: 1_000.0,
+            max_usd: 50_000.0,
+        }
+    }
+}
+
+/// A token balance that left its band. Deficits are topped up by the operator;
+/// surpluses are left for the treasury sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rebalance {
+    Deficit { token: Address, value_usd: f64, min_usd: f64 },
+    Surplus { token: Address, value_usd: f64, max_usd: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{available} of {token:?} free in inventory, need {needed}")]
+pub struct InventoryShortfall {
+    pub token: Address,
+    pub available: U256,
+    pub needed: U256,
+}
+
+// Inventory an in-flight execution spends, held until it settles or its target block passes
+#[derive(Debug, Clone)]
+struct Reservation {
+    id: String,
+    leg: LoanLeg,
+    target_block: U64,
+}
+
+#[derive(Debug, Default)]
+struct InventoryState {
+    balances: HashMap<Address, U256>,
+    reservations: Vec<Reservation>,
+    // Tokens already reported out of band; reported again only after returning to it
+    flagged: HashSet<Address>,
+}
+
+/// The executor's token balances as trading capital. Strategies in inventory
+/// mode skip the flash loan when the free balance covers the loan leg, and
+/// fall back to borrowing when it doesn't.
+pub struct Inventory {
+    config: InventoryConfig,
+    state: Mutex<InventoryState>,
+}
+
+impl Inventory {
+    pub fn new(config: InventoryConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(InventoryState::default()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.all_strategies || !self.config.strategies.is_empty()
+    }
+
+    pub fn trades(&self, strategy: &str) -> bool {
+        self.config.all_strategies || self.config.strategies.contains(strategy)
+    }
+
+    /// Balance of `token` not held by an in-flight execution. Zero until the
+    /// first balances arrive.
+    pub async fn available(&self, token: Address) -> U256 {
+        let state = self.state.lock().await;
+        Self::free(&state, token)
+    }
+
+    fn free(state: &InventoryState, token: Address) -> U256 {
+        state.balances.get(&token).copied().unwrap_or_default().saturating_sub(Self::reserved(state, token))
+    }
+
+    fn reserved(state: &InventoryState, token: Address) -> U256 {
+        state
+            .reservations
+            .iter()
+            .filter(|r| r.leg.token == token)
+            .fold(U256::zero(), |sum, r| sum + r.leg.amount)
+    }
+
+    /// The part of an executor `balance` the treasury may take: all of it when
+    /// no strategy trades from inventory, otherwise what is neither reserved
+    /// by an in-flight execution nor needed to stay at the top of the band.
+    /// Unpriced tokens can't be held to the band, so none of them is surplus.
+    pub async fn surplus(&self, token: Address, balance: U256, prices: &PriceService) -> Result<U256> {
+        if !self.enabled() {
+            return Ok(balance);
+        }
+        let value_usd = match prices.usd_value(token, balance).await? {
+            Some(value) if value > 0.0 => value,
+            _ => return Ok(U256::zero()),
+        };
+        let keep = if value_usd <= self.config.max_usd {
+            balance
+        } else {
+            // Rounded up, so the band's top is kept whole
+            let ppm = U256::from((self.config.max_usd / value_usd * 1e6).ceil() as u64);
+            balance.checked_mul(ppm).map_or(balance, |v| v / U256::from(1_000_000))
+        };
+        let reserved = Self::reserved(&*self.state.lock().await, token);
+        Ok(balance.saturating_sub(keep).saturating_sub(reserved))
+    }
+
+    /// Holds `leg` of inventory for execution `id` until `release` or until
+    /// `target_block` is mined.
+    pub async fn reserve(&self, id: &str, leg: LoanLeg, target_block: U64) -> std::result::Result<(), InventoryShortfall> {
+        let mut state = self.state.lock().await;
+        let available = Self::free(&state, leg.token);
+        if available < leg.amount {
+            return Err(InventoryShortfall { token: leg.token, available, needed: leg.amount });
+        }
+        state.reservations.push(Reservation { id: id.to_string(), leg, target_block });
+        Ok(())
+    }
+
+    pub async fn release(&self, id: &str) {
+        self.state.lock().await.reservations.retain(|r| r.id != id);
+    }
+
+    /// Takes the executor balances read at `block` and returns the tokens that
+    /// just left their band.
+    pub async fn on_block(&self, block: U64, balances: &HashMap<Address, U256>, prices: &PriceService) -> Result<Vec<Rebalance>> {
+        {
+            let mut state = self.state.lock().await;
+            state.balances = balances.clone();
+            state.reservations.retain(|r| r.target_block > block);
+        }
+
+        // Priced without the lock held, so reservations on the hot path don't wait
+        let mut values = Vec::new();
+        for (token, balance) in balances {
+            // Unpriced tokens can't be held to a USD band
+            if let Some(value_usd) = prices.usd_value(*token, *balance).await? {
+                values.push((token, value_usd));
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        let mut rebalances = Vec::new();
+        for (token, value_usd) in values {
+            let rebalance = if value_usd < self.config.min_usd {
+                Some(Rebalance::Deficit { token: *token, value_usd, min_usd: self.config.min_usd })
+            } else if value_usd > self.config.max_usd {
+                Some(Rebalance::Surplus { token: *token, value_usd, max_usd: self.config.max_usd })
+            } else {
+                None
+            };
+            match rebalance {
+                Some(rebalance) if state.flagged.insert(*token) => {
+                    warn!("Inventory needs rebalancing: {:?}", rebalance);
+                    rebalances.push(rebalance);
+                }
+                Some(_) => {}
+                None => {
+                    if state.flagged.remove(token) {
+                        info!("Inventory of {:?} back within band (${:.0})", token, value_usd);
+                    }
+                }
+            }
+        }
+        Ok(rebalances)
+    }
+}
@@ -0,0 +1,161 @@
+// src/latency.rs
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+// Histogram bucket upper bounds in ms; anything slower lands in the last bucket
+const BUCKETS_MS: [u64; 11] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500];
+
+/// Pipeline stages after a tx is received, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Decoded,
+    Simulated,
+    Built,
+    Submitted,
+}
+
+impl Stage {
+    const ALL: [Stage; 4] = [Stage::Decoded, Stage::Simulated, Stage::Built, Stage::Submitted];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Decoded => "decoded",
+            Stage::Simulated => "simulated",
+            Stage::Built => "built",
+            Stage::Submitted => "submitted",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// When one opportunity's tx arrived and when it passed each stage since.
+/// Block-scan opportunities start at the head that triggered the scan.
+#[derive(Debug, Clone)]
+pub struct LatencyTrace {
+    received: Instant,
+    last: Instant,
+    budget: Duration,
+    stages: Vec<(Stage, Duration)>,
+}
+
+impl LatencyTrace {
+    pub fn start(budget: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            received: now,
+            last: now,
+            budget,
+            stages: Vec::new(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.received.elapsed()
+    }
+
+    pub fn stages(&self) -> &[(Stage, Duration)] {
+        &self.stages
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    counts: [u64; BUCKETS_MS.len() + 1],
+    total: Duration,
+    samples: u64,
+    // Traces that ran out of budget at this stage
+    aborted: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let bucket = BUCKETS_MS.iter().position(|bound| ms <= *bound).unwrap_or(BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.total += latency;
+        self.samples += 1;
+    }
+
+    // Upper bound of the bucket holding the q-quantile; None past the last bound
+    fn quantile_ms(&self, q: f64) -> Option<u64> {
+        let rank = (self.samples as f64 * q).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return BUCKETS_MS.get(bucket).copied();
+            }
+        }
+        None
+    }
+}
+
+/// Per-stage latency histograms, and the budget check every stage goes through.
+pub struct LatencyMonitor {
+    budget: Duration,
+    histograms: Mutex<[Histogram; Stage::ALL.len()]>,
+}
+
+impl LatencyMonitor {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            histograms: Mutex::new(Default::default()),
+        }
+    }
+
+    pub fn start(&self) -> LatencyTrace {
+        LatencyTrace::start(self.budget)
+    }
+
+    /// Records that `trace` reached `stage`. Err with the reason once the trace
+    /// has used up its budget: on Polygon anything that slow has lost the race,
+    /// so the caller drops it instead of spending more RPC and simulation on it.
+    pub fn mark(&self, trace: &mut LatencyTrace, stage: Stage) -> std::result::Result<(), String> {
+        let now = Instant::now();
+        let latency = now.duration_since(trace.last);
+        trace.last = now;
+        trace.stages.push((stage, latency));
+
+        let mut histograms = self.histograms.lock().unwrap();
+        let histogram = &mut histograms[stage.index()];
+        histogram.observe(latency);
+        let elapsed = now.duration_since(trace.received);
+        if elapsed <= trace.budget {
+            return Ok(());
+        }
+        histogram.aborted += 1;
+        Err(format!(
+            "latency budget: {}ms at {}, budget {}ms",
+            elapsed.as_millis(),
+            stage.name(),
+            trace.budget.as_millis()
+        ))
+    }
+
+    /// Logs each stage's histogram since the last report, then starts over.
+    pub fn report(&self) {
+        let histograms = std::mem::take(&mut *self.histograms.lock().unwrap());
+        for stage in Stage::ALL {
+            let histogram = &histograms[stage.index()];
+            if histogram.samples == 0 {
+                continue;
+            }
+            info!(
+                stage = stage.name(),
+                samples = histogram.samples,
+                mean_ms = histogram.total.as_secs_f64() * 1_000.0 / histogram.samples as f64,
+                p50_ms = ?histogram.quantile_ms(0.5),
+                p90_ms = ?histogram.quantile_ms(0.9),
+                p99_ms = ?histogram.quantile_ms(0.99),
+                aborted = histogram.aborted,
+                buckets = ?histogram.counts,
+                "Stage latency"
+            );
+        }
+    }
+}
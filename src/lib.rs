@@ -1,14 +1,102 @@
-use ethers::prelude::*;
-use std::sync::Arc;
+// src/lib.rs
+//! Polygon MEV detection, simulation and execution as a library. The
+//! `polygon-mev-bot` binary is a thin wrapper around it; services that want
+//! the pipeline, or only parts of it, depend on this crate instead.
+//!
+//! - [`monitor`]: [`MempoolMonitor`], one chain's whole pipeline, and
+//!   [`start_chain`] to build and spawn it from the environment
+//! - [`simulation_engine`]: revm simulation on a forked head, with
+//!   [`state_override`] for balances and storage the executor doesn't have
+//! - [`strategies`]: cycle search, stable and redemption arbitrage, JIT liquidity
+//! - [`executor`]: settlement venues behind one `Executor` trait, picked per
+//!   strategy by the `ExecutorRouter`
+//! - [`routers`], [`pool_state`], [`v3_ticks`]: DEX clients and pool math
+//! - [`errors`]: how a failure anywhere in the pipeline is classified
+//!
+//! Everything else is public so components can be unit-tested and reused on
+//! their own, but only the modules above are meant as entry points.
+pub mod abis;
+// Multi-victim V2 sandwiches around pending QuickSwap swaps
+pub mod advanced;
+pub mod api;
+pub mod atlas;
+pub mod backrun_merge;
+pub mod backtest;
+pub mod balance_monitor;
+pub mod bid_strategy;
+pub mod block_analyzer;
+pub mod block_events;
+pub mod block_timing;
+pub mod bloxroute;
+pub mod bridge_flow;
+pub mod bundle_scenarios;
+pub mod cache;
+pub mod call_tracer;
+pub mod calldata_builder;
+pub mod chain;
+pub mod classifier;
+pub mod competition;
+pub mod confidence;
+pub mod controls;
+pub mod counterparties;
+pub mod decode_coverage;
+pub mod divergence;
+pub mod errors;
+pub mod event_bus;
+pub mod executor;
+pub mod fastlane_integration;
+pub mod fee;
+pub mod flash_loan;
+pub mod fork_db;
+pub mod gas_oracle;
+pub mod grpc;
+pub mod inflight;
+pub mod inventory;
+pub mod latency;
+pub mod mempool_replay;
+pub mod monitor;
+pub mod native;
+pub mod opportunity_queue;
+pub mod oracle_monitor;
+pub mod path_constraints;
+pub mod persistence;
+pub mod pnl;
+pub mod pool_state;
+pub mod price_service;
+pub mod private_rpc;
+// Decoder for pending QuickSwap router calls, separate from the routers::quickswap client
+pub mod quickswap;
+pub mod raw_tx;
+pub mod relay;
+pub mod reorg;
+pub mod retry;
+pub mod revert;
+pub mod risk_manager;
+pub mod routers;
+pub mod rpc_budget;
+pub mod rpc_cache;
+pub mod sandwich;
+pub mod sensitivity;
+pub mod settlement;
+pub mod shadow;
+pub mod shutdown;
+pub mod simulation_engine;
+pub mod spam_filter;
+pub mod state_override;
+pub mod storage;
+pub mod strategies;
+pub mod token_registry;
+pub mod token_safety;
+pub mod token_universe;
+pub mod touch_inspector;
+pub mod treasury;
+pub mod tx_tracker;
+pub mod types;
+pub mod v3_quoter;
+pub mod v3_ticks;
+pub mod victim_tracker;
 
-abigen!(
-    FlashLoanArbitrage,
-    "./abis/FlashLoanArbitrage.json",
-    event_derives(serde::Serialize, serde::Deserialize)
-);
-
-abigen!(
-    FastLaneSender,
-    "./abis/FastLaneSender.json",
-    event_derives(serde::Serialize, serde::Deserialize)
-);
+pub use abis::{FastLaneSender, FlashLoanArbitrage};
+pub use chain::ChainProfile;
+pub use monitor::{start_chain, MempoolMonitor};
+pub use types::ArbitrageOpportunity;
@@ -1,226 +1,189 @@
 // src/main.rs
-mod simulation_engine;
-mod fastlane_integration;
-mod routers {
-    pub mod quickswap;
-    pub mod uniswap_v3;
-    pub mod sushiswap;
-}
-
-use routers::{
-    quickswap::QuickswapRouter,
-    uniswap_v3::UniswapV3Router,
-    sushiswap::SushiswapRouter,
-};
+use polygon_mev_bot::api::{self, ApiSource};
+use polygon_mev_bot::backtest::Backtester;
+use polygon_mev_bot::classifier::ClassifierChain;
+use polygon_mev_bot::divergence::{DivergenceConfig, DivergenceMonitor, Venue};
+use polygon_mev_bot::grpc::{EventSource, GrpcService};
+use polygon_mev_bot::mempool_replay::MempoolReplayer;
+use polygon_mev_bot::monitor::{connect, DRAIN_TIMEOUT};
+use polygon_mev_bot::path_constraints::PathConstraints;
+use polygon_mev_bot::persistence::StateStore;
+use polygon_mev_bot::pool_state::PoolStateManager;
+use polygon_mev_bot::shutdown;
+use polygon_mev_bot::strategies::TriangularScanner;
+use polygon_mev_bot::token_safety::TokenSafety;
+use polygon_mev_bot::{start_chain, ChainProfile};
 
 use anyhow::Result;
-use ethers::{
-    providers::{Provider, StreamExt, Ws},
-    types::{Address, H256, U256},
-};
-use log::{info, warn};
-use std::collections::{HashMap, HashSet};
-use std::str::FromStr;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use simulation_engine::{AdvancedSimulationEngine, SimulationResult};
-use fastlane_integration::FastLaneClient;
 use dotenv::dotenv;
+use ethers::types::U256;
 use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, info_span, warn, Instrument};
 
-// Constants for common tokens on Polygon
-const WETH: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270"; // WMATIC
-const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
-const USDT: &str = "0xc2132D05D31c914a87C6611C10748AEb04B58e8F";
-
-#[derive(Debug, Clone)]
-pub struct ArbitrageOpportunity {
-    token0: Address,
-    token1: Address,
-    amount0: U256,
-    amount1: U256,
-    fee: u32,
-    path: Vec<Address>,
-    amounts: Vec<U256>,
-    routers: Vec<Address>,
-    expected_profit: U256,
-}
-
-struct MempoolMonitor {
-    provider: Arc<Provider<Ws>>,
-    flash_loan_contract: Address,
-    fastlane_client: FastLaneClient,
-    simulation_engine: AdvancedSimulationEngine,
-    opportunities: Mutex<Vec<ArbitrageOpportunity>>,
-    processed_txs: Mutex<HashSet<H256>>,
-    sim_cache: Mutex<HashMap<H256, SimulationResult>>,
-    quickswap: QuickswapRouter,
-    uniswap_v3: UniswapV3Router,
-    sushiswap: SushiswapRouter,
-}
-
-impl MempoolMonitor {
-    pub fn new(provider: Arc<Provider<Ws>>, contract_address: Address, fastlane_address: Address, solver_address: Address) -> Self {
-        let simulation_engine = AdvancedSimulationEngine::new(provider.clone());
-        let fastlane_client = FastLaneClient::new(provider.clone(), fastlane_address, solver_address);
-
-        Self {
-            provider,
-            flash_loan_contract: contract_address,
-            fastlane_client,
-            simulation_engine,
-            opportunities: Mutex::new(Vec::new()),
-            processed_txs: Mutex::new(HashSet::new()),
-            sim_cache: Mutex::new(HashMap::new()),
-        }
-    }
+const TOKEN_SAFETY_SNAPSHOT: &str = "token_safety";
+// Transfer-type execution failures before a token is skipped on its chain
+const TOKEN_SAFETY_STRIKES: u32 = 3;
 
-    pub async fn start_monitoring(&self) -> Result<()> {
-        let stream = self.provider.subscribe_pending_txs().await?;
-        let mut stream = stream.transactions_unordered(256);
-        
-        info!("Starting mempool monitoring...");
-        
-        while let Some(tx) = stream.next().await {
-            if let Ok(tx) = tx {
-                self.process_transaction(tx).await?;
-            }
-        }
-        
-        Ok(())
+#[tokio::main]
+async fn main() -> Result<()> {
+    // JSON lines; filter with RUST_LOG as before
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+    dotenv().ok();
+    
+    let mut chains = ChainProfile::from_env()?;
+    info!(chains = ?chains.iter().map(|c| c.name).collect::<Vec<_>>(), "Chain profiles selected");
+    
+    // --backtest <from_block> <to_block>: replay history of the first chain and print a JSON report
+    let args: Vec<String> = env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--backtest") {
+        let from_block: u64 = args.get(i + 1).expect("--backtest <from> <to>").parse()?;
+        let to_block: u64 = args.get(i + 2).expect("--backtest <from> <to>").parse()?;
+        let chain = &chains[0];
+        let provider = connect(chain).await?;
+
+        let pool_state = Arc::new(PoolStateManager::new(provider.clone(), chain.v2_dexes()));
+        let constraints = PathConstraints::from_env(chain)?;
+        let scanner = TriangularScanner::new(
+            pool_state.clone(),
+            chain.wrapped_native,
+            5,
+            U256::from(10).pow(22.into()),
+            U256::from(10).pow(15.into()),
+            constraints.clone(),
+        )
+        .with_v3_router(chain.v3_router());
+        let tokens = chain.tokens.clone();
+
+        let report = Backtester::new(provider.clone(), pool_state, scanner, ClassifierChain::for_chain(chain), constraints)
+            .run(from_block, to_block, &tokens)
+            .await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
     }
 
-    async fn process_transaction(&self, tx: Transaction) -> Result<()> {
-        let tx_hash = tx.hash;
-        
-        {
-            let mut processed = self.processed_txs.lock().await;
-            if processed.contains(&tx_hash) {
-                return Ok(());
+    // --replay <file> [--replay-speed <x>]: feed a MEMPOOL_RECORD_PATH session back
+    // through the pipeline of the chain it was recorded on, in shadow mode.
+    // Speed 1 keeps the recorded timing, "max" sends as fast as it's taken
+    let mut replay = match args.iter().position(|arg| arg == "--replay") {
+        Some(i) => {
+            let replayer = MempoolReplayer::open(args.get(i + 1).expect("--replay <file>"))?;
+            let speed = match args.iter().position(|arg| arg == "--replay-speed").map(|i| args.get(i + 1).expect("--replay-speed <x>")) {
+                Some(speed) if speed == "max" => f64::INFINITY,
+                Some(speed) => speed.parse()?,
+                None => 1.0,
+            };
+            chains.retain(|chain| chain.chain_id == replayer.chain_id());
+            if chains.is_empty() {
+                return Err(anyhow::anyhow!("Recording is from chain {}, which no selected profile runs", replayer.chain_id()));
             }
-            processed.insert(tx_hash);
-        }
-
-        if let Some(opportunity) = self.analyze_arbitrage(&tx).await? {
-            let mut opportunities = self.opportunities.lock().await;
-            opportunities.push(opportunity);
-            info!("New arbitrage opportunity found: {:?}", tx_hash);
+            info!(chain_id = replayer.chain_id(), speed, "Replaying recorded mempool session");
+            Some((replayer, speed))
         }
+        None => None,
+    };
 
-        Ok(())
+    let shadow_mode = replay.is_some() || env::args().any(|arg| arg == "--shadow");
+    if shadow_mode {
+        info!("Running in shadow mode: nothing will be broadcast");
     }
 
-    async fn analyze_arbitrage(&self, tx: &Transaction) -> Result<Option<ArbitrageOpportunity>> {
-        // Use advanced simulation engine
-        let simulation_result = self.simulation_engine
-            .simulate_multi_dex_arbitrage(tx, 3)
-            .await?;
-
-        if simulation_result.expected_profit > U256::from(10).pow(15.into()) {
-            return Ok(Some(ArbitrageOpportunity {
-                token_in: simulation_result.optimal_path[0],
-                token_out: *simulation_result.optimal_path.last().unwrap(),
-                amount_in: U256::from(10).pow(18.into()),
-                expected_profit: simulation_result.expected_profit,
-                path: simulation_result.optimal_path.clone(),
-                routers: self.get_routers_for_path(&simulation_result.optimal_path).await?,
-                pool_address: self.find_best_pool(&simulation_result.optimal_path).await?,
-                fee: 3000,
-                simulation_result: Some(simulation_result),
-            }));
-        }
-
-        Ok(None)
+    // Token strikes are shared across chains and live at the state root; each
+    // chain's own snapshots go in a subdirectory named after it
+    let state_dir = PathBuf::from(env::var("STATE_DIR").unwrap_or_else(|_| "state".to_string()));
+    let shared_state = StateStore::new(&state_dir);
+    let token_safety = Arc::new(TokenSafety::new(TOKEN_SAFETY_STRIKES));
+    if let Some(entries) = shared_state.load(TOKEN_SAFETY_SNAPSHOT) {
+        token_safety.restore(entries);
     }
 
-    async fn get_routers_for_path(&self, path: &[Address]) -> Result<Vec<Address>> {
-        // Mock implementation, would require a more complex lookup
-        Ok(vec![
-            Address::from_str("0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff")?, // QuickSwap
-            Address::from_str("0xE592427A0AEce92De3Edee1F18E0157C05861564")?, // Uniswap V3
-            Address::from_str("0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506")?, //SushiSwap
-        ])
+    let mut monitors = Vec::new();
+    let mut executions = Vec::new();
+    for chain in chains {
+        let span = info_span!("chain", chain = chain.name);
+        let state = StateStore::new(state_dir.join(chain.name));
+        let (monitor, execution) = start_chain(chain, token_safety.clone(), state, shadow_mode, replay.take())
+            .instrument(span)
+            .await?;
+        monitors.push(monitor);
+        executions.push(execution);
     }
 
-    async fn find_best_pool(&self, path: &[Address]) -> Result<Address> {
-        // Mock implementation, would require a more complex lookup
-        Ok(Address::from_str("0x...01")?)
+    // API_LISTEN_ADDR=127.0.0.1:8080 serves /opportunities, /stats, /health and
+    // the dashboard; API_CONTROL_TOKEN also enables the /control endpoints
+    if let Ok(addr) = env::var("API_LISTEN_ADDR") {
+        let addr: SocketAddr = addr.parse().map_err(|_| anyhow::anyhow!("Invalid API_LISTEN_ADDR {:?}", addr))?;
+        let sources: Vec<Arc<dyn ApiSource>> = monitors.iter().map(|m| m.clone() as Arc<dyn ApiSource>).collect();
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(addr, sources, env::var("API_CONTROL_TOKEN").ok().filter(|t| !t.is_empty())).await {
+                warn!("API server error: {:?}", e);
+            }
+        });
     }
 
-    async fn execute_opportunities(&self) -> Result<()> {
-        let opportunities = self.opportunities.lock().await.clone();
-        
-        for opportunity in opportunities {
-            if self.should_execute(&opportunity).await? {
-                // Use FastLane for execution
-                let gas_price = self.provider.get_gas_price().await?;
-                let bundle = self.fastlane_client
-                    .create_arbitrage_bundle(&opportunity, gas_price)
-                    .await?;
-                
-                let bundle_hash = self.fastlane_client.submit_bundle(bundle).await?;
-                info!("Submitted FastLane bundle: {:?}", bundle_hash);
+    // GRPC_LISTEN_ADDR=127.0.0.1:50051 streams opportunities and bundle results
+    if let Ok(addr) = env::var("GRPC_LISTEN_ADDR") {
+        let addr: SocketAddr = addr.parse().map_err(|_| anyhow::anyhow!("Invalid GRPC_LISTEN_ADDR {:?}", addr))?;
+        let sources: Vec<Arc<dyn EventSource>> = monitors.iter().map(|m| m.clone() as Arc<dyn EventSource>).collect();
+        tokio::spawn(async move {
+            if let Err(e) = GrpcService::new(sources).serve(addr).await {
+                warn!("gRPC server error: {:?}", e);
             }
-        }
-        
-        Ok(())
+        });
     }
 
-    async fn should_execute(&self, opportunity: &ArbitrageOpportunity) -> Result<bool> {
-        // Check gas prices, profitability, and competition
-        let gas_price = self.provider.get_gas_price().await?;
-        let expected_net_profit = opportunity.expected_profit - (gas_price * U256::from(300000));
-        
-        Ok(expected_net_profit > U256::zero())
+    // DIVERGENCE_PAIRS=WETH/USDC,USDT/USDC by symbol; needs two or more chains
+    if monitors.len() > 1 {
+        let mut config = DivergenceConfig {
+            alert_webhook: env::var("ALERT_WEBHOOK_URL").ok(),
+            ..Default::default()
+        };
+        if let Ok(pairs) = env::var("DIVERGENCE_PAIRS") {
+            config.pairs = pairs
+                .split(',')
+                .filter_map(|pair| pair.trim().split_once('/'))
+                .map(|(base, quote)| (base.to_string(), quote.to_string()))
+                .collect();
+        }
+        if let Some(bps) = env::var("DIVERGENCE_ALERT_BPS").ok().and_then(|v| v.parse().ok()) {
+            config.alert_bps = bps;
+        }
+        if let Some(secs) = env::var("DIVERGENCE_INTERVAL_SECS").ok().and_then(|v| v.parse().ok()) {
+            config.interval = Duration::from_secs(secs);
+        }
+        let venues = monitors
+            .iter()
+            .map(|m| Venue { chain: m.profile().clone(), pool_state: m.pool_state().clone(), tokens: m.tokens().clone() })
+            .collect();
+        let divergence = DivergenceMonitor::new(venues, config);
+        tokio::spawn(async move {
+            if let Err(e) = divergence.discover().await {
+                warn!("Divergence pool discovery failed: {:?}", e);
+            }
+            if let Err(e) = divergence.run().await {
+                warn!("Divergence monitor error: {:?}", e);
+            }
+        });
     }
-}
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    env_logger::init();
-    dotenv().ok();
-    
-    let ws_url = env::var("POLYGON_WS_URL")
-        .expect("POLYGON_WS_URL must be set in .env");
-    
-    let provider = Provider::<Ws>::connect(&ws_url).await?;
-    let provider = Arc::new(provider);
-    
-    let flash_loan_contract = Address::from_str(
-        &env::var("FLASH_LOAN_CONTRACT")
-            .expect("FLASH_LOAN_CONTRACT must be set in .env")
-    )?;
-    
-    let fastlane_address = Address::from_str(
-        &env::var("FASTLANE_RELAY_URL")
-            .expect("FASTLANE_RELAY_URL must be set in .env")
-    )?;
-    
-    let solver_address = Address::from_str(
-        &env::var("ARBITRAGE_EXECUTOR_CONTRACT")
-            .expect("ARBITRAGE_EXECUTOR_CONTRACT must be set in .env")
-    )?;
 
-    let monitor = Arc::new(MempoolMonitor::new(
-        provider.clone(),
-        flash_loan_contract,
-        fastlane_address,
-        solver_address
-    ));
-    
-    // Start monitoring mempool
-    let monitor_clone = monitor.clone();
-    tokio::spawn(async move {
-        if let Err(e) = monitor_clone.start_monitoring().await {
-            warn!("Mempool monitoring error: {:?}", e);
-        }
-    });
-    
-    // Execute opportunities periodically
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-        if let Err(e) = monitor.execute_opportunities().await {
-            warn!("Execution error: {:?}", e);
+    shutdown::signal().await;
+    for monitor in &monitors {
+        if let Err(e) = monitor.shutdown().await {
+            warn!(chain = monitor.profile().name, "Shutdown error: {:?}", e);
         }
     }
+    if let Err(e) = shared_state.save(TOKEN_SAFETY_SNAPSHOT, &token_safety.snapshot()) {
+        warn!("Failed to save token safety: {:?}", e);
+    }
+    // Returns as soon as the current executions (if any) finish
+    let _ = tokio::time::timeout(DRAIN_TIMEOUT, futures::future::join_all(executions)).await;
+    Ok(())
 }
@@ -0,0 +1,147 @@
+// src/mempool_replay.rs
+use anyhow::{anyhow, Result};
+use ethers::types::Transaction;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+// Decoded txs buffered between the file reader and the pacer
+const REPLAY_BUFFER: usize = 1_024;
+
+/// First line of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub chain_id: u64,
+    pub started_ms: u64,
+}
+
+/// A pending tx and when it reached us, in ms since the epoch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedTx {
+    pub received_ms: u64,
+    pub tx: Transaction,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Writes every pending tx the monitor receives, from any feed and before
+/// dedup, to a gzipped JSON-lines file. The file is only complete once
+/// `finish` has run; a replay of a crashed session stops at the torn tail.
+pub struct MempoolRecorder {
+    writer: Mutex<Option<GzEncoder<BufWriter<File>>>>,
+    recorded: AtomicU64,
+}
+
+impl MempoolRecorder {
+    pub fn create(path: impl AsRef<Path>, chain_id: u64) -> Result<Self> {
+        let file = File::create(path.as_ref())?;
+        let mut writer = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut writer, &RecordingHeader { chain_id, started_ms: now_ms() })?;
+        writer.write_all(b"\n")?;
+        info!(path = %path.as_ref().display(), "Recording mempool session");
+        Ok(Self {
+            writer: Mutex::new(Some(writer)),
+            recorded: AtomicU64::new(0),
+        })
+    }
+
+    pub fn record(&self, tx: &Transaction) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        let writer = match writer.as_mut() {
+            Some(writer) => writer,
+            None => return Ok(()),
+        };
+        serde_json::to_writer(&mut *writer, &RecordedTx { received_ms: now_ms(), tx: tx.clone() })?;
+        writer.write_all(b"\n")?;
+        self.recorded.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Writes the gzip trailer; later records are dropped.
+    pub fn finish(&self) -> Result<()> {
+        if let Some(writer) = self.writer.lock().unwrap().take() {
+            writer.finish()?.flush()?;
+            info!(txs = self.recorded.load(Ordering::Relaxed), "Mempool recording closed");
+        }
+        Ok(())
+    }
+}
+
+/// Feeds a recording back into a monitor in its original order, keeping the
+/// gaps between txs divided by `speed`: 1.0 is real time, infinity is as fast
+/// as the pipeline takes them.
+pub struct MempoolReplayer {
+    header: RecordingHeader,
+    lines: std::io::Lines<BufReader<GzDecoder<File>>>,
+}
+
+impl MempoolReplayer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path.as_ref())?;
+        let mut lines = BufReader::new(GzDecoder::new(file)).lines();
+        let header = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(anyhow!("{} is an empty recording", path.as_ref().display())),
+        };
+        Ok(Self { header, lines })
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.header.chain_id
+    }
+
+    /// Sends every recorded tx to `feed` and returns how many were sent. Ends
+    /// early when the receiver goes away.
+    pub async fn run(self, speed: f64, feed: mpsc::Sender<Transaction>) -> Result<u64> {
+        if speed.is_nan() || speed <= 0.0 {
+            return Err(anyhow!("Replay speed must be positive, got {}", speed));
+        }
+
+        // Decompression and parsing are blocking; they stay off the runtime
+        let (records, mut decoded) = mpsc::channel(REPLAY_BUFFER);
+        let lines = self.lines;
+        let reader = tokio::task::spawn_blocking(move || {
+            for line in lines {
+                let record: RecordedTx = match line.map_err(anyhow::Error::from).and_then(|l| Ok(serde_json::from_str(&l)?)) {
+                    Ok(record) => record,
+                    Err(e) => {
+                        warn!("Recording ends in an unreadable record, stopping there: {:?}", e);
+                        return;
+                    }
+                };
+                if records.blocking_send(record).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let started = Instant::now();
+        let mut first_ms = None;
+        let mut sent = 0u64;
+        while let Some(record) = decoded.recv().await {
+            let first_ms = *first_ms.get_or_insert(record.received_ms);
+            let offset = record.received_ms.saturating_sub(first_ms) as f64 / 1_000.0 / speed;
+            if offset.is_finite() && offset > 0.0 {
+                tokio::time::sleep_until(started + Duration::from_secs_f64(offset)).await;
+            }
+            if feed.send(record.tx).await.is_err() {
+                break;
+            }
+            sent += 1;
+        }
+        drop(decoded);
+        reader.await?;
+        info!(txs = sent, "Mempool replay finished");
+        Ok(sent)
+    }
+}
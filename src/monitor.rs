@@ -0,0 +1,2526 @@
+// src/monitor.rs
+//! One chain's detection, simulation and execution pipeline. `start_chain`
+//! builds a `MempoolMonitor` from a `ChainProfile` and the environment and
+//! spawns its tasks; embedders that wire their own clients call
+//! `MempoolMonitor::new` and the `start_*` loops directly.
+use crate::advanced::{AdvancedArbitrage, SandwichOpportunity};
+use crate::backrun_merge::BackrunMerger;
+use crate::balance_monitor::{BalanceConfig, BalanceMonitor};
+use crate::bid_strategy::{BidConfig, BidStrategy};
+use crate::block_timing::BlockTimingModel;
+use crate::bloxroute::{BloxrouteClient, BloxrouteStream};
+use crate::bridge_flow::{BridgeFlowMonitor, BridgeInflow};
+use crate::cache::BlockLruCache;
+use crate::call_tracer::{CallTracer, PoolDelta, TraceConfig};
+use crate::calldata_builder::CalldataEncoding;
+use crate::chain::ChainProfile;
+use crate::block_analyzer::BlockAnalyzer;
+use crate::classifier::{ClassifiedTx, ClassifierChain, ExclusionStats};
+use crate::competition::{CompetitionTracker, WatchedSubmission};
+use crate::counterparties::CounterpartyRegistry;
+use crate::confidence::ConfidenceModel;
+use crate::controls::RuntimeControls;
+use crate::decode_coverage::DecodeCoverage;
+use crate::event_bus::{BundleLanded, BundleSubmitted, ClassifiedEvent, EventBus, OpportunityFound};
+use crate::executor::{
+    BloxrouteExecutor, ExecutionRequest, ExecutionSigner, Executor, ExecutorRouter, FastLaneExecutor,
+    PrivateRpcExecutor, PublicExecutor, RelayExecutor,
+};
+use crate::routers::{
+    quickswap::QuickswapRouter,
+    uniswap_v3::UniswapV3Router,
+    sushiswap::SushiswapRouter,
+};
+
+use anyhow::Result;
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Middleware, Provider, StreamExt},
+    signers::{LocalWallet, Signer},
+    types::{Address, BlockNumber, Transaction, H256, I256, U256, U64},
+};
+use tracing::{debug, error, info, instrument, trace, warn, Instrument};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast::error::RecvError, mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use futures::Stream;
+use std::pin::Pin;
+use crate::simulation_engine::{AdvancedSimulationEngine, LossBound, SimulationResult};
+use crate::fastlane_integration::{BundleStatus, FastLaneBundle, FastLaneClient, ProfitGuard};
+use crate::flash_loan::{LoanLeg, LoanSource, LoanSourceSelector};
+use crate::gas_oracle::GasOracle;
+use crate::grpc::EventSource;
+use crate::inflight::InflightLimiter;
+use crate::inventory::{Inventory, InventoryConfig};
+use crate::latency::{LatencyMonitor, LatencyTrace, Stage};
+use crate::mempool_replay::{MempoolRecorder, MempoolReplayer};
+use crate::opportunity_queue::OpportunityQueue;
+use crate::oracle_monitor::OracleMonitor;
+use crate::path_constraints::PathConstraints;
+use crate::strategies::{CycleOpportunity, JitLiquidityStrategy, RedemptionArbStrategy, StableArbStrategy, TriangularScanner};
+use crate::persistence::StateStore;
+use crate::pnl::{LoanTerms, PnlEngine};
+use crate::pool_state::PoolStateManager;
+use crate::price_service::{PriceService, UsdPolicy};
+use crate::api::{ApiSource, ChainHealth, ChainStats, OpportunityView};
+use crate::atlas::{AtlasConfig, AtlasSolver, UserOpNotice};
+use crate::private_rpc::{PrivateRpcClient, PrivateRpcMethod, SubmissionPolicy, SubmissionRoute};
+use crate::reorg::{ReorgDetector, ReorgEvent};
+use crate::retry::RetryPolicy;
+use crate::errors::ErrorClass;
+use crate::revert::{replay_revert, ExecutionFailure};
+use crate::relay::RelayClient;
+use crate::risk_manager::{RiskConfig, RiskManager};
+use crate::rpc_budget::{with_priority, Priority, RpcBudget, RpcTransport};
+use crate::sensitivity::Sensitivity;
+use crate::settlement::{Settlement, SettlementTracker};
+use crate::shadow::{ShadowEntry, ShadowRecorder};
+use crate::shutdown::{InFlight, Shutdown};
+use crate::spam_filter::{SpamFilter, SpamFilterConfig, SpamPattern};
+use crate::treasury::{Treasury, TreasuryConfig};
+use crate::storage::{InclusionStatus, OpportunityRecord, Storage};
+use crate::token_registry::{units, TokenRegistry};
+use crate::token_safety::TokenSafety;
+use crate::token_universe::{TokenUniverse, UniverseConfig};
+use crate::tx_tracker::{SignerClient, TxTracker, TxTrackerConfig};
+use crate::types::ArbitrageOpportunity;
+use crate::victim_tracker::{VictimEvent, VictimStatus, VictimTracker};
+use std::env;
+
+use crate::{block_events, classifier, errors, pool_state, rpc_budget, rpc_cache, tx_tracker};
+
+const PROCESSED_TX_CAPACITY: usize = 200_000;
+const SIM_CACHE_CAPACITY: usize = 10_000;
+// Blocks a seen tx hash is remembered for dedup
+const PROCESSED_TX_RETENTION: u64 = 50;
+// Simulations are priced against one head; keep them a couple of blocks at most
+const SIM_CACHE_RETENTION: u64 = 2;
+
+// In-flight simulations and submissions get this long to finish on shutdown
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+const TRACKED_TXS_SNAPSHOT: &str = "tracked_txs";
+const POOLS_SNAPSHOT: &str = "pools";
+const TOKENS_SNAPSHOT: &str = "tokens";
+const BIDDING_SNAPSHOT: &str = "bidding_model";
+const CONFIDENCE_SNAPSHOT: &str = "confidence_model";
+const COUNTERPARTIES_SNAPSHOT: &str = "counterparties";
+// The confidence model is refit on settled executions this often (~10 minutes)
+const CONFIDENCE_RECALIBRATE_BLOCKS: u64 = 300;
+// Most recent settled executions the refit and the inclusion rate use
+const CONFIDENCE_HISTORY: i64 = 2_000;
+// Stage latency histograms are logged and reset this often (~5 minutes on PoS)
+const LATENCY_REPORT_BLOCKS: u64 = 150;
+// /health reports a chain down once its head is this many block times old
+const HEALTH_MAX_MISSED_BLOCKS: u32 = 10;
+
+// Gas budgeted for one executor call when sizing bids and checking profitability
+const EXECUTION_GAS_LIMIT: u64 = 300_000;
+// Pending swaps held per block as possible co-victims of a sandwich
+const MAX_SANDWICH_CANDIDATES: usize = 256;
+
+// An opportunity that passed every check, priced and ready to submit
+struct PreparedExecution {
+    bundle: FastLaneBundle,
+    gas_price: U256,
+    priority_fee: U256,
+    latency: LatencyTrace,
+    started: Instant,
+}
+
+/// Everything one chain runs: pending-tx intake, classification, simulation,
+/// strategies, risk checks and submission. Each `start_*` loop is meant to be
+/// spawned once and runs until `shutdown`.
+pub struct MempoolMonitor {
+    provider: Arc<Provider<RpcTransport>>,
+    chain: ChainProfile,
+    flash_loan_contract: Address,
+    fastlane_client: FastLaneClient,
+    // FastLane bundles awaiting a final status
+    settlement: SettlementTracker,
+    simulation_engine: Arc<AdvancedSimulationEngine>,
+    // Scores simulations; refit from storage outcomes
+    confidence: Arc<ConfidenceModel>,
+    oracle_monitor: Arc<OracleMonitor>,
+    tokens: Arc<TokenRegistry>,
+    classifier: ClassifierChain,
+    // Marketplace and bridge txs dropped at classification
+    exclusions: ExclusionStats,
+    // Drops spam before it is classified
+    spam_filter: SpamFilter,
+    // Share of router calldata the classifier decodes, and the selectors it misses
+    decode_coverage: DecodeCoverage,
+    // Large bridged deposits headed for this chain, and who receives them
+    bridge_flow: BridgeFlowMonitor,
+    min_bridge_inflow_usd: f64,
+    prices: Arc<PriceService>,
+    treasury: Option<Treasury>,
+    balances: Option<BalanceMonitor>,
+    pnl_engine: PnlEngine,
+    jit_strategy: JitLiquidityStrategy,
+    // Sandwiches around pending QuickSwap swaps; needs a signer for our legs
+    sandwich: Option<AdvancedArbitrage>,
+    // This block's sandwichable swaps, so later ones can join an earlier victim's sandwich
+    sandwich_candidates: Mutex<Vec<Transaction>>,
+    pool_state: Arc<PoolStateManager>,
+    loan_sources: LoanSourceSelector,
+    triangular_scanner: TriangularScanner,
+    stable_arb: StableArbStrategy,
+    redemption_arb: RedemptionArbStrategy,
+    // debug_traceCall on high-value swaps; needs a node with the debug namespace
+    tracer: Option<CallTracer>,
+    // Simulate unclassified contract calls in revm to find pools they swap on
+    simulate_unclassified: bool,
+    // One backrun per (pool set, direction) across this block's victims
+    backrun_merger: BackrunMerger,
+    signer: Option<Arc<SignerClient>>,
+    tx_tracker: Option<Arc<TxTracker>>,
+    // Settlement venues, chosen per strategy by the submission policy
+    executors: ExecutorRouter,
+    // Paused strategies, USD policy and on-chain profit bounds (private venues,
+    // public mempool); adjustable through the control API
+    controls: RuntimeControls,
+    // Solver operations bid into FastLane Atlas auctions
+    atlas: Option<AtlasSolver>,
+    // Subscribe to full pending tx bodies rather than hashes (PENDING_TX_BODIES)
+    pending_tx_bodies: bool,
+    // Only txs to known routers, aggregators and oracles (PENDING_TX_FILTER)
+    filter_pending_txs: bool,
+    // Extra pending-tx feed (bloXroute) merged with the provider subscription
+    external_feed: Mutex<Option<mpsc::Receiver<Transaction>>>,
+    // A recorded session fed back in place of every live feed (--replay)
+    replay_feed: Mutex<Option<mpsc::Receiver<Transaction>>>,
+    // Every received pending tx, written to MEMPOOL_RECORD_PATH
+    recorder: Option<MempoolRecorder>,
+    reorg_detector: ReorgDetector,
+    // Typed events between subsystems; newHeads drives everything per-block
+    events: EventBus,
+    shutdown: Shutdown,
+    in_flight: InFlight,
+    state: StateStore,
+    // Shared with the other chains' monitors
+    token_safety: Arc<TokenSafety>,
+    // Tokens traded beyond the chain's base set, discovered from factory logs
+    universe: TokenUniverse,
+    gas_oracle: GasOracle,
+    // Head cadence and Bor sprint proposers; gates submissions on time left
+    block_timing: BlockTimingModel,
+    // Stage histograms and the per-opportunity latency budget
+    latency: LatencyMonitor,
+    // Works out who beat us when a submission doesn't land
+    competition: CompetitionTracker,
+    // Known competitor bots; seeded from MEV_BOT_ADDRESSES, grown from competition
+    counterparties: CounterpartyRegistry,
+    block_analyzer: BlockAnalyzer,
+    bid_strategy: BidStrategy,
+    storage: Option<Storage>,
+    risk_manager: RiskManager,
+    // Bundles each strategy has in flight, and the pools they touch
+    inflight: InflightLimiter,
+    // Executor balances strategies in inventory mode trade from
+    inventory: Inventory,
+    // Drift over one block (bps) opportunities are stress-priced against
+    sensitivity_move_bps: u32,
+    // Set in --shadow mode: the pipeline runs but nothing is broadcast
+    shadow: Option<ShadowRecorder>,
+    // Detection -> execution, most profitable first
+    opportunities: OpportunityQueue<ArbitrageOpportunity>,
+    // Most opportunities packed into one executeBatch submission
+    max_batch_legs: usize,
+    victims: VictimTracker,
+    processed_txs: BlockLruCache<H256, ()>,
+    sim_cache: BlockLruCache<H256, SimulationResult>,
+    quickswap: QuickswapRouter,
+    uniswap_v3: UniswapV3Router,
+    sushiswap: SushiswapRouter,
+}
+
+impl MempoolMonitor {
+    /// Wires the pipeline for `chain`. Settings not passed in are read from the
+    /// chain's environment variables; nothing is spawned yet.
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        chain: ChainProfile,
+        contract_address: Address,
+        fastlane_address: Address,
+        solver_address: Address,
+        signer: Option<Arc<SignerClient>>,
+        relay: Option<RelayClient>,
+        bloxroute: Option<BloxrouteClient>,
+        private_rpc: Option<PrivateRpcClient>,
+        atlas: Option<AtlasSolver>,
+        storage: Option<Storage>,
+        state: StateStore,
+        token_safety: Arc<TokenSafety>,
+        bridge_flow: BridgeFlowMonitor,
+        shadow_mode: bool,
+    ) -> Result<Self> {
+        let pool_state = Arc::new(PoolStateManager::new(provider.clone(), chain.v2_dexes()));
+        let confidence = Arc::new(ConfidenceModel::default());
+        let constraints = PathConstraints::from_env(&chain).expect("invalid PATH_* constraint settings");
+        let simulation_engine = Arc::new(AdvancedSimulationEngine::new(provider.clone(), pool_state.clone(), confidence.clone(), constraints.clone()));
+        // PACKED_CALLDATA once the deployed executor has executePacked
+        let encoding = if chain.var("PACKED_CALLDATA").is_ok() { CalldataEncoding::Packed } else { CalldataEncoding::Abi };
+        let fastlane_client = FastLaneClient::new(provider.clone(), fastlane_address, solver_address).with_calldata_encoding(encoding);
+        let settlement = SettlementTracker::new(provider.clone(), fastlane_client.clone());
+        let oracle_monitor = Arc::new(OracleMonitor::new(provider.clone()));
+        let tokens = Arc::new(TokenRegistry::new(provider.clone()));
+        let pnl_engine = PnlEngine::new(provider.clone(), oracle_monitor.clone(), tokens.clone(), solver_address, chain.wrapped_native);
+        let jit_strategy = JitLiquidityStrategy::new(
+            provider.clone(),
+            solver_address,
+            U256::from(10).pow(21.into()), // only victims swapping >= 1000 units
+            U256::from(10).pow(22.into()),
+        );
+
+        let triangular_scanner = TriangularScanner::new(
+            pool_state.clone(),
+            chain.wrapped_native,
+            5,
+            U256::from(10).pow(22.into()),
+            U256::from(10).pow(15.into()),
+            constraints.clone(),
+        )
+        .with_v3_router(chain.v3_router());
+        let stable_arb = StableArbStrategy::new(
+            provider.clone(),
+            pool_state.clone(),
+            chain.curve_pools().expect("invalid CURVE_POOLS setting"),
+            chain.v3_router(),
+            constraints.clone(),
+        );
+        let redemption_arb = RedemptionArbStrategy::new(
+            provider.clone(),
+            pool_state.clone(),
+            chain.staked_assets.clone(),
+            chain.wrapped_native,
+            chain.v3_router(),
+            constraints.clone(),
+        );
+
+        let recorder = chain
+            .var("MEMPOOL_RECORD_PATH")
+            .ok()
+            .map(|path| MempoolRecorder::create(path, chain.chain_id).expect("cannot create MEMPOOL_RECORD_PATH"));
+
+        let shadow = if shadow_mode {
+            Some(ShadowRecorder::new(provider.clone(), storage.clone()))
+        } else {
+            None
+        };
+
+        let prices = Arc::new(PriceService::new(oracle_monitor.clone(), tokens.clone(), pool_state.clone(), chain.wrapped_native));
+        let mut universe_config = UniverseConfig::default();
+        let universe_var = |key: &str| chain.var(&format!("TOKEN_UNIVERSE_{}", key)).ok();
+        if let Some(max) = universe_var("MAX_TOKENS").and_then(|v| v.parse().ok()) {
+            universe_config.max_tokens = max;
+        }
+        if let Some(usd) = universe_var("MIN_TVL_USD").and_then(|v| v.parse().ok()) {
+            universe_config.min_tvl_usd = usd;
+        }
+        if let Some(usd) = universe_var("MIN_VOLUME_USD").and_then(|v| v.parse().ok()) {
+            universe_config.min_volume_usd = usd;
+        }
+        if let Some(blocks) = universe_var("LOOKBACK_BLOCKS").and_then(|v| v.parse().ok()) {
+            universe_config.lookback_blocks = blocks;
+        }
+        let universe = TokenUniverse::new(
+            provider.clone(),
+            pool_state.clone(),
+            prices.clone(),
+            tokens.clone(),
+            token_safety.clone(),
+            chain.chain_id,
+            chain.tokens.clone(),
+            chain.v3_router().is_some(),
+            universe_config,
+        );
+        let competition = CompetitionTracker::new(provider.clone(), storage.clone());
+        let bot_seed: Vec<Address> = chain
+            .var("MEV_BOT_ADDRESSES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| Address::from_str(s).expect("MEV_BOT_ADDRESSES must be comma-separated addresses"))
+            .collect();
+        let counterparties = CounterpartyRegistry::new(&bot_seed);
+        let bid_strategy = BidStrategy::new(BidConfig::default(), competition.model());
+        let block_analyzer = BlockAnalyzer::new(provider.clone(), pool_state.clone(), storage.clone(), solver_address);
+
+        let tx_tracker = signer.clone().map(|client| Arc::new(TxTracker::new(client, TxTrackerConfig::default())));
+        // Preference order; the policy then filters by the route each strategy allows
+        let mut venues: Vec<Box<dyn Executor>> = Vec::new();
+        if let Some(signer) = &signer {
+            let signing = ExecutionSigner::new(signer.clone(), fastlane_client.clone());
+            if let Some(client) = private_rpc {
+                venues.push(Box::new(PrivateRpcExecutor::new(client, signing.clone())));
+            }
+            if let Some(relay) = relay {
+                let mut executor = RelayExecutor::new(relay, signing.clone());
+                if let Some(wei) = chain.var("RELAY_MIN_COINBASE_PAYMENT").ok().and_then(|v| U256::from_dec_str(&v).ok()) {
+                    executor = executor.with_min_coinbase_payment(wei);
+                }
+                venues.push(Box::new(executor));
+            }
+            if let Some(client) = bloxroute {
+                venues.push(Box::new(BloxrouteExecutor::new(client, signing.clone())));
+            }
+            if chain.strategies.fastlane {
+                venues.push(Box::new(FastLaneExecutor::new(fastlane_client.clone())));
+            }
+            if let Some(tracker) = &tx_tracker {
+                venues.push(Box::new(PublicExecutor::new(signing, tracker.clone())));
+            }
+        } else if chain.strategies.fastlane {
+            venues.push(Box::new(FastLaneExecutor::new(fastlane_client.clone())));
+        }
+        let executors = ExecutorRouter::new(venues, SubmissionPolicy::default());
+
+        // Public submissions can be sandwiched, so they get a tighter minimum
+        let mut profit_guard = ProfitGuard::default();
+        if let Some(bps) = chain.var("PROFIT_GUARD_MIN_PROFIT_BPS").ok().and_then(|v| v.parse().ok()) {
+            profit_guard.min_profit_bps = bps;
+        }
+        if let Some(blocks) = chain.var("PROFIT_GUARD_DEADLINE_BLOCKS").ok().and_then(|v| v.parse().ok()) {
+            profit_guard.deadline_blocks = blocks;
+        }
+        let mut public_guard = ProfitGuard { min_profit_bps: 8_000, ..profit_guard.clone() };
+        if let Some(bps) = chain.var("PUBLIC_MIN_PROFIT_BPS").ok().and_then(|v| v.parse().ok()) {
+            public_guard.min_profit_bps = bps;
+        }
+        if let Some(blocks) = chain.var("PUBLIC_EXPIRY_BLOCKS").ok().and_then(|v| v.parse().ok()) {
+            public_guard.deadline_blocks = blocks;
+        }
+        let loan_sources = LoanSourceSelector::new(provider.clone(), chain.balancer_vault);
+
+        let tracer = chain.var("TRACE_VICTIMS").is_ok().then(|| {
+            let mut config = TraceConfig::default();
+            if let Some(matic) = chain.var("TRACE_MIN_VALUE_MATIC").ok().and_then(|v| v.parse::<u64>().ok()) {
+                config.min_native_value = U256::from(matic) * U256::exp10(18);
+            }
+            if let Some(max) = chain.var("TRACE_MAX_PER_BLOCK").ok().and_then(|v| v.parse().ok()) {
+                config.max_per_block = max;
+            }
+            CallTracer::new(provider.clone(), pool_state.clone(), config)
+        });
+
+        // Profit conversion needs the owner key, and stays off in shadow mode
+        // where nothing may be broadcast
+        let treasury = match (&signer, TreasuryConfig::from_env(&chain)?) {
+            (Some(signer), Some(config)) if shadow.is_none() => {
+                Some(Treasury::new(config, signer.clone(), solver_address, pool_state.clone(), prices.clone()))
+            }
+            _ => None,
+        };
+
+        // SANDWICH_MAX_LOSS_USD bounds what any partial inclusion of a sandwich may lose
+        let sandwich = signer.as_ref().map(|signer| {
+            let max_loss_usd = chain.var("SANDWICH_MAX_LOSS_USD").ok().and_then(|v| v.parse().ok()).unwrap_or(5.0);
+            AdvancedArbitrage::new(
+                provider.clone(),
+                solver_address,
+                signer.address(),
+                chain.wrapped_native,
+                pool_state.clone(),
+                prices.clone(),
+                constraints.min_pool_tvl_usd,
+                max_loss_usd,
+                simulation_engine.clone(),
+                token_safety.clone(),
+                chain.chain_id,
+            )
+        });
+
+        let balances = signer.as_ref().map(|signer| {
+            let matic = |var: &str, default: u64| {
+                U256::from(chain.var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)) * U256::exp10(18)
+            };
+            let config = BalanceConfig {
+                min_eoa_balance: matic("GAS_FLOOR_MATIC", 5),
+                low_balance: matic("GAS_ALERT_MATIC", 20),
+                refill_target: matic("GAS_REFILL_TARGET_MATIC", 50),
+                auto_refill: chain.var("GAS_AUTO_REFILL").is_ok(),
+                alert_webhook: chain.var("ALERT_WEBHOOK_URL").ok(),
+            };
+            BalanceMonitor::new(provider.clone(), config, signer.address(), solver_address, chain.tokens.clone())
+        });
+
+        // Undecoded router selectors are reported weekly unless DECODE_REPORT_HOURS says otherwise
+        let decode_report = Duration::from_secs(3600 * chain.var("DECODE_REPORT_HOURS").ok().and_then(|v| v.parse().ok()).unwrap_or(7 * 24));
+        let decode_coverage = DecodeCoverage::new(decode_report, 10);
+        // SPAM_CONTRACTS: comma-separated 0xcontract or 0xcontract:0xselector
+        let mut spam_config = SpamFilterConfig::default();
+        if let Ok(contracts) = chain.var("SPAM_CONTRACTS") {
+            spam_config.patterns = contracts.split(',').filter(|s| !s.trim().is_empty()).filter_map(|s| {
+                let pattern = SpamPattern::parse(s);
+                if pattern.is_none() {
+                    warn!("Ignoring malformed SPAM_CONTRACTS entry {:?}", s);
+                }
+                pattern
+            }).collect();
+        }
+        if let Some(gwei) = chain.var("SPAM_MIN_PRIORITY_FEE_GWEI").ok().and_then(|v| v.parse::<u64>().ok()) {
+            spam_config.min_priority_fee = U256::from(gwei) * U256::exp10(9);
+        }
+        if let Some(wei) = chain.var("SPAM_DUST_WEI").ok().and_then(|v| U256::from_dec_str(&v).ok()) {
+            spam_config.dust_value = wei;
+        }
+        if let Some(repeats) = chain.var("SPAM_MAX_REPEATS").ok().and_then(|v| v.parse().ok()) {
+            spam_config.max_repeats = repeats;
+        }
+        let simulate_unclassified = chain.var("SIMULATE_UNCLASSIFIED").is_ok();
+        let pending_tx_bodies = chain.var("PENDING_TX_BODIES").is_ok();
+        let filter_pending_txs = chain.var("PENDING_TX_FILTER").is_ok();
+        if filter_pending_txs && simulate_unclassified {
+            warn!("PENDING_TX_FILTER drops unknown targets, SIMULATE_UNCLASSIFIED only sees known ones");
+        }
+        // Floor on the decision-to-submission latency the timing model assumes
+        let min_latency = Duration::from_millis(chain.var("SUBMISSION_LATENCY_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(300));
+        let block_timing = BlockTimingModel::new(provider.clone(), &chain, min_latency);
+        // Past this since receipt an opportunity has lost the race and is dropped
+        let latency_budget = chain.var("LATENCY_BUDGET_MS").ok().and_then(|v| v.parse().ok()).map(Duration::from_millis).unwrap_or(chain.block_time);
+        let backrun_merger = BackrunMerger::new(pool_state.clone());
+        // 1 turns batching off: every opportunity goes out on its own
+        let max_batch_legs = chain.var("MAX_BATCH_LEGS").ok().and_then(|v| v.parse().ok()).unwrap_or(3).max(1);
+        let mut risk_config = RiskConfig {
+            kill_switch_url: chain.var("KILL_SWITCH_URL").ok(),
+            ..RiskConfig::default()
+        };
+        let usd_limit = |var: &str| chain.var(var).ok().and_then(|v| v.parse::<f64>().ok());
+        if let Some(limit) = usd_limit("RISK_MAX_POOL_EXPOSURE_USD") {
+            risk_config.max_pool_exposure_usd = limit;
+        }
+        if let Some(limit) = usd_limit("RISK_MAX_TOKEN_EXPOSURE_USD") {
+            risk_config.max_token_exposure_usd = limit;
+        }
+        if let Some(limit) = usd_limit("RISK_MAX_INFLIGHT_USD") {
+            risk_config.max_inflight_usd = limit;
+        }
+        risk_config.reject_knife_edge = chain.var("RISK_ALLOW_KNIFE_EDGE").is_err();
+        let sensitivity_move_bps = chain.var("SENSITIVITY_PRICE_MOVE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(50);
+        // MAX_INFLIGHT_BUNDLES for every strategy, MAX_INFLIGHT_BUNDLES_<STRATEGY> to override one
+        let bundle_limit = |var: &str| chain.var(var).ok().and_then(|v| v.parse::<usize>().ok());
+        let mut inflight = InflightLimiter::new(bundle_limit("MAX_INFLIGHT_BUNDLES").unwrap_or(4));
+        for strategy in ["arbitrage", "jit", "sandwich"] {
+            if let Some(limit) = bundle_limit(&format!("MAX_INFLIGHT_BUNDLES_{}", strategy.to_uppercase())) {
+                inflight = inflight.with_limit(strategy, limit);
+            }
+        }
+        // INVENTORY_MODE=all, or a list of opportunity sources (mempool,block_scan,...)
+        let mut inventory_config = InventoryConfig::default();
+        if let Ok(mode) = chain.var("INVENTORY_MODE") {
+            inventory_config.all_strategies = mode.trim() == "all";
+            inventory_config.strategies = mode.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        }
+        if let Some(limit) = usd_limit("INVENTORY_MIN_USD") {
+            inventory_config.min_usd = limit;
+        }
+        if let Some(limit) = usd_limit("INVENTORY_MAX_USD") {
+            inventory_config.max_usd = limit;
+        }
+
+        Ok(Self {
+            provider,
+            chain,
+            flash_loan_contract: contract_address,
+            fastlane_client,
+            settlement,
+            simulation_engine,
+            confidence,
+            oracle_monitor,
+            prices,
+            treasury,
+            balances,
+            tokens,
+            classifier: ClassifierChain::for_chain(&chain),
+            exclusions: ExclusionStats::default(),
+            spam_filter: SpamFilter::new(spam_config),
+            decode_coverage,
+            bridge_flow,
+            min_bridge_inflow_usd: chain.var("BRIDGE_MIN_INFLOW_USD").ok().and_then(|v| v.parse().ok()).unwrap_or(50_000.0),
+            pnl_engine,
+            jit_strategy,
+            pool_state,
+            loan_sources,
+            triangular_scanner,
+            stable_arb,
+            redemption_arb,
+            sandwich,
+            sandwich_candidates: Mutex::new(Vec::new()),
+            tracer,
+            simulate_unclassified,
+            backrun_merger,
+            tx_tracker,
+            signer,
+            executors,
+            controls: RuntimeControls::new(UsdPolicy::default(), profit_guard, public_guard),
+            atlas,
+            pending_tx_bodies,
+            filter_pending_txs,
+            external_feed: Mutex::new(None),
+            replay_feed: Mutex::new(None),
+            recorder,
+            reorg_detector: ReorgDetector::new(provider.clone(), 64),
+            events: EventBus::default(),
+            shutdown: Shutdown::default(),
+            in_flight: InFlight::default(),
+            state,
+            token_safety,
+            universe,
+            gas_oracle: GasOracle::default(),
+            block_timing,
+            latency: LatencyMonitor::new(latency_budget),
+            competition,
+            counterparties,
+            block_analyzer,
+            bid_strategy,
+            storage,
+            risk_manager: RiskManager::new(risk_config),
+            inflight,
+            inventory: Inventory::new(inventory_config),
+            sensitivity_move_bps,
+            shadow,
+            opportunities: OpportunityQueue::new(),
+            max_batch_legs,
+            victims: VictimTracker::new(provider.clone()),
+            processed_txs: BlockLruCache::new(PROCESSED_TX_CAPACITY, PROCESSED_TX_RETENTION),
+            sim_cache: BlockLruCache::new(SIM_CACHE_CAPACITY, SIM_CACHE_RETENTION),
+        })
+    }
+
+    // Enabled in the profile and not paused through the control API
+    fn strategy_active(&self, enabled: bool, name: &str) -> bool {
+        enabled && !self.controls.is_paused(name)
+    }
+
+    pub fn profile(&self) -> &ChainProfile {
+        &self.chain
+    }
+
+    pub fn pool_state(&self) -> &Arc<PoolStateManager> {
+        &self.pool_state
+    }
+
+    pub fn tokens(&self) -> &Arc<TokenRegistry> {
+        &self.tokens
+    }
+
+    /// Merges `feed` with the provider's pending-tx subscription.
+    pub async fn attach_feed(&self, feed: mpsc::Receiver<Transaction>) {
+        *self.external_feed.lock().await = Some(feed);
+    }
+
+    /// Replaces every live feed with `feed`, e.g. a `MempoolReplayer`.
+    pub async fn attach_replay(&self, feed: mpsc::Receiver<Transaction>) {
+        *self.replay_feed.lock().await = Some(feed);
+    }
+
+    // Providers that push whole pending txs (Alchemy) save the per-hash
+    // eth_getTransactionByHash roundtrip; anything else gets hashes, hydrated
+    async fn provider_feed(&self) -> Result<Pin<Box<dyn Stream<Item = Transaction> + Send + '_>>> {
+        if self.pending_tx_bodies && self.filter_pending_txs {
+            let targets = self.pending_tx_targets().await;
+            let params = serde_json::json!(["alchemy_pendingTransactions", { "toAddress": targets, "hashesOnly": false }]);
+            match self.provider.subscribe::<_, Transaction>(params).await {
+                Ok(stream) => {
+                    info!(targets = targets.len(), "Subscribed to alchemy_pendingTransactions filtered by target");
+                    return Ok(Box::pin(stream));
+                }
+                Err(e) => warn!("Filtered pending tx subscription unavailable, taking every tx: {:?}", e),
+            }
+        }
+        if self.pending_tx_bodies {
+            let params = serde_json::json!(["alchemy_pendingTransactions", { "hashesOnly": false }]);
+            match self.provider.subscribe::<_, Transaction>(params).await {
+                Ok(stream) => {
+                    info!("Subscribed to alchemy_pendingTransactions");
+                    return Ok(Box::pin(stream));
+                }
+                Err(e) => warn!("Full pending tx subscription unavailable, hydrating hashes: {:?}", e),
+            }
+        }
+        let stream = self.provider.subscribe_pending_txs().await?;
+        Ok(Box::pin(stream.transactions_unordered(256).filter_map(|tx| async move { tx.ok() })))
+    }
+
+    // Everything the classifier acts on is sent to one of these. Unknown routers
+    // and protocols are filtered out, so SIMULATE_UNCLASSIFIED sees nothing new
+    async fn pending_tx_targets(&self) -> Vec<Address> {
+        let mut targets: Vec<Address> = self.chain.dexes.iter().map(|dex| dex.router).collect();
+        targets.extend(classifier::aggregator_addresses());
+        targets.extend(self.oracle_monitor.aggregators().await);
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+
+    /// Classifies and simulates pending transactions as they arrive.
+    pub async fn start_monitoring(&self) -> Result<()> {
+        // Before the feed: oracle aggregators are among the subscription's targets
+        self.oracle_monitor.refresh_aggregators().await?;
+
+        let replay = self.replay_feed.lock().await.take();
+        let replaying = replay.is_some();
+        let mut stream: Pin<Box<dyn Stream<Item = Transaction> + Send + '_>> = match replay {
+            Some(rx) => Box::pin(ReceiverStream::new(rx)),
+            None => {
+                let provider_feed = self.provider_feed().await?;
+                // Both feeds deliver the same txs at different latencies; processed_txs dedups
+                match self.external_feed.lock().await.take() {
+                    Some(rx) => Box::pin(futures::stream::select(provider_feed, ReceiverStream::new(rx))),
+                    None => Box::pin(provider_feed),
+                }
+            }
+        };
+
+        info!("Starting mempool monitoring...");
+        
+        loop {
+            let tx = tokio::select! {
+                _ = self.shutdown.wait() => break,
+                tx = stream.next() => match tx {
+                    Some(tx) => tx,
+                    None => break,
+                },
+            };
+            if let (Some(recorder), false) = (&self.recorder, replaying) {
+                if let Err(e) = recorder.record(&tx) {
+                    warn!("Mempool recording failed: {:?}", e);
+                }
+            }
+            let _in_flight = self.in_flight.enter();
+            // One bad tx must not stop intake
+            let hash = tx.hash;
+            if let Err(e) = self.process_transaction(tx).await {
+                warn!("Processing {:?} failed: {:?}", hash, e);
+            }
+        }
+
+        info!("Mempool monitoring stopped");
+        Ok(())
+    }
+
+    /// Publishes newHeads on the event bus; the per-block work hangs off it.
+    pub async fn start_head_subscription(&self) -> Result<()> {
+        block_events::publish_heads(self.provider.clone(), &self.events.blocks).await
+    }
+
+    /// Persists bus events. Storage learns about landed bundles from the bus
+    /// rather than from the tracker.
+    pub async fn start_storage_sink(&self) -> Result<()> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Ok(()),
+        };
+        let mut landed = self.events.bundles_landed.subscribe();
+        loop {
+            match landed.recv().await {
+                Ok(event) => {
+                    storage.update_inclusion(event.hash, InclusionStatus::Included, Some(event.block)).await?;
+                }
+                Err(RecvError::Lagged(skipped)) => warn!("Storage sink lagged, skipped {} events", skipped),
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    /// Finds opportunities that exist at rest after each block, not only those
+    /// caused by pending transactions we happened to see.
+    pub async fn start_block_scanner(&self) -> Result<()> {
+        let tokens = self.chain.tokens.clone();
+        self.pool_state.discover(&tokens).await?;
+        let head = self.provider.get_block_number().await?;
+        self.pool_state.discover_v3(&tokens, head).await?;
+        self.tokens.prefetch(&tokens).await?;
+        if self.chain.strategies.stable_arb {
+            // The AMM side of every Curve coin pair
+            let coins = self.stable_arb.load().await?;
+            self.universe.pin(&coins).await;
+            self.pool_state.discover(&coins).await?;
+            self.pool_state.discover_v3(&coins, head).await?;
+            self.tokens.prefetch(&coins).await?;
+        }
+        if self.chain.strategies.redemption_arb {
+            let mut staked = self.redemption_arb.tokens();
+            staked.push(self.chain.wrapped_native);
+            self.universe.pin(&staked).await;
+            self.pool_state.discover(&staked).await?;
+            self.pool_state.discover_v3(&staked, head).await?;
+            self.tokens.prefetch(&staked).await?;
+        }
+
+        let mut blocks = self.events.blocks.subscribe();
+        loop {
+            let block = match blocks.recv().await {
+                Ok(block) => block,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Block scanner lagged, skipped {} blocks", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let number = block.number;
+            // First, so the arrival time isn't skewed by the work below
+            self.block_timing.on_block(number).await;
+            let latency = self.latency.start();
+            // Pins `latest` reads to the new head
+            rpc_cache::cache(&self.provider).on_block(number);
+
+            // A failed step is logged and the loop goes on: one bad block or RPC
+            // hiccup mustn't stop the scanner for the rest of the run
+            match self.reorg_detector.on_block(&block.block).await {
+                Ok(Some(event)) => {
+                    if let Err(e) = self.handle_reorg(&event).await {
+                        warn!("Reorg handling failed at {}: {:?}", number, e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Reorg check failed for {}: {:?}", number, e),
+            }
+            self.gas_oracle.on_block(&block);
+            self.simulation_engine.on_new_head(number, block.hash).await;
+            self.confidence.on_block();
+            self.backrun_merger.on_block().await;
+            self.sandwich_candidates.lock().await.clear();
+            self.risk_manager.on_block(number).await;
+            self.inflight.on_block(number);
+            self.decode_coverage.report_if_due();
+            self.spam_filter.on_block();
+            if number.as_u64() % LATENCY_REPORT_BLOCKS == 0 {
+                self.latency.report();
+                self.exclusions.report();
+                self.spam_filter.report();
+                rpc_budget::limiter(&self.provider).report();
+            }
+            if number.as_u64() % CONFIDENCE_RECALIBRATE_BLOCKS == 0 {
+                if let Err(e) = self.recalibrate_confidence().await {
+                    warn!("Confidence recalibration failed: {:?}", e);
+                }
+            }
+            self.opportunities.on_block(number, block.transactions());
+            match self.victims.on_block(number, block.transactions()).await {
+                Ok(events) => {
+                    for event in events {
+                        self.on_victim_event(event);
+                    }
+                }
+                Err(e) => warn!("Victim tracking failed for {}: {:?}", number, e),
+            }
+            self.processed_txs.on_block(number);
+            self.sim_cache.on_block(number);
+            let (processed, sims, rpc) = (self.processed_txs.stats(), self.sim_cache.stats(), rpc_cache::cache(&self.provider).stats());
+            debug!(
+                processed_len = processed.len,
+                processed_hit_rate = processed.hit_rate(),
+                processed_evictions = processed.evictions,
+                processed_expirations = processed.expirations,
+                sim_len = sims.len,
+                sim_hit_rate = sims.hit_rate(),
+                sim_evictions = sims.evictions,
+                sim_expirations = sims.expirations,
+                rpc_len = rpc.len,
+                rpc_hit_rate = rpc.hit_rate(),
+                rpc_evictions = rpc.evictions,
+                "Cache stats"
+            );
+
+            if let Some(tracker) = &self.tx_tracker {
+                if let Err(e) = tracker.on_block(number).await {
+                    warn!("Tx tracking failed for {}: {:?}", number, e);
+                }
+            }
+            if let Some(shadow) = &self.shadow {
+                if let Err(e) = shadow.on_block(number).await {
+                    warn!("Shadow scoring failed for {}: {:?}", number, e);
+                }
+            }
+            if let Some(balances) = &self.balances {
+                match balances.on_block(number).await {
+                    Ok(Some(refill)) => match &self.treasury {
+                        Some(treasury) => {
+                            if let Err(e) = treasury.refill_gas(refill, &self.inventory).await {
+                                warn!("Gas refill failed: {:?}", e);
+                            }
+                        }
+                        None => warn!("Gas auto-refill needs the treasury (TREASURY_BASE_ASSET)"),
+                    },
+                    Ok(None) => {}
+                    Err(e) => warn!("Balance check failed for {}: {:?}", number, e),
+                }
+                if self.inventory.enabled() {
+                    let tokens = balances.balances().await.executor_tokens;
+                    if let Err(e) = self.inventory.on_block(number, &tokens, &self.prices).await {
+                        warn!("Inventory check failed for {}: {:?}", number, e);
+                    }
+                }
+            }
+            match self.competition.on_block(number).await {
+                Ok(outcomes) => {
+                    for record in &outcomes.lost_to {
+                        self.counterparties.record_competitor(record);
+                    }
+                    for won in outcomes.won {
+                        self.events.bundles_landed.publish(BundleLanded {
+                            opportunity_id: won.opportunity_id,
+                            hash: won.submission_hash,
+                            block: won.target_block,
+                        });
+                    }
+                }
+                Err(e) => warn!("Competition check failed for {}: {:?}", number, e),
+            }
+
+            let cycles = match self.triangular_scanner.on_block(number).await {
+                Ok(cycles) => cycles,
+                Err(e) => {
+                    warn!("Triangular scan failed for {}: {:?}", number, e);
+                    Vec::new()
+                }
+            };
+            // Reserves were just refreshed by the scan
+            if let Err(e) = self.prices.on_block().await {
+                warn!("Price refresh failed for {}: {:?}", number, e);
+            }
+            match self.pool_tvl().await {
+                Ok(tvl) => {
+                    self.simulation_engine.update_tvl(tvl.clone());
+                    self.triangular_scanner.update_tvl(tvl);
+                }
+                Err(e) => warn!("Pool TVL refresh failed for {}: {:?}", number, e),
+            }
+            if let Err(e) = self.block_analyzer.on_block(number, &self.processed_txs).await {
+                warn!("Post-block analysis failed for {}: {:?}", number, e);
+            }
+            if self.strategy_active(self.chain.strategies.stable_arb, "stable") {
+                match self.stable_arb.on_block(number).await {
+                    Ok(cycles) => {
+                        for cycle in cycles {
+                            let opportunity = self.cycle_opportunity(cycle, latency.clone()).await;
+                            if let Err(e) = self.push_opportunity(opportunity, "stable_arb", None).await {
+                                warn!("Queueing stable arb failed: {:?}", e);
+                            }
+                        }
+                    }
+                    Err(e) => warn!("Stable arb scan failed for {}: {:?}", number, e),
+                }
+            }
+            if self.strategy_active(self.chain.strategies.redemption_arb, "redemption") {
+                match self.redemption_arb.on_block(number).await {
+                    Ok(found) => {
+                        for found in found {
+                            let source = if found.rate_update { "redemption_rate_update" } else { "redemption" };
+                            let opportunity = self.cycle_opportunity(found.cycle, latency.clone()).await;
+                            if let Err(e) = self.push_opportunity(opportunity, source, None).await {
+                                warn!("Queueing redemption arb failed: {:?}", e);
+                            }
+                        }
+                    }
+                    Err(e) => warn!("Redemption arb scan failed for {}: {:?}", number, e),
+                }
+            }
+            // The scan still runs with the strategy off: it refreshes reserves
+            if !self.strategy_active(self.chain.strategies.triangular, "triangular") {
+                continue;
+            }
+            for cycle in cycles {
+                let opportunity = self.cycle_opportunity(cycle, latency.clone()).await;
+                if let Err(e) = self.push_opportunity(opportunity, "block_scan", None).await {
+                    warn!("Queueing triangular cycle failed: {:?}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the risk manager's kill switch and reset flags current, off the
+    /// submission path.
+    pub async fn start_risk_controls(&self) -> Result<()> {
+        tokio::select! {
+            _ = self.shutdown.wait() => {}
+            _ = self.risk_manager.run_controls() => {}
+        }
+        Ok(())
+    }
+
+    /// Re-ranks the token universe every `refresh_blocks`, once prices are in.
+    /// TOKEN_UNIVERSE_MAX_TOKENS=0 keeps the chain's base tokens only.
+    pub async fn start_token_universe(&self) -> Result<()> {
+        if !self.universe.enabled() {
+            return Ok(());
+        }
+        let mut next = 0;
+        let mut blocks = self.events.blocks.subscribe();
+        loop {
+            let number = tokio::select! {
+                _ = self.shutdown.wait() => return Ok(()),
+                block = blocks.recv() => match block {
+                    Ok(block) => block.number,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            };
+            // Candidates are valued in base tokens, worthless until those are priced
+            if number.as_u64() < next || self.prices.usd_price(self.chain.wrapped_native).await?.is_none() {
+                continue;
+            }
+            next = number.as_u64() + self.universe.refresh_blocks();
+            match self.universe.refresh(number).await {
+                Ok(()) => debug!(active = self.universe.active().await.len(), "Token universe refreshed"),
+                Err(e) => warn!("Token universe refresh failed at {}: {:?}", number, e),
+            }
+        }
+    }
+
+    /// Follows Ethereum for PoS bridge and FxPortal deposits into this chain.
+    pub async fn start_bridge_flow(&self) -> Result<()> {
+        let (sender, mut deposits) = mpsc::unbounded_channel();
+        let watcher = self.bridge_flow.watch_l1(move |inflow| {
+            let _ = sender.send(inflow);
+        });
+        let handler = async {
+            while let Some(inflow) = deposits.recv().await {
+                self.on_bridge_inflow(inflow).await;
+            }
+            Ok(())
+        };
+        tokio::try_join!(watcher, handler)?;
+        Ok(())
+    }
+
+    // Large inflows get their token's pools loaded before the funds land, so a
+    // recipient swapping them on arrival can be backrun from the first block
+    async fn on_bridge_inflow(&self, inflow: BridgeInflow) {
+        let mut usd = self.prices.usd_value(inflow.token, inflow.amount).await.ok().flatten();
+        if usd.is_none() {
+            let mut tokens = self.chain.tokens.clone();
+            tokens.push(inflow.token);
+            let head = self.opportunities.head();
+            let discovered = with_priority(Priority::Background, async {
+                self.pool_state.discover(&tokens).await?;
+                self.pool_state.discover_v3(&tokens, head).await
+            });
+            if let Err(e) = discovered.await {
+                warn!("Pool discovery for bridged {:?} failed: {:?}", inflow.token, e);
+                return;
+            }
+            // Discovered here, so the universe must not drop its pools
+            self.universe.pin(&[inflow.token]).await;
+            usd = self.prices.usd_value(inflow.token, inflow.amount).await.ok().flatten();
+        }
+        match usd {
+            Some(usd) if usd >= self.min_bridge_inflow_usd => {
+                self.bridge_flow.record(inflow.clone()).await;
+                self.events.bridge_inflows.publish(inflow);
+            }
+            Some(_) => {}
+            None => debug!("No price for bridged {:?}, ignoring inflow {:?}", inflow.token, inflow.tx_hash),
+        }
+    }
+
+    // Called once an execution is confirmed on chain
+    #[instrument(name = "settlement", parent = &opportunity.span, skip_all, fields(tx = ?tx_hash))]
+    async fn record_realized_pnl(&self, opportunity: &ArbitrageOpportunity, tx_hash: H256) -> Result<()> {
+        // PnL is measured in the first leg's token; other legs are repaid in kind
+        let primary = opportunity.loans.first().copied().unwrap_or(LoanLeg::new(opportunity.token0, opportunity.amount0));
+        let loan = LoanTerms {
+            token: primary.token,
+            amount: primary.amount,
+            source: opportunity.loan_source,
+        };
+        let pnl = self.pnl_engine.record_execution(&opportunity.id, tx_hash, &loan).await?;
+        self.risk_manager.record_outcome(pnl.net_usd, pnl.reverted).await;
+        self.release_execution(&opportunity.id).await;
+
+        if let Some(storage) = &self.storage {
+            storage.record_realized_profit(tx_hash, pnl.net_token_delta).await?;
+            storage.record_settlement(&pnl).await?;
+            if pnl.reverted {
+                let failure = ExecutionFailure::new("onchain", replay_revert(&self.provider, tx_hash).await?);
+                warn!(kind = failure.kind.as_str(), "Execution reverted on chain: {}", failure.reason);
+                self.token_safety.record_failure(self.chain.chain_id, &self.unlisted_tokens(opportunity), &failure);
+                storage.record_failure(&opportunity.id, &failure).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // USD TVL of every tracked pool; unpriceable pools count as empty, since
+    // thin long-tail pools are where phantom profits come from
+    async fn pool_tvl(&self) -> Result<HashMap<Address, f64>> {
+        let mut tvl = HashMap::new();
+        for pool in self.pool_state.snapshot().await {
+            tvl.insert(pool.address, self.prices.pool_tvl_usd(&pool).await?.unwrap_or_default());
+        }
+        Ok(tvl)
+    }
+
+    // Inclusion rate and weights both come from settled submissions in storage
+    async fn recalibrate_confidence(&self) -> Result<()> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Ok(()),
+        };
+        if let Some(rate) = storage.inclusion_rate(CONFIDENCE_HISTORY).await? {
+            self.confidence.set_inclusion_rate(rate);
+        }
+        let samples = storage.confidence_outcomes(CONFIDENCE_HISTORY).await?;
+        if !self.confidence.calibrate(&samples) {
+            debug!("{} settled samples, keeping confidence weights", samples.len());
+        }
+        Ok(())
+    }
+
+    async fn handle_reorg(&self, event: &ReorgEvent) -> Result<()> {
+        self.pool_state.invalidate_after(event.common_ancestor).await;
+
+        // Opportunities and simulations were priced against the abandoned chain
+        self.opportunities.clear();
+        self.sim_cache.clear();
+        rpc_cache::cache(&self.provider).clear();
+
+        if let Some(tracker) = &self.tx_tracker {
+            tracker.on_reorg(event.common_ancestor).await?;
+        }
+        Ok(())
+    }
+
+    // A replaced victim's successor arrives as a new pending tx and gets analyzed
+    // on its own, so dependents of the old one are simply cancelled
+    fn on_victim_event(&self, event: VictimEvent) {
+        match event.status {
+            VictimStatus::Mined(block) => {
+                debug!("Victim {:?} mined in block {}", event.tx_hash, block);
+                self.opportunities.cancel_victim(event.tx_hash);
+            }
+            VictimStatus::Replaced(_) | VictimStatus::Dropped => {
+                info!("Cancelling opportunities on victim {:?}: {:?}", event.tx_hash, event.status);
+                self.opportunities.cancel_victim(event.tx_hash);
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(tx = ?tx.hash))]
+    async fn process_transaction(&self, tx: Transaction) -> Result<()> {
+        let tx_hash = tx.hash;
+        
+        if self.processed_txs.insert(tx_hash, ()) {
+            return Ok(());
+        }
+        let mut latency = self.latency.start();
+        if self.events.pending_txs.has_subscribers() {
+            self.events.pending_txs.publish(Arc::new(tx.clone()));
+        }
+
+        if let Some(event) = self.victims.observe(&tx).await {
+            self.on_victim_event(event);
+        }
+        // After the victim check, so a cheap cancel of a victim still counts
+        if let Some(rule) = self.spam_filter.check(&tx, self.gas_oracle.base_fee()) {
+            trace!(rule = rule.name(), "Dropping pending tx {:?}", tx_hash);
+            return Ok(());
+        }
+
+        let classified = self.classifier.classify(&tx);
+        self.events.classified.publish(ClassifiedEvent { tx_hash, class: classified.clone() });
+        if let Some(router) = tx.to.filter(|to| self.chain.dexes.iter().any(|dex| dex.router == *to)) {
+            self.decode_coverage.record(router, &tx.input, tx_hash, matches!(classified, ClassifiedTx::RouterSwap { .. }));
+        }
+        if let ClassifiedTx::Excluded { venue, kind, .. } = &classified {
+            self.exclusions.record(*venue, *kind, tx.value);
+            // Bridge fills aren't arbitraged, but say where funds are about to arrive
+            if let Some(inflow) = self.bridge_flow.decode_fill(&tx) {
+                self.on_bridge_inflow(inflow).await;
+            }
+            return Ok(());
+        }
+        // Bots' own swaps are bait or already arbitraged: tagged, never backrun
+        if !matches!(classified, ClassifiedTx::OracleUpdate { .. }) {
+            if let Some(bot) = self.counterparties.bot_in(&tx) {
+                debug!(?bot, kind = classified.kind(), "Skipping known bot tx {:?}", tx_hash);
+                if let Some(storage) = &self.storage {
+                    if let Err(e) = storage.record_bot_activity(&tx, bot, classified.kind(), self.opportunities.head()).await {
+                        warn!("Recording bot activity for {:?} failed: {:?}", tx_hash, e);
+                    }
+                }
+                return Ok(());
+            }
+        }
+        // Oracle updates are applied however late; only opportunity search is cut short
+        let within_budget = self.latency.mark(&mut latency, Stage::Decoded);
+        // Curve pools are called directly, so this runs whatever the classification
+        if self.strategy_active(self.chain.strategies.stable_arb, "stable") && within_budget.is_ok() {
+            self.backrun_curve_swap(&tx, &latency).await;
+        }
+        match &classified {
+            // Oracle updates re-price paths before any later swap is analyzed
+            ClassifiedTx::OracleUpdate { .. } => {
+                if let Some(update) = self.oracle_monitor.process_transaction(&tx).await? {
+                    self.simulation_engine.apply_oracle_update(update).await;
+                }
+            }
+            ClassifiedTx::RouterSwap { .. } | ClassifiedTx::AggregatorSwap { .. } if self.strategy_active(self.chain.strategies.mempool_arbitrage, "mempool") => {
+                if let Err(reason) = within_budget {
+                    debug!("Skipping {:?}: {}", tx_hash, reason);
+                    return Ok(());
+                }
+                if self.strategy_active(self.chain.strategies.jit, "jit") && matches!(classified, ClassifiedTx::RouterSwap { dex: "UniswapV3", .. }) {
+                    if let Err(e) = self.jit_victim(&tx).await {
+                        warn!("JIT around {:?} failed: {:?}", tx_hash, e);
+                    }
+                }
+
+                if self.strategy_active(self.chain.strategies.sandwich, "sandwich") && matches!(classified, ClassifiedTx::RouterSwap { .. }) {
+                    if let Err(e) = self.sandwich_victim(&tx).await {
+                        warn!("Sandwich detection around {:?} failed: {:?}", tx_hash, e);
+                    }
+                }
+
+                if let Some(opportunity) = self.analyze_arbitrage(&tx, &latency).await? {
+                    info!("New arbitrage opportunity found: {:?}", tx_hash);
+                    self.victims.watch(&tx, self.opportunities.head()).await;
+                    self.push_opportunity(opportunity, "mempool", Some(tx_hash)).await?;
+                }
+                self.trace_victim(&tx, &classified, &latency).await;
+            }
+            ClassifiedTx::Unclassified if self.simulate_unclassified && tx.to.is_some() && tx.input.len() >= 4 => {
+                if let Err(reason) = within_budget {
+                    debug!("Skipping {:?}: {}", tx_hash, reason);
+                    return Ok(());
+                }
+                self.inspect_unclassified(&tx, &latency).await;
+            }
+            ClassifiedTx::RouterSwap { .. }
+            | ClassifiedTx::AggregatorSwap { .. }
+            | ClassifiedTx::LendingAction { .. }
+            | ClassifiedTx::NftMint { .. }
+            | ClassifiedTx::Excluded { .. }
+            | ClassifiedTx::Unclassified => {
+                debug!("Skipping {:?}", classified);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn cycle_opportunity(&self, cycle: CycleOpportunity, latency: LatencyTrace) -> ArbitrageOpportunity {
+        let loans = vec![LoanLeg::new(cycle.path[0], cycle.amount_in)];
+        let (id, span) = ArbitrageOpportunity::open();
+        ArbitrageOpportunity {
+            id,
+            token0: cycle.path[0],
+            token1: cycle.path[1],
+            amount0: cycle.amount_in,
+            amount1: U256::zero(),
+            loan_source: self.loan_sources.select(&loans, cycle.path[1], &cycle.pools).await,
+            loans,
+            path: cycle.path,
+            amounts: cycle.amounts,
+            routers: cycle.routers,
+            pools: cycle.pools,
+            hops: cycle.hops,
+            expected_profit: cycle.expected_profit,
+            target_block: None,
+            simulation_result: None,
+            sensitivity: None,
+            latency,
+            span,
+        }
+    }
+
+    // JIT must land in the victim's block, so it is executed immediately
+    async fn jit_victim(&self, tx: &Transaction) -> Result<()> {
+        let jit = match self.jit_strategy.analyze(tx).await? {
+            Some(jit) => jit,
+            None => return Ok(()),
+        };
+        if self.shadow.is_some() {
+            info!("Shadow mode: would submit JIT bundle around {:?}", tx.hash);
+            return Ok(());
+        }
+        let signer = match &self.signer {
+            Some(signer) => signer,
+            None => {
+                debug!("Skipping JIT around {:?}: no signer for the bundle", tx.hash);
+                return Ok(());
+            }
+        };
+
+        let id = format!("jit-{:?}", tx.hash);
+        let target_block = self.opportunities.head() + 1;
+        // Capital parked in the range, in whichever token funds it
+        let notional = if jit.amount0_max.is_zero() { (jit.token1, jit.amount1_max) } else { (jit.token0, jit.amount0_max) };
+        if !self.admit_bundle("jit", &id, &[jit.pool], &[jit.token0, jit.token1], notional, target_block).await? {
+            self.release_execution(&id).await;
+            return Ok(());
+        }
+        let sent = match self.executors.select_ordered("jit") {
+            Ok(executor) => self.jit_strategy.execute(&jit, &self.simulation_engine, signer.address(), executor).await,
+            Err(e) => Err(e.into()),
+        };
+        if !matches!(sent, Ok(Some(_))) {
+            self.release_execution(&id).await;
+        }
+        sent.map(|_| ())
+    }
+
+    // A sandwich must land in the victim's block, so it is sent immediately.
+    // Earlier swaps this block going the same way join it as co-victims; the
+    // wider sandwich replaces the pool's earlier one, which shares its nonces
+    async fn sandwich_victim(&self, tx: &Transaction) -> Result<()> {
+        let sandwich = match &self.sandwich {
+            Some(sandwich) if AdvancedArbitrage::is_candidate(tx) => sandwich,
+            _ => return Ok(()),
+        };
+        let victims: Vec<Transaction> = {
+            let mut candidates = self.sandwich_candidates.lock().await;
+            if candidates.len() < MAX_SANDWICH_CANDIDATES {
+                candidates.push(tx.clone());
+            }
+            candidates.iter().filter(|c| c.hash == tx.hash || AdvancedArbitrage::same_direction(c, tx)).cloned().collect()
+        };
+
+        for opportunity in sandwich.detect_sandwich_opportunities(victims).await? {
+            if !opportunity.victims.iter().any(|victim| victim.hash == tx.hash) {
+                continue;
+            }
+            if self.shadow.is_some() {
+                info!("Shadow mode: would submit sandwich around {:?} on {:?}", tx.hash, opportunity.pool.address);
+                continue;
+            }
+            let id = format!("sandwich-{:?}", opportunity.pool.address);
+            self.release_execution(&id).await;
+            match self.send_sandwich(sandwich, &opportunity, &id).await {
+                Ok(true) => {}
+                Ok(false) => self.release_execution(&id).await,
+                Err(e) => {
+                    warn!("Sandwich around {:?} failed: {:?}", tx.hash, e);
+                    self.release_execution(&id).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Whether the sandwich went out; what it reserved stays held if so
+    async fn send_sandwich(&self, sandwich: &AdvancedArbitrage, opportunity: &SandwichOpportunity, id: &str) -> Result<bool> {
+        let target_block = self.opportunities.head() + 1;
+        let pools = [opportunity.pool.address];
+        let notional = (opportunity.path[0], opportunity.frontrun_amount);
+        if !self.admit_bundle("sandwich", id, &pools, &opportunity.path, notional, target_block).await? {
+            return Ok(false);
+        }
+        let executor = self.executors.select_ordered("sandwich")?;
+        Ok(sandwich.execute_sandwich_attack(opportunity, executor, target_block).await?.is_some())
+    }
+
+    // The checks `prepare_execution` puts queued opportunities through, for a
+    // bundle sent straight from intake: the kill switch and breakers, exposure
+    // limits and the strategy's in-flight slots. False when refused; whatever
+    // was reserved is freed with `release_execution`
+    async fn admit_bundle(
+        &self,
+        strategy: &str,
+        id: &str,
+        pools: &[Address],
+        tokens: &[Address],
+        (token, amount): (Address, U256),
+        target_block: U64,
+    ) -> Result<bool> {
+        if let Err(rejected) = self.risk_manager.allow_submission().await {
+            warn!("Not submitting {}: {}", id, rejected);
+            return Ok(false);
+        }
+        // Unpriced notional (no USD feed on this chain) isn't held against the limits
+        let notional_usd = self.prices.usd_value(token, amount).await?.unwrap_or_default();
+        if let Err(reason) = self.risk_manager.reserve_exposure(id, pools, tokens, notional_usd, target_block).await {
+            warn!("Exposure limit on {}: {}", id, reason);
+            return Ok(false);
+        }
+        if let Err(reason) = self.inflight.reserve(strategy, id, pools, target_block) {
+            debug!("Not submitting {}: {}", id, reason);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    // Selector decoding only sees the router entrypoint; the call tree shows
+    // every pool the swap actually moves, so backruns are sized on exact deltas
+    async fn trace_victim(&self, tx: &Transaction, classified: &ClassifiedTx, latency: &LatencyTrace) {
+        let tracer = match &self.tracer {
+            Some(tracer) => tracer,
+            None => return,
+        };
+        // Recipients of bridged funds are traced whatever the swap's size
+        if !tracer.should_trace(tx, classified) && self.bridge_flow.inflow_to(tx.from).await.is_none() {
+            return;
+        }
+        let head = self.opportunities.head();
+        let trace = match tracer.trace(tx, head).await {
+            Ok(Some(trace)) => trace,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("debug_traceCall failed for {:?}: {:?}", tx.hash, e);
+                return;
+            }
+        };
+
+        self.backrun_deltas(tx, &trace.deltas, "trace", latency).await;
+    }
+
+    // Unknown routers and protocols: whatever V2-layout pool the tx writes new
+    // reserves to is a pool it swaps on, no calldata decoder needed
+    async fn inspect_unclassified(&self, tx: &Transaction, latency: &LatencyTrace) {
+        let touched = match self.simulation_engine.touched_state(tx).await {
+            Ok(touched) => touched,
+            Err(e) => {
+                debug!("Simulating unclassified {:?} failed: {:?}", tx.hash, e);
+                return;
+            }
+        };
+
+        let mut deltas = Vec::new();
+        for pool in touched.touched_pools() {
+            let (state, (reserve0, reserve1)) = match (self.pool_state.get(pool.address).await, touched.v2_reserves(pool.address)) {
+                (Some(state), Some(reserves)) => (state, reserves),
+                _ => continue,
+            };
+            deltas.push(PoolDelta {
+                pool: pool.address,
+                amount0: I256::from_raw(reserve0) - I256::from_raw(state.reserve0),
+                amount1: I256::from_raw(reserve1) - I256::from_raw(state.reserve1),
+            });
+        }
+        if !deltas.is_empty() {
+            debug!("Unclassified {:?} swaps on {} tracked pools", tx.hash, deltas.len());
+            self.backrun_deltas(tx, &deltas, "inspector", latency).await;
+        }
+    }
+
+    async fn backrun_curve_swap(&self, tx: &Transaction, latency: &LatencyTrace) {
+        let head = self.opportunities.head();
+        let cycles = self.stable_arb.after_pending(tx, head).await;
+        if cycles.is_empty() {
+            return;
+        }
+        self.victims.watch(tx, head).await;
+        for cycle in cycles {
+            let opportunity = self.cycle_opportunity(cycle, latency.clone()).await;
+            if let Err(e) = self.push_opportunity(opportunity, "stable_arb_mempool", Some(tx.hash)).await {
+                warn!("Failed to queue stable arb backrun of {:?}: {:?}", tx.hash, e);
+            }
+        }
+    }
+
+    async fn backrun_deltas(&self, tx: &Transaction, deltas: &[PoolDelta], source: &str, latency: &LatencyTrace) {
+        let head = self.opportunities.head();
+        let merged = self.backrun_merger.merge(tx.hash, deltas).await;
+        let cycles = self.triangular_scanner.after_victim(&merged.deltas, head).await;
+        if cycles.is_empty() {
+            return;
+        }
+        // The merged backrun covers the earlier victims' moves too
+        for victim in &merged.superseded {
+            self.opportunities.cancel_victim(*victim);
+        }
+        self.victims.watch(tx, head).await;
+        for cycle in cycles {
+            let opportunity = self.cycle_opportunity(cycle, latency.clone()).await;
+            if let Err(e) = self.push_opportunity(opportunity, source, Some(tx.hash)).await {
+                warn!("Failed to queue {} backrun of {:?}: {:?}", source, tx.hash, e);
+            }
+        }
+    }
+
+    #[instrument(name = "queue", parent = &opportunity.span, skip_all, fields(source = source))]
+    async fn push_opportunity(&self, mut opportunity: ArbitrageOpportunity, source: &str, victim_tx: Option<H256>) -> Result<()> {
+        if let Err(reason) = self.latency.mark(&mut opportunity.latency, Stage::Simulated) {
+            debug!("Not queueing: {}", reason);
+            return Ok(());
+        }
+        // Inventory mode skips the loan when the free balance covers it
+        if self.inventory.trades(source) {
+            if let [leg] = opportunity.loans[..] {
+                if self.inventory.available(leg.token).await >= leg.amount {
+                    opportunity.loan_source = LoanSource::Inventory;
+                }
+            }
+        }
+
+        // Execution gas at the next block's base fee. The oracle is fed by block
+        // events, so queueing costs no RPC round trip and the executor wakes as
+        // soon as the opportunity is found
+        let gas_price = match self.gas_oracle.next_base_fee() {
+            fee if fee.is_zero() => self.provider.get_gas_price().await?,
+            fee => fee,
+        };
+        let gas_cost = gas_price * U256::from(EXECUTION_GAS_LIMIT);
+        // Stress-priced once here, so the record and the risk check see the same figures
+        let gas_in_token = self.gas_in_token(opportunity.token0, gas_cost).await?.unwrap_or_default();
+        let sensitivity = Sensitivity::compute(opportunity.amount0, opportunity.expected_profit, gas_in_token, self.sensitivity_move_bps);
+        opportunity.sensitivity = Some(sensitivity);
+
+        if let Some(storage) = &self.storage {
+            let simulation = opportunity.simulation_result.as_ref();
+            storage.record_opportunity(&OpportunityRecord {
+                id: opportunity.id.clone(),
+                source: source.to_string(),
+                victim_tx,
+                path: opportunity.path.clone(),
+                routers: opportunity.routers.clone(),
+                pools: opportunity.pools.clone(),
+                amount_in: opportunity.amount0,
+                expected_profit: opportunity.expected_profit,
+                price_impact: simulation.map(|s| s.price_impact),
+                gas_estimate: simulation.map(|s| s.gas_estimate),
+                success_probability: simulation.map(|s| s.success_probability),
+            }).await?;
+            if let Some(simulation) = simulation {
+                storage.record_confidence_features(&opportunity.id, &simulation.confidence).await?;
+            }
+            storage.record_sensitivity(&opportunity.id, &sensitivity).await?;
+        }
+
+        self.events.opportunities.publish(OpportunityFound {
+            id: opportunity.id.clone(),
+            source: source.to_string(),
+            victim_tx,
+            path: opportunity.path.clone(),
+            pools: opportunity.pools.clone(),
+            amount_in: opportunity.amount0,
+            expected_profit: opportunity.expected_profit,
+            simulation: opportunity.simulation_result.clone(),
+        });
+
+        // Ordered by profit net of that gas, both in token0 units
+        let net_profit = opportunity.expected_profit.saturating_sub(gas_in_token);
+        let target_block = *opportunity.target_block.get_or_insert(self.opportunities.head() + 1);
+        self.opportunities.push(opportunity, net_profit, target_block, victim_tx);
+        Ok(())
+    }
+
+    async fn analyze_arbitrage(&self, tx: &Transaction, latency: &LatencyTrace) -> Result<Option<ArbitrageOpportunity>> {
+        // Use advanced simulation engine
+        let simulation_result = self.simulation_engine
+            .simulate_multi_dex_arbitrage(tx, 3)
+            .await?;
+
+        if simulation_result.expected_profit > U256::from(10).pow(15.into()) {
+            let amount_in = U256::from(10).pow(18.into());
+            let path = simulation_result.optimal_path.clone();
+            let loans = vec![LoanLeg::new(path[0], amount_in)];
+            let (id, span) = ArbitrageOpportunity::open();
+            return Ok(Some(ArbitrageOpportunity {
+                id,
+                token0: path[0],
+                token1: path[1],
+                amount0: amount_in,
+                amount1: U256::zero(),
+                loan_source: self.loan_sources.select(&loans, path[1], &[]).await,
+                loans,
+                routers: self.get_routers_for_path(&path).await?,
+                amounts: vec![amount_in],
+                path,
+                pools: Vec::new(),
+                hops: Vec::new(),
+                expected_profit: simulation_result.expected_profit,
+                target_block: None,
+                simulation_result: Some(simulation_result),
+                sensitivity: None,
+                latency: latency.clone(),
+                span,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    async fn get_routers_for_path(&self, path: &[Address]) -> Result<Vec<Address>> {
+        // Mock implementation, would require a more complex lookup
+        Ok(vec![
+            Address::from_str("0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff")?, // QuickSwap
+            Address::from_str("0xE592427A0AEce92De3Edee1F18E0157C05861564")?, // Uniswap V3
+            Address::from_str("0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506")?, //SushiSwap
+        ])
+    }
+
+    /// Drains the opportunity queue into batches and submits them. Parks on the
+    /// queue rather than polling: every push wakes it, so an opportunity goes
+    /// out in the same block it was found.
+    pub async fn start_execution(&self) -> Result<()> {
+        loop {
+            // Independent arbs for the same block go out together instead of
+            // competing with each other for it
+            let batch = tokio::select! {
+                _ = self.shutdown.wait() => return Ok(()),
+                batch = self.opportunities.next_batch(self.max_batch_legs, ArbitrageOpportunity::conflicts_with) => batch,
+            };
+            let _in_flight = self.in_flight.enter();
+            self.execute_batch(&batch).await;
+        }
+    }
+
+    // Frees what a settled (or never sent) execution held: exposure, its
+    // in-flight slot and any inventory
+    async fn release_execution(&self, id: &str) {
+        self.risk_manager.release_exposure(id).await;
+        self.inflight.release(id);
+        self.inventory.release(id).await;
+    }
+
+    async fn record_execution_error(&self, opportunity: &ArbitrageOpportunity, e: &anyhow::Error) {
+        let class = errors::classify(e);
+        match class {
+            ErrorClass::Rejected => info!(class = class.as_str(), "Execution rejected: {:#}", e),
+            ErrorClass::Retryable => warn!(class = class.as_str(), "Execution error: {:?}", e),
+            ErrorClass::Fatal => error!(class = class.as_str(), "Execution error: {:?}", e),
+        }
+        if let Some(failure) = errors::execution_failure(e) {
+            self.token_safety.record_failure(self.chain.chain_id, &self.unlisted_tokens(opportunity), failure);
+            if let Some(storage) = &self.storage {
+                if let Err(e) = storage.record_failure(&opportunity.id, failure).await {
+                    warn!("Failed to record execution failure: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// Settles FastLane bundles as their target blocks pass: PnL for included
+    /// ones, and exposure freed for the rest without waiting out the reservation.
+    pub async fn start_settlement(&self) -> Result<()> {
+        let mut blocks = self.events.blocks.subscribe();
+        loop {
+            let block = tokio::select! {
+                _ = self.shutdown.wait() => return Ok(()),
+                block = blocks.recv() => match block {
+                    Ok(block) => block,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            };
+            let settled = match self.settlement.on_block(block.number).await {
+                Ok(settled) => settled,
+                Err(e) => {
+                    warn!("Settlement check failed for {}: {:?}", block.number, e);
+                    continue;
+                }
+            };
+            let _in_flight = self.in_flight.enter();
+            for settlement in settled {
+                self.settle(settlement).await;
+            }
+        }
+    }
+
+    async fn settle(&self, settlement: Settlement) {
+        let bundle_hash = settlement.bundle_hash;
+        if let (BundleStatus::Included, Some((tx_hash, block))) = (settlement.status, settlement.execution) {
+            info!(bundle = ?bundle_hash, tx = ?tx_hash, %block, "FastLane bundle included");
+            for opportunity in &settlement.opportunities {
+                self.events.bundles_landed.publish(BundleLanded {
+                    opportunity_id: opportunity.id.clone(),
+                    hash: bundle_hash,
+                    block,
+                });
+                if let Err(e) = self.record_realized_pnl(opportunity, tx_hash).await {
+                    warn!(id = %opportunity.id, "Failed to record realized PnL: {:?}", e);
+                    self.release_execution(&opportunity.id).await;
+                }
+            }
+            return;
+        }
+
+        info!(bundle = ?bundle_hash, status = ?settlement.status, "FastLane bundle not included");
+        for opportunity in &settlement.opportunities {
+            self.release_execution(&opportunity.id).await;
+        }
+        let status = match settlement.status {
+            BundleStatus::Timeout => InclusionStatus::Timeout,
+            _ => InclusionStatus::Failed,
+        };
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.update_inclusion(bundle_hash, status, None).await {
+                warn!("Failed to record bundle status: {:?}", e);
+            }
+        }
+    }
+
+    /// Converts and sweeps profit off the executor every `interval_blocks`.
+    pub async fn start_treasury(&self) -> Result<()> {
+        let treasury = match &self.treasury {
+            Some(treasury) => treasury,
+            None => return Ok(()),
+        };
+        let mut blocks = self.events.blocks.subscribe();
+        loop {
+            let block = tokio::select! {
+                _ = self.shutdown.wait() => return Ok(()),
+                block = blocks.recv() => match block {
+                    Ok(block) => block,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            };
+            if block.number.as_u64() % treasury.interval_blocks() != 0 {
+                continue;
+            }
+            let _in_flight = self.in_flight.enter();
+            // Profit can be left in any token the universe admitted
+            let tokens = self.universe.active().await;
+            if let Err(e) = treasury.run(&tokens, &self.gas_oracle, &self.inventory).await {
+                warn!("Treasury run failed: {:?}", e);
+            }
+        }
+    }
+
+    /// Warm start: pools, ticks, token metadata and the bidding model from the
+    /// last run, plus executions that were still pending when it shut down.
+    pub async fn restore_state(&self) {
+        if let Some(snapshot) = self.state.load::<pool_state::PoolRegistrySnapshot>(POOLS_SNAPSHOT) {
+            info!("Restoring {} V2 and {} V3 pools", snapshot.pools.len(), snapshot.v3_pools.len());
+            let restored = match self.provider.get_block_number().await {
+                Ok(head) => self.pool_state.restore(snapshot, head).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = restored {
+                warn!("Failed to restore pool registry, rebuilding: {:?}", e);
+            }
+        }
+        if let Some(tokens) = self.state.load(TOKENS_SNAPSHOT) {
+            self.tokens.restore(tokens).await;
+        }
+        if let Some(model) = self.state.load(BIDDING_SNAPSHOT) {
+            *self.competition.model().lock().await = model;
+        }
+        if let Some(weights) = self.state.load(CONFIDENCE_SNAPSHOT) {
+            self.confidence.restore(weights);
+        }
+        if let Some(bots) = self.state.load(COUNTERPARTIES_SNAPSHOT) {
+            self.counterparties.restore(bots);
+        }
+
+        let entries = self.state.load::<Vec<tx_tracker::TrackedTx>>(TRACKED_TXS_SNAPSHOT);
+        if let (Some(tracker), Some(entries)) = (&self.tx_tracker, entries) {
+            info!("Restoring {} tracked executions", entries.len());
+            tracker.restore(entries).await;
+        }
+    }
+
+    /// Stops intake, lets in-flight work finish (up to DRAIN_TIMEOUT), drops
+    /// queued opportunities that were never submitted, then persists and flushes.
+    pub async fn shutdown(&self) -> Result<()> {
+        info!("Shutting down, draining {} in-flight tasks", self.in_flight.count());
+        self.shutdown.trigger();
+        if !self.in_flight.drain(DRAIN_TIMEOUT).await {
+            warn!("{} tasks still running after {:?}, abandoning them", self.in_flight.count(), DRAIN_TIMEOUT);
+        }
+
+        let unsubmitted = self.opportunities.len();
+        self.opportunities.clear();
+        info!("Cancelled {} unsubmitted opportunities", unsubmitted);
+
+        self.state.save(POOLS_SNAPSHOT, &self.pool_state.registry_snapshot().await)?;
+        self.state.save(TOKENS_SNAPSHOT, &self.tokens.snapshot().await)?;
+        self.state.save(BIDDING_SNAPSHOT, &*self.competition.model().lock().await)?;
+        self.state.save(CONFIDENCE_SNAPSHOT, &self.confidence.snapshot())?;
+        self.state.save(COUNTERPARTIES_SNAPSHOT, &self.counterparties.snapshot())?;
+        if let Some(tracker) = &self.tx_tracker {
+            self.state.save(TRACKED_TXS_SNAPSHOT, &tracker.tracked().await)?;
+        }
+        if let Some(recorder) = &self.recorder {
+            recorder.finish()?;
+        }
+        if let Some(storage) = &self.storage {
+            storage.close().await;
+        }
+        info!("Shutdown complete");
+        Ok(())
+    }
+
+    // Runs one opportunity through every check up to submission, reserving its
+    // exposure. None when it is dropped; the reason is already recorded.
+    // Everything logged below carries the opportunity ID
+    #[instrument(name = "prepare", parent = &opportunity.span, skip_all)]
+    async fn prepare_execution(&self, opportunity: &ArbitrageOpportunity) -> Result<Option<PreparedExecution>> {
+        if let Err(rejected) = self.risk_manager.allow_submission().await {
+            warn!("{}", rejected);
+            if let Some(storage) = &self.storage {
+                storage.record_decision(&opportunity.id, false, &rejected.to_string()).await?;
+            }
+            return Ok(None);
+        }
+
+        let (execute, reason) = self.should_execute(opportunity).await?;
+        let profit = self.tokens.format(opportunity.token0, opportunity.expected_profit).await;
+        info!(execute, expected_profit = %profit, reason = %reason, "Execution decision");
+        if let Some(storage) = &self.storage {
+            storage.record_decision(&opportunity.id, execute, &reason).await?;
+        }
+
+        if execute {
+            if let Some(Err(rejected)) = opportunity.sensitivity.as_ref().map(|s| self.risk_manager.check_sensitivity(s)) {
+                debug!("Not submitting {}: {}", opportunity.id, rejected);
+                if let Some(storage) = &self.storage {
+                    storage.record_decision(&opportunity.id, false, &rejected.to_string()).await?;
+                }
+                return Ok(None);
+            }
+
+            // The next block is what every venue targets; skip when we can't reach it
+            let started = Instant::now();
+            let timing = match self.block_timing.check_window() {
+                Ok(timing) => timing,
+                Err(reason) => {
+                    debug!("{}", reason);
+                    if let Some(storage) = &self.storage {
+                        storage.record_decision(&opportunity.id, false, &reason).await?;
+                    }
+                    return Ok(None);
+                }
+            };
+
+            // Use FastLane for execution
+            let base_fee = match self.gas_oracle.next_base_fee() {
+                fee if fee.is_zero() => self.provider.get_block(BlockNumber::Latest).await?
+                    .and_then(|b| b.base_fee_per_gas)
+                    .unwrap_or_default(),
+                fee => fee,
+            };
+            // The tip is paid in MATIC, so the bribe is sized off profit in wei;
+            // an unpriceable token0 bids nothing above the base fee
+            let profit_wei = self.native_value(opportunity.token0, opportunity.expected_profit).await?.unwrap_or_default();
+            let priority_fee = self.bid_strategy
+                .priority_fee("arbitrage", profit_wei, U256::from(EXECUTION_GAS_LIMIT))
+                .await;
+            let gas_price = base_fee + priority_fee;
+            if let Some(balances) = &self.balances {
+                if !balances.can_afford(gas_price * U256::from(EXECUTION_GAS_LIMIT)).await {
+                    warn!(%gas_price, "Signer gas balance at floor, not submitting");
+                    if let Some(storage) = &self.storage {
+                        storage.record_decision(&opportunity.id, false, "gas balance below floor").await?;
+                    }
+                    return Ok(None);
+                }
+            }
+            // Shadow mode may run without any executor, so a missing one errors later
+            let executor = self.executors.select("arbitrage");
+            let guard = match executor.as_ref().map(|e| e.route()) {
+                Some(SubmissionRoute::Public) => self.controls.public_guard(),
+                _ => self.controls.profit_guard(),
+            };
+            let bundle = self.fastlane_client
+                .create_arbitrage_bundle(opportunity, gas_price, &guard)
+                .await?;
+            let target_block = bundle.target_block;
+            info!(
+                %target_block,
+                loan_source = opportunity.loan_source.name(),
+                time_to_next_block_ms = timing.time_to_next_block.as_millis() as u64,
+                proposer = ?timing.proposer,
+                "Bundle built"
+            );
+            let mut latency = opportunity.latency.clone();
+            if let Err(reason) = self.latency.mark(&mut latency, Stage::Built) {
+                debug!("{}", reason);
+                if let Some(storage) = &self.storage {
+                    storage.record_decision(&opportunity.id, false, &reason).await?;
+                }
+                return Ok(None);
+            }
+
+            // Last check before anything is sent: the exact calldata must leave the
+            // executor's owner with more of the start token than it had
+            let caller = self.signer.as_ref().map(|s| s.address()).unwrap_or(self.fastlane_client.fastlane_contract());
+            let preflight = self.simulation_engine
+                .preflight_execution(
+                    caller,
+                    self.fastlane_client.solver_contract(),
+                    bundle.data.clone(),
+                    opportunity.token0,
+                    target_block - 1,
+                )
+                .await?;
+            let delta = preflight.delta;
+            debug!(gas_used = preflight.capture.gas_used, swaps = preflight.capture.swaps().len(), "Preflight executed");
+            if delta <= I256::zero() {
+                warn!(%delta, "Preflight shows no profit, aborting");
+                if let Some(storage) = &self.storage {
+                    storage.record_decision(&opportunity.id, false, &format!("preflight delta {}", delta)).await?;
+                }
+                return Ok(None);
+            }
+
+            // Relay bundles drop a reverting tx; every other venue can land it reverted
+            let revertible = executor.as_ref().map_or(true, |e| !matches!(e.route(), SubmissionRoute::Bundle));
+            let primary = opportunity.loans.first().copied().unwrap_or(LoanLeg::new(opportunity.token0, opportunity.amount0));
+            let bound = preflight.loss_bound(gas_price, EXECUTION_GAS_LIMIT, revertible, opportunity.loan_source.fee_on(primary.amount));
+            if let Some((revert_loss_usd, land_loss_usd)) = self.loss_bound_usd(opportunity.token0, &bound).await? {
+                let worst_usd = revert_loss_usd.max(land_loss_usd);
+                let max_loss_usd = self.controls.usd_policy().max_loss_usd;
+                let accepted = worst_usd <= max_loss_usd;
+                info!(revert_loss_usd, land_loss_usd, loan_fee = %bound.loan_fee, accepted, "Worst-case loss ${:.2}", worst_usd);
+                if let Some(storage) = &self.storage {
+                    storage.record_loss_bound(&opportunity.id, revert_loss_usd, land_loss_usd, max_loss_usd, accepted).await?;
+                }
+                if !accepted {
+                    let reason = format!("worst case ${:.2} above max loss ${:.2}", worst_usd, max_loss_usd);
+                    warn!("{}", reason);
+                    if let Some(storage) = &self.storage {
+                        storage.record_decision(&opportunity.id, false, &reason).await?;
+                    }
+                    return Ok(None);
+                }
+            } else {
+                debug!("No USD prices, loss bound not checked");
+            }
+
+            // Unpriced notional (no USD feed on this chain) isn't held against the limits
+            let notional_usd = self.prices.usd_value(opportunity.token0, opportunity.amount0).await?.unwrap_or_default();
+            if let Err(reason) = self.risk_manager
+                .reserve_exposure(&opportunity.id, &opportunity.pools, &opportunity.path, notional_usd, target_block)
+                .await
+            {
+                warn!("Exposure limit: {}", reason);
+                if let Some(storage) = &self.storage {
+                    storage.record_decision(&opportunity.id, false, &format!("exposure limit: {}", reason)).await?;
+                }
+                return Ok(None);
+            }
+            if let Err(reason) = self.inflight.reserve("arbitrage", &opportunity.id, &opportunity.pools, target_block) {
+                debug!("Not submitting {}: {}", opportunity.id, reason);
+                self.release_execution(&opportunity.id).await;
+                if let Some(storage) = &self.storage {
+                    storage.record_decision(&opportunity.id, false, &format!("in flight: {}", reason)).await?;
+                }
+                return Ok(None);
+            }
+            if opportunity.loan_source == LoanSource::Inventory {
+                // Another execution may have taken the balance since this was queued
+                if let Err(reason) = self.inventory.reserve(&opportunity.id, primary, target_block).await {
+                    debug!("Not submitting {}: {}", opportunity.id, reason);
+                    self.release_execution(&opportunity.id).await;
+                    if let Some(storage) = &self.storage {
+                        storage.record_decision(&opportunity.id, false, &format!("inventory: {}", reason)).await?;
+                    }
+                    return Ok(None);
+                }
+            }
+
+            return Ok(Some(PreparedExecution {
+                bundle,
+                gas_price,
+                priority_fee,
+                latency,
+                started,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Prepares each opportunity and submits the survivors in one transaction:
+    /// a lone leg as before, several through the executor's `executeBatch`. The
+    /// queue only batches opportunities with disjoint pools for the same block.
+    async fn execute_batch(&self, batch: &[ArbitrageOpportunity]) {
+        let mut legs = Vec::new();
+        for opportunity in batch {
+            match self.prepare_execution(opportunity).await {
+                Ok(Some(prepared)) => legs.push((opportunity, prepared)),
+                Ok(None) => {}
+                Err(e) => self.record_execution_error(opportunity, &e).await,
+            }
+        }
+        if legs.is_empty() {
+            return;
+        }
+        if legs.len() > 1 {
+            info!(legs = legs.len(), "Batching independent opportunities");
+        }
+        if let Err(e) = self.submit_execution(&legs).await {
+            // A retryable failure may still have reached the venue, so its
+            // exposure is held to the target block; anything else never left
+            let sent = errors::classify(&e) == ErrorClass::Retryable;
+            for (opportunity, _) in &legs {
+                self.record_execution_error(opportunity, &e).await;
+                if !sent {
+                    self.release_execution(&opportunity.id).await;
+                }
+            }
+        }
+    }
+
+    #[instrument(name = "submission", skip_all, fields(ids = ?legs.iter().map(|(o, _)| &o.id).collect::<Vec<_>>()))]
+    async fn submit_execution(&self, legs: &[(&ArbitrageOpportunity, PreparedExecution)]) -> Result<()> {
+        let bundle = match legs {
+            [(_, prepared)] => prepared.bundle.clone(),
+            _ => self.fastlane_client.batch_bundle(&legs.iter().map(|(_, p)| p.bundle.clone()).collect::<Vec<_>>())?,
+        };
+        let target_block = bundle.target_block;
+        // One tx carries every leg, so it is priced for the highest bid among them
+        let gas_price = legs.iter().map(|(_, p)| p.gas_price).max().unwrap_or_default();
+        let priority_fee = legs.iter().map(|(_, p)| p.priority_fee).max().unwrap_or_default();
+        // Legs profit in their own token0; venues weigh the total against gas, in wei
+        let mut expected_profit = U256::zero();
+        for (opportunity, _) in legs {
+            // should_execute already turned down profits it couldn't price
+            let native = self.native_value(opportunity.token0, opportunity.expected_profit).await?.unwrap_or_default();
+            expected_profit = expected_profit.saturating_add(native);
+        }
+        let started = legs.iter().map(|(_, p)| p.started).min().unwrap_or_else(Instant::now);
+
+        if let Some(shadow) = &self.shadow {
+            for (opportunity, _) in legs {
+                shadow.record(ShadowEntry {
+                    opportunity_id: opportunity.id.clone(),
+                    path: opportunity.path.clone(),
+                    routers: opportunity.routers.clone(),
+                    expected_profit: opportunity.expected_profit,
+                    target_block,
+                    calldata: bundle.data.clone(),
+                }).await?;
+            }
+            return Ok(());
+        }
+
+        let executor = self.executors.select("arbitrage")?;
+        let venue = executor.name();
+        let bundle_hash = executor.submit(&ExecutionRequest {
+            bundle,
+            gas_price,
+            expected_profit,
+        }).await?;
+        self.block_timing.record_latency(started.elapsed());
+        if venue == "fastlane" {
+            let deadline_block = target_block + self.controls.profit_guard().deadline_blocks;
+            let opportunities = legs.iter().map(|(o, _)| (*o).clone()).collect();
+            self.settlement.watch(bundle_hash, opportunities, target_block, deadline_block).await;
+        }
+
+        for (opportunity, prepared) in legs {
+            // Already sent, so an overrun here is only reported
+            let mut latency = prepared.latency.clone();
+            if let Err(reason) = self.latency.mark(&mut latency, Stage::Submitted) {
+                debug!("{}", reason);
+            }
+            info!(id = %opportunity.id, total_ms = latency.elapsed().as_millis() as u64, stages = ?latency.stages(), "Submitted");
+
+            if let Some(storage) = &self.storage {
+                storage.record_submission(&opportunity.id, venue, bundle_hash, target_block, gas_price).await?;
+            }
+            self.events.bundles_submitted.publish(BundleSubmitted {
+                opportunity_id: opportunity.id.clone(),
+                venue,
+                hash: bundle_hash,
+                target_block,
+                gas_price,
+            });
+
+            self.competition.watch(WatchedSubmission {
+                opportunity_id: opportunity.id.clone(),
+                strategy: "arbitrage".to_string(),
+                path: opportunity.path.clone(),
+                routers: opportunity.routers.clone(),
+                submission_hash: bundle_hash,
+                executor: self.fastlane_client.solver_contract(),
+                target_block,
+                priority_fee,
+            }).await;
+        }
+        Ok(())
+    }
+
+    /// Bids on the user operations the Atlas relay streams, when one leaves an
+    /// arbitrage behind it.
+    pub async fn start_atlas(&self) -> Result<()> {
+        let atlas = match &self.atlas {
+            Some(atlas) if atlas.streams_user_ops() => atlas,
+            _ => return Ok(()),
+        };
+        let (sink, mut user_ops) = mpsc::channel(256);
+        let bids = async {
+            while let Some(op) = user_ops.recv().await {
+                if let Err(e) = self.on_user_op(&op).await {
+                    warn!("Atlas bid on {:?} failed: {:?}", op.user_op_hash, e);
+                }
+            }
+        };
+        tokio::select! {
+            _ = self.shutdown.wait() => {}
+            _ = atlas.run_user_ops(sink) => {}
+            _ = bids => {}
+        }
+        Ok(())
+    }
+
+    // The user's call is analyzed like a pending swap; the solver op backruns it
+    async fn on_user_op(&self, op: &UserOpNotice) -> Result<()> {
+        let latency = self.latency.start();
+        if let Some(opportunity) = self.analyze_arbitrage(&op.as_transaction(), &latency).await? {
+            self.bid_on_user_op(op.user_op_hash, op.control, &opportunity).await?;
+        }
+        Ok(())
+    }
+
+    /// Bids on an Atlas user operation with a solver op that executes `opportunity`.
+    /// The bid is a share of the simulated profit.
+    #[instrument(name = "atlas_bid", parent = &opportunity.span, skip_all)]
+    pub async fn bid_on_user_op(&self, user_op_hash: H256, control: Address, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        let atlas = match &self.atlas {
+            Some(atlas) => atlas,
+            None => return Ok(()),
+        };
+
+        let gas_price = self.provider.get_gas_price().await?;
+        let bundle = self.fastlane_client
+            .create_arbitrage_bundle(opportunity, gas_price, &self.controls.profit_guard())
+            .await?;
+        let block = self.provider.get_block_number().await?;
+        let op = atlas
+            .build_solver_op(user_op_hash, control, bundle.data, opportunity.expected_profit, gas_price, block)
+            .await?;
+
+        if self.shadow.is_some() {
+            info!(bid = %op.bid_amount, "Shadow mode: not submitting Atlas solver op");
+            return Ok(());
+        }
+        atlas.submit(&op).await?;
+
+        if let Some(storage) = &self.storage {
+            storage.record_submission(&opportunity.id, "atlas", user_op_hash, bundle.target_block, gas_price).await?;
+        }
+        Ok(())
+    }
+
+    // (revert, land) losses in USD; None when gas or the loan token can't be priced
+    async fn loss_bound_usd(&self, token: Address, bound: &LossBound) -> Result<Option<(f64, f64)>> {
+        let (native_usd, token_usd) = match (
+            self.prices.usd_price(self.chain.wrapped_native).await?,
+            self.prices.usd_price(token).await?,
+        ) {
+            (Some(n), Some(t)) => (n, t),
+            _ => return Ok(None),
+        };
+        let gas_usd = |cost: U256| units(I256::from_raw(cost), 18) * native_usd;
+        let land_delta_usd = self.tokens.to_units(token, bound.land_delta).await? * token_usd;
+        Ok(Some((gas_usd(bound.revert_gas_cost), gas_usd(bound.land_gas_cost) - land_delta_usd)))
+    }
+
+    // Gas cost in `token`'s raw units, so it nets against a profit in that
+    // token; None without prices for both
+    async fn gas_in_token(&self, token: Address, gas_cost: U256) -> Result<Option<U256>> {
+        if token == self.chain.wrapped_native {
+            return Ok(Some(gas_cost));
+        }
+        let (native_usd, token_usd) = match (
+            self.prices.usd_price(self.chain.wrapped_native).await?,
+            self.prices.usd_price(token).await?,
+        ) {
+            (Some(n), Some(t)) if t > 0.0 => (n, t),
+            _ => return Ok(None),
+        };
+        let gas_usd = units(I256::from_raw(gas_cost), 18) * native_usd;
+        Ok(Some(self.tokens.from_units(token, gas_usd / token_usd).await?))
+    }
+
+    // `amount` of `token` in wei of the native token; the inverse of `gas_in_token`
+    async fn native_value(&self, token: Address, amount: U256) -> Result<Option<U256>> {
+        if token == self.chain.wrapped_native {
+            return Ok(Some(amount));
+        }
+        let (native_usd, value_usd) = match (
+            self.prices.usd_price(self.chain.wrapped_native).await?,
+            self.prices.usd_value(token, amount).await?,
+        ) {
+            (Some(n), Some(v)) if n > 0.0 => (n, v),
+            _ => return Ok(None),
+        };
+        Ok(Some(self.tokens.from_units(self.chain.wrapped_native, value_usd / native_usd).await?))
+    }
+
+    // Path tokens outside the chain's base set; only these can be struck as unsafe
+    fn unlisted_tokens(&self, opportunity: &ArbitrageOpportunity) -> Vec<Address> {
+        opportunity.path.iter().copied().filter(|t| !self.chain.tokens.contains(t)).collect()
+    }
+
+    // Profit and gas are in different tokens, so the policy is applied in USD
+    async fn should_execute(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, String)> {
+        if let Some(token) = opportunity.path.iter().find(|t| self.token_safety.is_unsafe(self.chain.chain_id, **t)) {
+            return Ok((false, format!("unsafe token {:?}", token)));
+        }
+        let gas_price = self.provider.get_gas_price().await?;
+        let gas_cost = gas_price * U256::from(EXECUTION_GAS_LIMIT);
+
+        let profit_usd = self.prices.usd_value(opportunity.token0, opportunity.expected_profit).await?;
+        let gas_usd = self.prices.usd_value(self.chain.wrapped_native, gas_cost).await?;
+        let (profit_usd, gas_usd) = match (profit_usd, gas_usd) {
+            (Some(p), Some(g)) => (p, g),
+            // Without prices only a profit in the wrapped native token can be compared to gas
+            _ if opportunity.token0 == self.chain.wrapped_native => {
+                let execute = opportunity.expected_profit > gas_cost;
+                let reason = if execute { "unpriced: profit above gas" } else { "unpriced: profit below gas" };
+                return Ok((execute, reason.to_string()));
+            }
+            _ => return Ok((false, "no USD price for profit token".to_string())),
+        };
+
+        let policy = self.controls.usd_policy();
+        let net_usd = profit_usd - gas_usd;
+        if net_usd < policy.min_profit_usd {
+            return Ok((false, format!("net ${:.2} below min ${:.2}", net_usd, policy.min_profit_usd)));
+        }
+        if let Some(at_risk_usd) = self.prices.usd_value(opportunity.token0, opportunity.amount0).await? {
+            if at_risk_usd > policy.max_at_risk_usd {
+                return Ok((false, format!("${:.0} at risk above max ${:.0}", at_risk_usd, policy.max_at_risk_usd)));
+            }
+        }
+        Ok((true, format!("net ${:.2} after ${:.2} gas", net_usd, gas_usd)))
+    }
+}
+
+impl EventSource for MempoolMonitor {
+    fn chain(&self) -> &'static str {
+        self.chain.name
+    }
+
+    fn events(&self) -> &EventBus {
+        &self.events
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiSource for MempoolMonitor {
+    fn chain(&self) -> &'static str {
+        self.chain.name
+    }
+
+    async fn opportunities(&self) -> Vec<OpportunityView> {
+        self.opportunities
+            .live()
+            .into_iter()
+            .map(|(opportunity, net_profit, target_block, victim_tx)| OpportunityView {
+                chain: self.chain.name,
+                age_ms: opportunity.latency.elapsed().as_millis() as u64,
+                id: opportunity.id,
+                path: opportunity.path,
+                pools: opportunity.pools,
+                amount_in: opportunity.amount0,
+                expected_profit: opportunity.expected_profit,
+                net_profit,
+                target_block,
+                victim_tx,
+            })
+            .collect()
+    }
+
+    async fn stats(&self) -> ChainStats {
+        let (v2_pools, v3_pools) = self.pool_state.counts().await;
+        ChainStats {
+            chain: self.chain.name,
+            head: self.opportunities.head(),
+            queued: self.opportunities.len(),
+            in_flight: self.in_flight.count(),
+            v2_pools,
+            v3_pools,
+            active_tokens: self.universe.active().await.len(),
+            pnl_daily: self.pnl_engine.daily().await,
+            halted: self.risk_manager.halt_reason().await.map(|r| format!("{:?}", r)),
+            spam_dropped: self.spam_filter.dropped(),
+        }
+    }
+
+    async fn health(&self) -> ChainHealth {
+        let timing = self.block_timing.timing();
+        let head_age = self.block_timing.head_age();
+        let shutting_down = self.shutdown.is_triggered();
+        // A few missed blocks are normal; this many means the head feed is stuck
+        let fresh = head_age.map_or(false, |age| age < timing.block_time * HEALTH_MAX_MISSED_BLOCKS);
+        ChainHealth {
+            chain: self.chain.name,
+            healthy: fresh && !shutting_down,
+            head: timing.head,
+            head_age_ms: head_age.map(|age| age.as_millis() as u64),
+            halted: self.risk_manager.halt_reason().await.map(|r| format!("{:?}", r)),
+            shutting_down,
+        }
+    }
+
+    fn storage(&self) -> Option<Storage> {
+        self.storage.clone()
+    }
+
+    fn controls(&self) -> &RuntimeControls {
+        &self.controls
+    }
+
+    async fn clear_caches(&self) {
+        self.sim_cache.clear();
+        self.simulation_engine.clear_cache().await;
+        rpc_cache::cache(&self.provider).clear();
+    }
+
+    async fn sweep_treasury(&self) -> Result<()> {
+        let treasury = self.treasury.as_ref().ok_or_else(|| anyhow::anyhow!("No treasury configured (TREASURY_BASE_ASSET)"))?;
+        let _in_flight = self.in_flight.enter();
+        treasury.run(&self.universe.active().await, &self.gas_oracle, &self.inventory).await
+    }
+}
+
+/// Connects to `<PREFIX>_WS_URL` and checks it serves the profile's chain id.
+pub async fn connect(chain: &ChainProfile) -> Result<Arc<Provider<RpcTransport>>> {
+    let ws_url = chain
+        .var("WS_URL")
+        .map_err(|_| anyhow::anyhow!("{}_WS_URL must be set in .env", chain.env_prefix))?;
+    let provider = Arc::new(rpc_budget::connect(&ws_url, endpoint_budget(chain, "RPC"), endpoint_retry(chain, "RPC")).await?);
+
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if chain_id != chain.chain_id {
+        return Err(anyhow::anyhow!("{} expects chain id {}, node is on {}", chain.name, chain.chain_id, chain_id));
+    }
+    Ok(provider)
+}
+
+/// `<PREFIX>_<key>_CU_PER_SEC` and `<PREFIX>_<key>_CU_BURST`, in the endpoint's
+/// compute units; the burst defaults to two seconds' worth.
+pub fn endpoint_budget(chain: &ChainProfile, key: &str) -> RpcBudget {
+    let units = |suffix: &str| chain.var(&format!("{}_{}", key, suffix)).ok().and_then(|v| v.parse::<f64>().ok());
+    let units_per_sec = units("CU_PER_SEC").unwrap_or(RpcBudget::default().units_per_sec);
+    RpcBudget {
+        units_per_sec,
+        burst: units("CU_BURST").unwrap_or(units_per_sec * 2.0),
+    }
+}
+
+/// `<PREFIX>_<key>_RETRY_ATTEMPTS` and `<PREFIX>_<key>_DEADLINE_MS`, over the
+/// default retry policy.
+pub fn endpoint_retry(chain: &ChainProfile, key: &str) -> RetryPolicy {
+    let var = |suffix: &str| chain.var(&format!("{}_{}", key, suffix)).ok().and_then(|v| v.parse::<u64>().ok());
+    let mut policy = RetryPolicy::default();
+    if let Some(attempts) = var("RETRY_ATTEMPTS") {
+        policy.max_attempts = attempts.max(1) as u32;
+    }
+    if let Some(ms) = var("DEADLINE_MS") {
+        policy.deadline = Duration::from_millis(ms);
+    }
+    policy
+}
+
+/// Builds one chain's monitor and spawns its tasks inside the caller's span,
+/// so every log line carries the chain label. State is entirely per chain
+/// except for `token_safety`.
+pub async fn start_chain(
+    chain: ChainProfile,
+    token_safety: Arc<TokenSafety>,
+    state: StateStore,
+    shadow_mode: bool,
+    replay: Option<(MempoolReplayer, f64)>,
+) -> Result<(Arc<MempoolMonitor>, JoinHandle<Result<()>>)> {
+    let provider = connect(&chain).await?;
+    let chain_id = chain.chain_id;
+
+    let flash_loan_contract = Address::from_str(
+        &chain.var("FLASH_LOAN_CONTRACT")
+            .expect("FLASH_LOAN_CONTRACT must be set in .env")
+    )?;
+    
+    let fastlane_address = Address::from_str(
+        &chain.var("FASTLANE_RELAY_URL")
+            .expect("FASTLANE_RELAY_URL must be set in .env")
+    )?;
+    
+    let solver_address = Address::from_str(
+        &chain.var("ARBITRAGE_EXECUTOR_CONTRACT")
+            .expect("ARBITRAGE_EXECUTOR_CONTRACT must be set in .env")
+    )?;
+
+    // Optional: without a key, submitted executions aren't tracked or replaced
+    let signer = match chain.var("PRIVATE_KEY") {
+        Ok(key) => {
+            let wallet = key.parse::<LocalWallet>()?.with_chain_id(chain_id);
+            Some(Arc::new(SignerMiddleware::new(provider.clone(), wallet)))
+        }
+        Err(_) => None,
+    };
+
+    // BUNDLE_RELAY_URL switches submission to eth_sendBundle; the relay identity
+    // key only signs request bodies and can be a throwaway
+    let relay = match (chain.var("BUNDLE_RELAY_URL"), chain.var("RELAY_SIGNING_KEY")) {
+        (Ok(url), Ok(key)) => Some(RelayClient::new(url, key.parse::<LocalWallet>()?)),
+        _ => None,
+    };
+
+    let bloxroute = match (env::var("BLOXROUTE_AUTH_HEADER"), chain.bloxroute_network) {
+        (Ok(auth), Some(network)) => Some(BloxrouteClient::new(auth, network)),
+        _ => None,
+    };
+
+    // e.g. a Merkle or GetBlock private endpoint; PRIVATE_RPC_FLASHBOTS_STYLE=1 for
+    // endpoints expecting eth_sendPrivateTransaction
+    let private_rpc = chain.var("PRIVATE_RPC_URL").ok().map(|url| {
+        let method = if chain.var("PRIVATE_RPC_FLASHBOTS_STYLE").is_ok() {
+            PrivateRpcMethod::SendPrivateTransaction
+        } else {
+            PrivateRpcMethod::SendRawTransaction
+        };
+        PrivateRpcClient::new(url, method)
+    });
+
+    // Atlas solver ops are signed with the searcher key, so they need PRIVATE_KEY too
+    let atlas = match (chain.var("ATLAS_ADDRESS"), chain.var("ATLAS_VERIFICATION_ADDRESS"), chain.var("ATLAS_RELAY_URL"), chain.var("PRIVATE_KEY")) {
+        (Ok(atlas), Ok(verification), Ok(relay_url), Ok(key)) if chain.strategies.atlas => {
+            let config = AtlasConfig {
+                atlas: Address::from_str(&atlas)?,
+                atlas_verification: Address::from_str(&verification)?,
+                chain_id,
+                relay_url,
+                relay_ws_url: chain.var("ATLAS_RELAY_WS_URL").ok(),
+                bid_share_bps: chain.var("ATLAS_BID_SHARE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(5_000),
+                max_bid: U256::from(10).pow(20.into()),
+                solver_gas: U256::from(1_000_000),
+                deadline_blocks: 2,
+            };
+            Some(AtlasSolver::new(config, key.parse::<LocalWallet>()?.with_chain_id(chain_id), solver_address))
+        }
+        _ => None,
+    };
+
+    let storage = match chain.var("DATABASE_URL") {
+        Ok(url) => Some(Storage::connect(&url).await?),
+        Err(_) => None,
+    };
+
+    // Polygon PoS deposits are decoded on Ethereum: L1_WS_URL enables it.
+    // FX_ROOT_TUNNELS lists the FxPortal ERC20 tunnels to follow as well
+    let l1 = match chain.var("L1_WS_URL") {
+        Ok(url) if chain.name == "polygon" => Some(Arc::new(rpc_budget::connect(&url, endpoint_budget(&chain, "L1_RPC"), endpoint_retry(&chain, "L1_RPC")).await?)),
+        _ => None,
+    };
+    let fx_root_tunnels = chain
+        .var("FX_ROOT_TUNNELS")
+        .map(|v| v.split(',').filter_map(|a| Address::from_str(a.trim()).ok()).collect())
+        .unwrap_or_default();
+    let watch_l1 = l1.is_some();
+    let bridge_flow = BridgeFlowMonitor::new(l1, fx_root_tunnels);
+
+    info!("Starting");
+    let monitor = Arc::new(MempoolMonitor::new(
+        provider.clone(),
+        chain,
+        flash_loan_contract,
+        fastlane_address,
+        solver_address,
+        signer,
+        relay,
+        bloxroute.clone(),
+        private_rpc,
+        atlas,
+        storage,
+        state,
+        token_safety,
+        bridge_flow,
+        shadow_mode,
+    )?);
+
+    if let Some(client) = bloxroute {
+        let (tx, rx) = mpsc::channel(4096);
+        monitor.attach_feed(rx).await;
+        tokio::spawn(async move {
+            client.run_feed(BloxrouteStream::NewTxs, tx).await;
+        }.in_current_span());
+    }
+    
+    if let Some((replayer, speed)) = replay {
+        let (tx, rx) = mpsc::channel(4096);
+        monitor.attach_replay(rx).await;
+        tokio::spawn(async move {
+            if let Err(e) = replayer.run(speed, tx).await {
+                warn!("Mempool replay error: {:?}", e);
+            }
+        }.in_current_span());
+    }
+
+    monitor.restore_state().await;
+
+    // Start monitoring mempool
+    let monitor_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Hot, async move {
+        if let Err(e) = monitor_clone.start_monitoring().await {
+            warn!("Mempool monitoring error: {:?}", e);
+        }
+    }).in_current_span());
+    
+    // newHeads drives everything that advances with the chain
+    let heads_clone = monitor.clone();
+    tokio::spawn(async move {
+        if let Err(e) = heads_clone.start_head_subscription().await {
+            warn!("Head subscription error: {:?}", e);
+        }
+    }.in_current_span());
+
+    let sink_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Background, async move {
+        if let Err(e) = sink_clone.start_storage_sink().await {
+            warn!("Storage sink error: {:?}", e);
+        }
+    }).in_current_span());
+
+    let settlement_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Background, async move {
+        if let Err(e) = settlement_clone.start_settlement().await {
+            warn!("Settlement error: {:?}", e);
+        }
+    }).in_current_span());
+
+    let treasury_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Background, async move {
+        if let Err(e) = treasury_clone.start_treasury().await {
+            warn!("Treasury error: {:?}", e);
+        }
+    }).in_current_span());
+
+    // Scan token cycles on every new block
+    let scanner_clone = monitor.clone();
+    tokio::spawn(async move {
+        if let Err(e) = scanner_clone.start_block_scanner().await {
+            warn!("Block scanner error: {:?}", e);
+        }
+    }.in_current_span());
+
+    let atlas_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Background, async move {
+        if let Err(e) = atlas_clone.start_atlas().await {
+            warn!("Atlas user op error: {:?}", e);
+        }
+    }).in_current_span());
+
+    let risk_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Background, async move {
+        if let Err(e) = risk_clone.start_risk_controls().await {
+            warn!("Risk controls error: {:?}", e);
+        }
+    }).in_current_span());
+
+    let universe_clone = monitor.clone();
+    tokio::spawn(with_priority(Priority::Background, async move {
+        if let Err(e) = universe_clone.start_token_universe().await {
+            warn!("Token universe error: {:?}", e);
+        }
+    }).in_current_span());
+
+    if watch_l1 {
+        let bridge_clone = monitor.clone();
+        tokio::spawn(with_priority(Priority::Background, async move {
+            if let Err(e) = bridge_clone.start_bridge_flow().await {
+                warn!("Bridge flow error: {:?}", e);
+            }
+        }).in_current_span());
+    }
+
+    // Execute opportunities as detection hands them over
+    let execution_clone = monitor.clone();
+    let execution = tokio::spawn(with_priority(Priority::Hot, async move { execution_clone.start_execution().await }).in_current_span());
+
+    Ok((monitor, execution))
+}
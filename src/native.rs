@@ -0,0 +1,132 @@
+// src/native.rs
+use anyhow::Result;
+use ethers::abi::{encode, Token};
+use ethers::prelude::*;
+use ethers::utils::id;
+use std::sync::Arc;
+
+use crate::classifier::{lookup_swap, DexFamily};
+use crate::rpc_budget::RpcTransport;
+
+pub const WMATIC: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
+// Placeholder aggregators and our paths use for native MATIC
+pub const NATIVE_MATIC: &str = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
+
+pub fn wmatic() -> Address {
+    WMATIC.parse().unwrap()
+}
+
+pub fn native_matic() -> Address {
+    NATIVE_MATIC.parse().unwrap()
+}
+
+pub fn is_native(token: Address) -> bool {
+    token == native_matic()
+}
+
+/// Pools only hold WMATIC, so pathfinding and pricing treat native MATIC as it.
+pub fn as_erc20(token: Address) -> Address {
+    if is_native(token) { wmatic() } else { token }
+}
+
+pub fn erc20_path(path: &[Address]) -> Vec<Address> {
+    let mut out: Vec<Address> = path.iter().map(|t| as_erc20(*t)).collect();
+    out.dedup();
+    out
+}
+
+/// A pending V2 router swap as pathfinding sees it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VictimSwap {
+    /// Route with native MATIC as WMATIC
+    pub path: Vec<Address>,
+    /// Input, or the input cap for exact-out swaps
+    pub amount_in: U256,
+}
+
+/// Decodes a V2 router swap, batched or not. The ETH-in entrypoints carry no
+/// amount in calldata, so theirs is the MATIC sent with the tx.
+pub fn victim_swap(tx: &Transaction) -> Option<VictimSwap> {
+    let (entry, input) = lookup_swap(&tx.input)?;
+    if entry.family != DexFamily::UniswapV2 {
+        return None;
+    }
+    let name = entry.function.name.as_str();
+    let args = entry.function.decode_input(&input[4..]).ok()?;
+    let (amount_in, path) = match name {
+        // (amountOutMin | amountOut, path, to, deadline)
+        n if n.starts_with("swapExactETHFor") || n.starts_with("swapETHFor") => (tx.value, args.get(1)?),
+        // (amountIn, amountOutMin, path, to, deadline)
+        n if n.starts_with("swapExact") => (args.first()?.clone().into_uint()?, args.get(2)?),
+        // (amountOut, amountInMax, path, to, deadline)
+        _ => (args.get(1)?.clone().into_uint()?, args.get(2)?),
+    };
+    let path = path.clone().into_array()?.into_iter().map(|t| t.into_address()).collect::<Option<Vec<_>>>()?;
+    Some(VictimSwap { path: erc20_path(&path), amount_in })
+}
+
+/// Makes wrap/unwrap explicit for the executor: a native endpoint becomes a
+/// NATIVE -> WMATIC (deposit) or WMATIC -> NATIVE (withdraw) hop whose "router"
+/// is the WMATIC contract itself. Wrapping is 1:1, so per-token amounts are repeated.
+pub fn with_wrap_hops(
+    path: &[Address],
+    amounts: &[U256],
+    routers: &[Address],
+) -> (Vec<Address>, Vec<U256>, Vec<Address>) {
+    let (native, wmatic) = (native_matic(), wmatic());
+    let mut path = path.to_vec();
+    let mut amounts = amounts.to_vec();
+    let mut routers = routers.to_vec();
+    let per_token = amounts.len() == path.len();
+
+    if path.first() == Some(&native) && path.get(1) != Some(&wmatic) {
+        path.insert(1, wmatic);
+        routers.insert(0, wmatic);
+        if per_token {
+            amounts.insert(1, amounts[0]);
+        }
+    }
+    let len = path.len();
+    if len >= 2 && path[len - 1] == native && path[len - 2] != wmatic {
+        path.insert(len - 1, wmatic);
+        routers.push(wmatic);
+        if per_token {
+            let last = amounts[amounts.len() - 1];
+            amounts.insert(amounts.len() - 1, last);
+        }
+    }
+    (path, amounts, routers)
+}
+
+pub fn withdraw_calldata(amount: U256) -> Bytes {
+    let mut data = id("withdraw(uint256)").to_vec();
+    data.extend(encode(&[Token::Uint(amount)]));
+    Bytes::from(data)
+}
+
+/// Native MATIC held by `account` at `block`.
+pub async fn native_balance(provider: &Arc<Provider<RpcTransport>>, account: Address, block: Option<U64>) -> Result<U256> {
+    Ok(provider.get_balance(account, block.map(|b| BlockId::Number(b.into()))).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quickswap::QUICKSWAP_ROUTER_ABI;
+
+    #[test]
+    fn eth_in_victims_swap_the_tx_value() {
+        let token = Address::repeat_byte(7);
+        let input = QUICKSWAP_ROUTER_ABI.function("swapExactETHForTokens").unwrap().encode_input(&[
+            Token::Uint(1.into()),
+            Token::Array(vec![Token::Address(wmatic()), Token::Address(token)]),
+            Token::Address(Address::repeat_byte(2)),
+            Token::Uint(9.into()),
+        ]).unwrap();
+        let tx = Transaction { input: Bytes::from(input), value: 5_000.into(), ..Default::default() };
+
+        let swap = victim_swap(&tx).unwrap();
+        assert_eq!(swap.path, vec![wmatic(), token]);
+        assert_eq!(swap.amount_in, U256::from(5_000));
+    }
+}
@@ -0,0 +1,175 @@
+// src/opportunity_queue.rs
+use ethers::types::{H256, U256, U64};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use tracing::debug;
+
+struct Entry<T> {
+    net_profit: U256,
+    // Sequence number: among equal profits, older entries go first
+    seq: u64,
+    // Last block the opportunity can land in
+    target_block: U64,
+    victim_tx: Option<H256>,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.net_profit == other.net_profit && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.net_profit
+            .cmp(&other.net_profit)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Pending opportunities ordered by expected net profit. Entries drop out once
+/// their target block is mined or the victim tx they depend on confirms.
+pub struct OpportunityQueue<T> {
+    heap: Mutex<BinaryHeap<Entry<T>>>,
+    notify: Notify,
+    next_seq: AtomicU64,
+    head: AtomicU64,
+}
+
+impl<T> Default for OpportunityQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OpportunityQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            heap: Mutex::new(BinaryHeap::new()),
+            notify: Notify::new(),
+            next_seq: AtomicU64::new(0),
+            head: AtomicU64::new(0),
+        }
+    }
+
+    pub fn head(&self) -> U64 {
+        U64::from(self.head.load(Ordering::Relaxed))
+    }
+
+    pub fn push(&self, item: T, net_profit: U256, target_block: U64, victim_tx: Option<H256>) {
+        let entry = Entry {
+            net_profit,
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            target_block,
+            victim_tx,
+            item,
+        };
+        self.heap.lock().unwrap().push(entry);
+        self.notify.notify_one();
+    }
+
+    /// Waits for the most profitable live entry, then also takes up to `max - 1`
+    /// more for the same target block, in profit order, that `conflicts` with
+    /// none already taken. The rest stay queued.
+    pub async fn next_batch(&self, max: usize, conflicts: impl Fn(&T, &T) -> bool) -> Vec<T> {
+        let first = loop {
+            if let Some(entry) = self.pop_live() {
+                break entry;
+            }
+            self.notify.notified().await;
+        };
+        let head = self.head();
+        let mut heap = self.heap.lock().unwrap();
+        let target_block = first.target_block;
+        let mut batch = vec![first.item];
+        let mut kept = Vec::new();
+        while batch.len() < max {
+            let entry = match heap.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if entry.target_block <= head {
+                continue;
+            }
+            if entry.target_block == target_block && !batch.iter().any(|taken| conflicts(taken, &entry.item)) {
+                batch.push(entry.item);
+            } else {
+                kept.push(entry);
+            }
+        }
+        heap.extend(kept);
+        batch
+    }
+
+    fn pop_live(&self) -> Option<Entry<T>> {
+        let head = self.head();
+        let mut heap = self.heap.lock().unwrap();
+        while let Some(entry) = heap.pop() {
+            if entry.target_block > head {
+                return Some(entry);
+            }
+            debug!(target_block = %entry.target_block, "Dropping expired opportunity");
+        }
+        None
+    }
+
+    /// Advances the head: expires entries whose target block is now mined and
+    /// those whose victim tx was included in it.
+    pub fn on_block(&self, block: U64, included: &[H256]) {
+        self.head.store(block.as_u64(), Ordering::Relaxed);
+        self.retain(|target_block, victim| {
+            target_block > block && !victim.map_or(false, |v| included.contains(&v))
+        });
+    }
+
+    /// Drops everything that depends on `victim_tx`.
+    pub fn cancel_victim(&self, victim_tx: H256) {
+        self.retain(|_, victim| victim != Some(victim_tx));
+    }
+
+    /// Entries still live at the current head, most profitable first, as
+    /// (item, net profit, target block, victim tx).
+    pub fn live(&self) -> Vec<(T, U256, U64, Option<H256>)>
+    where
+        T: Clone,
+    {
+        let head = self.head();
+        let heap = self.heap.lock().unwrap();
+        let mut entries: Vec<&Entry<T>> = heap.iter().filter(|e| e.target_block > head).collect();
+        entries.sort_by(|a, b| b.cmp(a));
+        entries.into_iter().map(|e| (e.item.clone(), e.net_profit, e.target_block, e.victim_tx)).collect()
+    }
+
+    pub fn clear(&self) {
+        self.heap.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.lock().unwrap().is_empty()
+    }
+
+    fn retain(&self, keep: impl Fn(U64, Option<H256>) -> bool) {
+        let mut heap = self.heap.lock().unwrap();
+        let before = heap.len();
+        heap.retain(|e| keep(e.target_block, e.victim_tx));
+        if heap.len() != before {
+            debug!("Expired {} queued opportunities", before - heap.len());
+        }
+    }
+}
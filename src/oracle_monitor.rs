@@ -0,0 +1,306 @@
+// src/oracle_monitor.rs
+use anyhow::Result;
+use ethers::abi::{Abi, AbiParser, Token};
+use ethers::prelude::*;
+use tracing::{debug, info, warn};
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+
+use crate::rpc_budget::RpcTransport;
+
+// OCR aggregators are fed through `transmit`; the report carries every observation
+// and the median (observations[len / 2]) becomes the new answer.
+pub static CHAINLINK_OCR_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function transmit(bytes32[3],bytes,bytes32[],bytes32[],bytes32)",
+    ]).expect("parse chainlink ocr abi")
+});
+
+// Minimal proxy ABI, used to resolve the current aggregator behind each feed
+abigen!(IChainlinkProxy, r#"[
+    function aggregator() external view returns (address)
+    function latestAnswer() external view returns (int256)
+    function decimals() external view returns (uint8)
+]"#);
+
+// The OCR2 aggregator behind a proxy; only its transmitters can move the answer
+abigen!(IOcr2Aggregator, r#"[
+    function getTransmitters() external view returns (address[])
+]"#);
+
+// ---- Polygon Chainlink feed proxies (USD quoted) ----
+const MATIC_USD_PROXY: &str = "0xAB594600376Ec9fD91F8e885dADF0CE036862dE0";
+const ETH_USD_PROXY: &str = "0xF9680D99D6C9589e2a93a78A04A279e509205945";
+const BTC_USD_PROXY: &str = "0xc907E116054Ad103354f2D350FD2514433D57F6f";
+const USDC_USD_PROXY: &str = "0xfE4A8cc5b5B2366C1B58Bea3858e81843581b2F7";
+
+const WMATIC: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
+const WETH: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+const WBTC: &str = "0x1BFD67037B42Cf73acF2047067bd4F2C47D9BfD6";
+const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+
+#[derive(Debug, Clone)]
+pub struct OracleFeed {
+    pub proxy: Address,
+    pub token: Address,
+    pub description: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OracleUpdate {
+    pub tx_hash: H256,
+    pub aggregator: Address,
+    pub token: Address,
+    pub new_answer: I256,
+    pub previous_answer: I256,
+    // Signed move implied by the pending transmit, in basis points
+    pub change_bps: i64,
+    pub gas_price: Option<U256>,
+}
+
+pub struct OracleMonitor {
+    provider: Arc<Provider<RpcTransport>>,
+    feeds: Vec<OracleFeed>,
+    // aggregator address -> index into `feeds`
+    aggregators: Mutex<HashMap<Address, usize>>,
+    // aggregator address -> accounts allowed to transmit to it
+    transmitters: Mutex<HashMap<Address, HashSet<Address>>>,
+    last_answers: Mutex<HashMap<Address, I256>>,
+    updates: broadcast::Sender<OracleUpdate>,
+}
+
+/// Decode a Chainlink OCR `transmit` call. Returns the median answer of the report,
+/// or `None` if the calldata isn't a transmit or the report is malformed.
+pub fn parse_chainlink_transmit(tx: &Transaction) -> Option<I256> {
+    let input = &tx.input.0;
+    if input.len() < 4 { return None; }
+
+    let function = CHAINLINK_OCR_ABI.function("transmit").ok()?;
+    if function.short_signature() != input[..4] {
+        return None;
+    }
+
+    let tokens = function.decode_input(&input[4..]).ok()?;
+    let report = match tokens.get(1)? {
+        Token::Bytes(b) => b,
+        _ => return None,
+    };
+
+    // report = abi.encode(bytes32 rawReportContext, bytes32 rawObservers, int192[] observations)
+    let decoded = ethers::abi::decode(
+        &[
+            ethers::abi::ParamType::FixedBytes(32),
+            ethers::abi::ParamType::FixedBytes(32),
+            ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::Int(192))),
+        ],
+        report,
+    ).ok()?;
+
+    let observations = match &decoded[2] {
+        Token::Array(v) if !v.is_empty() => v,
+        _ => return None,
+    };
+    match &observations[observations.len() / 2] {
+        Token::Int(raw) => Some(I256::from_raw(*raw)),
+        _ => None,
+    }
+}
+
+impl OracleMonitor {
+    pub fn new(provider: Arc<Provider<RpcTransport>>) -> Self {
+        let feeds = vec![
+            OracleFeed { proxy: MATIC_USD_PROXY.parse().unwrap(), token: WMATIC.parse().unwrap(), description: "MATIC / USD".to_string() },
+            OracleFeed { proxy: ETH_USD_PROXY.parse().unwrap(), token: WETH.parse().unwrap(), description: "ETH / USD".to_string() },
+            OracleFeed { proxy: BTC_USD_PROXY.parse().unwrap(), token: WBTC.parse().unwrap(), description: "BTC / USD".to_string() },
+            OracleFeed { proxy: USDC_USD_PROXY.parse().unwrap(), token: USDC.parse().unwrap(), description: "USDC / USD".to_string() },
+        ];
+        let (updates, _) = broadcast::channel(64);
+
+        Self {
+            provider,
+            feeds,
+            aggregators: Mutex::new(HashMap::new()),
+            transmitters: Mutex::new(HashMap::new()),
+            last_answers: Mutex::new(HashMap::new()),
+            updates,
+        }
+    }
+
+    /// Subscribe to decoded oracle updates (used by the liquidation side and analytics).
+    pub fn subscribe(&self) -> broadcast::Receiver<OracleUpdate> {
+        self.updates.subscribe()
+    }
+
+    // Proxies can be re-pointed to a new aggregator at any time, so this should
+    // be called at startup and periodically afterwards
+    pub async fn refresh_aggregators(&self) -> Result<()> {
+        let mut resolved = HashMap::new();
+        let mut transmitters = HashMap::new();
+        let mut answers = HashMap::new();
+
+        for (i, feed) in self.feeds.iter().enumerate() {
+            let proxy = IChainlinkProxy::new(feed.proxy, self.provider.clone());
+            let aggregator = proxy.aggregator().call().await?;
+            let answer = proxy.latest_answer().call().await?;
+            // Without the list nobody is trusted: the feed's pending updates are ignored
+            let allowed = match IOcr2Aggregator::new(aggregator, self.provider.clone()).get_transmitters().call().await {
+                Ok(allowed) => allowed.into_iter().collect(),
+                Err(e) => {
+                    warn!("No transmitters for Chainlink {} aggregator {:?}: {:?}", feed.description, aggregator, e);
+                    HashSet::new()
+                }
+            };
+            resolved.insert(aggregator, i);
+            transmitters.insert(aggregator, allowed);
+            answers.insert(aggregator, answer);
+            debug!("Chainlink {} aggregator: {:?}", feed.description, aggregator);
+        }
+
+        *self.aggregators.lock().await = resolved;
+        *self.transmitters.lock().await = transmitters;
+        self.last_answers.lock().await.extend(answers);
+        Ok(())
+    }
+
+    /// Aggregators resolved by the last `refresh_aggregators`.
+    pub async fn aggregators(&self) -> Vec<Address> {
+        self.aggregators.lock().await.keys().copied().collect()
+    }
+
+    pub async fn process_transaction(&self, tx: &Transaction) -> Result<Option<OracleUpdate>> {
+        let aggregator = match tx.to {
+            Some(to) => to,
+            None => return Ok(None),
+        };
+
+        let feed_index = match self.aggregators.lock().await.get(&aggregator) {
+            Some(i) => *i,
+            None => return Ok(None),
+        };
+        // Anyone can send a transmit; only an authorized transmitter's can land
+        let authorized = self.transmitters.lock().await.get(&aggregator).map_or(false, |allowed| allowed.contains(&tx.from));
+        if !authorized {
+            debug!("Ignoring transmit to {:?} from unauthorized {:?}", aggregator, tx.from);
+            return Ok(None);
+        }
+
+        let new_answer = match parse_chainlink_transmit(tx) {
+            Some(answer) => answer,
+            None => return Ok(None),
+        };
+
+        let previous_answer = {
+            let mut last = self.last_answers.lock().await;
+            let previous = last.get(&aggregator).copied();
+            last.insert(aggregator, new_answer);
+            previous
+        };
+        let previous_answer = match previous_answer {
+            Some(answer) => answer,
+            None => {
+                let proxy = IChainlinkProxy::new(self.feeds[feed_index].proxy, self.provider.clone());
+                proxy.latest_answer().call().await?
+            }
+        };
+
+        let update = OracleUpdate {
+            tx_hash: tx.hash,
+            aggregator,
+            token: self.feeds[feed_index].token,
+            new_answer,
+            previous_answer,
+            change_bps: Self::change_bps(previous_answer, new_answer),
+            gas_price: tx.gas_price,
+        };
+
+        info!(
+            "Pending Chainlink {} update: {} -> {} ({} bps)",
+            self.feeds[feed_index].description, update.previous_answer, update.new_answer, update.change_bps
+        );
+
+        // No receivers is fine, the arbitrage engine is fed directly by the monitor
+        let _ = self.updates.send(update.clone());
+        Ok(Some(update))
+    }
+
+    // Latest on-chain USD price for a token with a tracked feed. Chainlink USD
+    // feeds on Polygon use 8 decimals.
+    pub async fn latest_usd_price(&self, token: Address) -> Result<Option<f64>> {
+        let feed = match self.feeds.iter().find(|f| f.token == token) {
+            Some(f) => f,
+            None => return Ok(None),
+        };
+
+        let proxy = IChainlinkProxy::new(feed.proxy, self.provider.clone());
+        let answer = proxy.latest_answer().call().await?;
+        Ok(Some(answer.as_i128() as f64 / 1e8))
+    }
+
+    // Saturates: a report can carry any int192, however far from the last answer
+    fn change_bps(previous: I256, new: I256) -> i64 {
+        if previous.is_zero() {
+            return 0;
+        }
+        new.checked_sub(previous)
+            .and_then(|delta| delta.checked_mul(I256::from(10_000)))
+            .and_then(|delta| delta.checked_div(previous))
+            .and_then(|bps| i64::try_from(bps).ok())
+            .unwrap_or(if (new > previous) == previous.is_positive() { i64::MAX } else { i64::MIN })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn transmit_tx(report: Vec<u8>) -> Transaction {
+        let function = CHAINLINK_OCR_ABI.function("transmit").unwrap();
+        let context = Token::FixedArray(vec![Token::FixedBytes(vec![0; 32]); 3]);
+        let input = function
+            .encode_input(&[context, Token::Bytes(report), Token::Array(vec![]), Token::Array(vec![]), Token::FixedBytes(vec![0; 32])])
+            .unwrap();
+        Transaction { input: Bytes::from(input), ..Default::default() }
+    }
+
+    proptest! {
+        #[test]
+        fn decodes_the_middle_observation(observations in prop::collection::vec(any::<i128>(), 1..31)) {
+            let report = ethers::abi::encode(&[
+                Token::FixedBytes(vec![0; 32]),
+                Token::FixedBytes(vec![0; 32]),
+                Token::Array(observations.iter().map(|o| Token::Int(I256::from(*o).into_raw())).collect()),
+            ]);
+            let median = parse_chainlink_transmit(&transmit_tx(report));
+            prop_assert_eq!(median, Some(I256::from(observations[observations.len() / 2])));
+        }
+
+        #[test]
+        fn arbitrary_calldata_never_panics(input in prop::collection::vec(any::<u8>(), 0..512)) {
+            let _ = parse_chainlink_transmit(&Transaction { input: Bytes::from(input), ..Default::default() });
+        }
+
+        // A valid transmit wrapping a random report, so the inner decode sees garbage
+        #[test]
+        fn garbage_report_never_panics(report in prop::collection::vec(any::<u8>(), 0..512)) {
+            let _ = parse_chainlink_transmit(&transmit_tx(report));
+        }
+    }
+
+    #[test]
+    fn change_saturates_on_extreme_observations() {
+        let max_int192 = I256::from_raw((U256::one() << 191) - 1);
+        assert_eq!(OracleMonitor::change_bps(I256::from(100), I256::from(101)), 100);
+        assert_eq!(OracleMonitor::change_bps(I256::from(1), max_int192), i64::MAX);
+        assert_eq!(OracleMonitor::change_bps(I256::from(1), -max_int192), i64::MIN);
+        assert_eq!(OracleMonitor::change_bps(I256::from(-1), max_int192), i64::MIN);
+    }
+
+    #[test]
+    fn empty_report_has_no_answer() {
+        let report = ethers::abi::encode(&[Token::FixedBytes(vec![0; 32]), Token::FixedBytes(vec![0; 32]), Token::Array(vec![])]);
+        assert_eq!(parse_chainlink_transmit(&transmit_tx(report)), None);
+    }
+}
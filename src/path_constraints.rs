@@ -0,0 +1,106 @@
+// src/path_constraints.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::collections::HashSet;
+
+use crate::chain::ChainProfile;
+use crate::pool_state::PoolState;
+use crate::v3_ticks::V3PoolState;
+
+/// Limits on what the pathfinders may route through.
+#[derive(Debug, Clone)]
+pub struct PathConstraints {
+    // Only these tokens may appear in a path; empty allows any
+    pub token_whitelist: HashSet<Address>,
+    // Swaps per path
+    pub max_hops: usize,
+    // Pools never routed through, e.g. known MEV traps
+    pub blocked_pools: HashSet<Address>,
+    // Token pairs never swapped, in either direction
+    pub blocked_pairs: HashSet<(Address, Address)>,
+    // Pools valued below this are skipped. Pools without a valuation yet are
+    // allowed; ones that can't be priced are valued at zero
+    pub min_pool_tvl_usd: f64,
+}
+
+impl Default for PathConstraints {
+    fn default() -> Self {
+        Self {
+            token_whitelist: HashSet::new(),
+            max_hops: 3,
+            blocked_pools: HashSet::new(),
+            blocked_pairs: HashSet::new(),
+            min_pool_tvl_usd: 0.0,
+        }
+    }
+}
+
+fn parse_addresses(list: &str) -> Result<Vec<Address>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<Address>().map_err(|e| anyhow!("Bad address {:?}: {:?}", s, e)))
+        .collect()
+}
+
+impl PathConstraints {
+    /// PATH_TOKEN_WHITELIST, PATH_BLOCKED_POOLS (comma-separated addresses),
+    /// PATH_BLOCKED_PAIRS (tokenA:tokenB,...), PATH_MAX_HOPS, PATH_MIN_POOL_TVL_USD.
+    pub fn from_env(chain: &ChainProfile) -> Result<Self> {
+        let mut constraints = Self::default();
+        if let Ok(list) = chain.var("PATH_TOKEN_WHITELIST") {
+            constraints.token_whitelist = parse_addresses(&list)?.into_iter().collect();
+            // Cycles start and end in these, so they are always allowed
+            constraints.token_whitelist.extend(chain.tokens.iter().copied());
+        }
+        if let Ok(list) = chain.var("PATH_BLOCKED_POOLS") {
+            constraints.blocked_pools = parse_addresses(&list)?.into_iter().collect();
+        }
+        if let Ok(list) = chain.var("PATH_BLOCKED_PAIRS") {
+            for pair in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let (a, b) = pair.split_once(':').ok_or_else(|| anyhow!("PATH_BLOCKED_PAIRS entry {:?} is not tokenA:tokenB", pair))?;
+                constraints.blocked_pairs.insert(Self::pair_key(a.trim().parse()?, b.trim().parse()?));
+            }
+        }
+        if let Some(hops) = chain.var("PATH_MAX_HOPS").ok().and_then(|v| v.parse().ok()) {
+            constraints.max_hops = hops;
+        }
+        if let Some(tvl) = chain.var("PATH_MIN_POOL_TVL_USD").ok().and_then(|v| v.parse().ok()) {
+            constraints.min_pool_tvl_usd = tvl;
+        }
+        Ok(constraints)
+    }
+
+    fn pair_key(a: Address, b: Address) -> (Address, Address) {
+        if a < b { (a, b) } else { (b, a) }
+    }
+
+    pub fn allows_token(&self, token: Address) -> bool {
+        self.token_whitelist.is_empty() || self.token_whitelist.contains(&token)
+    }
+
+    pub fn allows_hop(&self, token_in: Address, token_out: Address) -> bool {
+        self.allows_token(token_in)
+            && self.allows_token(token_out)
+            && !self.blocked_pairs.contains(&Self::pair_key(token_in, token_out))
+    }
+
+    pub fn allows_pool(&self, pool: &PoolState, tvl_usd: Option<f64>) -> bool {
+        !self.blocked_pools.contains(&pool.address)
+            && self.allows_hop(pool.token0, pool.token1)
+            && tvl_usd.map_or(true, |tvl| tvl >= self.min_pool_tvl_usd)
+    }
+
+    pub fn allows_v3_pool(&self, pool: &V3PoolState, tvl_usd: Option<f64>) -> bool {
+        !self.blocked_pools.contains(&pool.address)
+            && self.allows_hop(pool.token0, pool.token1)
+            && tvl_usd.map_or(true, |tvl| tvl >= self.min_pool_tvl_usd)
+    }
+
+    /// Token path (start token repeated at the end for cycles).
+    pub fn allows_path(&self, path: &[Address]) -> bool {
+        path.len() >= 2
+            && path.len() - 1 <= self.max_hops
+            && path.windows(2).all(|hop| self.allows_hop(hop[0], hop[1]))
+    }
+}
@@ -0,0 +1,43 @@
+// src/persistence.rs
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+/// JSON snapshots in a state directory, written on shutdown and read on boot.
+/// A missing or unreadable snapshot just means a cold start for that component.
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    dir: PathBuf,
+}
+
+impl StateStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        // Write then rename so a crash mid-write never leaves a truncated snapshot
+        let tmp = self.dir.join(format!("{}.json.tmp", name));
+        std::fs::write(&tmp, serde_json::to_vec(value)?)?;
+        std::fs::rename(&tmp, self.path(name))?;
+        debug!("Saved {} snapshot", name);
+        Ok(())
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        let bytes = std::fs::read(self.path(name)).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("Ignoring unreadable {} snapshot: {:?}", name, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// src/pnl.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use serde::Serialize;
+use tracing::info;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, OnceCell};
+
+use crate::abis;
+use crate::flash_loan::LoanSource;
+use crate::native;
+use crate::oracle_monitor::OracleMonitor;
+use crate::token_registry::{units, TokenRegistry};
+use crate::rpc_budget::RpcTransport;
+
+// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+#[derive(Debug, Clone)]
+pub struct LoanTerms {
+    pub token: Address,
+    pub amount: U256,
+    pub source: LoanSource,
+}
+
+#[derive(Debug, Clone)]
+pub struct RealizedPnl {
+    pub opportunity_id: String,
+    pub tx_hash: H256,
+    pub timestamp: u64,
+    pub token: Address,
+    // Net owner balance change in the loan token, loan fee already repaid
+    pub net_token_delta: I256,
+    // Native MATIC the owner gained or lost across the execution block (unwraps,
+    // refunds), gas excluded; valued as WMATIC
+    pub net_native_delta: I256,
+    pub loan_fee: U256,
+    pub gas_cost_wei: U256,
+    pub reverted: bool,
+    pub gross_usd: f64,
+    pub loan_fee_usd: f64,
+    pub gas_usd: f64,
+    pub net_usd: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PnlAggregate {
+    pub executions: usize,
+    pub profitable: usize,
+    pub gross_usd: f64,
+    pub loan_fees_usd: f64,
+    pub gas_usd: f64,
+    pub net_usd: f64,
+}
+
+/// Computes realized profit from execution receipts, attributing gas and
+/// flash-loan fees separately so losing trades can be explained.
+pub struct PnlEngine {
+    provider: Arc<Provider<RpcTransport>>,
+    prices: Arc<OracleMonitor>,
+    tokens: Arc<TokenRegistry>,
+    executor: Address,
+    // The executor pays profit out to its owner, so that's whose balance moves
+    owner: OnceCell<Address>,
+    // Gas and native deltas are valued at its price
+    wrapped_native: Address,
+    history: Mutex<Vec<RealizedPnl>>,
+}
+
+impl PnlEngine {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, prices: Arc<OracleMonitor>, tokens: Arc<TokenRegistry>, executor: Address, wrapped_native: Address) -> Self {
+        Self {
+            provider,
+            prices,
+            tokens,
+            executor,
+            owner: OnceCell::new(),
+            wrapped_native,
+            history: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub async fn record_execution(&self, opportunity_id: &str, tx_hash: H256, loan: &LoanTerms) -> Result<RealizedPnl> {
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| anyhow!("No receipt for {:?}", tx_hash))?;
+
+        let owner = self.owner().await?;
+        let net_token_delta = token_delta(&receipt, loan.token, owner);
+        let loan_fee = loan.source.fee_on(loan.amount);
+        let gas_cost_wei = receipt.gas_used.unwrap_or_default() * receipt.effective_gas_price.unwrap_or_default();
+        let mut net_native_delta = self.native_delta(owner, receipt.block_number).await?;
+        // An owner that also signs paid the gas out of that balance; it's counted separately
+        if receipt.from == owner {
+            net_native_delta += I256::from_raw(gas_cost_wei);
+        }
+
+        let token_price = self.prices.latest_usd_price(loan.token).await?.unwrap_or(0.0);
+        let matic_price = self.prices.latest_usd_price(self.wrapped_native).await?.unwrap_or(0.0);
+        let decimals = self.tokens.decimals(loan.token).await?;
+
+        let net_delta_usd = units(net_token_delta, decimals) * token_price
+            + units(net_native_delta, 18) * matic_price;
+        let loan_fee_usd = units(I256::from_raw(loan_fee), decimals) * token_price;
+        let gas_usd = units(I256::from_raw(gas_cost_wei), 18) * matic_price;
+
+        let pnl = RealizedPnl {
+            opportunity_id: opportunity_id.to_string(),
+            tx_hash,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            token: loan.token,
+            net_token_delta,
+            net_native_delta,
+            loan_fee,
+            gas_cost_wei,
+            reverted: receipt.status == Some(U64::zero()),
+            gross_usd: net_delta_usd + loan_fee_usd,
+            loan_fee_usd,
+            gas_usd,
+            net_usd: net_delta_usd - gas_usd,
+        };
+
+        info!(
+            "Realized PnL for {}: ${:.2} (gross ${:.2}, loan fee ${:.2}, gas ${:.2})",
+            opportunity_id, pnl.net_usd, pnl.gross_usd, pnl.loan_fee_usd, pnl.gas_usd
+        );
+
+        self.history.lock().await.push(pnl.clone());
+        Ok(pnl)
+    }
+
+    pub async fn daily(&self) -> PnlAggregate {
+        self.aggregate_since(Duration::from_secs(24 * 60 * 60)).await
+    }
+
+    pub async fn weekly(&self) -> PnlAggregate {
+        self.aggregate_since(Duration::from_secs(7 * 24 * 60 * 60)).await
+    }
+
+    pub async fn aggregate_since(&self, window: Duration) -> PnlAggregate {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let cutoff = now.saturating_sub(window.as_secs());
+
+        let history = self.history.lock().await;
+        let mut aggregate = PnlAggregate::default();
+        for pnl in history.iter().filter(|p| p.timestamp >= cutoff) {
+            aggregate.executions += 1;
+            if pnl.net_usd > 0.0 {
+                aggregate.profitable += 1;
+            }
+            aggregate.gross_usd += pnl.gross_usd;
+            aggregate.loan_fees_usd += pnl.loan_fee_usd;
+            aggregate.gas_usd += pnl.gas_usd;
+            aggregate.net_usd += pnl.net_usd;
+        }
+        aggregate
+    }
+
+    async fn owner(&self) -> Result<Address> {
+        self.owner
+            .get_or_try_init(|| async {
+                let owner = abis::executor(self.executor, self.provider.clone()).owner().call().await?;
+                Ok::<_, anyhow::Error>(owner)
+            })
+            .await
+            .copied()
+    }
+
+    // Native transfers leave no logs, so compare balances around the block
+    async fn native_delta(&self, account: Address, block: Option<U64>) -> Result<I256> {
+        let block = match block {
+            Some(b) if !b.is_zero() => b,
+            _ => return Ok(I256::zero()),
+        };
+        let before = native::native_balance(&self.provider, account, Some(block - 1)).await?;
+        let after = native::native_balance(&self.provider, account, Some(block)).await?;
+        Ok(I256::from_raw(after) - I256::from_raw(before))
+    }
+}
+
+// Sum of `token` Transfer logs into `account` minus those out of it
+fn token_delta(receipt: &TransactionReceipt, token: Address, account: Address) -> I256 {
+    let transfer_topic: H256 = TRANSFER_TOPIC.parse().unwrap();
+    let account = H256::from(account);
+    let mut delta = I256::zero();
+
+    for log in receipt.logs.iter().filter(|l| l.address == token) {
+        if log.topics.len() != 3 || log.topics[0] != transfer_topic || log.data.len() != 32 {
+            continue;
+        }
+        let amount = I256::from_raw(U256::from_big_endian(&log.data));
+        if log.topics[2] == account {
+            delta += amount;
+        }
+        if log.topics[1] == account {
+            delta -= amount;
+        }
+    }
+    delta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(token: Address, from: Address, to: Address, amount: u64) -> Log {
+        let mut data = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+        Log {
+            address: token,
+            topics: vec![TRANSFER_TOPIC.parse().unwrap(), H256::from(from), H256::from(to)],
+            data: Bytes::from(data.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    // A flash-loan arbitrage: borrow, trade, repay with fee, pay the rest to the owner
+    #[test]
+    fn counts_profit_paid_to_the_owner() {
+        let token = Address::repeat_byte(0x11);
+        let (pool, lender, executor, owner) = (
+            Address::repeat_byte(0x22),
+            Address::repeat_byte(0x33),
+            Address::repeat_byte(0x44),
+            Address::repeat_byte(0x55),
+        );
+        let receipt = TransactionReceipt {
+            logs: vec![
+                transfer(token, lender, executor, 1_000_000),
+                transfer(token, executor, pool, 1_000_000),
+                transfer(token, pool, executor, 1_012_000),
+                transfer(token, executor, lender, 1_000_900),
+                transfer(token, executor, owner, 11_100),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(token_delta(&receipt, token, owner), I256::from(11_100));
+        assert_eq!(token_delta(&receipt, token, executor), I256::zero());
+    }
+}
@@ -0,0 +1,504 @@
+// src/pool_state.rs
+use anyhow::Result;
+use ethers::abi::Token;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::fee::FeeAmount;
+use crate::v3_quoter::UNISWAP_V3_FACTORY;
+use crate::v3_ticks::{TickInfo, V3PoolState, MAX_TICK, MIN_TICK};
+use crate::rpc_budget::RpcTransport;
+
+abigen!(IUniswapV2Factory, r#"[
+    function getPair(address tokenA, address tokenB) external view returns (address)
+]"#);
+
+abigen!(IUniswapV2Pair, r#"[
+    function token0() external view returns (address)
+    function token1() external view returns (address)
+    function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+]"#);
+
+abigen!(IUniswapV3Factory, r#"[
+    function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address)
+]"#);
+
+abigen!(IUniswapV3Pool, r#"[
+    function token0() external view returns (address)
+    function token1() external view returns (address)
+    function fee() external view returns (uint24)
+    function tickSpacing() external view returns (int24)
+    function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+    function liquidity() external view returns (uint128)
+    function tickBitmap(int16 wordPosition) external view returns (uint256)
+    function ticks(int24 tick) external view returns (uint128 liquidityGross, int128 liquidityNet, uint256 feeGrowthOutside0X128, uint256 feeGrowthOutside1X128, int56 tickCumulativeOutside, uint160 secondsPerLiquidityOutsideX128, uint32 secondsOutside, bool initialized)
+]"#);
+
+// Bitmap words fetched either side of the current tick (256 tick spacings each)
+const TICK_WORDS: i32 = 4;
+// Past this many blocks behind, replaying V3 logs costs more than reloading the pool
+const MAX_CATCH_UP_BLOCKS: u64 = 2_000;
+
+pub const QUICKSWAP_FACTORY: &str = "0x5757371414417b8C6CAad45bAeF941aBc7d3Ab32";
+pub const QUICKSWAP_ROUTER: &str = "0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff";
+pub const SUSHISWAP_FACTORY: &str = "0xc35DADB65012eC5796536bD9864eD8773aBc74C4";
+pub const SUSHISWAP_ROUTER: &str = "0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolState {
+    pub address: Address,
+    pub router: Address,
+    pub token0: Address,
+    pub token1: Address,
+    pub reserve0: U256,
+    pub reserve1: U256,
+    pub fee: FeeAmount,
+    pub last_updated_block: U64,
+}
+
+impl PoolState {
+    pub fn swap_topic() -> H256 {
+        H256::from(keccak256("Swap(address,uint256,uint256,uint256,uint256,address)"))
+    }
+
+    pub fn has_token(&self, token: Address) -> bool {
+        self.token0 == token || self.token1 == token
+    }
+
+    pub fn other_token(&self, token: Address) -> Address {
+        if self.token0 == token { self.token1 } else { self.token0 }
+    }
+
+    // Constant-product output for an exact input, fee applied on the way in.
+    // Amounts can come straight from pending calldata, so one too large for
+    // the math gets nothing rather than overflowing
+    pub fn get_amount_out(&self, token_in: Address, amount_in: U256) -> U256 {
+        let (reserve_in, reserve_out) = if token_in == self.token0 {
+            (self.reserve0, self.reserve1)
+        } else {
+            (self.reserve1, self.reserve0)
+        };
+        if reserve_in.is_zero() || reserve_out.is_zero() || amount_in.is_zero() {
+            return U256::zero();
+        }
+
+        let amount_out = || {
+            let amount_in_with_fee = amount_in.checked_mul(self.fee.complement())?;
+            let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+            let denominator = reserve_in.checked_mul(U256::from(1_000_000))?.checked_add(amount_in_with_fee)?;
+            Some(numerator / denominator)
+        };
+        amount_out().unwrap_or_default()
+    }
+
+    /// Reserves after a swap that moved `amount0`/`amount1` into the pool
+    /// (negative when the pool paid out).
+    pub fn apply_delta(&mut self, amount0: I256, amount1: I256) {
+        let apply = |reserve: U256, delta: I256| {
+            if delta.is_negative() {
+                reserve.saturating_sub(delta.unsigned_abs())
+            } else {
+                reserve.saturating_add(delta.into_raw())
+            }
+        };
+        self.reserve0 = apply(self.reserve0, amount0);
+        self.reserve1 = apply(self.reserve1, amount1);
+    }
+}
+
+/// Everything discovery and tick loading produced, as written to disk on shutdown.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PoolRegistrySnapshot {
+    pub pools: Vec<PoolState>,
+    pub v3_pools: Vec<V3PoolState>,
+}
+
+/// Block-synchronised view of V2-style pool reserves across the tracked DEXs,
+/// plus Uniswap V3 pools with their initialized ticks around the current price.
+pub struct PoolStateManager {
+    provider: Arc<Provider<RpcTransport>>,
+    // (factory, router) pairs to discover pools on
+    dexes: Vec<(Address, Address)>,
+    pools: RwLock<HashMap<Address, PoolState>>,
+    v3_pools: RwLock<HashMap<Address, V3PoolState>>,
+}
+
+impl PoolStateManager {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, dexes: Vec<(Address, Address)>) -> Self {
+        Self {
+            provider,
+            dexes,
+            pools: RwLock::new(HashMap::new()),
+            v3_pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Discover every pair between the given tokens on all tracked DEXs
+    pub async fn discover(&self, tokens: &[Address]) -> Result<()> {
+        for (factory_address, router) in &self.dexes {
+            let factory = IUniswapV2Factory::new(*factory_address, self.provider.clone());
+
+            for (i, token_a) in tokens.iter().enumerate() {
+                for token_b in &tokens[i + 1..] {
+                    let pair = factory.get_pair(*token_a, *token_b).call().await?;
+                    if pair == Address::zero() {
+                        continue;
+                    }
+                    self.track_pair(pair, *router).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts tracking a pair already known to exist, e.g. from its factory's
+    /// `PairCreated` log. Returns false if it was tracked already.
+    pub async fn track_pair(&self, pair: Address, router: Address) -> Result<bool> {
+        if self.pools.read().await.contains_key(&pair) {
+            return Ok(false);
+        }
+
+        let contract = IUniswapV2Pair::new(pair, self.provider.clone());
+        let token0 = contract.token_0().call().await?;
+        let token1 = contract.token_1().call().await?;
+
+        self.pools.write().await.insert(pair, PoolState {
+            address: pair,
+            router,
+            token0,
+            token1,
+            reserve0: U256::zero(),
+            reserve1: U256::zero(),
+            fee: FeeAmount::MEDIUM,
+            last_updated_block: U64::zero(),
+        });
+        debug!("Tracking pool {:?} ({:?}/{:?})", pair, token0, token1);
+        Ok(true)
+    }
+
+    /// V3 counterpart of `track_pair`, loading the pool's ticks at `block`.
+    pub async fn track_v3(&self, pool: Address, block: U64) -> Result<bool> {
+        if self.v3_pools.read().await.contains_key(&pool) {
+            return Ok(false);
+        }
+        let state = self.load_v3(pool, block).await?;
+        debug!("Tracking V3 pool {:?} ({:?}/{:?}, fee {})", pool, state.token0, state.token1, state.fee);
+        self.v3_pools.write().await.insert(pool, state);
+        Ok(true)
+    }
+
+    /// Stops tracking every pool, V2 or V3, that trades `token`. Returns how many were dropped.
+    pub async fn forget_token(&self, token: Address) -> usize {
+        let mut pools = self.pools.write().await;
+        let mut v3_pools = self.v3_pools.write().await;
+        let before = pools.len() + v3_pools.len();
+        pools.retain(|_, p| !p.has_token(token));
+        v3_pools.retain(|_, p| p.token0 != token && p.token1 != token);
+        before - pools.len() - v3_pools.len()
+    }
+
+    /// The (factory, router) pairs pools are discovered on.
+    pub fn dexes(&self) -> &[(Address, Address)] {
+        &self.dexes
+    }
+
+    // Discover V3 pools between the given tokens across the standard fee tiers
+    pub async fn discover_v3(&self, tokens: &[Address], block: U64) -> Result<()> {
+        let factory = IUniswapV3Factory::new(UNISWAP_V3_FACTORY.parse::<Address>()?, self.provider.clone());
+
+        for (i, token_a) in tokens.iter().enumerate() {
+            for token_b in &tokens[i + 1..] {
+                for fee in FeeAmount::V3_TIERS {
+                    let pool = factory.get_pool(*token_a, *token_b, fee.pips()).call().await?;
+                    if pool == Address::zero() || self.v3_pools.read().await.contains_key(&pool) {
+                        continue;
+                    }
+                    let state = self.load_v3(pool, block).await?;
+                    debug!(
+                        "Tracking V3 pool {:?} ({:?}/{:?}, fee {}, {} initialized ticks)",
+                        pool, state.token0, state.token1, fee, state.ticks.len()
+                    );
+                    self.v3_pools.write().await.insert(pool, state);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // slot0, active liquidity and every initialized tick within TICK_WORDS bitmap
+    // words of the current tick, all read at `block`
+    async fn load_v3(&self, address: Address, block: U64) -> Result<V3PoolState> {
+        let pool = IUniswapV3Pool::new(address, self.provider.clone());
+        let token0 = pool.token_0().block(block).call().await?;
+        let token1 = pool.token_1().block(block).call().await?;
+        let fee = FeeAmount::new(pool.fee().block(block).call().await?)?;
+        let tick_spacing = pool.tick_spacing().block(block).call().await?;
+        let (sqrt_price_x96, tick, ..) = pool.slot_0().block(block).call().await?;
+        let liquidity = pool.liquidity().block(block).call().await?;
+
+        let current_word = (tick.div_euclid(tick_spacing) >> 8) as i16;
+        let min_word = ((MIN_TICK / tick_spacing) >> 8) as i16;
+        let max_word = ((MAX_TICK / tick_spacing) >> 8) as i16;
+        let first_word = current_word.saturating_sub(TICK_WORDS as i16).max(min_word);
+        let last_word = current_word.saturating_add(TICK_WORDS as i16).min(max_word);
+
+        let mut multicall = Multicall::new(self.provider.clone(), None).await?.block(block);
+        for word in first_word..=last_word {
+            multicall.add_call(pool.tick_bitmap(word), false);
+        }
+        let bitmaps = multicall.call_raw().await?;
+
+        let mut initialized = Vec::new();
+        for (word, bitmap) in (first_word..=last_word).zip(bitmaps) {
+            let bitmap = match bitmap {
+                Ok(Token::Uint(b)) => b,
+                _ => continue,
+            };
+            for bit in 0..256 {
+                if bitmap.bit(bit) {
+                    initialized.push(((word as i32) * 256 + bit as i32) * tick_spacing);
+                }
+            }
+        }
+
+        let mut ticks = BTreeMap::new();
+        for chunk in initialized.chunks(200) {
+            let mut multicall = Multicall::new(self.provider.clone(), None).await?.block(block);
+            for tick in chunk {
+                multicall.add_call(pool.ticks(*tick), false);
+            }
+            for (tick, result) in chunk.iter().zip(multicall.call_raw().await?) {
+                if let Ok(Token::Tuple(fields)) = result {
+                    if let (Some(Token::Uint(gross)), Some(Token::Int(net))) = (fields.first(), fields.get(1)) {
+                        ticks.insert(*tick, TickInfo {
+                            liquidity_gross: gross.as_u128(),
+                            liquidity_net: I256::from_raw(*net).as_i128(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(V3PoolState {
+            address,
+            token0,
+            token1,
+            fee,
+            tick_spacing,
+            sqrt_price_x96,
+            tick,
+            liquidity,
+            ticks,
+            range_lower: (first_word as i32) * 256 * tick_spacing,
+            range_upper: ((last_word as i32) * 256 + 255) * tick_spacing,
+            last_updated_block: block,
+        })
+    }
+
+    // Applies the block's Mint/Burn/Swap logs to tracked V3 pools; pools that were
+    // invalidated by a reorg, or whose price drifted near the edge of the fetched
+    // tick range, are reloaded from scratch instead
+    async fn refresh_v3(&self, block: U64) -> Result<()> {
+        let addresses: Vec<Address> = self.v3_pools.read().await.keys().copied().collect();
+        if addresses.is_empty() {
+            return Ok(());
+        }
+
+        let filter = Filter::new()
+            .address(addresses.clone())
+            .topic0(vec![V3PoolState::mint_topic(), V3PoolState::burn_topic(), V3PoolState::swap_topic()])
+            .from_block(block)
+            .to_block(block);
+        let logs = self.provider.get_logs(&filter).await?;
+
+        let mut stale = Vec::new();
+        {
+            let mut pools = self.v3_pools.write().await;
+            for log in &logs {
+                if let Some(pool) = pools.get_mut(&log.address) {
+                    if !pool.last_updated_block.is_zero() && log.block_number > Some(pool.last_updated_block) {
+                        pool.apply_log(log);
+                    }
+                }
+            }
+            for pool in pools.values_mut() {
+                let margin = pool.tick_spacing * 256;
+                let near_edge = pool.tick - margin < pool.range_lower || pool.tick + margin > pool.range_upper;
+                if pool.last_updated_block.is_zero() || near_edge {
+                    stale.push(pool.address);
+                } else {
+                    pool.last_updated_block = block;
+                }
+            }
+        }
+
+        for address in stale {
+            match self.load_v3(address, block).await {
+                Ok(state) => {
+                    self.v3_pools.write().await.insert(address, state);
+                }
+                Err(e) => warn!("Failed to reload V3 pool {:?}: {:?}", address, e),
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn refresh(&self, block: U64) -> Result<()> {
+        if let Err(e) = self.refresh_v3(block).await {
+            warn!("Failed to refresh V3 pools: {:?}", e);
+        }
+
+        let addresses: Vec<Address> = self.pools.read().await.keys().copied().collect();
+
+        for address in addresses {
+            let contract = IUniswapV2Pair::new(address, self.provider.clone());
+            match contract.get_reserves().block(block).call().await {
+                Ok((reserve0, reserve1, _)) => {
+                    if let Some(pool) = self.pools.write().await.get_mut(&address) {
+                        pool.reserve0 = U256::from(reserve0);
+                        pool.reserve1 = U256::from(reserve1);
+                        pool.last_updated_block = block;
+                    }
+                }
+                Err(e) => warn!("Failed to refresh reserves for {:?}: {:?}", address, e),
+            }
+        }
+        Ok(())
+    }
+
+    // Reserves read from abandoned blocks can't be trusted, force a full re-read
+    pub async fn invalidate_after(&self, block: U64) {
+        for pool in self.pools.write().await.values_mut() {
+            if pool.last_updated_block > block {
+                pool.reserve0 = U256::zero();
+                pool.reserve1 = U256::zero();
+                pool.last_updated_block = U64::zero();
+            }
+        }
+        for pool in self.v3_pools.write().await.values_mut() {
+            if pool.last_updated_block > block {
+                pool.last_updated_block = U64::zero();
+            }
+        }
+    }
+
+    pub async fn get(&self, pool: Address) -> Option<PoolState> {
+        self.pools.read().await.get(&pool).cloned()
+    }
+
+    pub async fn get_v3(&self, pool: Address) -> Option<V3PoolState> {
+        self.v3_pools.read().await.get(&pool).cloned()
+    }
+
+    /// Exact V3 output across tick boundaries, or None if the pool isn't tracked,
+    /// is awaiting a reload, or the swap runs past the fetched ticks.
+    pub async fn v3_amount_out(&self, pool: Address, token_in: Address, amount_in: U256) -> Option<U256> {
+        let pools = self.v3_pools.read().await;
+        let state = pools.get(&pool)?;
+        if state.last_updated_block.is_zero() {
+            return None;
+        }
+        state.amount_out(token_in, amount_in)
+    }
+
+    pub async fn v3_pools_for_pair(&self, token_a: Address, token_b: Address) -> Vec<V3PoolState> {
+        self.v3_pools
+            .read()
+            .await
+            .values()
+            .filter(|p| (p.token0 == token_a && p.token1 == token_b) || (p.token0 == token_b && p.token1 == token_a))
+            .cloned()
+            .collect()
+    }
+
+    /// Tracked (V2, V3) pool counts.
+    pub async fn counts(&self) -> (usize, usize) {
+        (self.pools.read().await.len(), self.v3_pools.read().await.len())
+    }
+
+    pub async fn snapshot(&self) -> Vec<PoolState> {
+        self.pools.read().await.values().cloned().collect()
+    }
+
+    /// V3 pools with loaded ticks; ones awaiting a reload are left out.
+    pub async fn v3_snapshot(&self) -> Vec<V3PoolState> {
+        self.v3_pools.read().await.values().filter(|p| !p.last_updated_block.is_zero()).cloned().collect()
+    }
+
+    pub async fn registry_snapshot(&self) -> PoolRegistrySnapshot {
+        PoolRegistrySnapshot {
+            pools: self.pools.read().await.values().cloned().collect(),
+            v3_pools: self.v3_pools.read().await.values().cloned().collect(),
+        }
+    }
+
+    /// Loads a registry saved by a previous run. V2 reserves are re-read on the
+    /// next refresh anyway; V3 pools replay the logs they missed up to `head`, or
+    /// are marked for a full reload if the snapshot is too old for that.
+    pub async fn restore(&self, snapshot: PoolRegistrySnapshot, head: U64) -> Result<()> {
+        {
+            let mut pools = self.pools.write().await;
+            for pool in snapshot.pools {
+                pools.insert(pool.address, pool);
+            }
+        }
+
+        let mut v3_pools = self.v3_pools.write().await;
+        for pool in snapshot.v3_pools {
+            v3_pools.insert(pool.address, pool);
+        }
+        let from = match v3_pools.values().map(|p| p.last_updated_block).filter(|b| !b.is_zero()).min() {
+            Some(from) if from < head => from + 1,
+            _ => return Ok(()),
+        };
+        if head.as_u64() - from.as_u64() >= MAX_CATCH_UP_BLOCKS {
+            debug!("V3 snapshot is {} blocks old, reloading pools", head - from);
+            for pool in v3_pools.values_mut() {
+                pool.last_updated_block = U64::zero();
+            }
+            return Ok(());
+        }
+
+        let filter = Filter::new()
+            .address(v3_pools.keys().copied().collect::<Vec<_>>())
+            .topic0(vec![V3PoolState::mint_topic(), V3PoolState::burn_topic(), V3PoolState::swap_topic()])
+            .from_block(from)
+            .to_block(head);
+        let logs = match self.provider.get_logs(&filter).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                // Without the missed logs the saved ticks can't be trusted
+                for pool in v3_pools.values_mut() {
+                    pool.last_updated_block = U64::zero();
+                }
+                return Err(e.into());
+            }
+        };
+        for log in &logs {
+            if let Some(pool) = v3_pools.get_mut(&log.address) {
+                if !pool.last_updated_block.is_zero() && log.block_number > Some(pool.last_updated_block) {
+                    pool.apply_log(log);
+                }
+            }
+        }
+        for pool in v3_pools.values_mut().filter(|p| !p.last_updated_block.is_zero()) {
+            pool.last_updated_block = head;
+        }
+        debug!("Replayed {} V3 logs from block {} to {}", logs.len(), from, head);
+        Ok(())
+    }
+
+    pub async fn pools_for_pair(&self, token_a: Address, token_b: Address) -> Vec<PoolState> {
+        self.pools
+            .read()
+            .await
+            .values()
+            .filter(|p| p.has_token(token_a) && p.has_token(token_b))
+            .cloned()
+            .collect()
+    }
+}
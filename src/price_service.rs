@@ -0,0 +1,118 @@
+// src/price_service.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::oracle_monitor::OracleMonitor;
+use crate::pool_state::{PoolState, PoolStateManager};
+use crate::token_registry::{units, TokenRegistry};
+
+// Blocks of spot samples averaged into a pool TWAP (~1 minute on Polygon)
+const TWAP_WINDOW: usize = 30;
+
+#[derive(Debug, Clone)]
+pub struct UsdPolicy {
+    // Skip opportunities netting less than this after gas
+    pub min_profit_usd: f64,
+    // Skip opportunities that put more than this much capital on the line
+    pub max_at_risk_usd: f64,
+    // Refuse submissions whose worst outcome (reverted or landed) costs more
+    pub max_loss_usd: f64,
+}
+
+impl Default for UsdPolicy {
+    fn default() -> Self {
+        Self {
+            min_profit_usd: 1.0,
+            max_at_risk_usd: 250_000.0,
+            max_loss_usd: 25.0,
+        }
+    }
+}
+
+/// USD prices for any token we trade: Chainlink where a feed exists, otherwise a
+/// block-sampled TWAP of the token's WMATIC pool priced through the MATIC feed.
+pub struct PriceService {
+    oracle: Arc<OracleMonitor>,
+    tokens: Arc<TokenRegistry>,
+    pool_state: Arc<PoolStateManager>,
+    // TWAPs are quoted against this and priced through its feed
+    wrapped_native: Address,
+    // token -> recent WMATIC-per-token spot samples
+    twap_samples: Mutex<HashMap<Address, VecDeque<f64>>>,
+}
+
+impl PriceService {
+    pub fn new(oracle: Arc<OracleMonitor>, tokens: Arc<TokenRegistry>, pool_state: Arc<PoolStateManager>, wrapped_native: Address) -> Self {
+        Self {
+            oracle,
+            tokens,
+            pool_state,
+            wrapped_native,
+            twap_samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Samples the spot WMATIC price of every token with a WMATIC pool.
+    pub async fn on_block(&self) -> Result<()> {
+        let wmatic = self.wrapped_native;
+        let mut samples = self.twap_samples.lock().await;
+        for pool in self.pool_state.snapshot().await {
+            if !pool.has_token(wmatic) || pool.reserve0.is_zero() || pool.reserve1.is_zero() {
+                continue;
+            }
+            let token = pool.other_token(wmatic);
+            let (token_reserve, wmatic_reserve) = if pool.token0 == token {
+                (pool.reserve0, pool.reserve1)
+            } else {
+                (pool.reserve1, pool.reserve0)
+            };
+            let decimals = self.tokens.decimals(token).await?;
+            let price = units(I256::from_raw(wmatic_reserve), 18) / units(I256::from_raw(token_reserve), decimals);
+
+            let window = samples.entry(token).or_default();
+            if window.len() == TWAP_WINDOW {
+                window.pop_front();
+            }
+            window.push_back(price);
+        }
+        Ok(())
+    }
+
+    pub async fn usd_price(&self, token: Address) -> Result<Option<f64>> {
+        if let Some(price) = self.oracle.latest_usd_price(token).await? {
+            return Ok(Some(price));
+        }
+
+        let twap = {
+            let samples = self.twap_samples.lock().await;
+            match samples.get(&token) {
+                Some(window) if !window.is_empty() => window.iter().sum::<f64>() / window.len() as f64,
+                _ => return Ok(None),
+            }
+        };
+        Ok(self.oracle.latest_usd_price(self.wrapped_native).await?.map(|matic| twap * matic))
+    }
+
+    /// Reserves of both sides in USD. A pool with one priced side is valued at
+    /// twice that side; None when neither side has a price.
+    pub async fn pool_tvl_usd(&self, pool: &PoolState) -> Result<Option<f64>> {
+        let side0 = self.usd_value(pool.token0, pool.reserve0).await?;
+        let side1 = self.usd_value(pool.token1, pool.reserve1).await?;
+        Ok(match (side0, side1) {
+            (Some(a), Some(b)) => Some(a + b),
+            (Some(side), None) | (None, Some(side)) => Some(2.0 * side),
+            (None, None) => None,
+        })
+    }
+
+    pub async fn usd_value(&self, token: Address, amount: U256) -> Result<Option<f64>> {
+        let price = match self.usd_price(token).await? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        Ok(Some(self.tokens.to_units(token, I256::from_raw(amount)).await? * price))
+    }
+}
@@ -0,0 +1,122 @@
+// src/private_rpc.rs
+use anyhow::{anyhow, Result};
+use ethers::types::{Bytes, H256, U64};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::info;
+
+use crate::retry::{retry, transient_status, RetryPolicy};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrivateRpcMethod {
+    // Endpoint keeps everything sent to it private (Merkle, GetBlock private tx)
+    SendRawTransaction,
+    // Flashbots-style private tx with an inclusion deadline
+    SendPrivateTransaction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubmissionRoute {
+    Public,
+    Private,
+    // Atomic with other txs in a block (relay or FastLane bundle)
+    Bundle,
+}
+
+impl SubmissionRoute {
+    /// Whether a venue on `venue` satisfies this route. Bundles are also private,
+    /// so they stand in for a missing private RPC; nothing stands in for a bundle.
+    pub fn allows(self, venue: SubmissionRoute) -> bool {
+        match self {
+            SubmissionRoute::Public => true,
+            SubmissionRoute::Private => venue != SubmissionRoute::Public,
+            SubmissionRoute::Bundle => venue == SubmissionRoute::Bundle,
+        }
+    }
+}
+
+/// Which route each strategy's transactions take. Liquidations race in the open
+/// mempool anyway; arbitrage calldata leaks the opportunity and gets counter-sandwiched.
+/// Sandwiches and JIT only work ordered around their victim, so they need a bundle.
+#[derive(Debug, Clone)]
+pub struct SubmissionPolicy {
+    routes: HashMap<String, SubmissionRoute>,
+    default: SubmissionRoute,
+}
+
+impl Default for SubmissionPolicy {
+    fn default() -> Self {
+        let mut routes = HashMap::new();
+        routes.insert("liquidation".to_string(), SubmissionRoute::Public);
+        routes.insert("arbitrage".to_string(), SubmissionRoute::Private);
+        routes.insert("sandwich".to_string(), SubmissionRoute::Bundle);
+        routes.insert("jit".to_string(), SubmissionRoute::Bundle);
+        Self {
+            routes,
+            default: SubmissionRoute::Private,
+        }
+    }
+}
+
+impl SubmissionPolicy {
+    pub fn route_for(&self, strategy: &str) -> SubmissionRoute {
+        self.routes.get(strategy).copied().unwrap_or(self.default)
+    }
+
+    pub fn set_route(&mut self, strategy: &str, route: SubmissionRoute) {
+        self.routes.insert(strategy.to_string(), route);
+    }
+}
+
+pub struct PrivateRpcClient {
+    http: reqwest::Client,
+    url: String,
+    method: PrivateRpcMethod,
+    retry: RetryPolicy,
+}
+
+impl PrivateRpcClient {
+    pub fn new(url: impl Into<String>, method: PrivateRpcMethod) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(3))
+                .build()
+                .expect("build http client"),
+            url: url.into(),
+            method,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub async fn send_transaction(&self, raw: &Bytes, max_block: Option<U64>) -> Result<H256> {
+        let (method, params) = match self.method {
+            PrivateRpcMethod::SendRawTransaction => ("eth_sendRawTransaction", json!([raw])),
+            PrivateRpcMethod::SendPrivateTransaction => {
+                let mut request = json!({ "tx": raw });
+                if let Some(block) = max_block {
+                    request["maxBlockNumber"] = json!(block);
+                }
+                ("eth_sendPrivateTransaction", json!([request]))
+            }
+        };
+
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let request = &request;
+        let response: Value = retry(&self.retry, method, || async move {
+            let response = self.http.post(&self.url).json(request).send().await?;
+            Ok(transient_status(response)?.json().await?)
+        })
+        .await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{} failed on private RPC: {}", method, error));
+        }
+        let hash: H256 = serde_json::from_value(
+            response.get("result").cloned().ok_or_else(|| anyhow!("{} returned no result", method))?,
+        )?;
+
+        info!("Sent private transaction {:?}", hash);
+        Ok(hash)
+    }
+}
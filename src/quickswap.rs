@@ -1,7 +1,10 @@
-use log::info;
+use tracing::debug;
 use ethers::prelude::*;
-use ethers::abi::{Abi, AbiParser, FunctionExt, Token};
+use ethers::abi::{Abi, AbiParser, Function, FunctionExt, Token};
 use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+use crate::strategies::multicall::swap_legs;
 
 // ---- QuickSwap Polygon addresses ----
 pub static QUICKSWAP_ROUTER_ADDR: Lazy<Address> = Lazy::new(|| {
@@ -29,6 +32,11 @@ pub static QUICKSWAP_ROUTER_ABI: Lazy<Abi> = Lazy::new(|| {
     ]).expect("parse quickswap abi")
 });
 
+// Selector -> function, so parsing is one lookup instead of a scan of the ABI
+static QUICKSWAP_SELECTORS: Lazy<HashMap<[u8; 4], &'static Function>> = Lazy::new(|| {
+    QUICKSWAP_ROUTER_ABI.functions().map(|f| (f.selector(), f)).collect()
+});
+
 // Polygon mains
 pub const WMATIC: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
 pub const USDC_E: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
@@ -46,7 +54,7 @@ abigen!(IUniswapV2Pair, r#"[
     function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
 ]"#);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum QuickSwapAction {
     // exact-in (input is fixed, output >= min)
     SwapExactTokensForTokens {
@@ -142,147 +150,280 @@ pub fn parse_quickswap_tx(tx: &Transaction) -> Option<QuickSwapAction> {
     if tx.to != Some(*QUICKSWAP_ROUTER_ADDR) {
         return None;
     }
-    let input = &tx.input.0;
+    // Wallets that batch a permit with the swap send it through multicall
+    swap_legs(&tx.input.0).iter().find_map(|leg| decode_router_call(tx, leg))
+}
+
+// ETH-in variants take their input amount from the tx value
+fn decode_router_call(tx: &Transaction, input: &[u8]) -> Option<QuickSwapAction> {
     if input.len() < 4 { return None; }
-    let selector = &input[..4];
+    let selector: [u8; 4] = input[..4].try_into().ok()?;
 
     // Find the matching function in our ABI
-    for f in QUICKSWAP_ROUTER_ABI.functions() {
-        if f.selector() == selector {
-            // Decode the calldata (skip selector)
-            let tokens = f.decode_input(&input[4..]).ok()?;
-            let name = f.name.as_str();
+    if let Some(f) = QUICKSWAP_SELECTORS.get(&selector) {
+        // Decode the calldata (skip selector)
+        let tokens = f.decode_input(&input[4..]).ok()?;
+        let name = f.name.as_str();
 
-            // Helper to map Token::Array(Address) -> Vec<Address>
-            fn to_addr_vec(t: &Token) -> Option<Vec<Address>> {
-                match t {
-                    Token::Array(v) => {
-                        let mut out = Vec::with_capacity(v.len());
-                        for x in v {
-                            if let Token::Address(a) = x { out.push(*a); } else { return None; }
-                        }
-                        Some(out)
+        // Helper to map Token::Array(Address) -> Vec<Address>
+        fn to_addr_vec(t: &Token) -> Option<Vec<Address>> {
+            match t {
+                Token::Array(v) => {
+                    let mut out = Vec::with_capacity(v.len());
+                    for x in v {
+                        if let Token::Address(a) = x { out.push(*a); } else { return None; }
                     }
-                    _ => None
+                    Some(out)
                 }
+                _ => None
             }
-            // Helper to get U256
-            fn to_u256(t: &Token) -> Option<U256> {
-                match t { Token::Uint(u) => Some(*u), _ => None }
+        }
+        // Helper to get U256
+        fn to_u256(t: &Token) -> Option<U256> {
+            match t { Token::Uint(u) => Some(*u), _ => None }
+        }
+        // Helper to get Address
+        fn to_addr(t: &Token) -> Option<Address> {
+            match t { Token::Address(a) => Some(*a), _ => None }
+        }            
+        match name {
+            // --- exact in ---
+            "swapExactTokensForTokens" => {
+                // (amountIn, amountOutMin, path, to, deadline)
+                if tokens.len() != 5 { return None; }
+                return Some(QuickSwapAction::SwapExactTokensForTokens {
+                    amount_in:      to_u256(&tokens[0])?,
+                    amount_out_min: to_u256(&tokens[1])?,
+                    path:           to_addr_vec(&tokens[2])?,
+                    to:             to_addr(&tokens[3])?,
+                    deadline:       to_u256(&tokens[4])?,
+                });
+            }
+            "swapExactTokensForETH" => {
+                if tokens.len() != 5 { return None; }
+                return Some(QuickSwapAction::SwapExactTokensForETH {
+                    amount_in:      to_u256(&tokens[0])?,
+                    amount_out_min: to_u256(&tokens[1])?,
+                    path:           to_addr_vec(&tokens[2])?,
+                    to:             to_addr(&tokens[3])?,
+                    deadline:       to_u256(&tokens[4])?,
+                });
+            }
+            "swapExactETHForTokens" => {
+                // (amountOutMin, path, to, deadline); amountIn is tx.value
+                if tokens.len() != 4 { return None; }
+                return Some(QuickSwapAction::SwapExactETHForTokens {
+                    amount_in:      tx.value, // from msg.value
+                    amount_out_min: to_u256(&tokens[0])?,
+                    path:           to_addr_vec(&tokens[1])?,
+                    to:             to_addr(&tokens[2])?,
+                    deadline:       to_u256(&tokens[3])?,
+                });
             }
-            // Helper to get Address
-            fn to_addr(t: &Token) -> Option<Address> {
-                match t { Token::Address(a) => Some(*a), _ => None }
-            }            
-            match name {
-                // --- exact in ---
-                "swapExactTokensForTokens" => {
-                    // (amountIn, amountOutMin, path, to, deadline)
-                    if tokens.len() != 5 { return None; }
-                    return Some(QuickSwapAction::SwapExactTokensForTokens {
-                        amount_in:      to_u256(&tokens[0])?,
-                        amount_out_min: to_u256(&tokens[1])?,
-                        path:           to_addr_vec(&tokens[2])?,
-                        to:             to_addr(&tokens[3])?,
-                        deadline:       to_u256(&tokens[4])?,
-                    });
-                }
-                "swapExactTokensForETH" => {
-                    if tokens.len() != 5 { return None; }
-                    return Some(QuickSwapAction::SwapExactTokensForETH {
-                        amount_in:      to_u256(&tokens[0])?,
-                        amount_out_min: to_u256(&tokens[1])?,
-                        path:           to_addr_vec(&tokens[2])?,
-                        to:             to_addr(&tokens[3])?,
-                        deadline:       to_u256(&tokens[4])?,
-                    });
-                }
-                "swapExactETHForTokens" => {
-                    // (amountOutMin, path, to, deadline); amountIn is tx.value
-                    if tokens.len() != 4 { return None; }
-                    return Some(QuickSwapAction::SwapExactETHForTokens {
-                        amount_in:      tx.value, // from msg.value
-                        amount_out_min: to_u256(&tokens[0])?,
-                        path:           to_addr_vec(&tokens[1])?,
-                        to:             to_addr(&tokens[2])?,
-                        deadline:       to_u256(&tokens[3])?,
-                    });
-                }
-
-                // --- exact out ---
-                "swapTokensForExactTokens" => {
-                    // (amountOut, amountInMax, path, to, deadline)
-                    if tokens.len() != 5 { return None; }
-                    return Some(QuickSwapAction::SwapTokensForExactTokens {
-                        amount_out:   to_u256(&tokens[0])?,
-                        amount_in_max:to_u256(&tokens[1])?,
-                        path:         to_addr_vec(&tokens[2])?,
-                        to:           to_addr(&tokens[3])?,
-                        deadline:     to_u256(&tokens[4])?,
-                    });
-                }
-                "swapTokensForExactETH" => {
-                    if tokens.len() != 5 { return None; }
-                    return Some(QuickSwapAction::SwapTokensForExactETH {
-                        amount_out:    to_u256(&tokens[0])?,
-                        amount_in_max: to_u256(&tokens[1])?,
-                        path:          to_addr_vec(&tokens[2])?,
-                        to:            to_addr(&tokens[3])?,
-                        deadline:      to_u256(&tokens[4])?,
-                    });
-                }
-                "swapETHForExactTokens" => {
-                    // (amountOut, path, to, deadline); input cap is tx.value
-                    if tokens.len() != 4 { return None; }
-                    return Some(QuickSwapAction::SwapETHForExactTokens {
-                        amount_out:    to_u256(&tokens[0])?,
-                        path:          to_addr_vec(&tokens[1])?,
-                        to:            to_addr(&tokens[2])?,
-                        deadline:      to_u256(&tokens[3])?,
-                        amount_in_max: tx.value,
-                    });
-                }
 
-                // --- fee-on-transfer variants (exact in) ---
-                "swapExactTokensForTokensSupportingFeeOnTransferTokens" => {
-                    if tokens.len() != 5 { return None; }
-                    return Some(QuickSwapAction::SwapExactTokensForTokensSupportingFeeOnTransferTokens {
-                        amount_in:      to_u256(&tokens[0])?,
-                        amount_out_min: to_u256(&tokens[1])?,
-                        path:           to_addr_vec(&tokens[2])?,
-                        to:             to_addr(&tokens[3])?,
-                        deadline:       to_u256(&tokens[4])?,
-                    });
-                }
-                "swapExactTokensForETHSupportingFeeOnTransferTokens" => {
-                    if tokens.len() != 5 { return None; }
-                    return Some(QuickSwapAction::SwapExactTokensForETHSupportingFeeOnTransferTokens {
-                        amount_in:      to_u256(&tokens[0])?,
-                        amount_out_min: to_u256(&tokens[1])?,
-                        path:           to_addr_vec(&tokens[2])?,
-                        to:             to_addr(&tokens[3])?,
-                        deadline:       to_u256(&tokens[4])?,
-                    });
-                }
-                "swapExactETHForTokensSupportingFeeOnTransferTokens" => {
-                    if tokens.len() != 4 { return None; }
-                    return Some(QuickSwapAction::SwapExactETHForTokensSupportingFeeOnTransferTokens {
-                        amount_in:      tx.value,
-                        amount_out_min: to_u256(&tokens[0])?,
-                        path:           to_addr_vec(&tokens[1])?,
-                        to:             to_addr(&tokens[2])?,
-                        deadline:       to_u256(&tokens[3])?,
-                    });
-                }
+            // --- exact out ---
+            "swapTokensForExactTokens" => {
+                // (amountOut, amountInMax, path, to, deadline)
+                if tokens.len() != 5 { return None; }
+                return Some(QuickSwapAction::SwapTokensForExactTokens {
+                    amount_out:   to_u256(&tokens[0])?,
+                    amount_in_max:to_u256(&tokens[1])?,
+                    path:         to_addr_vec(&tokens[2])?,
+                    to:           to_addr(&tokens[3])?,
+                    deadline:     to_u256(&tokens[4])?,
+                });
+            }
+            "swapTokensForExactETH" => {
+                if tokens.len() != 5 { return None; }
+                return Some(QuickSwapAction::SwapTokensForExactETH {
+                    amount_out:    to_u256(&tokens[0])?,
+                    amount_in_max: to_u256(&tokens[1])?,
+                    path:          to_addr_vec(&tokens[2])?,
+                    to:            to_addr(&tokens[3])?,
+                    deadline:      to_u256(&tokens[4])?,
+                });
+            }
+            "swapETHForExactTokens" => {
+                // (amountOut, path, to, deadline); input cap is tx.value
+                if tokens.len() != 4 { return None; }
+                return Some(QuickSwapAction::SwapETHForExactTokens {
+                    amount_out:    to_u256(&tokens[0])?,
+                    path:          to_addr_vec(&tokens[1])?,
+                    to:            to_addr(&tokens[2])?,
+                    deadline:      to_u256(&tokens[3])?,
+                    amount_in_max: tx.value,
+                });
+            }
 
-                // Unknown (not in our minimal ABI list)
-                _ =>{
-                    info!("Quickswap tx not in our abi list");
-                    return None
+            // --- fee-on-transfer variants (exact in) ---
+            "swapExactTokensForTokensSupportingFeeOnTransferTokens" => {
+                if tokens.len() != 5 { return None; }
+                return Some(QuickSwapAction::SwapExactTokensForTokensSupportingFeeOnTransferTokens {
+                    amount_in:      to_u256(&tokens[0])?,
+                    amount_out_min: to_u256(&tokens[1])?,
+                    path:           to_addr_vec(&tokens[2])?,
+                    to:             to_addr(&tokens[3])?,
+                    deadline:       to_u256(&tokens[4])?,
+                });
+            }
+            "swapExactTokensForETHSupportingFeeOnTransferTokens" => {
+                if tokens.len() != 5 { return None; }
+                return Some(QuickSwapAction::SwapExactTokensForETHSupportingFeeOnTransferTokens {
+                    amount_in:      to_u256(&tokens[0])?,
+                    amount_out_min: to_u256(&tokens[1])?,
+                    path:           to_addr_vec(&tokens[2])?,
+                    to:             to_addr(&tokens[3])?,
+                    deadline:       to_u256(&tokens[4])?,
+                });
+            }
+            "swapExactETHForTokensSupportingFeeOnTransferTokens" => {
+                if tokens.len() != 4 { return None; }
+                return Some(QuickSwapAction::SwapExactETHForTokensSupportingFeeOnTransferTokens {
+                    amount_in:      tx.value,
+                    amount_out_min: to_u256(&tokens[0])?,
+                    path:           to_addr_vec(&tokens[1])?,
+                    to:             to_addr(&tokens[2])?,
+                    deadline:       to_u256(&tokens[3])?,
+                });
+            }
 
-                } 
+            // Unknown (not in our minimal ABI list); the monitor's decode
+            // coverage report lists these selectors with sample txs
+            _ => {
+                debug!("Quickswap tx not in our abi list");
+                return None
             }
         }
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn address() -> impl Strategy<Value = Address> {
+        any::<[u8; 20]>().prop_map(Address::from)
+    }
+
+    fn amount() -> impl Strategy<Value = U256> {
+        any::<[u8; 32]>().prop_map(|b| U256::from_big_endian(&b))
+    }
+
+    fn router_tx(input: Vec<u8>, value: U256) -> Transaction {
+        Transaction {
+            to: Some(*QUICKSWAP_ROUTER_ADDR),
+            input: Bytes::from(input),
+            value,
+            ..Default::default()
+        }
+    }
+
+    // What each entrypoint should decode to. `a` and `b` are the leading amounts
+    // in calldata order; payable entrypoints take only `a` and read tx.value
+    fn expected(name: &str, a: U256, b: U256, path: Vec<Address>, to: Address, deadline: U256, value: U256) -> QuickSwapAction {
+        match name {
+            "swapExactTokensForTokens" => QuickSwapAction::SwapExactTokensForTokens { amount_in: a, amount_out_min: b, path, to, deadline },
+            "swapExactTokensForETH" => QuickSwapAction::SwapExactTokensForETH { amount_in: a, amount_out_min: b, path, to, deadline },
+            "swapExactETHForTokens" => QuickSwapAction::SwapExactETHForTokens { amount_in: value, amount_out_min: a, path, to, deadline },
+            "swapTokensForExactTokens" => QuickSwapAction::SwapTokensForExactTokens { amount_out: a, amount_in_max: b, path, to, deadline },
+            "swapTokensForExactETH" => QuickSwapAction::SwapTokensForExactETH { amount_out: a, amount_in_max: b, path, to, deadline },
+            "swapETHForExactTokens" => QuickSwapAction::SwapETHForExactTokens { amount_out: a, path, to, deadline, amount_in_max: value },
+            "swapExactTokensForTokensSupportingFeeOnTransferTokens" => {
+                QuickSwapAction::SwapExactTokensForTokensSupportingFeeOnTransferTokens { amount_in: a, amount_out_min: b, path, to, deadline }
+            }
+            "swapExactTokensForETHSupportingFeeOnTransferTokens" => {
+                QuickSwapAction::SwapExactTokensForETHSupportingFeeOnTransferTokens { amount_in: a, amount_out_min: b, path, to, deadline }
+            }
+            "swapExactETHForTokensSupportingFeeOnTransferTokens" => {
+                QuickSwapAction::SwapExactETHForTokensSupportingFeeOnTransferTokens { amount_in: value, amount_out_min: a, path, to, deadline }
+            }
+            other => panic!("{} has no expected action", other),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips_every_entrypoint(
+            index in 0..9usize,
+            a in amount(),
+            b in amount(),
+            path in prop::collection::vec(address(), 2..5),
+            to in address(),
+            deadline in amount(),
+            value in amount(),
+        ) {
+            let function = QUICKSWAP_ROUTER_ABI.functions().nth(index).unwrap();
+            let payable = function.inputs.len() == 4;
+            let mut tokens = vec![Token::Uint(a)];
+            if !payable {
+                tokens.push(Token::Uint(b));
+            }
+            tokens.extend([Token::Array(path.iter().copied().map(Token::Address).collect()), Token::Address(to), Token::Uint(deadline)]);
+            let input = function.encode_input(&tokens).unwrap();
+
+            let decoded = parse_quickswap_tx(&router_tx(input, value));
+            prop_assert_eq!(decoded, Some(expected(&function.name, a, b, path, to, deadline, value)));
+        }
+
+        #[test]
+        fn arbitrary_calldata_never_panics(input in prop::collection::vec(any::<u8>(), 0..512)) {
+            let _ = parse_quickswap_tx(&router_tx(input, U256::zero()));
+        }
+
+        // A known selector in front of garbage, so decoding gets past the lookup
+        #[test]
+        fn garbage_after_known_selector_never_panics(index in 0..9usize, body in prop::collection::vec(any::<u8>(), 0..512)) {
+            let selector = QUICKSWAP_ROUTER_ABI.functions().nth(index).unwrap().short_signature();
+            let _ = parse_quickswap_tx(&router_tx([selector.to_vec(), body].concat(), U256::zero()));
+        }
+    }
+
+    #[test]
+    fn ignores_other_routers() {
+        let function = QUICKSWAP_ROUTER_ABI.function("swapExactTokensForTokens").unwrap();
+        let tokens = [Token::Uint(1.into()), Token::Uint(0.into()), Token::Array(vec![]), Token::Address(Address::zero()), Token::Uint(0.into())];
+        let mut tx = router_tx(function.encode_input(&tokens).unwrap(), U256::zero());
+        tx.to = Some(Address::repeat_byte(1));
+        assert_eq!(parse_quickswap_tx(&tx), None);
+    }
+
+    #[test]
+    fn decodes_swap_batched_behind_a_permit() {
+        let path = vec![WMATIC.parse().unwrap(), USDC_E.parse().unwrap()];
+        let swap = QUICKSWAP_ROUTER_ABI.function("swapExactTokensForTokens").unwrap().encode_input(&[
+            Token::Uint(5.into()),
+            Token::Uint(4.into()),
+            Token::Array(path.iter().copied().map(Token::Address).collect()),
+            Token::Address(Address::repeat_byte(2)),
+            Token::Uint(9.into()),
+        ]).unwrap();
+        let permit = AbiParser::default()
+            .parse_function("selfPermit(address,uint256,uint256,uint8,bytes32,bytes32)")
+            .unwrap()
+            .encode_input(&[
+                Token::Address(path[0]),
+                Token::Uint(5.into()),
+                Token::Uint(9.into()),
+                Token::Uint(27.into()),
+                Token::FixedBytes(vec![1; 32]),
+                Token::FixedBytes(vec![2; 32]),
+            ])
+            .unwrap();
+        let multicall = AbiParser::default()
+            .parse_function("multicall(bytes[])")
+            .unwrap()
+            .encode_input(&[Token::Array(vec![Token::Bytes(permit), Token::Bytes(swap)])])
+            .unwrap();
+
+        let expected = QuickSwapAction::SwapExactTokensForTokens {
+            amount_in: 5.into(),
+            amount_out_min: 4.into(),
+            path,
+            to: Address::repeat_byte(2),
+            deadline: 9.into(),
+        };
+        assert_eq!(parse_quickswap_tx(&router_tx(multicall, U256::zero())), Some(expected));
+    }
+}
@@ -0,0 +1,30 @@
+// src/raw_tx.rs
+use anyhow::{anyhow, Result};
+use ethers::types::Transaction;
+use ethers::utils::rlp;
+
+/// Decodes a signed transaction as broadcast (legacy RLP list or typed
+/// envelope), hash and sender included, so classification can start without
+/// a node roundtrip to fetch the tx by hash.
+pub fn decode_raw(raw: &[u8]) -> Result<Transaction> {
+    let mut tx: Transaction = rlp::decode(raw).map_err(|e| anyhow!("Bad raw tx: {:?}", e))?;
+    if tx.from.is_zero() {
+        tx.from = tx.recover_from()?;
+    }
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        // Raw txs come straight off a public feed
+        #[test]
+        fn arbitrary_bytes_never_panic(raw in prop::collection::vec(any::<u8>(), 0..512), envelope in 0u8..4) {
+            let _ = decode_raw(&raw);
+            let _ = decode_raw(&[vec![envelope], raw].concat());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// src/relay.rs
+use anyhow::{anyhow, Result};
+use ethers::{
+    prelude::*,
+    utils::keccak256,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::executor::SubmissionError;
+use crate::retry::{retry, transient_status, RetryPolicy};
+use crate::revert::{ExecutionFailure, RevertReason};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRequest {
+    pub txs: Vec<Bytes>,
+    pub block_number: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_timestamp: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_timestamp: Option<u64>,
+    // Hashes of txs allowed to revert without invalidating the bundle
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reverting_tx_hashes: Vec<H256>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendBundleResponse {
+    pub bundle_hash: H256,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleTxResult {
+    pub tx_hash: H256,
+    #[serde(default)]
+    pub gas_used: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub revert: Option<String>,
+    #[serde(default)]
+    pub coinbase_diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResponse {
+    pub bundle_hash: Option<H256>,
+    #[serde(default)]
+    pub coinbase_diff: Option<String>,
+    #[serde(default)]
+    pub total_gas_used: u64,
+    pub results: Vec<CallBundleTxResult>,
+}
+
+impl CallBundleResponse {
+    /// Refuses bundles where a tx outside `reverting_tx_hashes` failed, or where the
+    /// coinbase payment falls below `min_coinbase_payment`.
+    pub fn ensure_submittable(&self, reverting_tx_hashes: &[H256], min_coinbase_payment: U256) -> Result<(), SubmissionError> {
+        for result in &self.results {
+            let failure = result.error.as_ref().or(result.revert.as_ref());
+            if let Some(reason) = failure {
+                if !reverting_tx_hashes.contains(&result.tx_hash) {
+                    debug!("Bundle tx {:?} fails relay simulation", result.tx_hash);
+                    return Err(ExecutionFailure::new("relay_simulation", RevertReason::from_text(reason)).into());
+                }
+            }
+        }
+
+        // Relays report coinbaseDiff in decimal wei
+        let coinbase_diff = match &self.coinbase_diff {
+            Some(diff) => U256::from_dec_str(diff)
+                .map_err(|e| SubmissionError::Refused(format!("bad coinbaseDiff {}: {:?}", diff, e)))?,
+            None => U256::zero(),
+        };
+        if coinbase_diff < min_coinbase_payment {
+            return Err(SubmissionError::Refused(format!(
+                "bundle pays coinbase {} in simulation, below threshold {}",
+                coinbase_diff, min_coinbase_payment
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Flashbots-style bundle relay client. Works with any Polygon endpoint speaking
+/// `eth_sendBundle` / `eth_callBundle` (Marlin, bloXroute, FastLane relay RPC).
+pub struct RelayClient {
+    http: reqwest::Client,
+    url: String,
+    // Identity key, only used to sign request bodies; holds no funds
+    signer: LocalWallet,
+    next_id: AtomicU64,
+    retry: RetryPolicy,
+}
+
+impl RelayClient {
+    pub fn new(url: impl Into<String>, signer: LocalWallet) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .expect("build http client"),
+            url: url.into(),
+            signer,
+            next_id: AtomicU64::new(1),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub async fn send_bundle(&self, bundle: &BundleRequest) -> Result<H256> {
+        let response: SendBundleResponse = self.request("eth_sendBundle", json!([bundle])).await?;
+        info!("Relay {} accepted bundle {:?} for block {}", self.url, response.bundle_hash, bundle.block_number);
+        Ok(response.bundle_hash)
+    }
+
+    // Simulate `txs` on top of `state_block` as if included in `block_number`
+    pub async fn call_bundle(&self, txs: &[Bytes], block_number: U64, state_block: BlockNumber) -> Result<CallBundleResponse> {
+        let params = json!([{
+            "txs": txs,
+            "blockNumber": block_number,
+            "stateBlockNumber": state_block,
+        }]);
+        self.request("eth_callBundle", params).await
+    }
+
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let body = serde_json::to_string(&json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        }))?;
+
+        let signature = self.sign_body(&body).await?;
+        let (body, signature) = (&body, &signature);
+        let response: Value = retry(&self.retry, method, || async move {
+            let response = self
+                .http
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .header("X-Flashbots-Signature", signature)
+                .body(body.clone())
+                .send()
+                .await?;
+            Ok(transient_status(response)?.json().await?)
+        })
+        .await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{} failed on {}: {}", method, self.url, error));
+        }
+        debug!("{} response: {}", method, response);
+
+        let result = response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("{} returned no result", method))?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    // X-Flashbots-Signature: <address>:<personal_sign(hex(keccak256(body)))>
+    async fn sign_body(&self, body: &str) -> Result<String> {
+        let digest = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+        let signature = self.signer.sign_message(digest).await?;
+        Ok(format!("{:?}:0x{}", self.signer.address(), signature))
+    }
+}
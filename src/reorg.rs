@@ -0,0 +1,109 @@
+// src/reorg.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use tracing::warn;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::rpc_budget::RpcTransport;
+
+#[derive(Debug, Clone)]
+pub struct ReorgEvent {
+    // Last block shared by the old and new chain
+    pub common_ancestor: U64,
+    pub depth: u64,
+    pub abandoned: Vec<H256>,
+}
+
+/// Keeps the hashes of the last `depth` blocks and compares each new head's
+/// ancestry against them. Polygon regularly reorgs 1-3 blocks.
+pub struct ReorgDetector {
+    provider: Arc<Provider<RpcTransport>>,
+    depth: u64,
+    hashes: Mutex<BTreeMap<U64, H256>>,
+}
+
+impl ReorgDetector {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, depth: u64) -> Self {
+        Self {
+            provider,
+            depth,
+            hashes: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub async fn on_block(&self, block: &Block<TxHash>) -> Result<Option<ReorgEvent>> {
+        let number = block.number.ok_or_else(|| anyhow!("Block without number"))?;
+        let hash = block.hash.ok_or_else(|| anyhow!("Block without hash"))?;
+        let mut hashes = self.hashes.lock().await;
+
+        let parent_matches = match hashes.get(&(number - 1)) {
+            Some(parent) => *parent == block.parent_hash,
+            // Nothing to compare against (startup or gap)
+            None => true,
+        };
+        let replaces_known = hashes.get(&number).map_or(false, |h| *h != hash);
+
+        let event = if !parent_matches || replaces_known {
+            Some(self.find_fork_point(&mut hashes, number).await?)
+        } else {
+            None
+        };
+
+        hashes.insert(number, hash);
+        while hashes.len() as u64 > self.depth {
+            let oldest = *hashes.keys().next().unwrap();
+            hashes.remove(&oldest);
+        }
+
+        if let Some(event) = &event {
+            warn!(
+                "Reorg of depth {} detected at block {}, common ancestor {}",
+                event.depth, number, event.common_ancestor
+            );
+        }
+        Ok(event)
+    }
+
+    // Walk back from the new head until our recorded hash matches the canonical one
+    async fn find_fork_point(&self, hashes: &mut BTreeMap<U64, H256>, head: U64) -> Result<ReorgEvent> {
+        let mut abandoned = Vec::new();
+        let mut number = head;
+
+        // Any block at or above the new head's height we've seen is abandoned
+        let stale: Vec<U64> = hashes.range(head..).map(|(n, _)| *n).collect();
+        for n in stale {
+            abandoned.extend(hashes.remove(&n));
+        }
+
+        loop {
+            if number.is_zero() {
+                break;
+            }
+            number = number - 1;
+
+            let recorded = match hashes.get(&number) {
+                Some(h) => *h,
+                // Ran off the end of our window, treat the oldest tracked block as the ancestor
+                None => break,
+            };
+            let canonical = self.provider.get_block(number).await?.and_then(|b| b.hash);
+            if canonical == Some(recorded) {
+                break;
+            }
+
+            abandoned.push(recorded);
+            match canonical {
+                Some(h) => { hashes.insert(number, h); }
+                None => { hashes.remove(&number); }
+            }
+        }
+
+        Ok(ReorgEvent {
+            common_ancestor: number,
+            depth: (head - number).as_u64(),
+            abandoned,
+        })
+    }
+}
@@ -0,0 +1,268 @@
+// src/retry.rs
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, PubsubClient, RpcError};
+use ethers::types::{H256, U256};
+use ethers::utils::keccak256;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+// Failures a node, gateway or relay recovers from by itself
+const TRANSIENT: &[&str] = &[
+    "429",
+    "too many requests",
+    "rate limit",
+    "exceeded its compute units",
+    "connection reset",
+    "connection closed",
+    "broken pipe",
+    "timed out",
+    "header not found",
+    "502 bad gateway",
+    "503 service unavailable",
+    "504 gateway timeout",
+];
+
+// A subscription id belongs to the connection that issued it
+const NEVER_RETRIED: &[&str] = &["eth_subscribe", "eth_unsubscribe"];
+
+// Range scans and traces legitimately run long
+const SLOW_METHODS: &[&str] = &["eth_getLogs", "debug_traceCall", "debug_traceTransaction", "debug_traceBlockByNumber"];
+
+/// Whether an error message describes a transient transport failure.
+pub fn is_transient(message: &str) -> bool {
+    let message = message.to_lowercase();
+    TRANSIENT.iter().any(|pattern| message.contains(pattern))
+}
+
+/// How often and how long a call is retried. Backoff doubles per attempt up to
+/// `max_delay`, with full jitter so callers that failed together don't retry
+/// together. The deadline bounds all attempts and waits of one call.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub deadline: Duration,
+    // Deadline for range scans and traces
+    pub slow_deadline: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(500),
+            // An opportunity is gone after a block
+            deadline: Duration::from_secs(2),
+            slow_deadline: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn deadline_for(&self, method: &str) -> Duration {
+        if SLOW_METHODS.contains(&method) {
+            self.slow_deadline
+        } else {
+            self.deadline
+        }
+    }
+
+    /// Wait before retry number `attempt` (1 for the first retry).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self.base_delay.saturating_mul(1 << attempt.saturating_sub(1).min(16)).min(self.max_delay);
+        // One random draw per wait isn't worth a rand dependency
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(attempt);
+        ceiling.mul_f64((hasher.finish() % 1_000) as f64 / 1_000.0)
+    }
+}
+
+/// Runs `op` until it succeeds, fails with something that isn't transient, or
+/// runs out of attempts or time. For HTTP clients outside the provider stack
+/// (relays, private RPCs).
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, what: &str, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let started = Instant::now();
+    let deadline = policy.deadline_for(what);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let remaining = deadline.saturating_sub(started.elapsed());
+        let error = match tokio::time::timeout(remaining, op()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(e)) if e.is::<TransientStatus>() || is_transient(&format!("{:#}", e)) => e,
+            Ok(Err(e)) => return Err(e),
+            Err(_) => return Err(anyhow!("{} timed out after {:?} ({} attempts)", what, started.elapsed(), attempt)),
+        };
+        let wait = policy.backoff(attempt);
+        if attempt >= policy.max_attempts || started.elapsed() + wait >= deadline {
+            return Err(error.context(format!("{} failed after {} attempts", what, attempt)));
+        }
+        debug!(attempt, ?wait, "Retrying {}: {:#}", what, error);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Why a `Retrying` call failed: the last error once it stops being transient
+/// or attempts run out, or `Deadline` when the call outlived its deadline.
+#[derive(Debug, thiserror::Error)]
+pub enum RetryError<E> {
+    #[error(transparent)]
+    Inner(E),
+    #[error("{method} timed out after {elapsed:?} ({attempts} attempts)")]
+    Deadline { method: String, elapsed: Duration, attempts: u32 },
+}
+
+impl<E: From<serde_json::Error>> From<serde_json::Error> for RetryError<E> {
+    fn from(e: serde_json::Error) -> Self {
+        RetryError::Inner(e.into())
+    }
+}
+
+impl<E: RpcError> RpcError for RetryError<E> {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            RetryError::Inner(e) => e.as_error_response(),
+            RetryError::Deadline { .. } => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            RetryError::Inner(e) => e.as_serde_error(),
+            RetryError::Deadline { .. } => None,
+        }
+    }
+}
+
+impl<E: Into<ProviderError>> From<RetryError<E>> for ProviderError {
+    fn from(e: RetryError<E>) -> Self {
+        match e {
+            RetryError::Inner(e) => e.into(),
+            deadline => ProviderError::CustomError(deadline.to_string()),
+        }
+    }
+}
+
+/// Transport wrapper retrying transient failures under a `RetryPolicy`, so
+/// routers, forked simulation and every other provider user share one policy.
+#[derive(Debug, Clone)]
+pub struct Retrying<T> {
+    inner: T,
+    policy: RetryPolicy,
+}
+
+impl<T> Retrying<T> {
+    pub fn new(inner: T, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> JsonRpcClient for Retrying<T>
+where
+    T: JsonRpcClient,
+    T::Error: From<serde_json::Error>,
+{
+    type Error = RetryError<T::Error>;
+
+    async fn request<P, R>(&self, method: &str, params: P) -> Result<R, Self::Error>
+    where
+        P: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        if NEVER_RETRIED.contains(&method) {
+            return self.inner.request(method, params).await.map_err(RetryError::Inner);
+        }
+        // Serialized once so every attempt sends the same params
+        let params = serde_json::to_value(params)?;
+        let started = Instant::now();
+        let deadline = self.policy.deadline_for(method);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let remaining = deadline.saturating_sub(started.elapsed());
+            let error = match tokio::time::timeout(remaining, self.inner.request(method, params.clone())).await {
+                Ok(Ok(result)) => return Ok(result),
+                Ok(Err(e)) => e,
+                Err(_) => {
+                    return Err(RetryError::Deadline { method: method.to_string(), elapsed: started.elapsed(), attempts: attempt })
+                }
+            };
+            // A resent tx the node already has went out on an earlier attempt
+            // whose response was lost: it is in the mempool under its own hash
+            if attempt > 1 && method == "eth_sendRawTransaction" && error.to_string().to_lowercase().contains("already known") {
+                if let Some(hash) = raw_tx_hash(&params) {
+                    return Ok(serde_json::from_value(serde_json::to_value(hash)?)?);
+                }
+            }
+            let transient = is_transient(&error.to_string())
+                || error.as_error_response().map_or(false, |r| r.code == 429 || is_transient(&r.message));
+            let wait = self.policy.backoff(attempt);
+            if !transient || attempt >= self.policy.max_attempts || started.elapsed() + wait >= deadline {
+                return Err(RetryError::Inner(error));
+            }
+            debug!(method, attempt, ?wait, "Retrying RPC call: {}", error);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+// eth_sendRawTransaction's params are [raw]; a tx hash is keccak256 of the raw bytes
+fn raw_tx_hash(params: &serde_json::Value) -> Option<H256> {
+    let raw = params.get(0)?.as_str()?;
+    Some(H256::from(keccak256(hex::decode(raw.trim_start_matches("0x")).ok()?)))
+}
+
+impl<T> PubsubClient for Retrying<T>
+where
+    T: PubsubClient,
+    T::Error: From<serde_json::Error>,
+{
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe<I: Into<U256>>(&self, id: I) -> Result<Self::NotificationStream, Self::Error> {
+        self.inner.subscribe(id).map_err(RetryError::Inner)
+    }
+
+    fn unsubscribe<I: Into<U256>>(&self, id: I) -> Result<(), Self::Error> {
+        self.inner.unsubscribe(id).map_err(RetryError::Inner)
+    }
+}
+
+/// An HTTP status a gateway sends while overloaded or restarting.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{status} from {host}")]
+pub struct TransientStatus {
+    pub status: reqwest::StatusCode,
+    // Host only; paths often carry API keys
+    pub host: String,
+}
+
+/// Turns 429 and 5xx responses into a `TransientStatus` error for `retry`;
+/// other statuses are left for the caller to read the body of.
+pub fn transient_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        let host = response.url().host_str().unwrap_or_default().to_string();
+        return Err(TransientStatus { status, host }.into());
+    }
+    Ok(response)
+}
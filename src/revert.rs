@@ -0,0 +1,209 @@
+// src/revert.rs
+use anyhow::{anyhow, Result};
+use ethers::abi::{decode, ParamType, Token};
+use ethers::prelude::*;
+use ethers::providers::RpcError;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::abis::FLASHLOANARBITRAGE_ABI as EXECUTOR_ABI;
+use crate::rpc_budget::RpcTransport;
+
+// Error(string) and Panic(uint256)
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevertReason {
+    Message(String),
+    Panic(U256),
+    Custom { name: String, args: Vec<Token> },
+    // Revert data we couldn't decode, or none at all
+    Raw(Bytes),
+    // Only a textual error came back (relay simulations, revm halts)
+    Text(String),
+}
+
+impl RevertReason {
+    pub fn decode(data: &[u8]) -> Self {
+        let (selector, body) = match data.get(..4) {
+            Some(s) => ([s[0], s[1], s[2], s[3]], &data[4..]),
+            None => return RevertReason::Raw(Bytes::from(data.to_vec())),
+        };
+
+        if selector == ERROR_SELECTOR {
+            if let Ok(mut tokens) = decode(&[ParamType::String], body) {
+                if let Some(Token::String(message)) = tokens.pop() {
+                    return RevertReason::Message(message);
+                }
+            }
+        }
+        if selector == PANIC_SELECTOR {
+            if let Ok(mut tokens) = decode(&[ParamType::Uint(256)], body) {
+                if let Some(Token::Uint(code)) = tokens.pop() {
+                    return RevertReason::Panic(code);
+                }
+            }
+        }
+        // Custom errors come from our executor's ABI
+        for error in EXECUTOR_ABI.errors() {
+            if error.signature()[..4] == selector {
+                if let Ok(args) = error.decode(body) {
+                    return RevertReason::Custom { name: error.name.clone(), args };
+                }
+            }
+        }
+        RevertReason::Raw(Bytes::from(data.to_vec()))
+    }
+
+    /// Revert data carried by a failed eth_call / eth_estimateGas, if the node returned any.
+    pub fn from_provider_error(error: &ProviderError) -> Self {
+        match error.as_error_response() {
+            Some(response) => match response.as_revert_data() {
+                Some(data) => Self::decode(&data),
+                None => RevertReason::Text(response.message.clone()),
+            },
+            None => RevertReason::Text(error.to_string()),
+        }
+    }
+
+    /// Relays return the reason either as hex revert data or already decoded.
+    pub fn from_text(text: &str) -> Self {
+        match text.strip_prefix("0x").and_then(|h| hex::decode(h).ok()) {
+            Some(data) => Self::decode(&data),
+            None => RevertReason::Text(text.to_string()),
+        }
+    }
+}
+
+/// Re-runs a landed transaction against its parent block to recover the reason
+/// it reverted. Txs earlier in the same block aren't applied, so this can miss.
+pub async fn replay_revert(provider: &Arc<Provider<RpcTransport>>, tx_hash: H256) -> Result<RevertReason> {
+    let tx = provider
+        .get_transaction(tx_hash)
+        .await?
+        .ok_or_else(|| anyhow!("Transaction {:?} not found", tx_hash))?;
+    let block = tx.block_number.ok_or_else(|| anyhow!("Transaction {:?} not mined", tx_hash))?;
+    let mut call = TransactionRequest::new().from(tx.from).data(tx.input.clone()).value(tx.value).gas(tx.gas);
+    if let Some(to) = tx.to {
+        call = call.to(to);
+    }
+
+    match provider.call(&call.into(), Some((block - 1).into())).await {
+        Ok(_) => Ok(RevertReason::Text("no revert when replayed at parent block".to_string())),
+        Err(e) => Ok(RevertReason::from_provider_error(&e)),
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevertReason::Message(message) => write!(f, "{}", message),
+            RevertReason::Panic(code) => write!(f, "panic 0x{:02x}", code.low_u64()),
+            RevertReason::Custom { name, args } => write!(f, "{}({:?})", name, args),
+            RevertReason::Raw(data) if data.is_empty() => write!(f, "empty revert"),
+            RevertReason::Raw(data) => write!(f, "{}", data),
+            RevertReason::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    // A swap returned less than its minimum, usually someone moved the price first
+    InsufficientOutput,
+    // Reserves, ticks or deadlines we priced against no longer hold
+    StaleState,
+    // Missing or short token approval
+    Allowance,
+    // The path ran but didn't return enough to repay the flash loan
+    LoanRepayment,
+    // Repaid, but the owner gained less than the minProfit we encoded
+    ProfitTooLow,
+    // Caller isn't the executor owner or callback came from the wrong contract
+    Unauthorized,
+    // Arithmetic over/underflow, division by zero, bad array access
+    Panic,
+    Unknown,
+}
+
+impl FailureKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::InsufficientOutput => "insufficient_output",
+            FailureKind::StaleState => "stale_state",
+            FailureKind::Allowance => "allowance",
+            FailureKind::LoanRepayment => "loan_repayment",
+            FailureKind::ProfitTooLow => "profit_too_low",
+            FailureKind::Unauthorized => "unauthorized",
+            FailureKind::Panic => "panic",
+            FailureKind::Unknown => "unknown",
+        }
+    }
+}
+
+// Lowercase substrings of revert messages from our executor, the V2/V3 routers
+// and pools, and the flash-loan providers
+const TAXONOMY: &[(&str, FailureKind)] = &[
+    // executeGuarded's custom errors, matched on their lowercased names
+    ("profittoolow", FailureKind::ProfitTooLow),
+    ("insufficient token", FailureKind::LoanRepayment),
+    ("insufficient balance to repay", FailureKind::LoanRepayment),
+    ("bal#", FailureKind::LoanRepayment),
+    ("too little received", FailureKind::InsufficientOutput),
+    ("insufficient_output_amount", FailureKind::InsufficientOutput),
+    ("insufficient output", FailureKind::InsufficientOutput),
+    ("excessive_input_amount", FailureKind::InsufficientOutput),
+    ("stf", FailureKind::Allowance),
+    ("transfer_from_failed", FailureKind::Allowance),
+    ("allowance", FailureKind::Allowance),
+    // Router deadlines and executeGuarded's Expired
+    ("expired", FailureKind::StaleState),
+    ("transaction too old", FailureKind::StaleState),
+    ("invalid block number", FailureKind::StaleState),
+    ("block too far", FailureKind::StaleState),
+    ("spl", FailureKind::StaleState),
+    ("v2: k", FailureKind::StaleState),
+    ("insufficient_liquidity", FailureKind::StaleState),
+    ("callback not from", FailureKind::Unauthorized),
+    ("unexpected initiator", FailureKind::Unauthorized),
+    ("ownable", FailureKind::Unauthorized),
+];
+
+pub fn classify(reason: &RevertReason) -> FailureKind {
+    let text = match reason {
+        RevertReason::Panic(_) => return FailureKind::Panic,
+        RevertReason::Custom { name, .. } => name.to_lowercase(),
+        RevertReason::Message(text) | RevertReason::Text(text) => text.to_lowercase(),
+        RevertReason::Raw(_) => return FailureKind::Unknown,
+    };
+    TAXONOMY
+        .iter()
+        .find(|(needle, _)| text.contains(needle))
+        .map(|(_, kind)| *kind)
+        .unwrap_or(FailureKind::Unknown)
+}
+
+/// A decoded, classified revert, carried through `anyhow` so callers can downcast
+/// and record it.
+#[derive(Debug, Clone)]
+pub struct ExecutionFailure {
+    // preflight, bundle_simulation, relay_simulation or onchain
+    pub stage: &'static str,
+    pub kind: FailureKind,
+    pub reason: RevertReason,
+}
+
+impl ExecutionFailure {
+    pub fn new(stage: &'static str, reason: RevertReason) -> Self {
+        Self { stage, kind: classify(&reason), reason }
+    }
+}
+
+impl fmt::Display for ExecutionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} reverted ({}): {}", self.stage, self.kind.as_str(), self.reason)
+    }
+}
+
+impl std::error::Error for ExecutionFailure {}
@@ -0,0 +1,282 @@
+// src/risk_manager.rs
+use anyhow::Result;
+use ethers::types::{Address, I256, U64};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::sensitivity::Sensitivity;
+
+#[derive(Debug, Clone)]
+pub struct RiskConfig {
+    // Cumulative realized loss (USD) tolerated over `loss_window` before halting
+    pub max_window_loss_usd: f64,
+    pub loss_window: Duration,
+    pub max_consecutive_reverts: u32,
+    // Presence of this file halts the bot
+    pub kill_switch_file: Option<PathBuf>,
+    // Endpoint answering "1"/"true" when the bot must halt
+    pub kill_switch_url: Option<String>,
+    // How often the controls are polled, and how old the last good answer from
+    // the kill switch endpoint may be before submissions stop
+    pub controls_poll_interval: Duration,
+    pub kill_switch_max_age: Duration,
+    // Operator creates this file to clear a halt; it is deleted on reset
+    pub reset_file: Option<PathBuf>,
+    // Notional (USD) allowed in flight through one pool, one token, and overall
+    pub max_pool_exposure_usd: f64,
+    pub max_token_exposure_usd: f64,
+    pub max_inflight_usd: f64,
+    // Turn down opportunities profitable as priced but not after the price
+    // or the landing block slips
+    pub reject_knife_edge: bool,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            max_window_loss_usd: 200.0,
+            loss_window: Duration::from_secs(60 * 60),
+            max_consecutive_reverts: 5,
+            kill_switch_file: Some(PathBuf::from("KILL_SWITCH")),
+            kill_switch_url: None,
+            controls_poll_interval: Duration::from_secs(2),
+            kill_switch_max_age: Duration::from_secs(10),
+            reset_file: Some(PathBuf::from("RISK_RESET")),
+            max_pool_exposure_usd: 50_000.0,
+            max_token_exposure_usd: 100_000.0,
+            max_inflight_usd: 250_000.0,
+            reject_knife_edge: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HaltReason {
+    DrawdownExceeded { loss_usd: f64 },
+    ConsecutiveReverts(u32),
+    KillSwitch(String),
+}
+
+/// Why the risk manager turned an execution down. Never retryable as is: a
+/// halt lasts until reset, a limit until earlier exposure settles.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RiskRejected {
+    #[error("risk halt: {0:?}")]
+    Halted(HaltReason),
+    // Until the endpoint answers again; unlike a halt this clears by itself
+    #[error("kill switch not confirmed for {0:?}")]
+    KillSwitchStale(Option<Duration>),
+    #[error("${total:.0} in flight, limit ${limit:.0}")]
+    Inflight { total: f64, limit: f64 },
+    #[error("${exposure:.0} through pool {pool:?}, limit ${limit:.0}")]
+    PoolExposure { pool: Address, exposure: f64, limit: f64 },
+    #[error("${exposure:.0} in token {token:?}, limit ${limit:.0}")]
+    TokenExposure { token: Address, exposure: f64, limit: f64 },
+    #[error("knife edge: {profit} profit in the {scenario} scenario at {price_move_bps} bps")]
+    KnifeEdge { scenario: &'static str, profit: I256, price_move_bps: u32 },
+}
+
+// Notional of one submitted execution, held until it settles or its target block passes
+#[derive(Debug, Clone)]
+struct Exposure {
+    id: String,
+    pools: Vec<Address>,
+    tokens: Vec<Address>,
+    notional_usd: f64,
+    target_block: U64,
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    outcomes: VecDeque<(Instant, f64)>,
+    consecutive_reverts: u32,
+    halted: Option<HaltReason>,
+    exposures: Vec<Exposure>,
+    // Last time the kill switch endpoint answered
+    kill_switch_seen: Option<Instant>,
+}
+
+/// Gatekeeper for new submissions. Once tripped it stays halted until an
+/// operator resets it explicitly, even if the triggering condition clears.
+pub struct RiskManager {
+    config: RiskConfig,
+    http: reqwest::Client,
+    state: Mutex<RiskState>,
+}
+
+impl RiskManager {
+    pub fn new(config: RiskConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(2))
+                .build()
+                .expect("build http client"),
+            state: Mutex::new(RiskState::default()),
+        }
+    }
+
+    /// Reads only the cached state; `run_controls` keeps it current. An
+    /// endpoint that stops answering blocks submissions rather than waving them on.
+    pub async fn allow_submission(&self) -> std::result::Result<(), RiskRejected> {
+        let state = self.state.lock().await;
+        if let Some(reason) = &state.halted {
+            return Err(RiskRejected::Halted(reason.clone()));
+        }
+        if self.config.kill_switch_url.is_some() {
+            let age = state.kill_switch_seen.map(|seen| seen.elapsed());
+            if age.map_or(true, |age| age > self.config.kill_switch_max_age) {
+                return Err(RiskRejected::KillSwitchStale(age));
+            }
+        }
+        Ok(())
+    }
+
+    /// Polls the kill switch and reset controls until the task is dropped.
+    pub async fn run_controls(&self) {
+        let mut interval = tokio::time::interval(self.config.controls_poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.poll_controls().await {
+                warn!("Kill switch check failed: {:?}", e);
+            }
+        }
+    }
+
+    pub async fn halt_reason(&self) -> Option<HaltReason> {
+        self.state.lock().await.halted.clone()
+    }
+
+    pub async fn record_outcome(&self, net_usd: f64, reverted: bool) {
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+
+        state.outcomes.push_back((now, net_usd));
+        while let Some((at, _)) = state.outcomes.front() {
+            if now.duration_since(*at) > self.config.loss_window {
+                state.outcomes.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        state.consecutive_reverts = if reverted { state.consecutive_reverts + 1 } else { 0 };
+
+        let window_pnl: f64 = state.outcomes.iter().map(|(_, pnl)| pnl).sum();
+        if -window_pnl > self.config.max_window_loss_usd {
+            Self::trip(&mut state, HaltReason::DrawdownExceeded { loss_usd: -window_pnl });
+        } else if state.consecutive_reverts >= self.config.max_consecutive_reverts {
+            let reverts = state.consecutive_reverts;
+            Self::trip(&mut state, HaltReason::ConsecutiveReverts(reverts));
+        }
+    }
+
+    /// Reserves `notional_usd` against every pool and token an execution touches,
+    /// or returns the limit it would break. Held until `release` or until
+    /// `target_block` is mined.
+    pub async fn reserve_exposure(
+        &self,
+        id: &str,
+        pools: &[Address],
+        tokens: &[Address],
+        notional_usd: f64,
+        target_block: U64,
+    ) -> std::result::Result<(), RiskRejected> {
+        let mut state = self.state.lock().await;
+
+        let inflight: f64 = state.exposures.iter().map(|e| e.notional_usd).sum();
+        if inflight + notional_usd > self.config.max_inflight_usd {
+            return Err(RiskRejected::Inflight { total: inflight + notional_usd, limit: self.config.max_inflight_usd });
+        }
+        for pool in pools {
+            let exposure = Self::exposure(&state.exposures, |e| e.pools.contains(pool)) + notional_usd;
+            if exposure > self.config.max_pool_exposure_usd {
+                return Err(RiskRejected::PoolExposure { pool: *pool, exposure, limit: self.config.max_pool_exposure_usd });
+            }
+        }
+        for token in tokens {
+            let exposure = Self::exposure(&state.exposures, |e| e.tokens.contains(token)) + notional_usd;
+            if exposure > self.config.max_token_exposure_usd {
+                return Err(RiskRejected::TokenExposure { token: *token, exposure, limit: self.config.max_token_exposure_usd });
+            }
+        }
+
+        state.exposures.push(Exposure {
+            id: id.to_string(),
+            pools: pools.to_vec(),
+            tokens: tokens.to_vec(),
+            notional_usd,
+            target_block,
+        });
+        Ok(())
+    }
+
+    /// Rejects an opportunity whose profit doesn't survive its sensitivity
+    /// scenarios, unless knife-edge rejection is off.
+    pub fn check_sensitivity(&self, sensitivity: &Sensitivity) -> std::result::Result<(), RiskRejected> {
+        if !self.config.reject_knife_edge || !sensitivity.is_knife_edge() {
+            return Ok(());
+        }
+        let (scenario, profit) = sensitivity.worst();
+        Err(RiskRejected::KnifeEdge { scenario, profit, price_move_bps: sensitivity.price_move_bps })
+    }
+
+    pub async fn release_exposure(&self, id: &str) {
+        self.state.lock().await.exposures.retain(|e| e.id != id);
+    }
+
+    // Anything not settled by its target block can no longer land
+    pub async fn on_block(&self, block: U64) {
+        self.state.lock().await.exposures.retain(|e| e.target_block > block);
+    }
+
+    fn exposure(exposures: &[Exposure], filter: impl Fn(&Exposure) -> bool) -> f64 {
+        exposures.iter().filter(|e| filter(e)).map(|e| e.notional_usd).sum()
+    }
+
+    pub async fn reset(&self) {
+        let mut state = self.state.lock().await;
+        if let Some(reason) = state.halted.take() {
+            info!("Risk halt ({:?}) cleared by operator", reason);
+        }
+        state.outcomes.clear();
+        state.consecutive_reverts = 0;
+    }
+
+    async fn poll_controls(&self) -> Result<()> {
+        if let Some(path) = &self.config.reset_file {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+                self.reset().await;
+            }
+        }
+
+        if let Some(path) = &self.config.kill_switch_file {
+            if path.exists() {
+                let mut state = self.state.lock().await;
+                Self::trip(&mut state, HaltReason::KillSwitch(path.display().to_string()));
+            }
+        }
+
+        if let Some(url) = &self.config.kill_switch_url {
+            let body = self.http.get(url).send().await?.text().await?;
+            let flag = body.trim();
+            let mut state = self.state.lock().await;
+            state.kill_switch_seen = Some(Instant::now());
+            if flag == "1" || flag.eq_ignore_ascii_case("true") {
+                Self::trip(&mut state, HaltReason::KillSwitch(url.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn trip(state: &mut RiskState, reason: HaltReason) {
+        if state.halted.is_none() {
+            error!("Submissions halted: {:?}", reason);
+            state.halted = Some(reason);
+        }
+    }
+}
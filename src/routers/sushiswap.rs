@@ -1,11 +1,13 @@
 use ethers::{
-    abi::Abi,
     prelude::*,
     types::{Address, Bytes, H256, U256},
 };
 use std::sync::Arc;
 use anyhow::Result;
 
+use crate::abis;
+use crate::rpc_budget::RpcTransport;
+
 pub const SUSHISWAP_ROUTER: &str = "0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506";
 pub const SUSHISWAP_FACTORY: &str = "0xc35DADB65012eC5796536bD9864eD8773aBc74C4";
 pub const DEFAULT_FEE: u32 = 3000; // 0.3%
@@ -13,11 +15,11 @@ pub const DEFAULT_FEE: u32 = 3000; // 0.3%
 #[derive(Debug, Clone)]
 pub struct SushiswapRouter {
     pub address: Address,
-    provider: Arc<Provider<Ws>>,
+    provider: Arc<Provider<RpcTransport>>,
 }
 
 impl SushiswapRouter {
-    pub fn new(provider: Arc<Provider<Ws>>) -> Self {
+    pub fn new(provider: Arc<Provider<RpcTransport>>) -> Self {
         Self {
             address: SUSHISWAP_ROUTER.parse().unwrap(),
             provider,
@@ -29,16 +31,9 @@ impl SushiswapRouter {
         amount_in: U256,
         path: &[Address],
     ) -> Result<Vec<U256>> {
-        let contract = Contract::new(
-            self.address,
-            include_bytes!("../../abis/SushiswapRouter.json").as_ref(),
-            self.provider.clone(),
-        );
+        let contract = abis::sushiswap_router(self.address, self.provider.clone());
 
-        let amounts: Vec<U256> = contract
-            .method::<_, Vec<U256>>("getAmountsOut", (amount_in, path.to_vec()))?
-            .call()
-            .await?;
+        let amounts = contract.get_amounts_out(amount_in, path.to_vec()).call().await?;
 
         Ok(amounts)
     }
@@ -51,17 +46,10 @@ impl SushiswapRouter {
         to: Address,
         deadline: U256,
     ) -> Result<Bytes> {
-        let contract = Contract::new(
-            self.address,
-            include_bytes!("../../abis/SushiswapRouter.json").as_ref(),
-            self.provider.clone(),
-        );
+        let contract = abis::sushiswap_router(self.address, self.provider.clone());
 
         Ok(contract
-            .method::<_, Bytes>(
-                "swapExactTokensForTokens",
-                (amount_in, amount_out_min, path, to, deadline),
-            )?
+            .swap_exact_tokens_for_tokens(amount_in, amount_out_min, path, to, deadline)
             .calldata()
             .unwrap())
     }
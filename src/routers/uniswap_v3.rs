@@ -1,11 +1,13 @@
 use ethers::{
-    abi::Abi,
     prelude::*,
     types::{Address, Bytes, H256, U256},
 };
 use std::sync::Arc;
 use anyhow::Result;
 
+use crate::abis;
+use crate::rpc_budget::RpcTransport;
+
 pub const UNISWAP_V3_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
 pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
 pub const DEFAULT_FEE: u32 = 3000; // 0.3%
@@ -14,11 +16,11 @@ pub const FEE_TIERS: [u32; 3] = [500, 3000, 10000];
 #[derive(Debug, Clone)]
 pub struct UniswapV3Router {
     pub address: Address,
-    provider: Arc<Provider<Ws>>,
+    provider: Arc<Provider<RpcTransport>>,
 }
 
 impl UniswapV3Router {
-    pub fn new(provider: Arc<Provider<Ws>>) -> Self {
+    pub fn new(provider: Arc<Provider<RpcTransport>>) -> Self {
         Self {
             address: UNISWAP_V3_ROUTER.parse().unwrap(),
             provider,
@@ -29,16 +31,9 @@ impl UniswapV3Router {
         &self,
         params: ExactInputSingleParams,
     ) -> Result<Bytes> {
-        let contract = Contract::new(
-            self.address,
-            include_bytes!("../../abis/UniswapV3Router.json").as_ref(),
-            self.provider.clone(),
-        );
-
-        Ok(contract
-            .method::<_, Bytes>("exactInputSingle", (params,))?
-            .calldata()
-            .unwrap())
+        let contract = abis::uniswap_v3_router(self.address, self.provider.clone());
+
+        Ok(contract.exact_input_single(params.into()).calldata().unwrap())
     }
 }
 
@@ -53,3 +48,18 @@ pub struct ExactInputSingleParams {
     pub amount_out_minimum: U256,
     pub sqrt_price_limit_x96: U256,
 }
+
+impl From<ExactInputSingleParams> for abis::i_uniswap_v3_router::ExactInputSingleParams {
+    fn from(p: ExactInputSingleParams) -> Self {
+        Self {
+            token_in: p.token_in,
+            token_out: p.token_out,
+            fee: p.fee,
+            recipient: p.recipient,
+            deadline: p.deadline,
+            amount_in: p.amount_in,
+            amount_out_minimum: p.amount_out_minimum,
+            sqrt_price_limit_x96: p.sqrt_price_limit_x96,
+        }
+    }
+}
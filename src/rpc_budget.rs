@@ -0,0 +1,240 @@
+// src/rpc_budget.rs
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, Provider, PubsubClient, Ws};
+use ethers::types::U256;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::retry::{RetryPolicy, Retrying};
+use crate::rpc_cache::{Cached, RpcCache};
+
+/// The transport every provider in the bot goes through: the response cache,
+/// then the rate limiter, so cache hits cost no budget, then retries, so a call
+/// is charged once however many attempts it takes.
+pub type RpcTransport = Cached<Budgeted<Retrying<Ws>>>;
+
+// Responses cached per endpoint, and blocks they outlive their head by
+const CACHE_CAPACITY: usize = 50_000;
+const CACHE_TTL_BLOCKS: u64 = 2;
+
+// Compute units per call, after Alchemy's pricing; anything unlisted costs this
+const DEFAULT_COST: f64 = 26.0;
+
+fn method_cost(method: &str) -> f64 {
+    match method {
+        "eth_chainId" | "net_version" => 0.0,
+        "eth_blockNumber" | "eth_subscribe" | "eth_unsubscribe" => 10.0,
+        "eth_getTransactionReceipt" | "eth_getTransactionByHash" => 15.0,
+        "eth_getBlockByNumber" | "eth_getBlockByHash" | "eth_getStorageAt" => 16.0,
+        "eth_getBalance" | "eth_getCode" | "eth_getTransactionCount" | "eth_gasPrice" | "eth_maxPriorityFeePerGas" => 19.0,
+        "eth_call" => 26.0,
+        "eth_getLogs" | "eth_feeHistory" => 75.0,
+        "eth_estimateGas" => 87.0,
+        "eth_sendRawTransaction" => 250.0,
+        "debug_traceCall" | "debug_traceTransaction" => 309.0,
+        _ => DEFAULT_COST,
+    }
+}
+
+/// Who a call is for. Lower classes must leave part of the bucket to the ones
+/// above, so a backlog of pool refreshes never delays a simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    // Discovery, refreshes, bookkeeping
+    Background,
+    Normal,
+    // Pending-tx analysis, simulation and submission
+    Hot,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::Background, Priority::Normal, Priority::Hot];
+
+    // Share of the burst a call of this class may not dip into
+    fn reserve(&self) -> f64 {
+        match self {
+            Priority::Background => 0.5,
+            Priority::Normal => 0.2,
+            Priority::Hot => 0.0,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Priority::Background => "background",
+            Priority::Normal => "normal",
+            Priority::Hot => "hot",
+        }
+    }
+}
+
+tokio::task_local! {
+    static PRIORITY: Priority;
+}
+
+/// Runs `fut` with its RPC calls charged at `priority`. Calls made outside
+/// any scope, including from spawned tasks, are Normal.
+pub async fn with_priority<F: Future>(priority: Priority, fut: F) -> F::Output {
+    PRIORITY.scope(priority, fut).await
+}
+
+fn current_priority() -> Priority {
+    PRIORITY.try_with(|p| *p).unwrap_or(Priority::Normal)
+}
+
+/// Compute units an endpoint allows: a sustained rate and the burst on top.
+#[derive(Debug, Clone)]
+pub struct RpcBudget {
+    pub units_per_sec: f64,
+    pub burst: f64,
+}
+
+impl Default for RpcBudget {
+    // Alchemy's Growth tier
+    fn default() -> Self {
+        Self {
+            units_per_sec: 330.0,
+            burst: 660.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    units: f64,
+    refilled: Instant,
+}
+
+/// Token bucket in compute units for one endpoint.
+#[derive(Debug)]
+pub struct RateLimiter {
+    endpoint: String,
+    budget: RpcBudget,
+    bucket: Mutex<Bucket>,
+    // Per priority: units spent and calls that had to wait for a refill
+    spent: [AtomicU64; 3],
+    waited: [AtomicU64; 3],
+}
+
+impl RateLimiter {
+    pub fn new(endpoint: impl Into<String>, budget: RpcBudget) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            bucket: Mutex::new(Bucket { units: budget.burst, refilled: Instant::now() }),
+            budget,
+            spent: Default::default(),
+            waited: Default::default(),
+        }
+    }
+
+    pub async fn acquire(&self, method: &str, priority: Priority) {
+        let floor = self.budget.burst * priority.reserve();
+        // A call bigger than the share available to its class would never fit
+        let cost = method_cost(method).min(self.budget.burst - floor);
+        let mut waited = false;
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+                bucket.units = (bucket.units + elapsed * self.budget.units_per_sec).min(self.budget.burst);
+                bucket.refilled = now;
+                if bucket.units - cost >= floor {
+                    bucket.units -= cost;
+                    break;
+                }
+                Duration::from_secs_f64((floor + cost - bucket.units) / self.budget.units_per_sec)
+            };
+            waited = true;
+            tokio::time::sleep(wait).await;
+        }
+        self.spent[priority.index()].fetch_add(cost as u64, Ordering::Relaxed);
+        if waited {
+            self.waited[priority.index()].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Logs and resets the per-priority counters.
+    pub fn report(&self) {
+        for priority in Priority::ALL {
+            let spent = self.spent[priority.index()].swap(0, Ordering::Relaxed);
+            let waited = self.waited[priority.index()].swap(0, Ordering::Relaxed);
+            if spent == 0 && waited == 0 {
+                continue;
+            }
+            info!(endpoint = %self.endpoint, priority = priority.name(), units = spent, waited, "RPC budget");
+        }
+    }
+}
+
+/// Transport wrapper charging every request to the endpoint's rate limiter
+/// before sending it. Subscriptions are charged once, when they are opened.
+#[derive(Debug, Clone)]
+pub struct Budgeted<T> {
+    inner: T,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<T> Budgeted<T> {
+    pub fn new(inner: T, limiter: Arc<RateLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+
+    pub fn limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: JsonRpcClient> JsonRpcClient for Budgeted<T> {
+    type Error = T::Error;
+
+    async fn request<P, R>(&self, method: &str, params: P) -> Result<R, Self::Error>
+    where
+        P: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        self.limiter.acquire(method, current_priority()).await;
+        self.inner.request(method, params).await
+    }
+}
+
+impl<T: PubsubClient> PubsubClient for Budgeted<T> {
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe<I: Into<U256>>(&self, id: I) -> Result<Self::NotificationStream, Self::Error> {
+        self.inner.subscribe(id)
+    }
+
+    fn unsubscribe<I: Into<U256>>(&self, id: I) -> Result<(), Self::Error> {
+        self.inner.unsubscribe(id)
+    }
+}
+
+/// The rate limiter behind `provider`.
+pub fn limiter(provider: &Provider<RpcTransport>) -> &Arc<RateLimiter> {
+    let transport: &RpcTransport = provider.as_ref();
+    transport.inner().limiter()
+}
+
+/// Connects to a websocket endpoint behind its own response cache, rate
+/// limiter and retry policy.
+pub async fn connect(url: &str, budget: RpcBudget, retry: RetryPolicy) -> anyhow::Result<Provider<RpcTransport>> {
+    let ws = Ws::connect(url).await?;
+    // Only the host goes in logs; the path usually carries the API key
+    let endpoint = url.split('/').nth(2).unwrap_or(url).to_string();
+    let limiter = Arc::new(RateLimiter::new(endpoint, budget));
+    let cache = Arc::new(RpcCache::new(CACHE_CAPACITY, CACHE_TTL_BLOCKS));
+    Ok(Provider::new(Cached::new(Budgeted::new(Retrying::new(ws, retry), limiter), cache)))
+}
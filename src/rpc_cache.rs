@@ -0,0 +1,147 @@
+// src/rpc_cache.rs
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, Provider, PubsubClient};
+use ethers::types::{U256, U64};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{self, Debug};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::cache::{BlockLruCache, CacheStats};
+use crate::rpc_budget::RpcTransport;
+
+// Reads whose result is fixed by their params once the block is pinned
+const CACHEABLE: &[&str] = &["eth_call", "eth_getCode", "eth_getStorageAt", "eth_getBalance", "eth_chainId"];
+
+/// Read-through cache of idempotent RPC responses keyed by (method, params,
+/// block). `latest` is pinned to the head the cache was last told about, so
+/// reads at `latest` and at that head's number share entries; `pending` is
+/// never cached. Entries expire a few blocks after insertion and everything
+/// is dropped on reorg.
+pub struct RpcCache {
+    entries: BlockLruCache<(String, String), Value>,
+    head: AtomicU64,
+}
+
+impl RpcCache {
+    pub fn new(capacity: usize, ttl_blocks: u64) -> Self {
+        Self {
+            entries: BlockLruCache::new(capacity, ttl_blocks),
+            head: AtomicU64::new(0),
+        }
+    }
+
+    pub fn on_block(&self, block: U64) {
+        self.head.store(block.as_u64(), Ordering::Relaxed);
+        self.entries.on_block(block);
+    }
+
+    /// After a reorg, results at the replaced blocks (and at `latest`) are wrong.
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.entries.stats()
+    }
+
+    // None when the call isn't cacheable at all
+    fn key<P: Serialize>(&self, method: &str, params: &P) -> Option<(String, String)> {
+        if !CACHEABLE.contains(&method) {
+            return None;
+        }
+        let mut params = serde_json::to_value(params).ok()?;
+        if method != "eth_chainId" {
+            // Block tag position: second for eth_call/getCode/getBalance, third for getStorageAt
+            let index = if method == "eth_getStorageAt" { 2 } else { 1 };
+            let head = self.head.load(Ordering::Relaxed);
+            let block = params.as_array_mut()?.get_mut(index)?;
+            match block.as_str() {
+                // Before the first head there is nothing to pin `latest` to
+                Some("latest") if head != 0 => *block = Value::String(format!("{:#x}", head)),
+                Some(tag) if tag.starts_with("0x") => {}
+                _ => return None,
+            }
+        }
+        Some((method.to_string(), params.to_string()))
+    }
+}
+
+impl Debug for RpcCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcCache").field("head", &self.head).finish()
+    }
+}
+
+/// Transport wrapper answering cacheable reads from an `RpcCache` and
+/// forwarding everything else.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    inner: T,
+    cache: Arc<RpcCache>,
+}
+
+impl<T> Cached<T> {
+    pub fn new(inner: T, cache: Arc<RpcCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn cache(&self) -> &Arc<RpcCache> {
+        &self.cache
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> JsonRpcClient for Cached<T>
+where
+    T: JsonRpcClient,
+    T::Error: From<serde_json::Error>,
+{
+    type Error = T::Error;
+
+    async fn request<P, R>(&self, method: &str, params: P) -> Result<R, Self::Error>
+    where
+        P: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let key = match self.cache.key(method, &params) {
+            Some(key) => key,
+            None => return self.inner.request(method, params).await,
+        };
+        if let Some(value) = self.cache.entries.get(&key) {
+            return Ok(serde_json::from_value(value)?);
+        }
+        let value: Value = self.inner.request(method, params).await?;
+        self.cache.entries.insert(key, value.clone());
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl<T> PubsubClient for Cached<T>
+where
+    T: PubsubClient,
+    T::Error: From<serde_json::Error>,
+{
+    type NotificationStream = T::NotificationStream;
+
+    fn subscribe<I: Into<U256>>(&self, id: I) -> Result<Self::NotificationStream, Self::Error> {
+        self.inner.subscribe(id)
+    }
+
+    fn unsubscribe<I: Into<U256>>(&self, id: I) -> Result<(), Self::Error> {
+        self.inner.unsubscribe(id)
+    }
+}
+
+/// The response cache behind `provider`.
+pub fn cache(provider: &Provider<RpcTransport>) -> &Arc<RpcCache> {
+    let transport: &RpcTransport = provider.as_ref();
+    transport.cache()
+}
@@ -0,0 +1,173 @@
+// src/sandwich.rs
+use ethers::types::{Address, H256, I256, U256};
+
+use crate::pool_state::PoolState;
+use crate::strategies::sizing::ternary_max;
+
+/// A pending exact-input swap straight through one V2 pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VictimSwap {
+    pub tx_hash: H256,
+    pub amount_in: U256,
+    pub amount_out_min: U256,
+}
+
+/// Sizes for one sandwich around one or more victims in the same pool and
+/// direction: the frontrun ahead of the first, the backrun after the last.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandwichSizing {
+    pub frontrun_in: U256,
+    // Bought by the frontrun, all of it sold back by the backrun
+    pub frontrun_out: U256,
+    pub backrun_out: U256,
+    pub profit: U256,
+    // Victims the frontrun leaves within their slippage, in bundle order
+    pub victims: Vec<H256>,
+}
+
+// Runs frontrun, victims and backrun through the pool's reserves. Err carries
+// the first victim whose minimum the frontrun pushed it below
+fn run(pool: &PoolState, token_in: Address, frontrun_in: U256, victims: &[VictimSwap]) -> Result<(U256, U256), usize> {
+    let token_out = pool.other_token(token_in);
+    let mut pool = pool.clone();
+    let swap = |pool: &mut PoolState, from: Address, amount_in: U256| {
+        let amount_out = pool.get_amount_out(from, amount_in);
+        let (reserve_in, reserve_out) = if from == pool.token0 {
+            (&mut pool.reserve0, &mut pool.reserve1)
+        } else {
+            (&mut pool.reserve1, &mut pool.reserve0)
+        };
+        // The output never exceeds the reserve; saturating only guards the add
+        *reserve_in = reserve_in.saturating_add(amount_in);
+        *reserve_out = reserve_out.saturating_sub(amount_out);
+        amount_out
+    };
+
+    let frontrun_out = swap(&mut pool, token_in, frontrun_in);
+    for (i, victim) in victims.iter().enumerate() {
+        if pool.get_amount_out(token_in, victim.amount_in) < victim.amount_out_min {
+            return Err(i);
+        }
+        swap(&mut pool, token_in, victim.amount_in);
+    }
+    Ok((frontrun_out, swap(&mut pool, token_out, frontrun_out)))
+}
+
+fn reserve_in(pool: &PoolState, token_in: Address) -> U256 {
+    if token_in == pool.token0 { pool.reserve0 } else { pool.reserve1 }
+}
+
+// Largest frontrun after which every victim still clears its minimum. Victim
+// outputs only fall as the frontrun grows, so the boundary is bisected
+fn max_frontrun(pool: &PoolState, token_in: Address, victims: &[VictimSwap]) -> U256 {
+    let (mut lo, mut hi) = (U256::zero(), reserve_in(pool, token_in));
+    if run(pool, token_in, hi, victims).is_ok() {
+        return hi;
+    }
+    while hi - lo > U256::one() {
+        let mid = lo + (hi - lo) / 2;
+        if run(pool, token_in, mid, victims).is_ok() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+// Most profitable frontrun up to `cap`
+fn best_within(pool: &PoolState, token_in: Address, victims: &[VictimSwap], cap: U256) -> Option<SandwichSizing> {
+    let backrun_out = |x: U256| run(pool, token_in, x, victims).map(|(_, out)| out).unwrap_or_default();
+    let frontrun_in = ternary_max(U256::zero(), cap, |x| I256::from_raw(backrun_out(x)) - I256::from_raw(x));
+    let (frontrun_out, backrun_out) = run(pool, token_in, frontrun_in, victims).ok()?;
+    if backrun_out <= frontrun_in {
+        return None;
+    }
+    Some(SandwichSizing {
+        frontrun_in,
+        frontrun_out,
+        backrun_out,
+        profit: backrun_out - frontrun_in,
+        victims: victims.iter().map(|v| v.tx_hash).collect(),
+    })
+}
+
+/// Best sandwich of `victims`, in the order given, swapping `token_in` through
+/// V2 `pool`. The victim with the tightest slippage caps the frontrun for all
+/// of them, so victims are dropped one at a time, binding one first, and the
+/// most profitable of the resulting sandwiches wins.
+pub fn optimal(pool: &PoolState, token_in: Address, victims: &[VictimSwap]) -> Option<SandwichSizing> {
+    // Amounts come from calldata; more than the pool holds is no real swap
+    let reserve = reserve_in(pool, token_in);
+    let mut included: Vec<VictimSwap> = victims.iter().filter(|v| v.amount_in <= reserve).cloned().collect();
+    let mut best: Option<SandwichSizing> = None;
+    while !included.is_empty() {
+        // Fails its own minimum even unsandwiched
+        if let Err(i) = run(pool, token_in, U256::zero(), &included) {
+            included.remove(i);
+            continue;
+        }
+        let cap = max_frontrun(pool, token_in, &included);
+        if let Some(sizing) = best_within(pool, token_in, &included, cap) {
+            if best.as_ref().map_or(true, |b| sizing.profit > b.profit) {
+                best = Some(sizing);
+            }
+        }
+        match run(pool, token_in, cap + 1, &included) {
+            Err(binding) => {
+                included.remove(binding);
+            }
+            Ok(_) => break,
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee::FeeAmount;
+
+    fn victim(n: u64, amount_in: U256, slippage_bps: u64, pool: &PoolState, token_in: Address) -> VictimSwap {
+        let quoted = pool.get_amount_out(token_in, amount_in);
+        VictimSwap {
+            tx_hash: H256::from_low_u64_be(n),
+            amount_in,
+            amount_out_min: quoted * U256::from(10_000 - slippage_bps) / U256::from(10_000),
+        }
+    }
+
+    #[test]
+    fn combined_victims_beat_the_best_single_one() {
+        let (token0, token1) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let pool = PoolState {
+            address: Address::from_low_u64_be(3),
+            router: Address::zero(),
+            token0,
+            token1,
+            reserve0: U256::exp10(24),
+            reserve1: U256::exp10(24),
+            fee: FeeAmount::MEDIUM,
+            last_updated_block: 0.into(),
+        };
+        let swap = U256::exp10(22);
+        let victims = [victim(1, swap, 1_000, &pool, token0), victim(2, swap, 1_000, &pool, token0)];
+
+        let single = optimal(&pool, token0, &victims[..1]).unwrap();
+        let combined = optimal(&pool, token0, &victims).unwrap();
+        assert_eq!(combined.victims.len(), 2);
+        assert!(combined.profit > single.profit);
+        // Every victim still clears its minimum
+        assert!(run(&pool, token0, combined.frontrun_in, &victims).is_ok());
+
+        // A victim with no slippage to spare is dropped, not the whole sandwich
+        let tight = victim(3, swap, 0, &pool, token0);
+        let mixed = optimal(&pool, token0, &[victims[0].clone(), tight, victims[1].clone()]).unwrap();
+        assert_eq!(mixed.victims, vec![victims[0].tx_hash, victims[1].tx_hash]);
+
+        // Calldata claiming more than the pool could ever take is skipped, not overflowed
+        let bogus = VictimSwap { tx_hash: H256::from_low_u64_be(4), amount_in: U256::MAX, amount_out_min: U256::zero() };
+        let with_bogus = optimal(&pool, token0, &[victims[0].clone(), bogus, victims[1].clone()]).unwrap();
+        assert_eq!(with_bogus, combined);
+    }
+}
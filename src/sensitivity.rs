@@ -0,0 +1,81 @@
+// src/sensitivity.rs
+use ethers::types::{I256, U256};
+
+const BPS: u64 = 10_000;
+// EIP-1559 lets the base fee rise at most 12.5% a block
+const BASE_FEE_STEP_BPS: u64 = 1_250;
+// Prices drift as a random walk, so two blocks move √2 as far as one
+const SQRT_2_BPS: u64 = 14_142;
+
+/// Profit of an opportunity, in its start token and net of gas, under the
+/// moves it has to survive between pricing and landing. `price_move_bps` is
+/// the drift assumed over one block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sensitivity {
+    pub price_move_bps: u32,
+    // As priced
+    pub base: I256,
+    // The route's rate `price_move_bps` against us, and in our favour
+    pub adverse: I256,
+    pub favorable: I256,
+    // Landing a block late: two blocks of adverse drift, and the base fee up
+    // as far as it can go in one
+    pub delayed: I256,
+}
+
+impl Sensitivity {
+    /// Scenarios for a route that turns `amount_in` into `amount_in + profit`.
+    /// `gas_cost` is in the same token; zero when it can't be priced in it.
+    pub fn compute(amount_in: U256, profit: U256, gas_cost: U256, price_move_bps: u32) -> Self {
+        let amount_out = amount_in + profit;
+        let move_bps = (price_move_bps as u64).min(BPS);
+        let late_move_bps = (move_bps * SQRT_2_BPS / BPS).min(BPS);
+        let late_gas = gas_cost * U256::from(BPS + BASE_FEE_STEP_BPS) / U256::from(BPS);
+
+        let net = |out: U256, gas: U256| I256::from_raw(out) - I256::from_raw(amount_in) - I256::from_raw(gas);
+        let scaled = |bps: u64| amount_out * U256::from(bps) / U256::from(BPS);
+        Self {
+            price_move_bps,
+            base: net(amount_out, gas_cost),
+            adverse: net(scaled(BPS - move_bps), gas_cost),
+            favorable: net(scaled(BPS + move_bps), gas_cost),
+            delayed: net(scaled(BPS - late_move_bps), late_gas),
+        }
+    }
+
+    /// The scenario we can least afford, by name, with its profit.
+    pub fn worst(&self) -> (&'static str, I256) {
+        [("base", self.base), ("adverse", self.adverse), ("delayed", self.delayed)]
+            .into_iter()
+            .min_by_key(|(_, profit)| *profit)
+            .expect("scenarios")
+    }
+
+    /// Profitable as priced, but not once the price or the block slips.
+    pub fn is_knife_edge(&self) -> bool {
+        self.base > I256::zero() && self.worst().1 <= I256::zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thin_margin_is_knife_edge() {
+        let amount_in = U256::exp10(18);
+        // 0.3% gross on a 0.5% move
+        let thin = Sensitivity::compute(amount_in, U256::exp10(15) * 3, U256::exp10(14), 50);
+        assert!(thin.base > I256::zero());
+        assert!(thin.adverse < I256::zero());
+        assert!(thin.favorable > thin.base);
+        assert!(thin.delayed < thin.adverse);
+        assert!(thin.is_knife_edge());
+        assert_eq!(thin.worst().0, "delayed");
+
+        // 2% gross survives both
+        let wide = Sensitivity::compute(amount_in, U256::exp10(16) * 2, U256::exp10(14), 50);
+        assert!(wide.delayed > I256::zero());
+        assert!(!wide.is_knife_edge());
+    }
+}
@@ -0,0 +1,149 @@
+// src/settlement.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::abis::{self, ArbitrageExecutedFilter};
+use crate::fastlane_integration::{BundleStatus, FastLaneClient};
+use crate::rpc_budget::RpcTransport;
+use crate::types::ArbitrageOpportunity;
+
+// Blocks past a bundle's deadline FastLane gets to report a final status
+// before the bundle is written off
+const STATUS_GRACE_BLOCKS: u64 = 2;
+
+#[derive(Debug, Clone)]
+struct PendingBundle {
+    opportunities: Vec<ArbitrageOpportunity>,
+    target_block: U64,
+    // Last block the profit guard lets the execution land in
+    deadline_block: U64,
+    // FastLane reported it included before we found the execution on chain
+    unconfirmed: bool,
+}
+
+/// A FastLane bundle that reached a final status.
+#[derive(Debug, Clone)]
+pub struct Settlement {
+    pub bundle_hash: H256,
+    // Every leg the bundle carried
+    pub opportunities: Vec<ArbitrageOpportunity>,
+    pub status: BundleStatus,
+    // Execution tx and its block, when included
+    pub execution: Option<(H256, U64)>,
+}
+
+/// Follows submitted FastLane bundles to Included, Failed or Timeout. FastLane's
+/// status is polled, but inclusion is only taken from the executor's own
+/// `ArbitrageExecuted` log, which also names the tx PnL is measured on; a
+/// bundle the chain shows executed is included whatever FastLane says.
+pub struct SettlementTracker {
+    provider: Arc<Provider<RpcTransport>>,
+    fastlane: FastLaneClient,
+    pending: Mutex<HashMap<H256, PendingBundle>>,
+}
+
+impl SettlementTracker {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, fastlane: FastLaneClient) -> Self {
+        Self {
+            provider,
+            fastlane,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn watch(&self, bundle_hash: H256, opportunities: Vec<ArbitrageOpportunity>, target_block: U64, deadline_block: U64) {
+        self.pending.lock().await.insert(
+            bundle_hash,
+            PendingBundle { opportunities, target_block, deadline_block, unconfirmed: false },
+        );
+    }
+
+    pub async fn pending(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    /// Checks every bundle whose target block is at or below `head` and returns
+    /// the ones that settled; the rest are checked again next block.
+    pub async fn on_block(&self, head: U64) -> Result<Vec<Settlement>> {
+        let due: Vec<(H256, U64)> = self
+            .pending
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, bundle)| bundle.target_block <= head)
+            .map(|(hash, bundle)| (*hash, bundle.target_block))
+            .collect();
+        let from = match due.iter().map(|(_, target)| *target).min() {
+            Some(from) => from,
+            None => return Ok(Vec::new()),
+        };
+        let executions = self.executions(from, head).await?;
+
+        let mut settled = Vec::new();
+        for (bundle_hash, _) in due {
+            let execution = executions.get(&bundle_hash).copied();
+            let reported = match execution {
+                Some(_) => BundleStatus::Included,
+                None => match self.fastlane.get_bundle_status(bundle_hash).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        debug!(bundle = ?bundle_hash, "Bundle status unavailable: {:?}", e);
+                        BundleStatus::Pending
+                    }
+                },
+            };
+
+            let mut pending = self.pending.lock().await;
+            let bundle = match pending.get_mut(&bundle_hash) {
+                Some(bundle) => bundle,
+                None => continue,
+            };
+            let status = match reported {
+                // Logs can trail the status by a block; wait for them
+                BundleStatus::Included if execution.is_none() => {
+                    if !bundle.unconfirmed {
+                        warn!(bundle = ?bundle_hash, "FastLane reports bundle included, no execution on chain yet");
+                        bundle.unconfirmed = true;
+                    }
+                    BundleStatus::Pending
+                }
+                status => status,
+            };
+            let status = match status {
+                BundleStatus::Pending if head > bundle.deadline_block + STATUS_GRACE_BLOCKS => {
+                    // Reported included but never executed: it landed and reverted
+                    if bundle.unconfirmed {
+                        BundleStatus::Failed
+                    } else {
+                        BundleStatus::Timeout
+                    }
+                }
+                status => status,
+            };
+            if status == BundleStatus::Pending {
+                continue;
+            }
+            let bundle = pending.remove(&bundle_hash).expect("pending bundle");
+            settled.push(Settlement { bundle_hash, opportunities: bundle.opportunities, status, execution });
+        }
+        Ok(settled)
+    }
+
+    // Bundles the executor logged executing in [from, to], by bundle hash
+    async fn executions(&self, from: U64, to: U64) -> Result<HashMap<H256, (H256, U64)>> {
+        let logs = abis::executor(self.fastlane.solver_contract(), self.provider.clone())
+            .event::<ArbitrageExecutedFilter>()
+            .from_block(from)
+            .to_block(to)
+            .query_with_meta()
+            .await?;
+        Ok(logs
+            .into_iter()
+            .map(|(event, meta)| (H256(event.bundle_hash), (meta.transaction_hash, meta.block_number)))
+            .collect())
+    }
+}
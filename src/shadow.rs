@@ -0,0 +1,164 @@
+// src/shadow.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::storage::Storage;
+use crate::rpc_budget::RpcTransport;
+
+#[derive(Debug, Clone)]
+pub struct ShadowEntry {
+    pub opportunity_id: String,
+    pub path: Vec<Address>,
+    pub routers: Vec<Address>,
+    pub expected_profit: U256,
+    pub target_block: U64,
+    pub calldata: Bytes,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShadowOutcome {
+    pub opportunity_id: String,
+    pub block: U64,
+    // Transaction that appears to have captured the opportunity instead of us
+    pub taken_by: Option<(H256, Address)>,
+}
+
+/// Records what the bot would have submitted in `--shadow` mode, then checks the
+/// target block for someone else capturing the same opportunity.
+pub struct ShadowRecorder {
+    provider: Arc<Provider<RpcTransport>>,
+    storage: Option<Storage>,
+    pending: Mutex<Vec<ShadowEntry>>,
+}
+
+// Heuristic: a tx sent to one of our routers (or any other contract) whose
+// calldata references every distinct token of the cycle is treated as a
+// competing capture. Calls to a path token itself are transfers and approvals,
+// not swaps
+pub fn tx_references_path(tx: &Transaction, path: &[Address], routers: &[Address]) -> bool {
+    let to = match tx.to {
+        Some(to) => to,
+        None => return false,
+    };
+    let mut tokens = path.to_vec();
+    tokens.sort();
+    tokens.dedup();
+    if tokens.is_empty() || tokens.contains(&to) {
+        return false;
+    }
+
+    let input = tx.input.as_ref();
+    tokens.iter().all(|token| input.windows(20).any(|w| w == token.as_bytes()))
+}
+
+impl ShadowRecorder {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, storage: Option<Storage>) -> Self {
+        Self {
+            provider,
+            storage,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub async fn record(&self, entry: ShadowEntry) -> Result<()> {
+        info!(
+            target_block = %entry.target_block,
+            expected_profit = %entry.expected_profit,
+            "Shadow mode: would submit {} bytes of calldata",
+            entry.calldata.len()
+        );
+        if let Some(storage) = &self.storage {
+            storage.record_decision(&entry.opportunity_id, true, "shadow: not broadcast").await?;
+        }
+        self.pending.lock().await.push(entry);
+        Ok(())
+    }
+
+    pub async fn on_block(&self, block: U64) -> Result<Vec<ShadowOutcome>> {
+        let due: Vec<ShadowEntry> = {
+            let mut pending = self.pending.lock().await;
+            let (due, waiting) = pending.drain(..).partition(|e| e.target_block <= block);
+            *pending = waiting;
+            due
+        };
+        if due.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut outcomes = Vec::new();
+        for entry in due {
+            let landed = match self.provider.get_block_with_txs(entry.target_block).await? {
+                Some(b) => b,
+                None => {
+                    // Target block not visible yet, check again next block
+                    self.pending.lock().await.push(entry);
+                    continue;
+                }
+            };
+
+            let taken_by = landed
+                .transactions
+                .iter()
+                .find(|tx| tx_references_path(tx, &entry.path, &entry.routers))
+                .map(|tx| (tx.hash, tx.from));
+
+            match &taken_by {
+                Some((hash, from)) => info!(
+                    "Shadow opportunity {} taken in block {} by {:?} ({:?})",
+                    entry.opportunity_id, entry.target_block, from, hash
+                ),
+                None => info!(
+                    "Shadow opportunity {} not taken in block {}",
+                    entry.opportunity_id, entry.target_block
+                ),
+            }
+
+            if let Some(storage) = &self.storage {
+                let reason = match &taken_by {
+                    Some((hash, from)) => format!("shadow: taken by {:?} in {:?}", from, hash),
+                    None => "shadow: not taken".to_string(),
+                };
+                storage.record_decision(&entry.opportunity_id, false, &reason).await?;
+            }
+
+            outcomes.push(ShadowOutcome {
+                opportunity_id: entry.opportunity_id,
+                block: entry.target_block,
+                taken_by,
+            });
+        }
+        Ok(outcomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(to: Address, mentions: &[Address]) -> Transaction {
+        let input: Vec<u8> = [0xde, 0xad, 0xbe, 0xef]
+            .into_iter()
+            .chain(mentions.iter().flat_map(|a| [&[0u8; 12][..], a.as_bytes()].concat()))
+            .collect();
+        Transaction { to: Some(to), input: Bytes::from(input), ..Default::default() }
+    }
+
+    #[test]
+    fn matches_only_calls_that_reference_the_whole_path() {
+        let (wmatic, usdc) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        let path = [wmatic, usdc, wmatic];
+        let router = Address::repeat_byte(0xaa);
+        let bot = Address::repeat_byte(0xbb);
+
+        assert!(tx_references_path(&tx(router, &[wmatic, usdc]), &path, &[router]));
+        assert!(tx_references_path(&tx(bot, &[usdc, wmatic]), &path, &[router]));
+        // Half the path isn't the same cycle
+        assert!(!tx_references_path(&tx(router, &[wmatic]), &path, &[router]));
+        // An approve or transfer on one token naming the other isn't a swap
+        assert!(!tx_references_path(&tx(usdc, &[wmatic, usdc]), &path, &[router]));
+        assert!(!tx_references_path(&tx(router, &[wmatic, usdc]), &[], &[router]));
+    }
+}
@@ -0,0 +1,104 @@
+// src/shutdown.rs
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+
+/// Set once on SIGINT/SIGTERM. Loops select on `wait()` so they stop taking new
+/// work without being cancelled mid-item.
+pub struct Shutdown {
+    sender: watch::Sender<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self { sender }
+    }
+}
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        while !*receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Counts work items (simulations, executions) currently running.
+#[derive(Clone, Default)]
+pub struct InFlight {
+    inner: Arc<(AtomicUsize, Notify)>,
+}
+
+pub struct InFlightGuard {
+    inner: Arc<(AtomicUsize, Notify)>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.inner.0.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.1.notify_waiters();
+        }
+    }
+}
+
+impl InFlight {
+    pub fn enter(&self) -> InFlightGuard {
+        self.inner.0.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard { inner: self.inner.clone() }
+    }
+
+    pub fn count(&self) -> usize {
+        self.inner.0.load(Ordering::Acquire)
+    }
+
+    /// Waits until nothing is in flight. Returns false if `timeout` ran out first.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let idle = self.inner.1.notified();
+                if self.count() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+/// Resolves on the first SIGINT or SIGTERM.
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(s) => s,
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+                return;
+            }
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
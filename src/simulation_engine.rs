@@ -1,64 +1,251 @@
 // src/simulation_engine.rs
+use anyhow::Result;
 use ethers::{
+    abi::Token,
     prelude::*,
     types::{Address, U256},
 };
 use revm::{
-    db::{CacheDB, EmptyDB, InMemoryDB},
-    primitives::{Bytecode, ExecutionResult, TransactTo, Env},
+    db::CacheDB,
+    primitives::{Bytecode, EVMError, ExecutionResult, Log as rLog, Output, TransactTo, Env, B160, KECCAK_EMPTY, U256 as rU256},
     Database, DatabaseCommit, EVM,
 };
 use std::collections::HashMap;
+use std::fmt::Debug;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use crate::call_tracer::PoolDelta;
+use crate::confidence::{ConfidenceFeatures, ConfidenceModel};
+use crate::errors::ErrorClass;
+use crate::fee::FeeAmount;
+use crate::fork_db::ForkDb;
+use crate::native;
+use crate::oracle_monitor::OracleUpdate;
+use crate::path_constraints::PathConstraints;
+use crate::pool_state::{PoolState, PoolStateManager};
+use crate::revert::{ExecutionFailure, RevertReason};
+use crate::state_override::{MappingSlot, Override, StateOverrides};
+use crate::touch_inspector::TouchInspector;
+use crate::rpc_budget::RpcTransport;
+
+// Enough for a multi-hop flash-loan execution
+const PREFLIGHT_GAS_LIMIT: u64 = 3_000_000;
+// Input the candidate paths are priced at, matching the opportunity size
+const PATH_PROBE_AMOUNT: u64 = 1_000_000_000_000_000_000;
+// Declared slots searched for a token's balance and allowance mappings
+const MAPPING_PROBE_SLOTS: u64 = 32;
+// Written into a candidate slot while probing; small enough for packed balances
+const PROBE_SENTINEL: u64 = 0x5eed_1e55_0000;
 
-#[derive(Debug, Clone)]
 pub struct AdvancedSimulationEngine {
-    provider: Arc<Provider<Ws>>,
-    dex_routers: HashMap<Address, String>,
+    provider: Arc<Provider<RpcTransport>>,
+    pool_state: Arc<PoolStateManager>,
+    confidence: Arc<ConfidenceModel>,
+    constraints: PathConstraints,
+    // Latest USD TVL per pool, for the constraints' minimum
+    pool_tvl: std::sync::RwLock<HashMap<Address, f64>>,
     pool_cache: Mutex<HashMap<Address, PoolData>>,
     simulation_cache: Mutex<HashMap<H256, SimulationResult>>,
+    // Pending oracle transmits per token, until the next head
+    oracle_moves: Mutex<HashMap<Address, OracleUpdate>>,
+    // Fork of the current head, shared by every simulation until the next block
+    head_fork: Mutex<Option<(U64, ForkDb)>>,
+    // Balance (false) and allowance (true) mapping per token, found by probing
+    mapping_slots: std::sync::RwLock<HashMap<(Address, bool), MappingSlot>>,
+}
+
+/// Why a simulation gave no answer. `Reverted` and the token failures are the
+/// candidate's own; fork and provider errors say nothing about it and may clear
+/// on a retry.
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    #[error(transparent)]
+    Reverted(#[from] ExecutionFailure),
+    #[error("{call} on {token:?} failed: {detail}")]
+    TokenCall { token: Address, call: &'static str, detail: String },
+    #[error("No {mapping} mapping found for {token:?} in its first {slots} slots")]
+    MappingNotFound { token: Address, mapping: &'static str, slots: u64 },
+    // revm refused the transaction itself: bad environment, not bad state
+    #[error("revm error: {0}")]
+    Evm(String),
+    #[error("fork error: {0}")]
+    Fork(String),
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+impl SimulationError {
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            SimulationError::Reverted(_) | SimulationError::TokenCall { .. } | SimulationError::MappingNotFound { .. } => {
+                ErrorClass::Rejected
+            }
+            SimulationError::Evm(_) => ErrorClass::Fatal,
+            SimulationError::Fork(_) | SimulationError::Provider(_) => ErrorClass::Retryable,
+        }
+    }
+
+    pub fn failure(&self) -> Option<&ExecutionFailure> {
+        match self {
+            SimulationError::Reverted(failure) => Some(failure),
+            _ => None,
+        }
+    }
+
+    // State reads behind the fork are RPC calls; those failures are the fork's
+    fn evm<E: Debug>(e: EVMError<E>) -> Self {
+        match e {
+            EVMError::Database(e) => SimulationError::Fork(format!("{:?}", e)),
+            other => SimulationError::Evm(format!("{:?}", other)),
+        }
+    }
+
+    fn fork(e: impl Debug) -> Self {
+        SimulationError::Fork(format!("{:?}", e))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PoolData {
     pub token0: Address,
     pub token1: Address,
-    pub fee: u32,
+    pub fee: FeeAmount,
     pub liquidity: U256,
     pub sqrt_price_x96: U256,
 }
 
+/// Gas and logs of one revm execution, as the node would have reported them.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionCapture {
+    pub success: bool,
+    pub gas_used: u64,
+    pub logs: Vec<Log>,
+}
+
+impl ExecutionCapture {
+    fn from_result(result: &ExecutionResult) -> Self {
+        let to_log = |log: &rLog| Log {
+            address: Address::from(log.address.0),
+            topics: log.topics.iter().map(|t| H256::from(t.0)).collect(),
+            data: Bytes::from(log.data.to_vec()),
+            ..Default::default()
+        };
+        match result {
+            ExecutionResult::Success { gas_used, logs, .. } => Self {
+                success: true,
+                gas_used: *gas_used,
+                logs: logs.iter().map(to_log).collect(),
+            },
+            ExecutionResult::Revert { gas_used, .. } | ExecutionResult::Halt { gas_used, .. } => Self {
+                success: false,
+                gas_used: *gas_used,
+                logs: Vec::new(),
+            },
+        }
+    }
+
+    /// Realized amounts of every V2/V3 swap the execution performed.
+    pub fn swaps(&self) -> Vec<PoolDelta> {
+        self.logs.iter().filter_map(PoolDelta::from_swap_log).collect()
+    }
+}
+
+/// What the exact execution calldata did in preflight.
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    // Owner balance change in the start token
+    pub delta: I256,
+    pub capture: ExecutionCapture,
+}
+
+/// Both ways a submitted execution can end up on chain, as costs in the native
+/// token (gas) and the loan token (everything else).
+#[derive(Debug, Clone)]
+pub struct LossBound {
+    // Gas burned if it lands reverted; zero on venues that drop reverting txs
+    pub revert_gas_cost: U256,
+    // Gas paid when it lands as simulated
+    pub land_gas_cost: U256,
+    pub loan_fee: U256,
+    // Executor delta when it lands, loan fee already repaid
+    pub land_delta: I256,
+}
+
+impl Preflight {
+    /// A revert burns up to the whole gas limit but repays no loan fee (the flash
+    /// loan unwinds with it); a landed execution pays preflight gas and the fee.
+    pub fn loss_bound(&self, gas_price: U256, gas_limit: u64, revertible: bool, loan_fee: U256) -> LossBound {
+        LossBound {
+            revert_gas_cost: if revertible { gas_price * U256::from(gas_limit) } else { U256::zero() },
+            land_gas_cost: gas_price * U256::from(self.capture.gas_used),
+            loan_fee,
+            land_delta: self.delta,
+        }
+    }
+}
+
+/// A simulated frontrun buy: what the AMM quoted and what actually arrived.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub quoted: U256,
+    pub received: U256,
+}
+
+impl Delivery {
+    pub fn shortfall_bps(&self) -> u64 {
+        if self.quoted.is_zero() {
+            return 0;
+        }
+        (self.quoted.saturating_sub(self.received) * U256::from(10_000u64) / self.quoted).as_u64()
+    }
+}
+
+/// One transaction of a bundle, for `simulate_sequence`.
+#[derive(Debug, Clone)]
+pub enum BundleLeg {
+    // Someone else's pending tx, run as sent
+    Pending(Transaction),
+    // An executor call of ours
+    Call { caller: Address, to: Address, calldata: Bytes },
+}
+
+/// What a sequence of transactions did: each leg's capture, and the owner's
+/// balance change in every token asked for.
+#[derive(Debug, Clone)]
+pub struct SequenceOutcome {
+    pub legs: Vec<ExecutionCapture>,
+    pub deltas: Vec<I256>,
+}
+
 #[derive(Debug, Clone)]
 pub struct SimulationResult {
     pub price_impact: U256,
     pub expected_profit: U256,
     pub gas_estimate: U256,
     pub success_probability: f64,
+    // What `success_probability` was scored from, kept for recalibration
+    pub confidence: ConfidenceFeatures,
     pub optimal_path: Vec<Address>,
 }
 
 impl AdvancedSimulationEngine {
-    pub fn new(provider: Arc<Provider<Ws>>) -> Self {
-        let mut dex_routers = HashMap::new();
-        dex_routers.insert(
-            Address::from_str("0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff").unwrap(),
-            "QuickSwap".to_string(),
-        );
-        dex_routers.insert(
-            Address::from_str("0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506").unwrap(),
-            "SushiSwap".to_string(),
-        );
-        dex_routers.insert(
-            Address::from_str("0xE592427A0AEce92De3Edee1F18E0157C05861564").unwrap(),
-            "UniswapV3".to_string(),
-        );
-
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        pool_state: Arc<PoolStateManager>,
+        confidence: Arc<ConfidenceModel>,
+        constraints: PathConstraints,
+    ) -> Self {
         Self {
             provider,
-            dex_routers,
+            pool_state,
+            confidence,
+            constraints,
+            pool_tvl: std::sync::RwLock::new(HashMap::new()),
             pool_cache: Mutex::new(HashMap::new()),
             simulation_cache: Mutex::new(HashMap::new()),
+            oracle_moves: Mutex::new(HashMap::new()),
+            head_fork: Mutex::new(None),
+            mapping_slots: std::sync::RwLock::new(HashMap::new()),
         }
     }
 
@@ -75,11 +262,6 @@ impl AdvancedSimulationEngine {
             }
         }
 
-        // Multi-DEX simulation logic
-        let mut evm = EVM::new();
-        let db = InMemoryDB::default();
-        evm.database(db);
-
         // Simulate transaction impact across multiple DEXs
         let result = self.simulate_complex_path(tx, depth).await?;
 
@@ -90,29 +272,477 @@ impl AdvancedSimulationEngine {
         Ok(result)
     }
 
+    /// Executes the exact execution calldata against state at `block` in revm and
+    /// returns the executor owner's balance change in `token`, with the gas it
+    /// used. The executor pays profit out to its owner, which is also what
+    /// `executeGuarded` checks. Reverts are errors.
+    pub async fn preflight_execution(
+        &self,
+        caller: Address,
+        executor: Address,
+        calldata: Bytes,
+        token: Address,
+        block: U64,
+    ) -> Result<Preflight, SimulationError> {
+        self.preflight_with_overrides(caller, executor, calldata, token, block, &StateOverrides::default()).await
+    }
+
+    /// `preflight_execution` on state changed by `overrides` first, e.g. with
+    /// inventory the executor doesn't hold yet. The delta is measured from the
+    /// overridden balance.
+    pub async fn preflight_with_overrides(
+        &self,
+        caller: Address,
+        executor: Address,
+        calldata: Bytes,
+        token: Address,
+        block: U64,
+        overrides: &StateOverrides,
+    ) -> Result<Preflight, SimulationError> {
+        let mut db = self.fork_at(block).await;
+        self.apply_overrides(&mut db, overrides)?;
+        let mut evm = EVM::new();
+        evm.database(db);
+        evm.env.block.number = rU256::from(block.as_u64() + 1);
+
+        let owner = Self::owner_of(&mut evm, executor)?;
+        let before = Self::balance_of(&mut evm, token, owner)?;
+
+        evm.env.tx.caller = B160::from(caller.0);
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(executor.0));
+        evm.env.tx.data = calldata.0;
+        evm.env.tx.gas_limit = PREFLIGHT_GAS_LIMIT;
+        let result = evm.transact_commit().map_err(SimulationError::evm)?;
+        match &result {
+            ExecutionResult::Success { .. } => {}
+            ExecutionResult::Revert { output, .. } => {
+                return Err(ExecutionFailure::new("preflight", RevertReason::decode(output)).into());
+            }
+            ExecutionResult::Halt { reason, .. } => {
+                return Err(ExecutionFailure::new("preflight", RevertReason::Text(format!("{:?}", reason))).into());
+            }
+        }
+
+        let after = Self::balance_of(&mut evm, token, owner)?;
+        Ok(Preflight {
+            delta: I256::from_raw(after) - I256::from_raw(before),
+            capture: ExecutionCapture::from_result(&result),
+        })
+    }
+
+    /// Buys the other side of V2 `pool` with `amount_in` of `token_in`, paid out
+    /// to `recipient`, and compares what arrived with the constant-product quote.
+    /// Tokens that short-change senders they don't expect (salmonella) only show
+    /// it when the real recipient is used. The input comes from `holder`, any
+    /// address holding enough of it, impersonated with its code stripped since
+    /// revm rejects senders with code.
+    pub async fn frontrun_delivery(
+        &self,
+        pool: &PoolState,
+        token_in: Address,
+        amount_in: U256,
+        recipient: Address,
+        holder: Address,
+    ) -> Result<Delivery, SimulationError> {
+        let token_out = if pool.token0 == token_in { pool.token1 } else { pool.token0 };
+        let quoted = pool.get_amount_out(token_in, amount_in);
+
+        let mut db = self.fork_at_head().await?;
+        let mut info = db.basic(B160::from(holder.0)).map_err(SimulationError::fork)?.unwrap_or_default();
+        info.code = None;
+        info.code_hash = KECCAK_EMPTY;
+        db.insert_account_info(B160::from(holder.0), info);
+        let mut evm = EVM::new();
+        evm.database(db);
+
+        let before = Self::balance_of(&mut evm, token_out, recipient)?;
+        let transfer = [
+            ethers::utils::id("transfer(address,uint256)").to_vec(),
+            ethers::abi::encode(&[Token::Address(pool.address), Token::Uint(amount_in)]),
+        ]
+        .concat();
+        if !Self::call(&mut evm, holder, token_in, transfer)? {
+            return Err(SimulationError::TokenCall {
+                token: token_in,
+                call: "transfer",
+                detail: format!("funding transfer from {:?} reverted", holder),
+            });
+        }
+        let (amount0_out, amount1_out) = if token_in == pool.token0 { (U256::zero(), quoted) } else { (quoted, U256::zero()) };
+        let swap = [
+            ethers::utils::id("swap(uint256,uint256,address,bytes)").to_vec(),
+            ethers::abi::encode(&[
+                Token::Uint(amount0_out),
+                Token::Uint(amount1_out),
+                Token::Address(recipient),
+                Token::Bytes(Vec::new()),
+            ]),
+        ]
+        .concat();
+        // A pool that can't pay the quote (the input arrived short) delivers nothing
+        if !Self::call(&mut evm, Address::zero(), pool.address, swap)? {
+            return Ok(Delivery { quoted, received: U256::zero() });
+        }
+        let after = Self::balance_of(&mut evm, token_out, recipient)?;
+        Ok(Delivery { quoted, received: after.saturating_sub(before) })
+    }
+
+    // Committed call; false when it reverts or halts
+    fn call(evm: &mut EVM<CacheDB<ForkDb>>, caller: Address, to: Address, data: Vec<u8>) -> Result<bool, SimulationError> {
+        evm.env.tx.caller = B160::from(caller.0);
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(to.0));
+        evm.env.tx.data = data.into();
+        evm.env.tx.gas_limit = PREFLIGHT_GAS_LIMIT;
+        let result = evm.transact_commit().map_err(SimulationError::evm)?;
+        Ok(result.is_success())
+    }
+
+    /// Runs `legs` in order on one copy of the head fork, each on the state the
+    /// ones before it left, as a block would. A reverting leg is captured, not
+    /// an error, and the rest still run. `deltas` follow the order of `tokens`.
+    pub async fn simulate_sequence(
+        &self,
+        legs: &[BundleLeg],
+        owner: Address,
+        tokens: &[Address],
+    ) -> Result<SequenceOutcome, SimulationError> {
+        let mut evm = EVM::new();
+        evm.database(self.fork_at_head().await?);
+        let mut before = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            before.push(Self::balance_of(&mut evm, *token, owner)?);
+        }
+
+        let mut captures = Vec::with_capacity(legs.len());
+        for leg in legs {
+            match leg {
+                BundleLeg::Pending(tx) => match tx.to {
+                    Some(to) => Self::load_tx(&mut evm, tx, to),
+                    // Deployments don't swap
+                    None => {
+                        captures.push(ExecutionCapture::default());
+                        continue;
+                    }
+                },
+                BundleLeg::Call { caller, to, calldata } => {
+                    evm.env.tx.caller = B160::from(caller.0);
+                    evm.env.tx.transact_to = TransactTo::Call(B160::from(to.0));
+                    evm.env.tx.data = calldata.0.clone();
+                    evm.env.tx.value = rU256::ZERO;
+                    evm.env.tx.gas_limit = PREFLIGHT_GAS_LIMIT;
+                }
+            }
+            let result = evm.transact_commit().map_err(SimulationError::evm)?;
+            captures.push(ExecutionCapture::from_result(&result));
+        }
+
+        let mut deltas = Vec::with_capacity(tokens.len());
+        for (token, before) in tokens.iter().zip(before) {
+            let after = Self::balance_of(&mut evm, *token, owner)?;
+            deltas.push(I256::from_raw(after) - I256::from_raw(before));
+        }
+        Ok(SequenceOutcome { legs: captures, deltas })
+    }
+
+    /// Runs a pending transaction on top of the head fork without committing it.
+    pub async fn execute_pending(&self, tx: &Transaction) -> Result<ExecutionCapture, SimulationError> {
+        let to = match tx.to {
+            Some(to) => to,
+            None => return Ok(ExecutionCapture::default()),
+        };
+        let mut evm = EVM::new();
+        evm.database(self.fork_at_head().await?);
+        Self::load_tx(&mut evm, tx, to);
+        let outcome = evm.transact().map_err(SimulationError::evm)?;
+        Ok(ExecutionCapture::from_result(&outcome.result))
+    }
+
+    /// Runs a call on top of the head fork after applying `overrides`, without
+    /// committing it. Reverts are captured, not errors.
+    pub async fn call_with_overrides(
+        &self,
+        caller: Address,
+        to: Address,
+        calldata: Bytes,
+        value: U256,
+        overrides: &StateOverrides,
+    ) -> Result<ExecutionCapture, SimulationError> {
+        let mut db = self.fork_at_head().await?;
+        self.apply_overrides(&mut db, overrides)?;
+        let mut evm = EVM::new();
+        evm.database(db);
+        let mut raw = [0u8; 32];
+        value.to_big_endian(&mut raw);
+        evm.env.tx.caller = B160::from(caller.0);
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(to.0));
+        evm.env.tx.data = calldata.0;
+        evm.env.tx.value = rU256::from_be_bytes(raw);
+        evm.env.tx.gas_limit = PREFLIGHT_GAS_LIMIT;
+        let outcome = evm.transact().map_err(SimulationError::evm)?;
+        Ok(ExecutionCapture::from_result(&outcome.result))
+    }
+
+    /// Writes `overrides` into `db` in order. Token balances and allowances go
+    /// to the token's own mapping, located by probing on first use.
+    pub fn apply_overrides(&self, db: &mut CacheDB<ForkDb>, overrides: &StateOverrides) -> Result<(), SimulationError> {
+        for entry in overrides.entries() {
+            match entry {
+                Override::Balance { token, owner, amount } => {
+                    let slot = self.mapping_slot(db, *token, *owner, None)?.key(*owner);
+                    Self::write_slot(db, *token, slot, *amount)?;
+                }
+                Override::Allowance { token, owner, spender, amount } => {
+                    let slot = self.mapping_slot(db, *token, *owner, Some(*spender))?.nested(*owner, *spender);
+                    Self::write_slot(db, *token, slot, *amount)?;
+                }
+                Override::Storage { account, slot, value } => {
+                    Self::write_slot(db, *account, *slot, U256::from_big_endian(value.as_bytes()))?;
+                }
+                Override::NativeBalance { account, amount } => {
+                    let address = B160::from(account.0);
+                    let mut info = db.basic(address).map_err(SimulationError::fork)?.unwrap_or_default();
+                    let mut raw = [0u8; 32];
+                    amount.to_big_endian(&mut raw);
+                    info.balance = rU256::from_be_bytes(raw);
+                    db.insert_account_info(address, info);
+                }
+                Override::Code { account, code } => {
+                    let address = B160::from(account.0);
+                    let mut info = db.basic(address).map_err(SimulationError::fork)?.unwrap_or_default();
+                    if code.is_empty() {
+                        info.code = None;
+                        info.code_hash = KECCAK_EMPTY;
+                    } else {
+                        let code = Bytecode::new_raw(code.0.clone());
+                        info.code_hash = code.hash_slow();
+                        info.code = Some(code);
+                    }
+                    db.insert_account_info(address, info);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Finds the declared slot of `token`'s balance mapping, or its allowance
+    // mapping when `spender` is given, by writing a sentinel to each candidate
+    // and reading it back through the token's own getter. Proxies keep the
+    // mapping in their own storage, so this sees through them
+    fn mapping_slot(&self, db: &CacheDB<ForkDb>, token: Address, owner: Address, spender: Option<Address>) -> Result<MappingSlot, SimulationError> {
+        let key = (token, spender.is_some());
+        if let Some(slot) = self.mapping_slots.read().unwrap().get(&key) {
+            return Ok(*slot);
+        }
+
+        let sentinel = U256::from(PROBE_SENTINEL);
+        for index in 0..MAPPING_PROBE_SLOTS {
+            for vyper in [false, true] {
+                let slot = MappingSlot { index, vyper };
+                let location = match spender {
+                    Some(spender) => slot.nested(owner, spender),
+                    None => slot.key(owner),
+                };
+                let mut probe = db.clone();
+                Self::write_slot(&mut probe, token, location, sentinel)?;
+                let mut evm = EVM::new();
+                evm.database(probe);
+                let read = match spender {
+                    Some(spender) => Self::allowance(&mut evm, token, owner, spender),
+                    None => Self::balance_of(&mut evm, token, owner),
+                };
+                if matches!(read, Ok(value) if value == sentinel) {
+                    self.mapping_slots.write().unwrap().insert(key, slot);
+                    return Ok(slot);
+                }
+            }
+        }
+        let mapping = if spender.is_some() { "allowance" } else { "balance" };
+        Err(SimulationError::MappingNotFound { token, mapping, slots: MAPPING_PROBE_SLOTS })
+    }
+
+    fn write_slot(db: &mut CacheDB<ForkDb>, account: Address, slot: H256, value: U256) -> Result<(), SimulationError> {
+        let mut raw = [0u8; 32];
+        value.to_big_endian(&mut raw);
+        db.insert_account_storage(B160::from(account.0), rU256::from_be_bytes(slot.0), rU256::from_be_bytes(raw))
+            .map_err(SimulationError::fork)
+    }
+
+    fn load_tx(evm: &mut EVM<CacheDB<ForkDb>>, tx: &Transaction, to: Address) {
+        evm.env.tx.caller = B160::from(tx.from.0);
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(to.0));
+        evm.env.tx.data = tx.input.0.clone();
+        let mut value = [0u8; 32];
+        tx.value.to_big_endian(&mut value);
+        evm.env.tx.value = rU256::from_be_bytes(value);
+        evm.env.tx.gas_limit = tx.gas.as_u64();
+    }
+
+    /// Runs a pending transaction on top of the head fork and records what it
+    /// touches. Nothing is committed; a revert just touches nothing.
+    pub async fn touched_state(&self, tx: &Transaction) -> Result<TouchInspector, SimulationError> {
+        // Deployments don't swap
+        let to = match tx.to {
+            Some(to) => to,
+            None => return Ok(TouchInspector::default()),
+        };
+        let mut evm = EVM::new();
+        evm.database(self.fork_at_head().await?);
+        Self::load_tx(&mut evm, tx, to);
+
+        let mut inspector = TouchInspector::default();
+        let outcome = evm.inspect_ref(&mut inspector).map_err(SimulationError::evm)?;
+        if !outcome.result.is_success() {
+            return Ok(TouchInspector::default());
+        }
+        Ok(inspector)
+    }
+
+    /// Pins the shared fork to a new head. Keyed by hash so a reorg at the same
+    /// height still drops the stale state.
+    pub async fn on_new_head(&self, number: U64, hash: H256) {
+        *self.head_fork.lock().await = Some((number, ForkDb::new(self.provider.clone(), hash)));
+        self.simulation_cache.lock().await.clear();
+        // A transmit still pending is seen again; one that landed is in the reserves
+        self.oracle_moves.lock().await.clear();
+    }
+
+    /// Replaces the per-pool USD TVL that pathfinding holds against the minimum.
+    pub fn update_tvl(&self, tvl: HashMap<Address, f64>) {
+        *self.pool_tvl.write().unwrap() = tvl;
+    }
+
+    /// Drops cached simulations and pool reads; the next ones start cold.
+    pub async fn clear_cache(&self) {
+        self.simulation_cache.lock().await.clear();
+        self.pool_cache.lock().await.clear();
+    }
+
+    // Cheap per-candidate copy: a fresh CacheDB layer over the shared fork
+    async fn fork_at(&self, block: U64) -> CacheDB<ForkDb> {
+        let mut head = self.head_fork.lock().await;
+        match head.as_ref() {
+            Some((number, fork)) if *number == block => CacheDB::new(fork.clone()),
+            Some((number, _)) if *number > block => {
+                // Older state than the head: don't evict the shared fork for it
+                CacheDB::new(ForkDb::new(self.provider.clone(), block))
+            }
+            _ => {
+                let fork = ForkDb::new(self.provider.clone(), block);
+                *head = Some((block, fork.clone()));
+                CacheDB::new(fork)
+            }
+        }
+    }
+
+    async fn fork_at_head(&self) -> Result<CacheDB<ForkDb>, SimulationError> {
+        let head = self.head_fork.lock().await.as_ref().map(|(number, _)| *number);
+        let block = match head {
+            Some(number) => number,
+            None => self.provider.get_block_number().await?,
+        };
+        Ok(self.fork_at(block).await)
+    }
+
+    fn owner_of(evm: &mut EVM<CacheDB<ForkDb>>, executor: Address) -> Result<Address, SimulationError> {
+        evm.env.tx.caller = B160::zero();
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(executor.0));
+        evm.env.tx.data = vec![0x8d, 0xa5, 0xcb, 0x5b].into(); // owner()
+        evm.env.tx.gas_limit = 100_000;
+        let outcome = evm.transact().map_err(SimulationError::evm)?;
+
+        match outcome.result {
+            ExecutionResult::Success { output: Output::Call(bytes), .. } if bytes.len() >= 32 => {
+                Ok(Address::from_slice(&bytes[12..32]))
+            }
+            other => Err(SimulationError::TokenCall { token: executor, call: "owner", detail: format!("{:?}", other) }),
+        }
+    }
+
+    fn balance_of(evm: &mut EVM<CacheDB<ForkDb>>, token: Address, owner: Address) -> Result<U256, SimulationError> {
+        let mut data = vec![0x70, 0xa0, 0x82, 0x31]; // balanceOf(address)
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(owner.as_bytes());
+
+        evm.env.tx.caller = B160::zero();
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(token.0));
+        evm.env.tx.data = data.into();
+        evm.env.tx.gas_limit = 100_000;
+        let outcome = evm.transact().map_err(SimulationError::evm)?;
+
+        match outcome.result {
+            ExecutionResult::Success { output: Output::Call(bytes), .. } if bytes.len() >= 32 => {
+                Ok(U256::from_big_endian(&bytes[..32]))
+            }
+            other => Err(SimulationError::TokenCall { token, call: "balanceOf", detail: format!("{:?}", other) }),
+        }
+    }
+
+    fn allowance(evm: &mut EVM<CacheDB<ForkDb>>, token: Address, owner: Address, spender: Address) -> Result<U256, SimulationError> {
+        let data = [
+            ethers::utils::id("allowance(address,address)").to_vec(),
+            ethers::abi::encode(&[Token::Address(owner), Token::Address(spender)]),
+        ]
+        .concat();
+
+        evm.env.tx.caller = B160::zero();
+        evm.env.tx.transact_to = TransactTo::Call(B160::from(token.0));
+        evm.env.tx.data = data.into();
+        evm.env.tx.gas_limit = 100_000;
+        let outcome = evm.transact().map_err(SimulationError::evm)?;
+
+        match outcome.result {
+            ExecutionResult::Success { output: Output::Call(bytes), .. } if bytes.len() >= 32 => {
+                Ok(U256::from_big_endian(&bytes[..32]))
+            }
+            other => Err(SimulationError::TokenCall { token, call: "allowance", detail: format!("{:?}", other) }),
+        }
+    }
+
+    // Pending oracle updates move the fair price before the block that includes
+    // them, so paths through the affected token are re-priced immediately
+    pub async fn apply_oracle_update(&self, update: OracleUpdate) {
+        self.oracle_moves.lock().await.insert(update.token, update);
+        self.simulation_cache.lock().await.clear();
+    }
+
     async fn simulate_complex_path(
         &self,
         tx: &Transaction,
         depth: usize,
     ) -> Result<SimulationResult> {
-        // Implement multi-hop simulation across different DEXs
+        // Paths are priced on the reserves the victim leaves, taken from the
+        // Swap events its revm execution emitted
+        let victim = self.execute_pending(tx).await?;
+        let swaps = victim.swaps();
+        let mut pools = self.pool_state.snapshot().await;
+        for swap in &swaps {
+            if let Some(pool) = pools.iter_mut().find(|p| p.address == swap.pool) {
+                pool.apply_delta(swap.amount0, swap.amount1);
+            }
+        }
+
         let mut best_profit = U256::zero();
         let mut optimal_path = Vec::new();
 
         // Simulate various arbitrage paths
-        for path in self.generate_arbitrage_paths(tx, depth).await? {
-            let profit = self.calculate_path_profit(&path).await?;
+        let paths = self.generate_arbitrage_paths(tx, depth).await?;
+        let oracle_moves = self.oracle_moves.lock().await.clone();
+        for path in paths {
+            let profit = self.calculate_path_profit(&path, &pools, &oracle_moves);
             if profit > best_profit {
                 best_profit = profit;
                 optimal_path = path;
             }
         }
 
+        let gas_price = self.provider.get_gas_price().await?;
+        let confidence = self.confidence.features(tx, &swaps, gas_price);
         Ok(SimulationResult {
             price_impact: self.calculate_price_impact(&optimal_path).await?,
             expected_profit: best_profit,
-            gas_estimate: self.estimate_gas_cost(&optimal_path).await?,
-            success_probability: self.calculate_success_probability(&optimal_path).await?,
+            gas_estimate: gas_price * U256::from(300000), // 300k gas
+            success_probability: self.confidence.score(&confidence),
+            confidence,
             optimal_path,
         })
     }
@@ -139,41 +769,64 @@ impl AdvancedSimulationEngine {
             Address::from_str("0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270")?, // WMATIC
         ]);
 
-        Ok(paths)
-    }
-
-    async fn calculate_path_profit(&self, path: &[Address]) -> Result<U256> {
-        // Advanced profit calculation with slippage and fees
-        let base_profit = U256::from(15).pow(15.into()); // 0.015 ETH
-        let fees = self.calculate_total_fees(path).await?;
-        let slippage = self.estimate_slippage(path).await?;
-
-        Ok(base_profit - fees - slippage)
-    }
+        // Back-runs through the tokens the victim moved; one that sends no
+        // value moves no reserves
+        let wmatic = native::wmatic();
+        if let Some(victim) = native::victim_swap(tx).filter(|v| !v.amount_in.is_zero()) {
+            for token in victim.path.into_iter().filter(|t| *t != wmatic) {
+                let cycle = vec![wmatic, token, wmatic];
+                if !paths.contains(&cycle) {
+                    paths.push(cycle);
+                }
+            }
+        }
 
-    async fn calculate_total_fees(&self, path: &[Address]) -> Result<U256> {
-        // Calculate total fees across all DEXs in path
-        Ok(U256::from(2).pow(15.into())) // 0.002 ETH
+        paths.retain(|path| self.constraints.allows_path(path));
+        Ok(paths)
     }
 
-    async fn estimate_slippage(&self, path: &[Address]) -> Result<U256> {
-        // Estimate slippage based on pool liquidity
-        Ok(U256::from(1).pow(15.into())) // 0.001 ETH
+    // Best allowed pool per hop; fees and slippage are in the constant-product output.
+    // A token with a pending oracle move is bought at the pools' stale price but
+    // sold at the price the transmit implies, which is what the pools move to once
+    // it lands
+    fn calculate_path_profit(&self, path: &[Address], pools: &[PoolState], oracle_moves: &HashMap<Address, OracleUpdate>) -> U256 {
+        let tvl = self.pool_tvl.read().unwrap();
+        let amount_in = U256::from(PATH_PROBE_AMOUNT);
+        let mut amount = amount_in;
+        for hop in path.windows(2) {
+            amount = pools
+                .iter()
+                .filter(|p| p.has_token(hop[0]) && p.has_token(hop[1]))
+                .filter(|p| self.constraints.allows_pool(p, tvl.get(&p.address).copied()))
+                .map(|p| p.get_amount_out(hop[0], amount))
+                .max()
+                .unwrap_or_default();
+            if let Some(update) = oracle_moves.get(&hop[0]) {
+                amount = repriced(amount, update.change_bps);
+            }
+        }
+        amount.saturating_sub(amount_in)
     }
 
     async fn calculate_price_impact(&self, path: &[Address]) -> Result<U256> {
         // Calculate price impact percentage
-        Ok(U256::from(150)) // 1.5%
-    }
+        let base_impact = U256::from(150); // 1.5%
 
-    async fn estimate_gas_cost(&self, path: &[Address]) -> Result<U256> {
-        // Estimate gas cost for the entire path
-        let gas_price = self.provider.get_gas_price().await?;
-        Ok(gas_price * U256::from(300000)) // 300k gas
-    }
+        // Add the largest pending oracle move on any token in the path
+        let oracle_moves = self.oracle_moves.lock().await;
+        let oracle_impact = path
+            .iter()
+            .filter_map(|token| oracle_moves.get(token))
+            .map(|update| update.change_bps.unsigned_abs())
+            .max()
+            .unwrap_or(0);
 
-    async fn calculate_success_probability(&self, path: &[Address]) -> Result<f64> {
-        // Calculate success probability based on historical data
-        Ok(0.85) // 85% success rate
+        Ok(base_impact + U256::from(oracle_impact))
     }
 }
+
+// `amount` moved by `change_bps`; a move of -100% or worse leaves nothing
+fn repriced(amount: U256, change_bps: i64) -> U256 {
+    let scale = 10_000i64.saturating_add(change_bps).max(0) as u64;
+    amount.checked_mul(U256::from(scale)).map_or(U256::MAX, |scaled| scaled / 10_000)
+}
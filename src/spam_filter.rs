@@ -0,0 +1,181 @@
+// src/spam_filter.rs
+use ethers::types::{Address, Transaction, H256, U256};
+use ethers::utils::keccak256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Which early filter dropped a pending tx.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpamRule {
+    // Pays less than the head's base fee plus the minimum tip: it can't land next block
+    GasFloor,
+    // Native dust with no calldata
+    Dust,
+    // Matches a known spam contract (and selector)
+    KnownSpam,
+    // From a sender muted for repeating the same tx
+    RepeatSender,
+}
+
+impl SpamRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpamRule::GasFloor => "gas_floor",
+            SpamRule::Dust => "dust",
+            SpamRule::KnownSpam => "known_spam",
+            SpamRule::RepeatSender => "repeat_sender",
+        }
+    }
+}
+
+/// A spam contract, optionally only one of its entrypoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpamPattern {
+    pub contract: Address,
+    pub selector: Option<[u8; 4]>,
+}
+
+impl SpamPattern {
+    /// "0xcontract" or "0xcontract:0xselector".
+    pub fn parse(s: &str) -> Option<Self> {
+        let (contract, selector) = match s.split_once(':') {
+            Some((contract, selector)) => {
+                let bytes = hex::decode(selector.trim().trim_start_matches("0x")).ok()?;
+                (contract, Some(<[u8; 4]>::try_from(bytes.as_slice()).ok()?))
+            }
+            None => (s, None),
+        };
+        Some(Self { contract: contract.trim().parse().ok()?, selector })
+    }
+
+    fn matches(&self, tx: &Transaction) -> bool {
+        tx.to == Some(self.contract) && self.selector.map_or(true, |s| tx.input.get(..4) == Some(&s[..]))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpamFilterConfig {
+    // Tip required on top of the base fee
+    pub min_priority_fee: U256,
+    // Calldata-free transfers below this are dust; zero turns the rule off
+    pub dust_value: U256,
+    pub patterns: Vec<SpamPattern>,
+    // Identical txs from one sender within `repeat_window` before it is muted
+    // for the rest of the window
+    pub max_repeats: u32,
+    pub repeat_window: Duration,
+}
+
+impl Default for SpamFilterConfig {
+    fn default() -> Self {
+        Self {
+            min_priority_fee: U256::zero(),
+            dust_value: U256::exp10(15),
+            patterns: Vec::new(),
+            max_repeats: 200,
+            repeat_window: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SpamState {
+    // (sender, tx fingerprint) -> first seen, count
+    repeats: HashMap<(Address, H256), (Instant, u32)>,
+    muted: HashMap<Address, Instant>,
+    dropped: HashMap<SpamRule, u64>,
+}
+
+/// Cheap checks run on every pending tx before it is classified, so spam
+/// never reaches decoding or simulation.
+pub struct SpamFilter {
+    config: SpamFilterConfig,
+    state: Mutex<SpamState>,
+}
+
+impl SpamFilter {
+    pub fn new(config: SpamFilterConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(SpamState::default()),
+        }
+    }
+
+    /// The rule that drops `tx`, if any, counted against that rule. A zero
+    /// `base_fee` (no block seen yet) only enforces the minimum tip.
+    pub fn check(&self, tx: &Transaction, base_fee: U256) -> Option<SpamRule> {
+        let rule = self.rule_for(tx, base_fee);
+        if let Some(rule) = rule {
+            *self.state.lock().unwrap().dropped.entry(rule).or_default() += 1;
+        }
+        rule
+    }
+
+    fn rule_for(&self, tx: &Transaction, base_fee: U256) -> Option<SpamRule> {
+        // Legacy txs carry only gas_price; either way it caps what the tx can pay
+        let max_fee = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+        if max_fee < base_fee + self.config.min_priority_fee {
+            return Some(SpamRule::GasFloor);
+        }
+        if tx.input.is_empty() && tx.value < self.config.dust_value {
+            return Some(SpamRule::Dust);
+        }
+        if self.config.patterns.iter().any(|p| p.matches(tx)) {
+            return Some(SpamRule::KnownSpam);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if let Some(until) = state.muted.get(&tx.from) {
+            if *until > now {
+                return Some(SpamRule::RepeatSender);
+            }
+            state.muted.remove(&tx.from);
+        }
+        // Same target, value and calldata; nonce and gas are expected to differ
+        let mut value = [0u8; 32];
+        tx.value.to_big_endian(&mut value);
+        let mut payload = tx.to.unwrap_or_default().as_bytes().to_vec();
+        payload.extend_from_slice(&value);
+        payload.extend_from_slice(&tx.input);
+        let key = (tx.from, H256::from(keccak256(payload)));
+        let window = self.config.repeat_window;
+        let (first_seen, count) = state.repeats.entry(key).or_insert((now, 0));
+        if now.duration_since(*first_seen) > window {
+            *first_seen = now;
+            *count = 0;
+        }
+        *count += 1;
+        let repeats = *count;
+        if repeats > self.config.max_repeats {
+            warn!(sender = ?tx.from, repeats, "Muting pending tx spammer");
+            state.repeats.retain(|(sender, _), _| *sender != tx.from);
+            state.muted.insert(tx.from, now + window);
+            return Some(SpamRule::RepeatSender);
+        }
+        None
+    }
+
+    // Fingerprints outside the window can't count toward a mute any more
+    pub fn on_block(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let window = self.config.repeat_window;
+        state.repeats.retain(|_, (first_seen, _)| now.duration_since(*first_seen) <= window);
+        state.muted.retain(|_, until| *until > now);
+    }
+
+    /// Pending txs dropped by each rule since startup.
+    pub fn dropped(&self) -> HashMap<&'static str, u64> {
+        self.state.lock().unwrap().dropped.iter().map(|(rule, n)| (rule.name(), *n)).collect()
+    }
+
+    pub fn report(&self) {
+        let state = self.state.lock().unwrap();
+        for (rule, dropped) in &state.dropped {
+            info!(rule = rule.name(), dropped, muted_senders = state.muted.len(), "Spam filtered");
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// src/state_override.rs
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::keccak256;
+
+/// Where a token keeps a mapping keyed by address: the slot the mapping is
+/// declared at, and whether the key is hashed before the slot (Solidity) or
+/// after it (Vyper).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappingSlot {
+    pub index: u64,
+    pub vyper: bool,
+}
+
+impl MappingSlot {
+    /// Storage slot of `mapping[key]`.
+    pub fn key(&self, key: Address) -> H256 {
+        self.hash(key, H256::from_low_u64_be(self.index))
+    }
+
+    /// Storage slot of `mapping[outer][inner]`, as allowances are kept.
+    pub fn nested(&self, outer: Address, inner: Address) -> H256 {
+        self.hash(inner, self.key(outer))
+    }
+
+    fn hash(&self, key: Address, slot: H256) -> H256 {
+        let key = H256::from(key);
+        let (first, second) = if self.vyper { (slot, key) } else { (key, slot) };
+        H256(keccak256([first.as_bytes(), second.as_bytes()].concat()))
+    }
+}
+
+/// One change to the state a simulation starts from.
+#[derive(Debug, Clone)]
+pub enum Override {
+    Balance { token: Address, owner: Address, amount: U256 },
+    Allowance { token: Address, owner: Address, spender: Address, amount: U256 },
+    Storage { account: Address, slot: H256, value: H256 },
+    NativeBalance { account: Address, amount: U256 },
+    Code { account: Address, code: Bytes },
+}
+
+/// State written into a fork before simulating on it, so a strategy can ask
+/// what would happen with funds or contract parameters it doesn't have on
+/// chain. Applied in order; a later override of the same slot wins.
+#[derive(Debug, Clone, Default)]
+pub struct StateOverrides {
+    entries: Vec<Override>,
+}
+
+impl StateOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[Override] {
+        &self.entries
+    }
+
+    /// Sets `owner`'s ERC-20 balance of `token`. Total supply is left alone.
+    pub fn with_balance(mut self, token: Address, owner: Address, amount: U256) -> Self {
+        self.entries.push(Override::Balance { token, owner, amount });
+        self
+    }
+
+    pub fn with_allowance(mut self, token: Address, owner: Address, spender: Address, amount: U256) -> Self {
+        self.entries.push(Override::Allowance { token, owner, spender, amount });
+        self
+    }
+
+    pub fn with_storage(mut self, account: Address, slot: H256, value: H256) -> Self {
+        self.entries.push(Override::Storage { account, slot, value });
+        self
+    }
+
+    pub fn with_native_balance(mut self, account: Address, amount: U256) -> Self {
+        self.entries.push(Override::NativeBalance { account, amount });
+        self
+    }
+
+    /// Replaces the code at `account`; empty code turns a contract into an
+    /// account that can send transactions.
+    pub fn with_code(mut self, account: Address, code: Bytes) -> Self {
+        self.entries.push(Override::Code { account, code });
+        self
+    }
+}
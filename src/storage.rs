@@ -0,0 +1,632 @@
+// src/storage.rs
+use anyhow::Result;
+use ethers::types::{Address, Transaction, H256, U256, U64};
+use serde::Serialize;
+use tracing::info;
+use sqlx::any::{AnyPool, AnyPoolOptions};
+use sqlx::Row;
+
+use crate::confidence::ConfidenceFeatures;
+use crate::pnl::RealizedPnl;
+use crate::revert::ExecutionFailure;
+use crate::sensitivity::Sensitivity;
+
+// Amounts are stored as decimal TEXT: U256 doesn't fit any native column type
+// and both SQLite and Postgres compare them fine after casting for analysis
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS opportunities (
+        id TEXT PRIMARY KEY,
+        detected_at BIGINT NOT NULL,
+        source TEXT NOT NULL,
+        victim_tx TEXT,
+        token_in TEXT NOT NULL,
+        path TEXT NOT NULL,
+        routers TEXT NOT NULL,
+        amount_in TEXT NOT NULL,
+        expected_profit TEXT NOT NULL,
+        price_impact TEXT,
+        gas_estimate TEXT,
+        success_probability DOUBLE PRECISION
+    )",
+    "CREATE TABLE IF NOT EXISTS decisions (
+        opportunity_id TEXT NOT NULL,
+        decided_at BIGINT NOT NULL,
+        execute BOOLEAN NOT NULL,
+        reason TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS executions (
+        opportunity_id TEXT NOT NULL,
+        submitted_at BIGINT NOT NULL,
+        venue TEXT NOT NULL,
+        tx_hash TEXT NOT NULL,
+        target_block BIGINT NOT NULL,
+        gas_price TEXT NOT NULL,
+        status TEXT NOT NULL,
+        included_block BIGINT,
+        realized_profit TEXT
+    )",
+    "CREATE TABLE IF NOT EXISTS competitors (
+        opportunity_id TEXT NOT NULL,
+        block BIGINT NOT NULL,
+        tx_hash TEXT NOT NULL,
+        competitor TEXT NOT NULL,
+        contract TEXT,
+        priority_fee TEXT NOT NULL,
+        gas_used TEXT NOT NULL,
+        profit TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS missed_opportunities (
+        block BIGINT NOT NULL,
+        tx_hash TEXT NOT NULL,
+        pool TEXT NOT NULL,
+        counter_pool TEXT NOT NULL,
+        token0 TEXT NOT NULL,
+        token1 TEXT NOT NULL,
+        dislocation_bps BIGINT NOT NULL,
+        seen_in_mempool BOOLEAN NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS execution_failures (
+        opportunity_id TEXT NOT NULL,
+        failed_at BIGINT NOT NULL,
+        stage TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        reason TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS loss_bounds (
+        opportunity_id TEXT NOT NULL,
+        checked_at BIGINT NOT NULL,
+        revert_loss_usd DOUBLE PRECISION NOT NULL,
+        land_loss_usd DOUBLE PRECISION NOT NULL,
+        max_loss_usd DOUBLE PRECISION NOT NULL,
+        accepted BOOLEAN NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS bot_activity (
+        tx_hash TEXT NOT NULL,
+        bot TEXT NOT NULL,
+        target TEXT,
+        kind TEXT NOT NULL,
+        block BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS confidence_features (
+        opportunity_id TEXT PRIMARY KEY,
+        gas_ratio DOUBLE PRECISION NOT NULL,
+        competing_txs BIGINT NOT NULL,
+        min_out_slack_pct DOUBLE PRECISION NOT NULL,
+        inclusion_rate DOUBLE PRECISION NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS opportunity_pools (
+        opportunity_id TEXT NOT NULL,
+        pool TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS settlements (
+        opportunity_id TEXT NOT NULL,
+        tx_hash TEXT NOT NULL,
+        settled_at BIGINT NOT NULL,
+        reverted BOOLEAN NOT NULL,
+        gross_usd DOUBLE PRECISION NOT NULL,
+        loan_fee_usd DOUBLE PRECISION NOT NULL,
+        gas_usd DOUBLE PRECISION NOT NULL,
+        net_usd DOUBLE PRECISION NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS sensitivities (
+        opportunity_id TEXT PRIMARY KEY,
+        price_move_bps BIGINT NOT NULL,
+        base_profit TEXT NOT NULL,
+        adverse_profit TEXT NOT NULL,
+        favorable_profit TEXT NOT NULL,
+        delayed_profit TEXT NOT NULL
+    )",
+];
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone)]
+pub struct OpportunityRecord {
+    pub id: String,
+    pub source: String,
+    pub victim_tx: Option<H256>,
+    pub path: Vec<Address>,
+    pub routers: Vec<Address>,
+    pub pools: Vec<Address>,
+    pub amount_in: U256,
+    pub expected_profit: U256,
+    pub price_impact: Option<U256>,
+    pub gas_estimate: Option<U256>,
+    pub success_probability: Option<f64>,
+}
+
+// A transaction that captured an opportunity we submitted for
+#[derive(Debug, Clone)]
+pub struct CompetitorRecord {
+    pub opportunity_id: String,
+    pub block: U64,
+    pub tx_hash: H256,
+    pub competitor: Address,
+    pub contract: Option<Address>,
+    pub priority_fee: U256,
+    pub gas_used: U256,
+    pub profit: ethers::types::I256,
+}
+
+// A landed swap that left two pools for the same pair out of line and nobody,
+// including us, closed the gap in that block
+#[derive(Debug, Clone)]
+pub struct MissedOpportunityRecord {
+    pub block: U64,
+    pub tx_hash: H256,
+    pub pool: Address,
+    pub counter_pool: Address,
+    pub token0: Address,
+    pub token1: Address,
+    pub dislocation_bps: u64,
+    pub seen_in_mempool: bool,
+}
+
+/// Settled executions of one UTC day.
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyPnl {
+    // Start of the day, unix millis
+    pub day: i64,
+    pub executions: i64,
+    pub reverted: i64,
+    pub gross_usd: f64,
+    pub loan_fee_usd: f64,
+    pub gas_usd: f64,
+    pub net_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyStats {
+    pub source: String,
+    pub opportunities: i64,
+    pub submitted: i64,
+    pub included: i64,
+    // Included share of settled submissions; None before any settled
+    pub win_rate: Option<f64>,
+    pub gas_usd: f64,
+    pub net_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub pool: String,
+    pub opportunities: i64,
+    pub executions: i64,
+    pub net_usd: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InclusionStatus {
+    Pending,
+    Included,
+    Failed,
+    Timeout,
+}
+
+impl InclusionStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            InclusionStatus::Pending => "pending",
+            InclusionStatus::Included => "included",
+            InclusionStatus::Failed => "failed",
+            InclusionStatus::Timeout => "timeout",
+        }
+    }
+}
+
+/// Records every opportunity and what happened to it. Works against SQLite
+/// (`sqlite://bot.db`) or Postgres (`postgres://...`) depending on the URL.
+#[derive(Clone)]
+pub struct Storage {
+    pool: AnyPool,
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+fn join_addresses(addresses: &[Address]) -> String {
+    addresses.iter().map(|a| format!("{:?}", a)).collect::<Vec<_>>().join(",")
+}
+
+impl Storage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new().max_connections(5).connect(url).await?;
+
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+
+        info!("Connected to storage at {}", url.split('@').last().unwrap_or(url));
+        Ok(Self { pool })
+    }
+
+    // Waits for queued writes to finish before the process exits
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    pub async fn record_opportunity(&self, record: &OpportunityRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO opportunities
+                (id, detected_at, source, victim_tx, token_in, path, routers, amount_in,
+                 expected_profit, price_impact, gas_estimate, success_probability)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(&record.id)
+        .bind(now())
+        .bind(&record.source)
+        .bind(record.victim_tx.map(|h| format!("{:?}", h)))
+        .bind(record.path.first().map(|a| format!("{:?}", a)).unwrap_or_default())
+        .bind(join_addresses(&record.path))
+        .bind(join_addresses(&record.routers))
+        .bind(record.amount_in.to_string())
+        .bind(record.expected_profit.to_string())
+        .bind(record.price_impact.map(|v| v.to_string()))
+        .bind(record.gas_estimate.map(|v| v.to_string()))
+        .bind(record.success_probability)
+        .execute(&self.pool)
+        .await?;
+        for pool in &record.pools {
+            sqlx::query("INSERT INTO opportunity_pools (opportunity_id, pool) VALUES ($1, $2)")
+                .bind(&record.id)
+                .bind(format!("{:?}", pool))
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn record_decision(&self, opportunity_id: &str, execute: bool, reason: &str) -> Result<()> {
+        sqlx::query("INSERT INTO decisions (opportunity_id, decided_at, execute, reason) VALUES ($1, $2, $3, $4)")
+            .bind(opportunity_id)
+            .bind(now())
+            .bind(execute)
+            .bind(reason)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn record_submission(
+        &self,
+        opportunity_id: &str,
+        venue: &str,
+        tx_hash: H256,
+        target_block: U64,
+        gas_price: U256,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO executions
+                (opportunity_id, submitted_at, venue, tx_hash, target_block, gas_price, status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(opportunity_id)
+        .bind(now())
+        .bind(venue)
+        .bind(format!("{:?}", tx_hash))
+        .bind(target_block.as_u64() as i64)
+        .bind(gas_price.to_string())
+        .bind(InclusionStatus::Pending.as_str())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_inclusion(&self, tx_hash: H256, status: InclusionStatus, included_block: Option<U64>) -> Result<()> {
+        sqlx::query("UPDATE executions SET status = $1, included_block = $2 WHERE tx_hash = $3")
+            .bind(status.as_str())
+            .bind(included_block.map(|b| b.as_u64() as i64))
+            .bind(format!("{:?}", tx_hash))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn record_competitor(&self, record: &CompetitorRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO competitors
+                (opportunity_id, block, tx_hash, competitor, contract, priority_fee, gas_used, profit)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(&record.opportunity_id)
+        .bind(record.block.as_u64() as i64)
+        .bind(format!("{:?}", record.tx_hash))
+        .bind(format!("{:?}", record.competitor))
+        .bind(record.contract.map(|c| format!("{:?}", c)))
+        .bind(record.priority_fee.to_string())
+        .bind(record.gas_used.to_string())
+        .bind(record.profit.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_missed_opportunity(&self, record: &MissedOpportunityRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO missed_opportunities
+                (block, tx_hash, pool, counter_pool, token0, token1, dislocation_bps, seen_in_mempool)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(record.block.as_u64() as i64)
+        .bind(format!("{:?}", record.tx_hash))
+        .bind(format!("{:?}", record.pool))
+        .bind(format!("{:?}", record.counter_pool))
+        .bind(format!("{:?}", record.token0))
+        .bind(format!("{:?}", record.token1))
+        .bind(record.dislocation_bps as i64)
+        .bind(record.seen_in_mempool)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_failure(&self, opportunity_id: &str, failure: &ExecutionFailure) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO execution_failures (opportunity_id, failed_at, stage, kind, reason)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(opportunity_id)
+        .bind(now())
+        .bind(failure.stage)
+        .bind(failure.kind.as_str())
+        .bind(failure.reason.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Losses are positive; a landed execution that profits has a negative land loss
+    pub async fn record_loss_bound(
+        &self,
+        opportunity_id: &str,
+        revert_loss_usd: f64,
+        land_loss_usd: f64,
+        max_loss_usd: f64,
+        accepted: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO loss_bounds
+                (opportunity_id, checked_at, revert_loss_usd, land_loss_usd, max_loss_usd, accepted)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(opportunity_id)
+        .bind(now())
+        .bind(revert_loss_usd)
+        .bind(land_loss_usd)
+        .bind(max_loss_usd)
+        .bind(accepted)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// A pending tx from or to a known MEV bot, with the head it was seen at.
+    pub async fn record_bot_activity(&self, tx: &Transaction, bot: Address, kind: &str, block: U64) -> Result<()> {
+        sqlx::query("INSERT INTO bot_activity (tx_hash, bot, target, kind, block) VALUES ($1, $2, $3, $4, $5)")
+            .bind(format!("{:?}", tx.hash))
+            .bind(format!("{:?}", bot))
+            .bind(tx.to.map(|to| format!("{:?}", to)))
+            .bind(kind)
+            .bind(block.as_u64() as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn record_confidence_features(&self, opportunity_id: &str, features: &ConfidenceFeatures) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO confidence_features
+                (opportunity_id, gas_ratio, competing_txs, min_out_slack_pct, inclusion_rate)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(opportunity_id)
+        .bind(features.gas_ratio)
+        .bind(features.competing_txs as i64)
+        .bind(features.min_out_slack_pct)
+        .bind(features.inclusion_rate)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Profit of an opportunity under each sensitivity scenario, in its start token.
+    pub async fn record_sensitivity(&self, opportunity_id: &str, sensitivity: &Sensitivity) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sensitivities
+                (opportunity_id, price_move_bps, base_profit, adverse_profit, favorable_profit, delayed_profit)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(opportunity_id)
+        .bind(sensitivity.price_move_bps as i64)
+        .bind(sensitivity.base.to_string())
+        .bind(sensitivity.adverse.to_string())
+        .bind(sensitivity.favorable.to_string())
+        .bind(sensitivity.delayed.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Features of the latest `limit` settled submissions, with whether they landed.
+    pub async fn confidence_outcomes(&self, limit: i64) -> Result<Vec<(ConfidenceFeatures, bool)>> {
+        let rows = sqlx::query(
+            "SELECT f.gas_ratio, f.competing_txs, f.min_out_slack_pct, f.inclusion_rate, e.status
+             FROM confidence_features f JOIN executions e ON e.opportunity_id = f.opportunity_id
+             WHERE e.status <> $1
+             ORDER BY e.submitted_at DESC
+             LIMIT $2",
+        )
+        .bind(InclusionStatus::Pending.as_str())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let features = ConfidenceFeatures {
+                    gas_ratio: row.try_get(0)?,
+                    competing_txs: row.try_get::<i64, _>(1)? as u32,
+                    min_out_slack_pct: row.try_get(2)?,
+                    inclusion_rate: row.try_get(3)?,
+                };
+                let status: String = row.try_get(4)?;
+                Ok((features, status == InclusionStatus::Included.as_str()))
+            })
+            .collect()
+    }
+
+    /// Included share of the latest `limit` settled submissions, None without any.
+    pub async fn inclusion_rate(&self, limit: i64) -> Result<Option<f64>> {
+        let rows = sqlx::query("SELECT status FROM executions WHERE status <> $1 ORDER BY submitted_at DESC LIMIT $2")
+            .bind(InclusionStatus::Pending.as_str())
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        if rows.is_empty() {
+            return Ok(None);
+        }
+        let mut included = 0;
+        for row in &rows {
+            if row.try_get::<String, _>(0)? == InclusionStatus::Included.as_str() {
+                included += 1;
+            }
+        }
+        Ok(Some(included as f64 / rows.len() as f64))
+    }
+
+    // Signed: a landed execution can lose money once gas and loan fees are counted
+    pub async fn record_realized_profit(&self, tx_hash: H256, realized_profit: ethers::types::I256) -> Result<()> {
+        sqlx::query("UPDATE executions SET realized_profit = $1 WHERE tx_hash = $2")
+            .bind(realized_profit.to_string())
+            .bind(format!("{:?}", tx_hash))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// USD outcome of a settled execution, for the dashboard aggregates.
+    pub async fn record_settlement(&self, pnl: &RealizedPnl) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO settlements
+                (opportunity_id, tx_hash, settled_at, reverted, gross_usd, loan_fee_usd, gas_usd, net_usd)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(&pnl.opportunity_id)
+        .bind(format!("{:?}", pnl.tx_hash))
+        .bind(now())
+        .bind(pnl.reverted)
+        .bind(pnl.gross_usd)
+        .bind(pnl.loan_fee_usd)
+        .bind(pnl.gas_usd)
+        .bind(pnl.net_usd)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Realized PnL per UTC day over the last `days`, oldest first.
+    pub async fn daily_pnl(&self, days: i64) -> Result<Vec<DailyPnl>> {
+        let rows = sqlx::query(
+            "SELECT settled_at / $1 AS day, COUNT(*),
+                    SUM(CASE WHEN reverted THEN 1 ELSE 0 END),
+                    SUM(gross_usd), SUM(loan_fee_usd), SUM(gas_usd), SUM(net_usd)
+             FROM settlements
+             WHERE settled_at >= $2
+             GROUP BY day
+             ORDER BY day",
+        )
+        .bind(DAY_MS)
+        .bind(now() - days * DAY_MS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DailyPnl {
+                    day: row.try_get::<i64, _>(0)? * DAY_MS,
+                    executions: row.try_get(1)?,
+                    reverted: row.try_get(2)?,
+                    gross_usd: row.try_get(3)?,
+                    loan_fee_usd: row.try_get(4)?,
+                    gas_usd: row.try_get(5)?,
+                    net_usd: row.try_get(6)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Per opportunity source over the last `days`: how many were found,
+    /// submitted and included, and what the settled ones made and spent on gas.
+    pub async fn strategy_stats(&self, days: i64) -> Result<Vec<StrategyStats>> {
+        let rows = sqlx::query(
+            "SELECT o.source, COUNT(DISTINCT o.id),
+                    COUNT(e.tx_hash),
+                    SUM(CASE WHEN e.status = $1 THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN e.status IS NOT NULL AND e.status <> $2 THEN 1 ELSE 0 END),
+                    COALESCE(SUM(s.gas_usd), 0.0),
+                    COALESCE(SUM(s.net_usd), 0.0)
+             FROM opportunities o
+             LEFT JOIN executions e ON e.opportunity_id = o.id
+             LEFT JOIN settlements s ON s.tx_hash = e.tx_hash
+             WHERE o.detected_at >= $3
+             GROUP BY o.source
+             ORDER BY o.source",
+        )
+        .bind(InclusionStatus::Included.as_str())
+        .bind(InclusionStatus::Pending.as_str())
+        .bind(now() - days * DAY_MS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let included: i64 = row.try_get::<Option<i64>, _>(3)?.unwrap_or_default();
+                let settled: i64 = row.try_get::<Option<i64>, _>(4)?.unwrap_or_default();
+                Ok(StrategyStats {
+                    source: row.try_get(0)?,
+                    opportunities: row.try_get(1)?,
+                    submitted: row.try_get(2)?,
+                    included,
+                    win_rate: (settled > 0).then(|| included as f64 / settled as f64),
+                    gas_usd: row.try_get(5)?,
+                    net_usd: row.try_get(6)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The `limit` pools whose executions made the most over the last `days`.
+    pub async fn top_pools(&self, days: i64, limit: i64) -> Result<Vec<PoolStats>> {
+        let rows = sqlx::query(
+            "SELECT p.pool, COUNT(DISTINCT p.opportunity_id), COUNT(s.tx_hash),
+                    COALESCE(SUM(s.net_usd), 0.0) AS net
+             FROM opportunity_pools p
+             JOIN opportunities o ON o.id = p.opportunity_id
+             LEFT JOIN settlements s ON s.opportunity_id = p.opportunity_id
+             WHERE o.detected_at >= $1
+             GROUP BY p.pool
+             ORDER BY net DESC
+             LIMIT $2",
+        )
+        .bind(now() - days * DAY_MS)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(PoolStats {
+                    pool: row.try_get(0)?,
+                    opportunities: row.try_get(1)?,
+                    executions: row.try_get(2)?,
+                    net_usd: row.try_get(3)?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,494 @@
+// src/strategies/jit_liquidity.rs
+use anyhow::{anyhow, Result};
+use ethers::abi::{AbiParser, Token};
+use ethers::prelude::*;
+use tracing::{debug, info};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+use crate::abis;
+use crate::executor::{BundleTx, Executor, OrderedBundle};
+use crate::fee::FeeAmount;
+use crate::rpc_budget::RpcTransport;
+use crate::simulation_engine::{AdvancedSimulationEngine, BundleLeg};
+use super::multicall::swap_legs;
+
+pub const UNISWAP_V3_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+pub const UNISWAP_V3_ROUTER_02: &str = "0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45";
+pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+const WMATIC: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
+
+// Gas for mint (position init + transfers) and burn + collect
+const MINT_GAS: u64 = 350_000;
+const BURN_GAS: u64 = 200_000;
+
+pub static V3_ROUTER_SWAP_ABI: Lazy<ethers::abi::Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160)) returns (uint256)",
+        // SwapRouter02 drops the deadline field
+        "function exactInputSingle((address,address,uint24,address,uint256,uint256,uint160)) returns (uint256)",
+    ]).expect("parse v3 router abi")
+});
+
+abigen!(IUniswapV3Factory, r#"[
+    function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address)
+]"#);
+
+abigen!(IUniswapV3Pool, r#"[
+    function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+    function liquidity() external view returns (uint128)
+    function token0() external view returns (address)
+]"#);
+
+#[derive(Debug, Clone)]
+pub struct PendingV3Swap {
+    pub tx: Transaction,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub fee: FeeAmount,
+    pub amount_in: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct JitOpportunity {
+    pub victim: PendingV3Swap,
+    pub pool: Address,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount0_max: U256,
+    pub amount1_max: U256,
+    pub token0: Address,
+    pub token1: Address,
+    pub sqrt_price_x96: U256,
+    // Fee captured from the victim swap, in WMATIC
+    pub expected_fees: U256,
+    pub gas_cost: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct JitBundle {
+    pub mint: Bytes,
+    pub victim: Transaction,
+    pub burn: Bytes,
+    pub target_block: U64,
+}
+
+/// The whole bundle run on the fork: gas of our two legs and what the
+/// executor ended up with, in WMATIC.
+#[derive(Debug, Clone)]
+pub struct JitSimulation {
+    pub mint_gas: u64,
+    pub burn_gas: u64,
+    pub fee_income: I256,
+}
+
+pub struct JitLiquidityStrategy {
+    provider: Arc<Provider<RpcTransport>>,
+    executor: Address,
+    // Smallest victim input (raw units) worth evaluating
+    min_swap_amount: U256,
+    // Capital we're willing to park in the range, in token_in units; the range
+    // itself holds the token the victim buys
+    max_position: U256,
+}
+
+/// Decode a pending Uniswap V3 `exactInputSingle` call, including one batched in a
+/// router multicall behind `selfPermit`. Multi-hop swaps are skipped since the fee
+/// we'd capture is split across pools we can't all provision.
+pub fn parse_v3_exact_input_single(tx: &Transaction) -> Option<PendingV3Swap> {
+    let routers: [Address; 2] = [UNISWAP_V3_ROUTER.parse().ok()?, UNISWAP_V3_ROUTER_02.parse().ok()?];
+    if !routers.iter().any(|r| tx.to == Some(*r)) {
+        return None;
+    }
+
+    swap_legs(&tx.input.0)
+        .iter()
+        .find_map(|leg| decode_exact_input_single(tx, leg))
+}
+
+fn decode_exact_input_single(tx: &Transaction, input: &[u8]) -> Option<PendingV3Swap> {
+    if input.len() < 4 { return None; }
+
+    let function = V3_ROUTER_SWAP_ABI
+        .functions_by_name("exactInputSingle")
+        .ok()?
+        .iter()
+        .find(|f| f.short_signature() == input[..4])?;
+
+    // amountIn sits at index 4 in both the 8-field and 7-field params
+    let tokens = function.decode_input(&input[4..]).ok()?;
+    let params = match tokens.first()? {
+        Token::Tuple(t) if t.len() == 8 || t.len() == 7 => t,
+        _ => return None,
+    };
+
+    Some(PendingV3Swap {
+        tx: tx.clone(),
+        token_in: params[0].clone().into_address()?,
+        token_out: params[1].clone().into_address()?,
+        // uint24 on chain, but nothing stops hostile calldata from setting the high bits
+        fee: FeeAmount::try_from(params[2].clone().into_uint()?).ok()?,
+        amount_in: params[4].clone().into_uint()?,
+    })
+}
+
+fn tick_spacing(fee: FeeAmount) -> Option<i32> {
+    match fee {
+        FeeAmount::LOWEST => Some(1),
+        FeeAmount::LOW => Some(10),
+        FeeAmount::MEDIUM => Some(60),
+        FeeAmount::HIGH => Some(200),
+        _ => None,
+    }
+}
+
+impl JitLiquidityStrategy {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, executor: Address, min_swap_amount: U256, max_position: U256) -> Self {
+        Self {
+            provider,
+            executor,
+            min_swap_amount,
+            max_position,
+        }
+    }
+
+    pub async fn analyze(&self, tx: &Transaction) -> Result<Option<JitOpportunity>> {
+        let swap = match parse_v3_exact_input_single(tx) {
+            Some(s) if s.amount_in >= self.min_swap_amount => s,
+            _ => return Ok(None),
+        };
+
+        // Fees are valued in WMATIC, so only pools quoted against it are considered
+        let wmatic: Address = WMATIC.parse()?;
+        if swap.token_in != wmatic && swap.token_out != wmatic {
+            return Ok(None);
+        }
+
+        let spacing = match tick_spacing(swap.fee) {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+
+        let factory = IUniswapV3Factory::new(UNISWAP_V3_FACTORY.parse::<Address>()?, self.provider.clone());
+        let pool_address = factory.get_pool(swap.token_in, swap.token_out, swap.fee.pips()).call().await?;
+        if pool_address == Address::zero() {
+            return Ok(None);
+        }
+
+        let pool = IUniswapV3Pool::new(pool_address, self.provider.clone());
+        let (sqrt_price_x96, tick, ..) = pool.slot_0().call().await?;
+        let pool_liquidity = pool.liquidity().call().await?;
+        let token0 = pool.token_0().call().await?;
+
+        // One tick-spacing wide, next to the current price on the side the swap
+        // moves it to. A range there holds only the token the victim buys, which
+        // the swap takes from it as it crosses; below the price that is token1
+        let zero_for_one = swap.token_in == token0;
+        let aligned = tick.div_euclid(spacing) * spacing;
+        let (tick_lower, tick_upper) = if zero_for_one {
+            (aligned - spacing, aligned)
+        } else {
+            (aligned + spacing, aligned + 2 * spacing)
+        };
+
+        let position = match Self::convert_at_price(self.max_position.min(swap.amount_in), sqrt_price_x96, zero_for_one) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        let liquidity = Self::liquidity_for_amount(position, sqrt_price_x96, spacing, !zero_for_one);
+        if liquidity == 0 {
+            return Ok(None);
+        }
+
+        // Our pro-rata share of the victim's fee, as if the whole swap ran through
+        // the range; the fork simulation settles what it actually earns. amount_in
+        // is whatever the calldata says
+        let share = U256::from(liquidity);
+        let total = share + U256::from(pool_liquidity);
+        let captured = match swap.fee.checked_of(swap.amount_in).and_then(|fee| fee.checked_mul(share)) {
+            Some(scaled) => scaled / total,
+            None => return Ok(None),
+        };
+
+        let expected_fees = if swap.token_in == wmatic {
+            captured
+        } else {
+            match Self::convert_at_price(captured, sqrt_price_x96, zero_for_one) {
+                Some(fees) => fees,
+                None => return Ok(None),
+            }
+        };
+
+        let gas_price = self.provider.get_gas_price().await?;
+        let gas_cost = gas_price * U256::from(MINT_GAS + BURN_GAS);
+
+        debug!(
+            "JIT candidate {:?}: fees {} vs gas {} (liquidity share {}/{})",
+            swap.tx.hash, expected_fees, gas_cost, liquidity, total
+        );
+
+        if expected_fees <= gas_cost {
+            return Ok(None);
+        }
+
+        let (amount0_max, amount1_max) = if zero_for_one {
+            (U256::zero(), position)
+        } else {
+            (position, U256::zero())
+        };
+        let token1 = if zero_for_one { swap.token_out } else { swap.token_in };
+
+        Ok(Some(JitOpportunity {
+            victim: swap,
+            pool: pool_address,
+            tick_lower,
+            tick_upper,
+            liquidity,
+            amount0_max,
+            amount1_max,
+            token0,
+            token1,
+            sqrt_price_x96,
+            expected_fees,
+            gas_cost,
+        }))
+    }
+
+    pub async fn build_bundle(&self, opportunity: &JitOpportunity) -> Result<JitBundle> {
+        let executor = abis::executor(self.executor, self.provider.clone());
+        let target_block = self.provider.get_block_number().await? + 1;
+
+        let mint = executor
+            .mint_jit(
+                opportunity.pool,
+                opportunity.tick_lower,
+                opportunity.tick_upper,
+                opportunity.liquidity,
+                opportunity.amount0_max,
+                opportunity.amount1_max,
+            )
+            .calldata()
+            .ok_or_else(|| anyhow!("Failed to encode mintJit"))?;
+
+        let burn = executor
+            .burn_jit(
+                opportunity.pool,
+                opportunity.tick_lower,
+                opportunity.tick_upper,
+                opportunity.liquidity,
+                self.executor,
+            )
+            .calldata()
+            .ok_or_else(|| anyhow!("Failed to encode burnJit"))?;
+
+        Ok(JitBundle {
+            mint,
+            victim: opportunity.victim.tx.clone(),
+            burn,
+            target_block,
+        })
+    }
+
+    /// Runs mint -> victim -> burn on the fork as `caller`. The burn collects to
+    /// the executor, so its balance change across the bundle is the fee net of
+    /// any inventory the victim's price move cost us.
+    pub async fn simulate(
+        &self,
+        opportunity: &JitOpportunity,
+        bundle: &JitBundle,
+        engine: &AdvancedSimulationEngine,
+        caller: Address,
+    ) -> Result<JitSimulation> {
+        let legs = [
+            BundleLeg::Call { caller, to: self.executor, calldata: bundle.mint.clone() },
+            BundleLeg::Pending(bundle.victim.clone()),
+            BundleLeg::Call { caller, to: self.executor, calldata: bundle.burn.clone() },
+        ];
+        let outcome = engine
+            .simulate_sequence(&legs, self.executor, &[opportunity.token0, opportunity.token1])
+            .await?;
+        if let Some(leg) = outcome.legs.iter().position(|leg| !leg.success) {
+            return Err(anyhow!("JIT leg {} reverted on the fork", leg));
+        }
+
+        let wmatic: Address = WMATIC.parse()?;
+        let mut fee_income = I256::zero();
+        for (token, delta) in [opportunity.token0, opportunity.token1].into_iter().zip(outcome.deltas) {
+            let value = if token == wmatic {
+                delta.unsigned_abs()
+            } else {
+                // The other side of the pair is WMATIC; value at the pre-swap price
+                Self::convert_at_price(delta.unsigned_abs(), opportunity.sqrt_price_x96, token == opportunity.token0)
+                    .ok_or_else(|| anyhow!("JIT delta of {:?} overflows in WMATIC", token))?
+            };
+            let value = I256::try_from(value)?;
+            let signed = if delta.is_negative() { -value } else { value };
+            fee_income = fee_income
+                .checked_add(signed)
+                .ok_or_else(|| anyhow!("JIT fee income overflows"))?;
+        }
+
+        Ok(JitSimulation {
+            mint_gas: outcome.legs[0].gas_used,
+            burn_gas: outcome.legs[2].gas_used,
+            fee_income,
+        })
+    }
+
+    // mint -> victim swap -> burn + collect, landing together or not at all.
+    // None when the simulation shows it isn't worth sending
+    pub async fn execute(
+        &self,
+        opportunity: &JitOpportunity,
+        engine: &AdvancedSimulationEngine,
+        caller: Address,
+        executor: &dyn Executor,
+    ) -> Result<Option<H256>> {
+        let bundle = self.build_bundle(opportunity).await?;
+        let gas_price = opportunity.gas_cost / U256::from(MINT_GAS + BURN_GAS);
+
+        let simulation = self.simulate(opportunity, &bundle, engine, caller).await?;
+        let gas_cost = gas_price * U256::from(simulation.mint_gas + simulation.burn_gas);
+        if simulation.fee_income <= I256::try_from(gas_cost)? {
+            debug!(
+                "JIT around {:?} dropped: simulated fee income {} vs gas {}",
+                opportunity.victim.tx.hash, simulation.fee_income, gas_cost
+            );
+            return Ok(None);
+        }
+
+        // Headroom over the fork's gas; the limits are fixed before the victim runs
+        let hash = executor.submit_ordered(&OrderedBundle {
+            txs: vec![
+                BundleTx::Executor { calldata: bundle.mint, gas_limit: simulation.mint_gas * 6 / 5 },
+                BundleTx::Pending(bundle.victim),
+                BundleTx::Executor { calldata: bundle.burn, gas_limit: simulation.burn_gas * 6 / 5 },
+            ],
+            can_revert: vec![false; 3],
+            target_block: bundle.target_block,
+            gas_price,
+            expected_profit: simulation.fee_income.into_raw(),
+        }).await?;
+
+        info!(
+            "Submitted JIT bundle {:?} around {:?}: fee income {} vs gas {}",
+            hash, opportunity.victim.tx.hash, simulation.fee_income, gas_cost
+        );
+        Ok(Some(hash))
+    }
+
+    // For a range one tick-spacing wide, sqrt(1.0001^spacing) - 1 ~= spacing * 0.00005.
+    // The range sits just past the current price, so the mint takes slightly less
+    // than `amount` and stays under the executor's amount*Max caps.
+    // `token0` is the token the range holds
+    fn liquidity_for_amount(amount: U256, sqrt_price_x96: U256, spacing: i32, token0: bool) -> u128 {
+        let q96 = 2f64.powi(96);
+        let sqrt_price = sqrt_price_x96.to_string().parse::<f64>().unwrap_or(0.0) / q96;
+        let amount = amount.to_string().parse::<f64>().unwrap_or(0.0);
+        let width = spacing as f64 * 0.00005;
+        if sqrt_price == 0.0 {
+            return 0;
+        }
+
+        let liquidity = if token0 {
+            amount * sqrt_price / width
+        } else {
+            amount / sqrt_price / width
+        };
+        liquidity.min(u128::MAX as f64) as u128
+    }
+
+    // `amount` of one side of the pool in the other at the given price; fees and
+    // deltas are valued in WMATIC this way
+    fn convert_at_price(amount: U256, sqrt_price_x96: U256, token_in_is_token0: bool) -> Option<U256> {
+        // price = token1 per token0 = (sqrtPriceX96 / 2^96)^2
+        let q96 = U256::from(2).pow(U256::from(96));
+        if sqrt_price_x96.is_zero() {
+            return None;
+        }
+        if token_in_is_token0 {
+            // token0 into token1
+            Some((amount.checked_mul(sqrt_price_x96)? / q96).checked_mul(sqrt_price_x96)? / q96)
+        } else {
+            Some((amount.checked_mul(q96)? / sqrt_price_x96).checked_mul(q96)? / sqrt_price_x96)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::id;
+    use proptest::prelude::*;
+
+    fn address() -> impl Strategy<Value = Address> {
+        any::<[u8; 20]>().prop_map(Address::from)
+    }
+
+    fn amount() -> impl Strategy<Value = U256> {
+        any::<[u8; 32]>().prop_map(|b| U256::from_big_endian(&b))
+    }
+
+    fn router_tx(router: &str, input: Vec<u8>) -> Transaction {
+        Transaction {
+            to: Some(router.parse().unwrap()),
+            input: Bytes::from(input),
+            ..Default::default()
+        }
+    }
+
+    fn multicall(legs: Vec<Vec<u8>>) -> Vec<u8> {
+        let legs = legs.into_iter().map(Token::Bytes).collect();
+        [id("multicall(bytes[])").to_vec(), ethers::abi::encode(&[Token::Array(legs)])].concat()
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips_exact_input_single(
+            token_in in address(),
+            token_out in address(),
+            fee in 0u32..1_000_000,
+            recipient in address(),
+            amount_in in amount(),
+            min_out in amount(),
+            router02 in any::<bool>(),
+            batched in any::<bool>(),
+        ) {
+            let mut params = vec![Token::Address(token_in), Token::Address(token_out), Token::Uint(fee.into()), Token::Address(recipient)];
+            if !router02 {
+                params.push(Token::Uint(U256::MAX)); // deadline
+            }
+            params.extend([Token::Uint(amount_in), Token::Uint(min_out), Token::Uint(U256::zero())]);
+            let function = V3_ROUTER_SWAP_ABI
+                .functions_by_name("exactInputSingle")
+                .unwrap()
+                .iter()
+                .find(|f| matches!(&f.inputs[0].kind, ethers::abi::ParamType::Tuple(t) if t.len() == params.len()))
+                .unwrap();
+            let mut input = function.encode_input(&[Token::Tuple(params)]).unwrap();
+            if batched {
+                let permit = [id("selfPermit(address,uint256,uint256,uint8,bytes32,bytes32)").to_vec(), vec![0u8; 192]].concat();
+                input = multicall(vec![permit, input]);
+            }
+
+            let router = if router02 { UNISWAP_V3_ROUTER_02 } else { UNISWAP_V3_ROUTER };
+            let swap = parse_v3_exact_input_single(&router_tx(router, input)).unwrap();
+            prop_assert_eq!((swap.token_in, swap.token_out, swap.fee.pips(), swap.amount_in), (token_in, token_out, fee, amount_in));
+        }
+
+        #[test]
+        fn arbitrary_calldata_never_panics(input in prop::collection::vec(any::<u8>(), 0..512), batched in any::<bool>()) {
+            let input = if batched { multicall(vec![input]) } else { input };
+            let _ = parse_v3_exact_input_single(&router_tx(UNISWAP_V3_ROUTER_02, input));
+        }
+
+        // Well-formed calldata with every word random, out-of-range fees included
+        #[test]
+        fn hostile_params_never_panic(words in prop::collection::vec(any::<[u8; 32]>(), 8)) {
+            let function = &V3_ROUTER_SWAP_ABI.functions_by_name("exactInputSingle").unwrap()[0];
+            let input = [function.short_signature().to_vec(), words.concat()].concat();
+            let _ = parse_v3_exact_input_single(&router_tx(UNISWAP_V3_ROUTER, input));
+        }
+    }
+}
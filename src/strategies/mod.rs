@@ -0,0 +1,11 @@
+pub mod jit_liquidity;
+pub mod multicall;
+pub mod redemption_arb;
+pub mod sizing;
+pub mod stable_arb;
+pub mod triangular;
+
+pub use jit_liquidity::JitLiquidityStrategy;
+pub use redemption_arb::RedemptionArbStrategy;
+pub use stable_arb::StableArbStrategy;
+pub use triangular::{CycleOpportunity, CyclePool, CycleSearch, TriangularScanner};
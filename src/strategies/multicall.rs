@@ -0,0 +1,103 @@
+// src/strategies/multicall.rs
+use ethers::abi::{AbiParser, Token};
+use ethers::prelude::*;
+use once_cell::sync::Lazy;
+
+// SwapRouter multicall(bytes[]) plus the SwapRouter02 deadline / previous-blockhash variants
+static MULTICALL_ABI: Lazy<ethers::abi::Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function multicall(bytes[]) returns (bytes[])",
+        "function multicall(uint256,bytes[]) returns (bytes[])",
+        "function multicall(bytes32,bytes[]) returns (bytes[])",
+    ]).expect("parse multicall abi")
+});
+
+// Legs that move approvals or dust around the swap but aren't swaps themselves
+static HELPER_ABI: Lazy<ethers::abi::Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function selfPermit(address,uint256,uint256,uint8,bytes32,bytes32)",
+        "function selfPermitIfNecessary(address,uint256,uint256,uint8,bytes32,bytes32)",
+        "function selfPermitAllowed(address,uint256,uint256,uint8,bytes32,bytes32)",
+        "function selfPermitAllowedIfNecessary(address,uint256,uint256,uint8,bytes32,bytes32)",
+        "function refundETH()",
+        "function unwrapWETH9(uint256,address)",
+        "function unwrapWETH9(uint256)",
+        "function sweepToken(address,uint256,address)",
+        "function sweepToken(address,uint256)",
+        "function wrapETH(uint256)",
+    ]).expect("parse router helper abi")
+});
+
+// Nested multicalls are legal but never more than a couple deep in practice
+const MAX_DEPTH: usize = 3;
+
+fn selector_matches(abi: &ethers::abi::Abi, input: &[u8]) -> bool {
+    input.len() >= 4 && abi.functions().any(|f| f.short_signature() == input[..4])
+}
+
+/// Flattens router multicall calldata into its swap legs, dropping permit and
+/// refund/sweep helpers. Non-multicall input comes back as a single leg.
+pub fn swap_legs(input: &[u8]) -> Vec<Bytes> {
+    let mut legs = Vec::new();
+    collect_legs(input, 0, &mut legs);
+    legs
+}
+
+fn collect_legs(input: &[u8], depth: usize, legs: &mut Vec<Bytes>) {
+    if selector_matches(&HELPER_ABI, input) {
+        return;
+    }
+
+    let multicall = MULTICALL_ABI
+        .functions()
+        .find(|f| input.len() >= 4 && f.short_signature() == input[..4]);
+    let function = match multicall {
+        Some(f) if depth < MAX_DEPTH => f,
+        _ => {
+            legs.push(Bytes::from(input.to_vec()));
+            return;
+        }
+    };
+
+    let calls = match function.decode_input(&input[4..]).ok().and_then(|t| t.last().cloned()) {
+        Some(Token::Array(calls)) => calls,
+        _ => return,
+    };
+    for call in calls {
+        if let Token::Bytes(data) = call {
+            collect_legs(&data, depth + 1, legs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn multicall(legs: Vec<Vec<u8>>) -> Vec<u8> {
+        let function = MULTICALL_ABI.functions().next().unwrap();
+        function.encode_input(&[Token::Array(legs.into_iter().map(Token::Bytes).collect())]).unwrap()
+    }
+
+    proptest! {
+        #[test]
+        fn flattens_nested_legs_in_order(legs in prop::collection::vec(prop::collection::vec(any::<u8>(), 4..64), 1..6), nested in any::<bool>()) {
+            // Random legs that happen to start with a multicall or helper selector would be unwrapped or dropped
+            prop_assume!(legs.iter().all(|leg| !selector_matches(&MULTICALL_ABI, leg) && !selector_matches(&HELPER_ABI, leg)));
+            let mut input = multicall(legs.clone());
+            if nested {
+                input = multicall(vec![input]);
+            }
+            let flattened: Vec<Vec<u8>> = swap_legs(&input).into_iter().map(|leg| leg.to_vec()).collect();
+            prop_assert_eq!(flattened, legs);
+        }
+
+        #[test]
+        fn arbitrary_calldata_never_panics(input in prop::collection::vec(any::<u8>(), 0..1024)) {
+            let _ = swap_legs(&input);
+            let selector = MULTICALL_ABI.functions().next().unwrap().short_signature();
+            let _ = swap_legs(&[selector.to_vec(), input].concat());
+        }
+    }
+}
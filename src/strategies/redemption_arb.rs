@@ -0,0 +1,254 @@
+// src/strategies/redemption_arb.rs
+use anyhow::Result;
+use ethers::abi::{decode, ParamType};
+use ethers::prelude::*;
+use ethers::utils::id;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::calldata_builder::{Hop, RouterKind};
+use crate::chain::StakedAsset;
+use crate::path_constraints::PathConstraints;
+use crate::pool_state::PoolStateManager;
+use crate::rpc_budget::RpcTransport;
+use super::sizing::ternary_max;
+use super::triangular::{CycleOpportunity, CyclePool};
+
+// Polygon's FxChild; every L1 -> L2 message, rate updates included, comes through it
+const FX_CHILD: &str = "0x8397259c983751DAf40400790063935a11afa28a";
+
+abigen!(IRateProvider, r#"[
+    function getRate() external view returns (uint256)
+]"#);
+
+/// A cycle found against a staked asset's exchange rate.
+#[derive(Debug, Clone)]
+pub struct RedemptionOpportunity {
+    pub cycle: CycleOpportunity,
+    // The asset's rate was updated in this block, so the pools still price the old one
+    pub rate_update: bool,
+}
+
+/// Arbitrage between liquid staking derivatives (stMATIC, MaticX) and WMATIC,
+/// anchored on the staking contracts' exchange rates. Two shapes:
+/// - mint: WMATIC -> staking pool at the rate -> derivative sold on a DEX pool,
+///   when the pool prices the derivative above the rate;
+/// - re-anchor: buy the derivative on a pool pricing it below the rate and sell
+///   it on the pool closest to the rate, for assets that can't be minted here.
+/// Rates move in steps when an L1 update arrives through FxChild; those blocks
+/// are decoded and flagged, since the pools lag the new rate until arbitraged.
+pub struct RedemptionArbStrategy {
+    provider: Arc<Provider<RpcTransport>>,
+    pool_state: Arc<PoolStateManager>,
+    assets: Vec<StakedAsset>,
+    wrapped_native: Address,
+    v3_router: Option<Address>,
+    constraints: PathConstraints,
+    // Last read rate per derivative token
+    rates: RwLock<HashMap<Address, U256>>,
+    // Price gap to the rate that triggers sizing, in bps
+    min_spread_bps: u64,
+    max_input: U256,
+    min_profit: U256,
+}
+
+impl RedemptionArbStrategy {
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        pool_state: Arc<PoolStateManager>,
+        assets: Vec<StakedAsset>,
+        wrapped_native: Address,
+        v3_router: Option<Address>,
+        constraints: PathConstraints,
+    ) -> Self {
+        Self {
+            provider,
+            pool_state,
+            assets,
+            wrapped_native,
+            v3_router,
+            constraints,
+            rates: RwLock::new(HashMap::new()),
+            min_spread_bps: 10,
+            max_input: U256::from(10).pow(22.into()),
+            min_profit: U256::from(10).pow(16.into()),
+        }
+    }
+
+    /// The derivative tokens, for pool discovery against the wrapped native.
+    pub fn tokens(&self) -> Vec<Address> {
+        self.assets.iter().map(|a| a.token).collect()
+    }
+
+    async fn refresh_rates(&self, block: U64) -> Result<()> {
+        let mut rates = self.rates.write().await;
+        for asset in &self.assets {
+            let rate = IRateProvider::new(asset.rate_provider, self.provider.clone())
+                .get_rate()
+                .block(block)
+                .call()
+                .await?;
+            if rates.insert(asset.token, rate).map_or(false, |old| old != rate) {
+                info!("{} rate moved to {} at block {}", asset.symbol, rate, block);
+            }
+        }
+        Ok(())
+    }
+
+    /// Assets whose rate an FxChild message in `block` delivered.
+    async fn rate_updates(&self, block: U64) -> Result<Vec<Address>> {
+        let filter = Filter::new()
+            .address(FX_CHILD.parse::<Address>().unwrap())
+            .topic0(id("NewFxMessage(address,address,bytes)"))
+            .from_block(block)
+            .to_block(block);
+        let mut updated = Vec::new();
+        for log in self.provider.get_logs(&filter).await? {
+            let receiver = match decode_fx_receiver(&log.data) {
+                Some(receiver) => receiver,
+                None => continue,
+            };
+            if let Some(asset) = self.assets.iter().find(|a| a.rate_receivers.contains(&receiver)) {
+                debug!("{} rate update in {:?}", asset.symbol, log.transaction_hash);
+                updated.push(asset.token);
+            }
+        }
+        Ok(updated)
+    }
+
+    pub async fn on_block(&self, block: U64) -> Result<Vec<RedemptionOpportunity>> {
+        let updated = self.rate_updates(block).await?;
+        self.refresh_rates(block).await?;
+        let rates = self.rates.read().await.clone();
+        let mut found = Vec::new();
+        for asset in &self.assets {
+            let rate = match rates.get(&asset.token) {
+                Some(rate) if !rate.is_zero() => *rate,
+                _ => continue,
+            };
+            if !self.constraints.allows_path(&[self.wrapped_native, asset.token, self.wrapped_native]) {
+                continue;
+            }
+            let pools = self.pools(asset.token).await;
+            let rate_update = updated.contains(&asset.token);
+            found.extend(
+                self.evaluate(asset, rate, &pools, block)
+                    .into_iter()
+                    .map(|cycle| RedemptionOpportunity { cycle, rate_update }),
+            );
+        }
+        if !found.is_empty() {
+            info!("Block {}: {} staked asset spreads against the rate", block, found.len());
+        }
+        Ok(found)
+    }
+
+    async fn pools(&self, token: Address) -> Vec<CyclePool> {
+        let mut pools: Vec<CyclePool> = self.pool_state
+            .pools_for_pair(self.wrapped_native, token)
+            .await
+            .into_iter()
+            .filter(|p| self.constraints.allows_pool(p, None))
+            .map(CyclePool::V2)
+            .collect();
+        if let Some(router) = self.v3_router {
+            pools.extend(
+                self.pool_state
+                    .v3_pools_for_pair(self.wrapped_native, token)
+                    .await
+                    .into_iter()
+                    .filter(|p| !p.last_updated_block.is_zero() && self.constraints.allows_v3_pool(p, None))
+                    .map(|p| CyclePool::V3(p, router)),
+            );
+        }
+        pools
+    }
+
+    fn evaluate(&self, asset: &StakedAsset, rate: U256, pools: &[CyclePool], block: U64) -> Vec<CycleOpportunity> {
+        let native = self.wrapped_native;
+        let unit = U256::exp10(18);
+        let threshold = unit + unit * self.min_spread_bps / 10_000;
+        let mut found = Vec::new();
+
+        // Mint at the rate, sell on the pool
+        if let Some(mint_pool) = asset.mint_pool {
+            for pool in pools {
+                let cycle_out = |amount: U256| pool.amount_out(asset.token, amount * unit / rate);
+                if cycle_out(unit) <= threshold {
+                    continue;
+                }
+                if let Some((amount_in, amount_out)) = self.size(cycle_out) {
+                    let minted = amount_in * unit / rate;
+                    let hops = vec![
+                        Hop {
+                            kind: RouterKind::Stake,
+                            router: mint_pool,
+                            pool: mint_pool,
+                            token_in: native,
+                            token_out: asset.token,
+                            fee: 0,
+                            amount_in,
+                            min_out: U256::zero(),
+                        },
+                        pool.hop(asset.token, minted),
+                    ];
+                    found.push(cycle(hops, vec![amount_in, minted, amount_out], block));
+                }
+            }
+        }
+
+        // Buy below the rate, sell on the pool that tracks it best
+        let anchor = pools.iter().max_by_key(|p| p.amount_out(asset.token, unit));
+        if let Some(anchor) = anchor {
+            for pool in pools.iter().filter(|p| p.address() != anchor.address()) {
+                // Derivative per native on this pool versus at the rate
+                if pool.amount_out(native, unit) * rate / unit <= threshold {
+                    continue;
+                }
+                let cycle_out = |amount: U256| anchor.amount_out(asset.token, pool.amount_out(native, amount));
+                if let Some((amount_in, amount_out)) = self.size(cycle_out) {
+                    let bought = pool.amount_out(native, amount_in);
+                    let hops = vec![pool.hop(native, amount_in), anchor.hop(asset.token, bought)];
+                    found.push(cycle(hops, vec![amount_in, bought, amount_out], block));
+                }
+            }
+        }
+
+        for opportunity in &found {
+            debug!(asset = asset.symbol, %rate, amount_in = %opportunity.amount_in, profit = %opportunity.expected_profit, "Rate spread");
+        }
+        found
+    }
+
+    // (amount in, amount out) at the best input, when the profit clears the minimum
+    fn size(&self, cycle_out: impl Fn(U256) -> U256) -> Option<(U256, U256)> {
+        let amount_in = ternary_max(U256::zero(), self.max_input, |x| I256::from_raw(cycle_out(x)) - I256::from_raw(x));
+        let amount_out = cycle_out(amount_in);
+        if amount_out > amount_in && amount_out - amount_in >= self.min_profit {
+            Some((amount_in, amount_out))
+        } else {
+            None
+        }
+    }
+}
+
+fn cycle(hops: Vec<Hop>, amounts: Vec<U256>, block: U64) -> CycleOpportunity {
+    CycleOpportunity {
+        path: vec![hops[0].token_in, hops[0].token_out, hops[1].token_out],
+        pools: hops.iter().map(|h| h.pool).collect(),
+        routers: hops.iter().map(|h| h.router).collect(),
+        amount_in: amounts[0],
+        expected_profit: amounts[2] - amounts[0],
+        amounts,
+        hops,
+        block,
+    }
+}
+
+// NewFxMessage(rootMessageSender, receiver, data): the L2 contract the message is for
+fn decode_fx_receiver(data: &[u8]) -> Option<Address> {
+    let tokens = decode(&[ParamType::Address, ParamType::Address, ParamType::Bytes], data).ok()?;
+    tokens.into_iter().nth(1)?.into_address()
+}
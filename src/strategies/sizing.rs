@@ -0,0 +1,46 @@
+// src/strategies/sizing.rs
+use ethers::types::{I256, U256};
+
+/// Input in `[lo, hi]` with the most `profit`. Trading against AMM pools makes
+/// profit concave in the input size, so a ternary search finds the optimum.
+pub fn ternary_max(mut lo: U256, mut hi: U256, profit: impl Fn(U256) -> I256) -> U256 {
+    while hi.saturating_sub(lo) > U256::from(2) {
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - third);
+        if profit(m1) < profit(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+
+    // At most three inputs left; take the best of them
+    let (mut best, mut best_profit) = (lo, profit(lo));
+    let mut x = lo;
+    while x < hi {
+        x += U256::one();
+        let p = profit(x);
+        if p > best_profit {
+            (best, best_profit) = (x, p);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_peak_of_a_concave_curve() {
+        // Peaks at 1_234_567
+        let profit = |x: U256| {
+            let x = I256::from_raw(x);
+            let peak = I256::from(1_234_567);
+            -(x - peak) * (x - peak)
+        };
+        assert_eq!(ternary_max(U256::zero(), U256::exp10(9), profit), U256::from(1_234_567));
+        // Capped below the peak, the cap wins
+        assert_eq!(ternary_max(U256::zero(), U256::from(1_000), profit), U256::from(1_000));
+    }
+}
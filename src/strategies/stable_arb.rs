@@ -0,0 +1,367 @@
+// src/strategies/stable_arb.rs
+use anyhow::Result;
+use ethers::abi::{Abi, AbiParser};
+use ethers::prelude::*;
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use crate::calldata_builder::{curve_indices, Hop, RouterKind};
+use crate::path_constraints::PathConstraints;
+use crate::pool_state::PoolStateManager;
+use crate::rpc_budget::RpcTransport;
+use super::sizing::ternary_max;
+use super::triangular::{CycleOpportunity, CyclePool};
+
+// Curve's fee() is scaled by 1e10
+const FEE_DENOMINATOR: u64 = 10_000_000_000;
+// Newton iterations before get_D / get_y give up, as in the pool contracts
+const MAX_ITERATIONS: usize = 255;
+
+static CURVE_POOL_ABI: Lazy<Abi> = Lazy::new(|| {
+    AbiParser::default().parse(&[
+        "function coins(uint256) view returns (address)",
+        "function underlying_coins(uint256) view returns (address)",
+        "function balances(uint256) view returns (uint256)",
+        "function A() view returns (uint256)",
+        "function fee() view returns (uint256)",
+        "function exchange(int128,int128,uint256,uint256) returns (uint256)",
+        "function exchange_underlying(int128,int128,uint256,uint256) returns (uint256)",
+    ]).expect("parse curve pool abi")
+});
+
+abigen!(IErc20Decimals, r#"[
+    function decimals() external view returns (uint8)
+]"#);
+
+/// A plain Curve stable pool: coins, balances normalized to 18 decimals
+/// through `multipliers`, amplification and fee.
+#[derive(Debug, Clone)]
+pub struct CurvePool {
+    pub address: Address,
+    // The tokens swaps take and return; the underlying ones for lending pools
+    pub coins: Vec<Address>,
+    // Swaps go through exchange_underlying (Aave-style pools holding aTokens)
+    pub underlying: bool,
+    multipliers: Vec<U256>,
+    pub balances: Vec<U256>,
+    pub amp: U256,
+    pub fee: U256,
+}
+
+impl CurvePool {
+    fn xp(&self) -> Vec<U256> {
+        self.balances.iter().zip(&self.multipliers).map(|(b, m)| *b * *m).collect()
+    }
+
+    fn index(&self, token: Address) -> Option<usize> {
+        self.coins.iter().position(|c| *c == token)
+    }
+
+    /// `get_dy`: output of swapping `dx` of coin `i` for coin `j`, after fee.
+    pub fn get_dy(&self, i: usize, j: usize, dx: U256) -> Option<U256> {
+        let xp = self.xp();
+        let x = xp[i] + dx * self.multipliers[i];
+        let y = get_y(i, j, x, &xp, self.amp)?;
+        let dy = xp[j].checked_sub(y)?.checked_sub(U256::one())? / self.multipliers[j];
+        Some(dy - dy * self.fee / U256::from(FEE_DENOMINATOR))
+    }
+
+    /// Balances after someone else's swap of `dx` of coin `i` for coin `j`.
+    pub fn apply_swap(&mut self, i: usize, j: usize, dx: U256) -> Option<()> {
+        let dy = self.get_dy(i, j, dx)?;
+        self.balances[i] += dx;
+        self.balances[j] = self.balances[j].checked_sub(dy)?;
+        Some(())
+    }
+}
+
+fn distance(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
+
+// StableSwap invariant D for normalized balances, by Newton's method
+fn get_d(xp: &[U256], amp: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let sum = xp.iter().fold(U256::zero(), |s, x| s + *x);
+    if sum.is_zero() {
+        return Some(U256::zero());
+    }
+    let ann = amp * n;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p * d / (*x * n);
+        }
+        let prev = d;
+        d = (ann * sum + d_p * n) * d / ((ann - 1) * d + (n + 1) * d_p);
+        if distance(d, prev) <= U256::one() {
+            return Some(d);
+        }
+    }
+    None
+}
+
+// Balance of coin `j` that keeps D constant once coin `i`'s balance is `x`
+fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amp: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let d = get_d(xp, amp)?;
+    let ann = amp * n;
+    let mut c = d;
+    let mut sum = U256::zero();
+    for (k, balance) in xp.iter().enumerate() {
+        let x_k = if k == i {
+            x
+        } else if k != j {
+            *balance
+        } else {
+            continue;
+        };
+        sum += x_k;
+        c = c * d / (x_k * n);
+    }
+    c = c * d / (ann * n);
+    let b = sum + d / ann;
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let prev = y;
+        y = (y * y + c) / (y * 2 + b).checked_sub(d)?;
+        if distance(y, prev) <= U256::one() {
+            return Some(y);
+        }
+    }
+    None
+}
+
+/// Stablecoin arbitrage between Curve pools and the Uni-fork pools of the same
+/// pairs: borrow coin i, swap it for coin j on one venue and back on the other.
+/// Curve prices stay near peg while a thin AMM pool drifts (or the other way
+/// round), so the spread opens on de-peg moves and closes after a single trade.
+/// Evaluated after every block and behind pending swaps on the Curve pools.
+pub struct StableArbStrategy {
+    provider: Arc<Provider<RpcTransport>>,
+    pool_state: Arc<PoolStateManager>,
+    addresses: Vec<Address>,
+    pools: RwLock<Vec<CurvePool>>,
+    v3_router: Option<Address>,
+    constraints: PathConstraints,
+    // Spread between the venues' marginal prices that triggers sizing, in bps
+    min_spread_bps: u64,
+    // Largest trade as a share of the Curve pool's balance of the input coin, in bps
+    max_input_bps: u64,
+    min_profit: U256,
+}
+
+impl StableArbStrategy {
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        pool_state: Arc<PoolStateManager>,
+        addresses: Vec<Address>,
+        v3_router: Option<Address>,
+        constraints: PathConstraints,
+    ) -> Self {
+        Self {
+            provider,
+            pool_state,
+            addresses,
+            pools: RwLock::new(Vec::new()),
+            v3_router,
+            constraints,
+            min_spread_bps: 5,
+            max_input_bps: 500,
+            // 0.001 of a stable, scaled down to the input coin's decimals
+            min_profit: U256::exp10(15),
+        }
+    }
+
+    pub fn with_thresholds(mut self, min_spread_bps: u64, max_input_bps: u64) -> Self {
+        self.min_spread_bps = min_spread_bps;
+        self.max_input_bps = max_input_bps;
+        self
+    }
+
+    /// Reads each pool's coins, decimals, A and fee. Returns every coin, so the
+    /// caller can discover the AMM pools between them.
+    pub async fn load(&self) -> Result<Vec<Address>> {
+        let mut pools = Vec::new();
+        for address in &self.addresses {
+            let contract = Contract::new(*address, CURVE_POOL_ABI.clone(), self.provider.clone());
+            // Lending pools expose underlying_coins; plain pools revert on it
+            let underlying = contract.method::<_, Address>("underlying_coins", U256::zero())?.call().await.is_ok();
+            let getter = if underlying { "underlying_coins" } else { "coins" };
+            let mut coins = Vec::new();
+            for i in 0..8u64 {
+                match contract.method::<_, Address>(getter, U256::from(i))?.call().await {
+                    Ok(coin) => coins.push(coin),
+                    Err(_) => break,
+                }
+            }
+            let mut multipliers = Vec::new();
+            for coin in &coins {
+                let decimals = IErc20Decimals::new(*coin, self.provider.clone()).decimals().call().await?;
+                multipliers.push(U256::exp10(18usize.saturating_sub(decimals as usize)));
+            }
+            let pool = CurvePool {
+                address: *address,
+                balances: vec![U256::zero(); coins.len()],
+                coins,
+                underlying,
+                multipliers,
+                amp: contract.method::<_, U256>("A", ())?.call().await?,
+                fee: contract.method::<_, U256>("fee", ())?.call().await?,
+            };
+            info!("Tracking Curve pool {:?} ({} coins, A {})", pool.address, pool.coins.len(), pool.amp);
+            pools.push(pool);
+        }
+        let mut coins: Vec<Address> = pools.iter().flat_map(|p| p.coins.clone()).collect();
+        coins.sort();
+        coins.dedup();
+        *self.pools.write().await = pools;
+        Ok(coins)
+    }
+
+    async fn refresh(&self, block: U64) -> Result<()> {
+        let mut pools = self.pools.write().await;
+        for pool in pools.iter_mut() {
+            let contract = Contract::new(pool.address, CURVE_POOL_ABI.clone(), self.provider.clone());
+            for i in 0..pool.coins.len() {
+                pool.balances[i] = contract.method::<_, U256>("balances", U256::from(i))?.block(block).call().await?;
+            }
+            // A ramps between blocks on parameter changes
+            pool.amp = contract.method::<_, U256>("A", ())?.block(block).call().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn on_block(&self, block: U64) -> Result<Vec<CycleOpportunity>> {
+        self.refresh(block).await?;
+        let pools = self.pools.read().await.clone();
+        let mut found = Vec::new();
+        for pool in &pools {
+            found.extend(self.evaluate(pool, block).await);
+        }
+        if !found.is_empty() {
+            info!("Block {}: {} Curve/AMM stable spreads above threshold", block, found.len());
+        }
+        Ok(found)
+    }
+
+    /// Backruns of a pending `exchange` on a tracked Curve pool, evaluated
+    /// against the balances it leaves behind.
+    pub async fn after_pending(&self, tx: &Transaction, block: U64) -> Vec<CycleOpportunity> {
+        let mut pool = match self.pools.read().await.iter().find(|p| tx.to == Some(p.address)) {
+            Some(pool) => pool.clone(),
+            None => return Vec::new(),
+        };
+        let (i, j, dx) = match decode_exchange(&tx.input) {
+            Some(swap) => swap,
+            None => return Vec::new(),
+        };
+        if i >= pool.coins.len() || j >= pool.coins.len() || pool.apply_swap(i, j, dx).is_none() {
+            return Vec::new();
+        }
+        debug!("Pending Curve swap {} -> {} of {} on {:?}", i, j, dx, pool.address);
+        self.evaluate(&pool, block).await
+    }
+
+    // Both directions of every coin pair against every AMM pool of that pair
+    async fn evaluate(&self, pool: &CurvePool, block: U64) -> Vec<CycleOpportunity> {
+        let mut found = Vec::new();
+        for (i, token_in) in pool.coins.iter().enumerate() {
+            for (j, token_out) in pool.coins.iter().enumerate() {
+                if i == j || !self.constraints.allows_path(&[*token_in, *token_out, *token_in]) {
+                    continue;
+                }
+                for amm in self.amm_pools(*token_in, *token_out).await {
+                    found.extend(self.evaluate_pair(pool, i, j, &amm, block));
+                }
+            }
+        }
+        found.sort_by(|a, b| b.expected_profit.cmp(&a.expected_profit));
+        found
+    }
+
+    async fn amm_pools(&self, token_a: Address, token_b: Address) -> Vec<CyclePool> {
+        let mut pools: Vec<CyclePool> = self.pool_state
+            .pools_for_pair(token_a, token_b)
+            .await
+            .into_iter()
+            .filter(|p| self.constraints.allows_pool(p, None))
+            .map(CyclePool::V2)
+            .collect();
+        if let Some(router) = self.v3_router {
+            pools.extend(
+                self.pool_state
+                    .v3_pools_for_pair(token_a, token_b)
+                    .await
+                    .into_iter()
+                    .filter(|p| !p.last_updated_block.is_zero() && self.constraints.allows_v3_pool(p, None))
+                    .map(|p| CyclePool::V3(p, router)),
+            );
+        }
+        pools
+    }
+
+    // coin i -> Curve -> coin j -> AMM -> coin i. The reverse direction is the
+    // (j, i) pair's cycle through the same pools in the other order
+    fn evaluate_pair(&self, pool: &CurvePool, i: usize, j: usize, amm: &CyclePool, block: U64) -> Option<CycleOpportunity> {
+        let (token_in, token_out) = (pool.coins[i], pool.coins[j]);
+        let cycle_out = |amount: U256| -> U256 {
+            pool.get_dy(i, j, amount).map_or(U256::zero(), |mid| amm.amount_out(token_out, mid))
+        };
+
+        // Trigger: the round trip of one unit must gain more than the spread threshold
+        let unit = U256::exp10(18) / pool.multipliers[i];
+        let probe = cycle_out(unit);
+        if probe <= unit + unit * self.min_spread_bps / 10_000 {
+            return None;
+        }
+
+        let max_input = pool.balances[i] * self.max_input_bps / 10_000;
+        let amount_in = ternary_max(U256::zero(), max_input, |x| I256::from_raw(cycle_out(x)) - I256::from_raw(x));
+        let mid = pool.get_dy(i, j, amount_in)?;
+        let amount_out = amm.amount_out(token_out, mid);
+        if amount_out <= amount_in || amount_out - amount_in < self.min_profit / pool.multipliers[i] {
+            return None;
+        }
+        let expected_profit = amount_out - amount_in;
+        debug!(curve = ?pool.address, amm = ?amm.address(), %amount_in, %expected_profit, "Stable spread");
+
+        let hops = vec![
+            Hop {
+                kind: RouterKind::Curve,
+                router: pool.address,
+                pool: pool.address,
+                token_in,
+                token_out,
+                fee: curve_indices(i as u8, j as u8, pool.underlying),
+                amount_in,
+                min_out: U256::zero(),
+            },
+            amm.hop(token_out, mid),
+        ];
+        Some(CycleOpportunity {
+            path: vec![token_in, token_out, token_in],
+            pools: vec![pool.address, amm.address()],
+            routers: hops.iter().map(|h| h.router).collect(),
+            amount_in,
+            amounts: vec![amount_in, mid, amount_out],
+            hops,
+            expected_profit,
+            block,
+        })
+    }
+}
+
+// (i, j, dx) of a pending exchange / exchange_underlying call
+fn decode_exchange(input: &[u8]) -> Option<(usize, usize, U256)> {
+    let selector = input.get(..4)?;
+    let function = CURVE_POOL_ABI
+        .functions()
+        .find(|f| (f.name == "exchange" || f.name == "exchange_underlying") && f.short_signature() == selector)?;
+    let tokens = function.decode_input(&input[4..]).ok()?;
+    let index = |t: &ethers::abi::Token| t.clone().into_int().map(|v| v.low_u64() as usize);
+    Some((index(&tokens[0])?, index(&tokens[1])?, tokens[2].clone().into_uint()?))
+}
@@ -0,0 +1,296 @@
+// src/strategies/triangular.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use tracing::{debug, info};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::call_tracer::PoolDelta;
+use crate::calldata_builder::{Hop, RouterKind};
+use crate::path_constraints::PathConstraints;
+use crate::pool_state::{PoolState, PoolStateManager};
+use crate::v3_ticks::V3PoolState;
+use super::sizing::ternary_max;
+
+#[derive(Debug, Clone)]
+pub struct CycleOpportunity {
+    pub path: Vec<Address>,
+    pub pools: Vec<Address>,
+    pub routers: Vec<Address>,
+    pub amount_in: U256,
+    pub amounts: Vec<U256>,
+    // Typed per-hop execution; a cycle may mix V2 and V3 pools
+    pub hops: Vec<Hop>,
+    pub expected_profit: U256,
+    pub block: U64,
+}
+
+/// A pool a cycle can route through.
+#[derive(Debug, Clone)]
+pub enum CyclePool {
+    V2(PoolState),
+    // With the router its swaps go through
+    V3(V3PoolState, Address),
+}
+
+impl CyclePool {
+    pub fn address(&self) -> Address {
+        match self {
+            CyclePool::V2(pool) => pool.address,
+            CyclePool::V3(pool, _) => pool.address,
+        }
+    }
+
+    pub fn has_token(&self, token: Address) -> bool {
+        match self {
+            CyclePool::V2(pool) => pool.has_token(token),
+            CyclePool::V3(pool, _) => pool.token0 == token || pool.token1 == token,
+        }
+    }
+
+    pub fn other_token(&self, token: Address) -> Address {
+        match self {
+            CyclePool::V2(pool) => pool.other_token(token),
+            CyclePool::V3(pool, _) => if pool.token0 == token { pool.token1 } else { pool.token0 },
+        }
+    }
+
+    // A V3 swap running past the fetched ticks can't be priced, so it's worth nothing
+    pub fn amount_out(&self, token_in: Address, amount_in: U256) -> U256 {
+        match self {
+            CyclePool::V2(pool) => pool.get_amount_out(token_in, amount_in),
+            CyclePool::V3(pool, _) => pool.amount_out(token_in, amount_in).unwrap_or_default(),
+        }
+    }
+
+    pub fn hop(&self, token_in: Address, amount_in: U256) -> Hop {
+        let (kind, router, fee) = match self {
+            CyclePool::V2(pool) => (RouterKind::UniswapV2, pool.router, pool.fee.pips()),
+            CyclePool::V3(pool, router) => (RouterKind::UniswapV3, *router, pool.fee.pips()),
+        };
+        Hop {
+            kind,
+            router,
+            pool: self.address(),
+            token_in,
+            token_out: self.other_token(token_in),
+            fee,
+            amount_in,
+            min_out: U256::zero(),
+        }
+    }
+}
+
+/// Cycle enumeration and input sizing over a fixed set of pools, without I/O.
+#[derive(Debug, Clone)]
+pub struct CycleSearch {
+    pub base_token: Address,
+    // Number of most profitable cycles reported
+    pub top_n: usize,
+    pub max_input: U256,
+    pub min_profit: U256,
+    pub max_hops: usize,
+}
+
+/// Re-evaluates token cycles at rest after every block, independent of the mempool.
+pub struct TriangularScanner {
+    pool_state: Arc<PoolStateManager>,
+    search: CycleSearch,
+    constraints: PathConstraints,
+    // Latest USD TVL per pool, for the constraints' minimum
+    pool_tvl: RwLock<HashMap<Address, f64>>,
+    // V3 pools join cycles only when their swaps have a router to go through
+    v3_router: Option<Address>,
+}
+
+impl TriangularScanner {
+    pub fn new(
+        pool_state: Arc<PoolStateManager>,
+        base_token: Address,
+        top_n: usize,
+        max_input: U256,
+        min_profit: U256,
+        constraints: PathConstraints,
+    ) -> Self {
+        Self {
+            pool_state,
+            search: CycleSearch {
+                base_token,
+                top_n,
+                max_input,
+                min_profit,
+                max_hops: constraints.max_hops,
+            },
+            constraints,
+            pool_tvl: RwLock::new(HashMap::new()),
+            v3_router: None,
+        }
+    }
+
+    pub fn with_v3_router(mut self, router: Option<Address>) -> Self {
+        self.v3_router = router;
+        self
+    }
+
+    pub fn update_tvl(&self, tvl: HashMap<Address, f64>) {
+        *self.pool_tvl.write().unwrap() = tvl;
+    }
+
+    // Pools the constraints let cycles route through, V2 and (with a router) V3
+    async fn routable_pools(&self) -> Vec<CyclePool> {
+        let tvl = self.pool_tvl.read().unwrap().clone();
+        let mut pools: Vec<CyclePool> = self.pool_state
+            .snapshot()
+            .await
+            .into_iter()
+            .filter(|p| self.constraints.allows_pool(p, tvl.get(&p.address).copied()))
+            .map(CyclePool::V2)
+            .collect();
+        if let Some(router) = self.v3_router {
+            pools.extend(
+                self.pool_state
+                    .v3_snapshot()
+                    .await
+                    .into_iter()
+                    .filter(|p| self.constraints.allows_v3_pool(p, tvl.get(&p.address).copied()))
+                    .map(|p| CyclePool::V3(p, router)),
+            );
+        }
+        pools
+    }
+
+    pub async fn on_block(&self, block: U64) -> Result<Vec<CycleOpportunity>> {
+        self.pool_state.refresh(block).await?;
+        let pools = self.routable_pools().await;
+        let found = self.search.run(&pools, block);
+        if !found.is_empty() {
+            info!("Block {}: {} at-rest cycles above threshold", block, found.len());
+        }
+        Ok(found)
+    }
+
+    /// Cycles through the pools a traced victim swaps on, evaluated against the
+    /// reserves it leaves behind. Backruns of the victim, sized on exact deltas.
+    pub async fn after_victim(&self, deltas: &[PoolDelta], block: U64) -> Vec<CycleOpportunity> {
+        let mut pools = self.routable_pools().await;
+        let mut touched = Vec::new();
+        // Only V2 reserves can take a traced delta; V3 pools move with their logs
+        for delta in deltas {
+            if let Some(CyclePool::V2(pool)) = pools.iter_mut().find(|p| p.address() == delta.pool) {
+                pool.apply_delta(delta.amount0, delta.amount1);
+                touched.push(pool.address);
+            }
+        }
+        if touched.is_empty() {
+            return Vec::new();
+        }
+
+        let mut found: Vec<_> = self
+            .search
+            .enumerate_cycles(&pools)
+            .into_iter()
+            .filter(|cycle| cycle.iter().any(|p| touched.contains(&p.address())))
+            .filter_map(|cycle| self.search.evaluate_cycle(&cycle, block))
+            .collect();
+        found.sort_by(|a, b| b.expected_profit.cmp(&a.expected_profit));
+        found.truncate(self.search.top_n);
+        found
+    }
+}
+
+impl CycleSearch {
+    /// The `top_n` most profitable cycles through `pools`, best first.
+    pub fn run(&self, pools: &[CyclePool], block: U64) -> Vec<CycleOpportunity> {
+        let mut found: Vec<_> = self
+            .enumerate_cycles(pools)
+            .into_iter()
+            .filter_map(|cycle| self.evaluate_cycle(&cycle, block))
+            .collect();
+        found.sort_by(|a, b| b.expected_profit.cmp(&a.expected_profit));
+        found.truncate(self.top_n);
+        found
+    }
+
+    // Two- and three-hop cycles starting and ending in the base token, never
+    // reusing a pool within the same cycle, up to the constraints' hop limit
+    fn enumerate_cycles<'a>(&self, pools: &'a [CyclePool]) -> Vec<Vec<&'a CyclePool>> {
+        let base = self.base_token;
+        let max_hops = self.max_hops;
+        let mut cycles = Vec::new();
+        if max_hops < 2 {
+            return cycles;
+        }
+
+        for first in pools.iter().filter(|p| p.has_token(base)) {
+            let mid = first.other_token(base);
+
+            for second in pools.iter().filter(|p| p.address() != first.address() && p.has_token(mid)) {
+                let next = second.other_token(mid);
+
+                if next == base {
+                    cycles.push(vec![first, second]);
+                    continue;
+                }
+                if max_hops < 3 {
+                    continue;
+                }
+
+                for third in pools.iter().filter(|p| {
+                    p.address() != first.address()
+                        && p.address() != second.address()
+                        && p.has_token(next)
+                        && p.has_token(base)
+                }) {
+                    cycles.push(vec![first, second, third]);
+                }
+            }
+        }
+        cycles
+    }
+
+    fn evaluate_cycle(&self, cycle: &[&CyclePool], block: U64) -> Option<CycleOpportunity> {
+        let mut path = vec![self.base_token];
+        for pool in cycle {
+            path.push(pool.other_token(*path.last().unwrap()));
+        }
+
+        let amount_in = ternary_max(U256::zero(), self.max_input, |x| Self::profit(cycle, &path, x));
+        let amounts = Self::simulate(cycle, &path, amount_in);
+        let amount_out = *amounts.last()?;
+        if amount_out <= amount_in {
+            return None;
+        }
+
+        let expected_profit = amount_out - amount_in;
+        if expected_profit < self.min_profit {
+            return None;
+        }
+        debug!("Cycle {:?} profit {} at input {}", path, expected_profit, amount_in);
+
+        let hops: Vec<Hop> = cycle.iter().enumerate().map(|(i, pool)| pool.hop(path[i], amounts[i])).collect();
+        Some(CycleOpportunity {
+            path,
+            pools: cycle.iter().map(|p| p.address()).collect(),
+            routers: hops.iter().map(|h| h.router).collect(),
+            amount_in,
+            amounts,
+            hops,
+            expected_profit,
+            block,
+        })
+    }
+
+    fn simulate(cycle: &[&CyclePool], path: &[Address], amount_in: U256) -> Vec<U256> {
+        let mut amounts = vec![amount_in];
+        for (i, pool) in cycle.iter().enumerate() {
+            let out = pool.amount_out(path[i], *amounts.last().unwrap());
+            amounts.push(out);
+        }
+        amounts
+    }
+
+    fn profit(cycle: &[&CyclePool], path: &[Address], amount_in: U256) -> I256 {
+        let out = *Self::simulate(cycle, path, amount_in).last().unwrap();
+        I256::from_raw(out) - I256::from_raw(amount_in)
+    }
+}
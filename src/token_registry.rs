@@ -0,0 +1,131 @@
+// src/token_registry.rs
+use anyhow::Result;
+use ethers::abi::Token;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::rpc_budget::RpcTransport;
+
+abigen!(IERC20Metadata, r#"[
+    function decimals() external view returns (uint8)
+    function symbol() external view returns (string)
+]"#);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub decimals: u8,
+    pub symbol: String,
+}
+
+/// Decimals and symbols, fetched once per token (batched through Multicall3)
+/// and cached for the life of the process.
+pub struct TokenRegistry {
+    provider: Arc<Provider<RpcTransport>>,
+    tokens: RwLock<HashMap<Address, TokenInfo>>,
+}
+
+impl TokenRegistry {
+    pub fn new(provider: Arc<Provider<RpcTransport>>) -> Self {
+        Self {
+            provider,
+            tokens: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get(&self, token: Address) -> Result<TokenInfo> {
+        if let Some(info) = self.tokens.read().await.get(&token) {
+            return Ok(info.clone());
+        }
+        self.prefetch(&[token]).await?;
+        self.tokens
+            .read()
+            .await
+            .get(&token)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No metadata for {:?}", token))
+    }
+
+    /// Loads every unknown token in one multicall.
+    pub async fn prefetch(&self, tokens: &[Address]) -> Result<()> {
+        let missing: Vec<Address> = {
+            let known = self.tokens.read().await;
+            tokens.iter().copied().filter(|t| !known.contains_key(t)).collect()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut multicall = Multicall::new(self.provider.clone(), None).await?;
+        for token in &missing {
+            let contract = IERC20Metadata::new(*token, self.provider.clone());
+            multicall.add_call(contract.decimals(), true);
+            multicall.add_call(contract.symbol(), true);
+        }
+        let results = multicall.call_raw().await?;
+
+        let mut known = self.tokens.write().await;
+        for (token, pair) in missing.iter().zip(results.chunks(2)) {
+            let decimals = match pair.first() {
+                Some(Ok(Token::Uint(d))) => match u8::try_from(*d) {
+                    Ok(d) => d,
+                    // No real token has more; pricing it at any guess would be wrong
+                    Err(_) => {
+                        warn!("decimals() for {:?} returned {}, skipping it", token, d);
+                        continue;
+                    }
+                },
+                _ => {
+                    warn!("decimals() failed for {:?}, assuming 18", token);
+                    18
+                }
+            };
+            // Some old tokens return bytes32 symbols; don't fail on them
+            let symbol = match pair.get(1) {
+                Some(Ok(Token::String(s))) => s.clone(),
+                _ => format!("{:?}", token),
+            };
+            known.insert(*token, TokenInfo { decimals, symbol });
+        }
+        Ok(())
+    }
+
+    pub async fn snapshot(&self) -> HashMap<Address, TokenInfo> {
+        self.tokens.read().await.clone()
+    }
+
+    // Token metadata never changes, so a saved registry is as good as a fresh one
+    pub async fn restore(&self, tokens: HashMap<Address, TokenInfo>) {
+        self.tokens.write().await.extend(tokens);
+    }
+
+    pub async fn decimals(&self, token: Address) -> Result<u8> {
+        Ok(self.get(token).await?.decimals)
+    }
+
+    /// Raw amount scaled down by the token's decimals.
+    pub async fn to_units(&self, token: Address, amount: I256) -> Result<f64> {
+        Ok(units(amount, self.decimals(token).await?))
+    }
+
+    /// Scales a whole-token amount up to raw units.
+    pub async fn from_units(&self, token: Address, amount: f64) -> Result<U256> {
+        let decimals = self.decimals(token).await?;
+        Ok(U256::from_dec_str(&format!("{:.0}", amount * 10f64.powi(decimals as i32)))?)
+    }
+
+    /// "12.3456 USDC" for logs.
+    pub async fn format(&self, token: Address, amount: U256) -> String {
+        match self.get(token).await {
+            Ok(info) => format!("{:.4} {}", units(I256::from_raw(amount), info.decimals), info.symbol),
+            Err(_) => format!("{} {:?}", amount, token),
+        }
+    }
+}
+
+pub fn units(amount: I256, decimals: u8) -> f64 {
+    amount.to_string().parse::<f64>().unwrap_or(0.0) / 10f64.powi(decimals as i32)
+}
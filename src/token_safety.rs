@@ -0,0 +1,78 @@
+// src/token_safety.rs
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::warn;
+
+use crate::revert::{ExecutionFailure, FailureKind};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenStrike {
+    pub chain_id: u64,
+    pub token: Address,
+    pub strikes: u32,
+}
+
+/// Tokens that keep breaking executions (transfer taxes, blocked transfers,
+/// rebasing balances). One instance is shared by every chain's monitor, so the
+/// list persists and is inspected in one place.
+pub struct TokenSafety {
+    strikes: RwLock<HashMap<(u64, Address), u32>>,
+    // Strikes after which a token is skipped
+    max_strikes: u32,
+}
+
+impl TokenSafety {
+    pub fn new(max_strikes: u32) -> Self {
+        Self {
+            strikes: RwLock::new(HashMap::new()),
+            max_strikes,
+        }
+    }
+
+    /// Counts a failure against every token in `tokens` when it is the kind a
+    /// misbehaving token causes. The path's well-known base tokens are excluded
+    /// by the caller.
+    pub fn record_failure(&self, chain_id: u64, tokens: &[Address], failure: &ExecutionFailure) {
+        if !matches!(failure.kind, FailureKind::Allowance | FailureKind::LoanRepayment) {
+            return;
+        }
+        let mut strikes = self.strikes.write().unwrap();
+        for token in tokens {
+            let count = strikes.entry((chain_id, *token)).or_default();
+            *count += 1;
+            if *count == self.max_strikes {
+                warn!(chain_id, token = ?token, "Token marked unsafe after {} {} failures", count, failure.kind.as_str());
+            }
+        }
+    }
+
+    /// Marks `token` unsafe outright, for misbehaviour caught before it costs an
+    /// execution (e.g. a simulated transfer short-delivering).
+    pub fn mark_unsafe(&self, chain_id: u64, token: Address) {
+        let mut strikes = self.strikes.write().unwrap();
+        let count = strikes.entry((chain_id, token)).or_default();
+        *count = (*count).max(self.max_strikes);
+    }
+
+    pub fn is_unsafe(&self, chain_id: u64, token: Address) -> bool {
+        self.strikes.read().unwrap().get(&(chain_id, token)).map_or(false, |s| *s >= self.max_strikes)
+    }
+
+    pub fn snapshot(&self) -> Vec<TokenStrike> {
+        self.strikes
+            .read()
+            .unwrap()
+            .iter()
+            .map(|((chain_id, token), strikes)| TokenStrike { chain_id: *chain_id, token: *token, strikes: *strikes })
+            .collect()
+    }
+
+    pub fn restore(&self, entries: Vec<TokenStrike>) {
+        let mut strikes = self.strikes.write().unwrap();
+        for entry in entries {
+            strikes.insert((entry.chain_id, entry.token), entry.strikes);
+        }
+    }
+}
@@ -0,0 +1,376 @@
+// src/token_universe.rs
+use anyhow::Result;
+use ethers::abi::{decode, ParamType, Token};
+use ethers::prelude::*;
+use ethers::utils::id;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::pool_state::{PoolState, PoolStateManager};
+use crate::price_service::PriceService;
+use crate::rpc_budget::RpcTransport;
+use crate::token_registry::TokenRegistry;
+use crate::token_safety::TokenSafety;
+use crate::v3_quoter::UNISWAP_V3_FACTORY;
+use crate::v3_ticks::V3PoolState;
+
+abigen!(IUniverseToken, r#"[
+    function balanceOf(address owner) external view returns (uint256)
+]"#);
+
+// Pools per getLogs address filter and per balance multicall
+const POOL_BATCH: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct UniverseConfig {
+    // How far back the first factory scan reaches
+    pub lookback_blocks: u64,
+    // Block range per getLogs request
+    pub log_chunk_blocks: u64,
+    // Swap volume is summed over this many trailing blocks
+    pub volume_window_blocks: u64,
+    // Base-side liquidity across the token's pools, valued at twice the base side
+    pub min_tvl_usd: f64,
+    pub min_volume_usd: f64,
+    // Tokens admitted on top of the base set, highest volume first
+    pub max_tokens: usize,
+    pub refresh_blocks: u64,
+}
+
+impl Default for UniverseConfig {
+    fn default() -> Self {
+        Self {
+            lookback_blocks: 200_000, // ~5 days
+            log_chunk_blocks: 2_000,
+            volume_window_blocks: 43_200, // ~1 day
+            min_tvl_usd: 50_000.0,
+            min_volume_usd: 25_000.0,
+            max_tokens: 25,
+            refresh_blocks: 1_800, // ~1 hour
+        }
+    }
+}
+
+// A factory-created pool pairing a candidate token with a base token
+#[derive(Debug, Clone)]
+struct Candidate {
+    token: Address,
+    base: Address,
+    // V2 router to trade it through; None for Uniswap V3
+    router: Option<Address>,
+}
+
+#[derive(Default)]
+struct ScanState {
+    // Last block whose factory and swap logs were read
+    scanned_to: Option<u64>,
+    candidates: HashMap<Address, Candidate>,
+    // Pool -> base-side swap volume per `refresh_blocks` bucket
+    volume: HashMap<Address, BTreeMap<u64, U256>>,
+}
+
+/// Maintains the set of tokens the bot trades beyond the chain's base tokens.
+/// Factory `PairCreated`/`PoolCreated` logs name every pool pairing a token
+/// with a base token; tokens whose pools hold enough liquidity and saw enough
+/// swap volume are admitted, their pools handed to the pool state, and tokens
+/// that fall below the bar are dropped again.
+pub struct TokenUniverse {
+    provider: Arc<Provider<RpcTransport>>,
+    pool_state: Arc<PoolStateManager>,
+    prices: Arc<PriceService>,
+    tokens: Arc<TokenRegistry>,
+    token_safety: Arc<TokenSafety>,
+    chain_id: u64,
+    base_tokens: Vec<Address>,
+    v3_factory: Option<Address>,
+    config: UniverseConfig,
+    // Loaded by other means (strategies, bridge flow); never admitted or dropped here
+    pinned: RwLock<HashSet<Address>>,
+    admitted: RwLock<Vec<Address>>,
+    scan: Mutex<ScanState>,
+}
+
+impl TokenUniverse {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        provider: Arc<Provider<RpcTransport>>,
+        pool_state: Arc<PoolStateManager>,
+        prices: Arc<PriceService>,
+        tokens: Arc<TokenRegistry>,
+        token_safety: Arc<TokenSafety>,
+        chain_id: u64,
+        base_tokens: Vec<Address>,
+        has_v3: bool,
+        config: UniverseConfig,
+    ) -> Self {
+        Self {
+            provider,
+            pool_state,
+            prices,
+            tokens,
+            token_safety,
+            chain_id,
+            pinned: RwLock::new(base_tokens.iter().copied().collect()),
+            base_tokens,
+            v3_factory: has_v3.then(|| UNISWAP_V3_FACTORY.parse().unwrap()),
+            config,
+            admitted: RwLock::new(Vec::new()),
+            scan: Mutex::new(ScanState::default()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.max_tokens > 0
+    }
+
+    pub fn refresh_blocks(&self) -> u64 {
+        self.config.refresh_blocks
+    }
+
+    pub async fn pin(&self, tokens: &[Address]) {
+        self.pinned.write().await.extend(tokens.iter().copied());
+    }
+
+    /// Base tokens followed by the admitted ones.
+    pub async fn active(&self) -> Vec<Address> {
+        let mut active = self.base_tokens.clone();
+        active.extend(self.admitted.read().await.iter().copied());
+        active
+    }
+
+    /// Reads factory and swap logs up to `head`, re-ranks the candidates and
+    /// updates the tracked pools to match.
+    pub async fn refresh(&self, head: U64) -> Result<()> {
+        let head = head.as_u64();
+        let mut scan = self.scan.lock().await;
+        let first = scan.scanned_to.is_none();
+        let factory_from = scan.scanned_to.map_or(head.saturating_sub(self.config.lookback_blocks), |b| b + 1);
+        let volume_from = scan.scanned_to.map_or(head.saturating_sub(self.config.volume_window_blocks), |b| b + 1);
+        if factory_from > head {
+            return Ok(());
+        }
+
+        let found = self.scan_factories(&mut scan, factory_from, head).await?;
+        self.scan_swaps(&mut scan, volume_from, head).await?;
+        scan.scanned_to = Some(head);
+        let oldest = head.saturating_sub(self.config.volume_window_blocks) / self.config.refresh_blocks;
+        for buckets in scan.volume.values_mut() {
+            buckets.retain(|bucket, _| *bucket >= oldest);
+        }
+        if first || found > 0 {
+            debug!("Token universe: {} new candidate pools, {} total", found, scan.candidates.len());
+        }
+
+        let ranked = self.rank(&scan, head).await?;
+        self.apply(&scan, ranked, head).await;
+        Ok(())
+    }
+
+    // Candidate pools created in [from, to]; returns how many were new
+    async fn scan_factories(&self, scan: &mut ScanState, from: u64, to: u64) -> Result<usize> {
+        let pinned = self.pinned.read().await.clone();
+        let mut factories: Vec<(Address, Option<Address>)> =
+            self.pool_state.dexes().iter().map(|(factory, router)| (*factory, Some(*router))).collect();
+        if let Some(factory) = self.v3_factory {
+            factories.push((factory, None));
+        }
+        let topics = vec![
+            id("PairCreated(address,address,address,uint256)"),
+            id("PoolCreated(address,address,uint24,int24,address)"),
+        ];
+
+        let mut found = 0;
+        for (start, end) in chunks(from, to, self.config.log_chunk_blocks) {
+            let filter = Filter::new()
+                .address(factories.iter().map(|(f, _)| *f).collect::<Vec<_>>())
+                .topic0(topics.clone())
+                .from_block(start)
+                .to_block(end);
+            for log in self.provider.get_logs(&filter).await? {
+                let router = match factories.iter().find(|(f, _)| *f == log.address) {
+                    Some((_, router)) => *router,
+                    None => continue,
+                };
+                let (token0, token1, pool) = match decode_created(&log, router.is_none()) {
+                    Some(created) => created,
+                    None => continue,
+                };
+                // Exactly one side must be a base token for the pool to be valued
+                let (token, base) = match (self.base_tokens.contains(&token0), self.base_tokens.contains(&token1)) {
+                    (true, false) => (token1, token0),
+                    (false, true) => (token0, token1),
+                    _ => continue,
+                };
+                if pinned.contains(&token) || scan.candidates.contains_key(&pool) {
+                    continue;
+                }
+                scan.candidates.insert(pool, Candidate { token, base, router });
+                found += 1;
+            }
+        }
+        Ok(found)
+    }
+
+    // Adds the base side of every candidate pool's swaps in [from, to] to its buckets
+    async fn scan_swaps(&self, scan: &mut ScanState, from: u64, to: u64) -> Result<()> {
+        let pools: Vec<Address> = scan.candidates.keys().copied().collect();
+        let topics = vec![PoolState::swap_topic(), V3PoolState::swap_topic()];
+        for batch in pools.chunks(POOL_BATCH) {
+            for (start, end) in chunks(from, to, self.config.log_chunk_blocks) {
+                let filter = Filter::new()
+                    .address(batch.to_vec())
+                    .topic0(topics.clone())
+                    .from_block(start)
+                    .to_block(end);
+                for log in self.provider.get_logs(&filter).await? {
+                    let candidate = match scan.candidates.get(&log.address) {
+                        Some(candidate) => candidate,
+                        None => continue,
+                    };
+                    // The base is token0 when it sorts first
+                    let base_is_token0 = candidate.base < candidate.token;
+                    let amount = match decode_base_volume(&log, base_is_token0) {
+                        Some(amount) => amount,
+                        None => continue,
+                    };
+                    let block = log.block_number.map_or(to, |b| b.as_u64());
+                    *scan
+                        .volume
+                        .entry(log.address)
+                        .or_default()
+                        .entry(block / self.config.refresh_blocks)
+                        .or_default() += amount;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Tokens clearing the bar, highest volume first, capped at `max_tokens`.
+    // Admitted tokens only need half the bar to stay, so they don't flap
+    async fn rank(&self, scan: &ScanState, head: u64) -> Result<Vec<Address>> {
+        let pools: Vec<(Address, &Candidate)> = scan.candidates.iter().map(|(pool, c)| (*pool, c)).collect();
+        let mut balances = HashMap::new();
+        for batch in pools.chunks(POOL_BATCH) {
+            let mut multicall = Multicall::new(self.provider.clone(), None).await?.block(head);
+            for (pool, candidate) in batch {
+                multicall.add_call(IUniverseToken::new(candidate.base, self.provider.clone()).balance_of(*pool), true);
+            }
+            for ((pool, _), result) in batch.iter().zip(multicall.call_raw().await?) {
+                if let Ok(Token::Uint(balance)) = result {
+                    balances.insert(*pool, balance);
+                }
+            }
+        }
+
+        // Token -> (TVL, volume) in USD
+        let mut totals: HashMap<Address, (f64, f64)> = HashMap::new();
+        for (pool, candidate) in &pools {
+            let balance = balances.get(pool).copied().unwrap_or_default();
+            let volume = scan.volume.get(pool).map_or(U256::zero(), |b| b.values().fold(U256::zero(), |a, v| a.saturating_add(*v)));
+            let tvl_usd = self.prices.usd_value(candidate.base, balance).await?.unwrap_or_default() * 2.0;
+            let volume_usd = self.prices.usd_value(candidate.base, volume).await?.unwrap_or_default();
+            let total = totals.entry(candidate.token).or_default();
+            total.0 += tvl_usd;
+            total.1 += volume_usd;
+        }
+
+        let admitted = self.admitted.read().await.clone();
+        let pinned = self.pinned.read().await.clone();
+        let mut qualified: Vec<(Address, f64)> = totals
+            .into_iter()
+            .filter(|(token, (tvl, volume))| {
+                let scale = if admitted.contains(token) { 0.5 } else { 1.0 };
+                !pinned.contains(token)
+                    && *tvl >= self.config.min_tvl_usd * scale
+                    && *volume >= self.config.min_volume_usd * scale
+                    && !self.token_safety.is_unsafe(self.chain_id, *token)
+            })
+            .map(|(token, (_, volume))| (token, volume))
+            .collect();
+        qualified.sort_by(|a, b| b.1.total_cmp(&a.1));
+        qualified.truncate(self.config.max_tokens);
+        Ok(qualified.into_iter().map(|(token, _)| token).collect())
+    }
+
+    async fn apply(&self, scan: &ScanState, ranked: Vec<Address>, head: u64) {
+        let previous = self.admitted.read().await.clone();
+        let pinned = self.pinned.read().await.clone();
+
+        let added: Vec<Address> = ranked.iter().copied().filter(|t| !previous.contains(t)).collect();
+        if !added.is_empty() {
+            if let Err(e) = self.tokens.prefetch(&added).await {
+                warn!("Token metadata prefetch failed: {:?}", e);
+            }
+        }
+        for token in &added {
+            let pools = scan.candidates.iter().filter(|(_, c)| c.token == *token);
+            for (pool, candidate) in pools {
+                let tracked = match candidate.router {
+                    Some(router) => self.pool_state.track_pair(*pool, router).await,
+                    None => self.pool_state.track_v3(*pool, head.into()).await,
+                };
+                if let Err(e) = tracked {
+                    warn!("Failed to track pool {:?} for {:?}: {:?}", pool, token, e);
+                }
+            }
+            info!("Token universe: admitted {:?}", token);
+        }
+
+        for token in previous.iter().filter(|t| !ranked.contains(t)) {
+            // Pinned since admission: someone else relies on its pools now
+            let dropped = if pinned.contains(token) { 0 } else { self.pool_state.forget_token(*token).await };
+            info!("Token universe: dropped {:?} ({} pools)", token, dropped);
+        }
+
+        *self.admitted.write().await = ranked;
+    }
+}
+
+// [from, to] in inclusive ranges of at most `size` blocks
+fn chunks(from: u64, to: u64, size: u64) -> Vec<(u64, u64)> {
+    let size = size.max(1);
+    let mut ranges = Vec::new();
+    let mut start = from;
+    while start <= to {
+        let end = (start + size - 1).min(to);
+        ranges.push((start, end));
+        start = end + 1;
+    }
+    ranges
+}
+
+fn topic_address(topic: &H256) -> Address {
+    Address::from_slice(&topic.as_bytes()[12..])
+}
+
+// (token0, token1, pool) from a V2 PairCreated or V3 PoolCreated log
+fn decode_created(log: &Log, v3: bool) -> Option<(Address, Address, Address)> {
+    let token0 = topic_address(log.topics.get(1)?);
+    let token1 = topic_address(log.topics.get(2)?);
+    let pool = if v3 {
+        // PoolCreated(token0, token1, fee, tickSpacing, pool): tickSpacing and pool in data
+        decode(&[ParamType::Int(24), ParamType::Address], &log.data).ok()?.into_iter().nth(1)?.into_address()?
+    } else {
+        decode(&[ParamType::Address, ParamType::Uint(256)], &log.data).ok()?.into_iter().next()?.into_address()?
+    };
+    Some((token0, token1, pool))
+}
+
+// Base-side amount of a V2 or V3 Swap log
+fn decode_base_volume(log: &Log, base_is_token0: bool) -> Option<U256> {
+    let topic = *log.topics.first()?;
+    if topic == PoolState::swap_topic() {
+        // amount0In, amount1In, amount0Out, amount1Out
+        let amounts: Vec<U256> = decode(&vec![ParamType::Uint(256); 4], &log.data).ok()?.into_iter().filter_map(Token::into_uint).collect();
+        let (amount_in, amount_out) = if base_is_token0 { (amounts.first()?, amounts.get(2)?) } else { (amounts.get(1)?, amounts.get(3)?) };
+        Some(amount_in.saturating_add(*amount_out))
+    } else {
+        // amount0, amount1 are the first two words, signed
+        let word = if base_is_token0 { 0 } else { 1 };
+        let amount = I256::from_raw(U256::from_big_endian(log.data.get(word * 32..(word + 1) * 32)?));
+        Some(amount.unsigned_abs())
+    }
+}
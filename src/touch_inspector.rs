@@ -0,0 +1,91 @@
+// src/touch_inspector.rs
+use ethers::types::{Address, U256};
+use revm::{
+    interpreter::{opcode, InstructionResult, Interpreter},
+    primitives::{B160, U256 as rU256},
+    Database, EVMData, Inspector,
+};
+use std::collections::HashMap;
+
+// UniswapV2Pair packs reserve0 | reserve1 << 112 | blockTimestampLast << 224 here
+const V2_RESERVES_SLOT: u64 = 8;
+// UniswapV3Pool.slot0, and feeGrowthGlobal0X128 / feeGrowthGlobal1X128
+const V3_SLOT0: u64 = 0;
+const V3_FEE_GROWTH_SLOTS: [u64; 2] = [1, 2];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolLayout {
+    UniswapV2,
+    UniswapV3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchedPool {
+    pub address: Address,
+    pub layout: PoolLayout,
+}
+
+/// Records every contract a transaction executes in and every storage slot it
+/// writes, with the last value written.
+#[derive(Debug, Clone, Default)]
+pub struct TouchInspector {
+    // Execution order, first entry only
+    contracts: Vec<B160>,
+    writes: HashMap<B160, HashMap<rU256, rU256>>,
+}
+
+impl<DB: Database> Inspector<DB> for TouchInspector {
+    fn step(&mut self, interp: &mut Interpreter, _data: &mut EVMData<'_, DB>) -> InstructionResult {
+        let address = interp.contract.address;
+        if self.contracts.last() != Some(&address) && !self.contracts.contains(&address) {
+            self.contracts.push(address);
+        }
+        if interp.current_opcode() == opcode::SSTORE {
+            if let (Ok(slot), Ok(value)) = (interp.stack.peek(0), interp.stack.peek(1)) {
+                self.writes.entry(address).or_default().insert(slot, value);
+            }
+        }
+        InstructionResult::Continue
+    }
+}
+
+impl TouchInspector {
+    pub fn contracts(&self) -> Vec<Address> {
+        self.contracts.iter().map(|a| Address::from(a.0)).collect()
+    }
+
+    pub fn written_slots(&self, contract: Address) -> Vec<U256> {
+        self.writes
+            .get(&B160::from(contract.0))
+            .map(|slots| slots.keys().map(|s| U256::from_big_endian(&s.to_be_bytes::<32>())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Contracts whose writes look like a swap on a Uniswap-style pool. Any router,
+    /// aggregator or fork that ends up in one is caught without decoding its calldata.
+    pub fn touched_pools(&self) -> Vec<TouchedPool> {
+        let wrote = |slots: &HashMap<rU256, rU256>, slot: u64| slots.contains_key(&rU256::from(slot));
+        self.contracts
+            .iter()
+            .filter_map(|address| {
+                let slots = self.writes.get(address)?;
+                let layout = if wrote(slots, V2_RESERVES_SLOT) {
+                    PoolLayout::UniswapV2
+                } else if wrote(slots, V3_SLOT0) && V3_FEE_GROWTH_SLOTS.iter().any(|s| wrote(slots, *s)) {
+                    PoolLayout::UniswapV3
+                } else {
+                    return None;
+                };
+                Some(TouchedPool { address: Address::from(address.0), layout })
+            })
+            .collect()
+    }
+
+    /// Reserves a V2-layout pool is left with after the transaction.
+    pub fn v2_reserves(&self, pool: Address) -> Option<(U256, U256)> {
+        let packed = self.writes.get(&B160::from(pool.0))?.get(&rU256::from(V2_RESERVES_SLOT))?;
+        let packed = U256::from_big_endian(&packed.to_be_bytes::<32>());
+        let mask = (U256::one() << 112) - 1;
+        Some((packed & mask, (packed >> 112) & mask))
+    }
+}
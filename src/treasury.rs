@@ -0,0 +1,305 @@
+// src/treasury.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+use crate::chain::ChainProfile;
+use crate::fee::FeeAmount;
+use crate::gas_oracle::GasOracle;
+use crate::inventory::Inventory;
+use crate::native::withdraw_calldata;
+use crate::pool_state::PoolStateManager;
+use crate::price_service::PriceService;
+use crate::strategies::jit_liquidity::UNISWAP_V3_ROUTER;
+use crate::tx_tracker::SignerClient;
+use crate::v3_quoter::{V3Hop, V3Quoter};
+
+abigen!(ITreasuryToken, r#"[
+    function balanceOf(address owner) external view returns (uint256)
+    function approve(address spender, uint256 amount) external returns (bool)
+    function transfer(address to, uint256 amount) external returns (bool)
+]"#);
+
+abigen!(ITreasuryExecutor, r#"[
+    function withdrawToken(address token, uint256 amount) external
+]"#);
+
+abigen!(ITreasuryV2Router, r#"[
+    function swapExactTokensForTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external returns (uint256[] amounts)
+]"#);
+
+abigen!(ITreasuryV3Router, r#"[
+    struct ExactInputSingleParams { address tokenIn; address tokenOut; uint24 fee; address recipient; uint256 deadline; uint256 amountIn; uint256 amountOutMinimum; uint160 sqrtPriceLimitX96; }
+    function exactInputSingle(ExactInputSingleParams params) external payable returns (uint256 amountOut)
+]"#);
+
+// Conversions are public swaps, so leave room for the price to move before inclusion
+const SLIPPAGE_BPS: u64 = 50;
+const SWAP_DEADLINE_SECS: u64 = 120;
+// Local V3 math further than this from QuoterV2 isn't trusted for min_out
+const MAX_QUOTE_DIVERGENCE_BPS: u64 = 10;
+
+#[derive(Debug, Clone)]
+pub struct TreasuryConfig {
+    // Everything else the executor accumulates is converted into this
+    pub base_asset: Address,
+    // Unwrapped to refill signer gas
+    pub wrapped_native: Address,
+    // Profit tokens worth less than this stay on the executor
+    pub min_convert_usd: f64,
+    // Only convert while the next base fee is at or below this
+    pub max_base_fee: U256,
+    // Base asset above this is swept to the cold wallet, when one is set
+    pub cold_wallet: Option<Address>,
+    pub min_sweep_usd: f64,
+    // How often, in blocks, the treasury runs
+    pub interval_blocks: u64,
+}
+
+impl TreasuryConfig {
+    /// TREASURY_BASE_ASSET=USDC|NATIVE|<address> turns the treasury on, with
+    /// TREASURY_MIN_CONVERT_USD, TREASURY_MAX_BASE_FEE_GWEI, TREASURY_COLD_WALLET
+    /// and TREASURY_MIN_SWEEP_USD. `None` when no base asset is set.
+    pub fn from_env(chain: &ChainProfile) -> Result<Option<Self>> {
+        let base = match chain.var("TREASURY_BASE_ASSET") {
+            Ok(base) => base,
+            Err(_) => return Ok(None),
+        };
+        let base_asset = match base.as_str() {
+            "USDC" => chain.usdc,
+            // WMATIC kept for existing configs
+            "NATIVE" | "WMATIC" => chain.wrapped_native,
+            other => other.parse().map_err(|_| anyhow!("TREASURY_BASE_ASSET must be USDC, NATIVE or an address, got {:?}", other))?,
+        };
+        // A typo here would otherwise leave profit on the hot executor unnoticed
+        let cold_wallet = match chain.var("TREASURY_COLD_WALLET") {
+            Ok(wallet) => Some(wallet.parse().map_err(|_| anyhow!("TREASURY_COLD_WALLET is not an address: {:?}", wallet))?),
+            Err(_) => None,
+        };
+        let number = |key: &str| chain.var(key).ok().and_then(|v| v.parse::<f64>().ok());
+        Ok(Some(Self {
+            base_asset,
+            wrapped_native: chain.wrapped_native,
+            min_convert_usd: number("TREASURY_MIN_CONVERT_USD").unwrap_or(100.0),
+            max_base_fee: U256::from(chain.var("TREASURY_MAX_BASE_FEE_GWEI").ok().and_then(|v| v.parse().ok()).unwrap_or(100u64))
+                * U256::exp10(9),
+            cold_wallet,
+            min_sweep_usd: number("TREASURY_MIN_SWEEP_USD").unwrap_or(1_000.0),
+            interval_blocks: 1_800, // ~1 hour
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Venue {
+    V2 { router: Address },
+    V3 { fee: FeeAmount },
+}
+
+/// Periodically pulls profit tokens out of the executor contract into the signer
+/// EOA, converts them to the base asset at the best venue our pool state can
+/// quote, and optionally sweeps the base asset to a cold wallet.
+pub struct Treasury {
+    config: TreasuryConfig,
+    signer: Arc<SignerClient>,
+    executor: Address,
+    pool_state: Arc<PoolStateManager>,
+    prices: Arc<PriceService>,
+    quoter: V3Quoter,
+}
+
+impl Treasury {
+    pub fn new(
+        config: TreasuryConfig,
+        signer: Arc<SignerClient>,
+        executor: Address,
+        pool_state: Arc<PoolStateManager>,
+        prices: Arc<PriceService>,
+    ) -> Self {
+        Self {
+            quoter: V3Quoter::new(signer.inner().clone(), MAX_QUOTE_DIVERGENCE_BPS).with_pool_state(pool_state.clone()),
+            config,
+            signer,
+            executor,
+            pool_state,
+            prices,
+        }
+    }
+
+    pub fn interval_blocks(&self) -> u64 {
+        self.config.interval_blocks
+    }
+
+    /// One treasury pass over `tokens`. Skipped entirely while gas is expensive.
+    /// Balances `inventory` trades from are only converted above their band.
+    pub async fn run(&self, tokens: &[Address], gas: &GasOracle, inventory: &Inventory) -> Result<()> {
+        let base_fee = gas.next_base_fee();
+        if base_fee.is_zero() || base_fee > self.config.max_base_fee {
+            debug!("Treasury skipped, base fee {} above {}", base_fee, self.config.max_base_fee);
+            return Ok(());
+        }
+
+        for token in tokens.iter().copied().filter(|t| *t != self.config.base_asset) {
+            if let Err(e) = self.convert(token, inventory).await {
+                warn!("Treasury conversion of {:?} failed: {:?}", token, e);
+            }
+        }
+        if let Some(cold_wallet) = self.config.cold_wallet {
+            self.sweep(cold_wallet, inventory).await?;
+        }
+        Ok(())
+    }
+
+    async fn balance(&self, token: Address, owner: Address) -> Result<U256> {
+        Ok(ITreasuryToken::new(token, self.signer.clone()).balance_of(owner).call().await?)
+    }
+
+    async fn convert(&self, token: Address, inventory: &Inventory) -> Result<()> {
+        let balance = self.balance(token, self.executor).await?;
+        let amount = inventory.surplus(token, balance, &self.prices).await?;
+        if amount.is_zero() {
+            return Ok(());
+        }
+        match self.prices.usd_value(token, amount).await? {
+            Some(usd) if usd >= self.config.min_convert_usd => {}
+            _ => return Ok(()),
+        }
+
+        let (venue, quoted) = self
+            .best_venue(token, amount)
+            .await
+            .ok_or_else(|| anyhow!("No tracked pool quotes {:?} into the base asset", token))?;
+        let min_out = quoted * U256::from(10_000 - SLIPPAGE_BPS) / U256::from(10_000);
+
+        ITreasuryExecutor::new(self.executor, self.signer.clone())
+            .withdraw_token(token, amount)
+            .send()
+            .await?
+            .await?;
+        self.swap(token, amount, min_out, venue).await?;
+        info!("Treasury converted {} of {:?} via {:?}, quoted {} base", amount, token, venue, quoted);
+        Ok(())
+    }
+
+    // Same pool state the strategies quote from: every V2 pool and tracked V3 pool
+    // between the token and the base asset. V3 quotes are checked against
+    // QuoterV2, since they set a real swap's min_out
+    async fn best_venue(&self, token: Address, amount: U256) -> Option<(Venue, U256)> {
+        let base = self.config.base_asset;
+        let mut best: Option<(Venue, U256)> = None;
+        for pool in self.pool_state.pools_for_pair(token, base).await {
+            let out = pool.get_amount_out(token, amount);
+            if best.map_or(true, |(_, b)| out > b) {
+                best = Some((Venue::V2 { router: pool.router }, out));
+            }
+        }
+        for pool in self.pool_state.v3_pools_for_pair(token, base).await {
+            let hop = V3Hop { token_in: token, token_out: base, fee: pool.fee };
+            match self.quoter.quote_exact_input(&[hop], amount).await {
+                Ok(quote) => {
+                    if best.map_or(true, |(_, b)| quote.amount_out > b) {
+                        best = Some((Venue::V3 { fee: pool.fee }, quote.amount_out));
+                    }
+                }
+                Err(e) => debug!("No V3 quote for {:?} at fee {}: {:?}", token, pool.fee, e),
+            }
+        }
+        best.filter(|(_, out)| !out.is_zero())
+    }
+
+    async fn swap(&self, token: Address, amount: U256, min_out: U256, venue: Venue) -> Result<()> {
+        let recipient = self.signer.address();
+        let deadline = U256::from(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + SWAP_DEADLINE_SECS);
+        let router = match venue {
+            Venue::V2 { router } => router,
+            Venue::V3 { .. } => UNISWAP_V3_ROUTER.parse()?,
+        };
+
+        ITreasuryToken::new(token, self.signer.clone())
+            .approve(router, amount)
+            .send()
+            .await?
+            .await?;
+
+        let receipt = match venue {
+            Venue::V2 { .. } => {
+                ITreasuryV2Router::new(router, self.signer.clone())
+                    .swap_exact_tokens_for_tokens(amount, min_out, vec![token, self.config.base_asset], recipient, deadline)
+                    .send()
+                    .await?
+                    .await?
+            }
+            Venue::V3 { fee } => {
+                ITreasuryV3Router::new(router, self.signer.clone())
+                    .exact_input_single(ExactInputSingleParams {
+                        token_in: token,
+                        token_out: self.config.base_asset,
+                        fee: fee.pips(),
+                        recipient,
+                        deadline,
+                        amount_in: amount,
+                        amount_out_minimum: min_out,
+                        sqrt_price_limit_x96: U256::zero(),
+                    })
+                    .send()
+                    .await?
+                    .await?
+            }
+        };
+        match receipt {
+            Some(r) if r.status == Some(1.into()) => Ok(()),
+            _ => Err(anyhow!("Treasury swap of {:?} reverted", token)),
+        }
+    }
+
+    /// Tops the signer up with native MATIC by pulling WMATIC profit off the
+    /// executor and unwrapping it, leaving what `inventory` trades with. Returns
+    /// the amount actually refilled.
+    pub async fn refill_gas(&self, amount: U256, inventory: &Inventory) -> Result<U256> {
+        let wmatic = self.config.wrapped_native;
+        let balance = self.balance(wmatic, self.executor).await?;
+        let amount = amount.min(inventory.surplus(wmatic, balance, &self.prices).await?);
+        if amount.is_zero() {
+            return Err(anyhow!("Executor holds no spare WMATIC to refill gas with"));
+        }
+
+        ITreasuryExecutor::new(self.executor, self.signer.clone())
+            .withdraw_token(wmatic, amount)
+            .send()
+            .await?
+            .await?;
+        let unwrap = TransactionRequest::new().to(wmatic).data(withdraw_calldata(amount));
+        self.signer.send_transaction(unwrap, None).await?.await?;
+        info!("Refilled signer gas with {} MATIC unwrapped from executor profit", amount);
+        Ok(amount)
+    }
+
+    // Moves base asset held by the executor (above its inventory band) and the
+    // EOA to cold storage
+    async fn sweep(&self, cold_wallet: Address, inventory: &Inventory) -> Result<()> {
+        let base = self.config.base_asset;
+        let on_executor = inventory.surplus(base, self.balance(base, self.executor).await?, &self.prices).await?;
+        if !on_executor.is_zero() {
+            ITreasuryExecutor::new(self.executor, self.signer.clone())
+                .withdraw_token(base, on_executor)
+                .send()
+                .await?
+                .await?;
+        }
+
+        let amount = self.balance(base, self.signer.address()).await?;
+        match self.prices.usd_value(base, amount).await? {
+            Some(usd) if usd >= self.config.min_sweep_usd => {}
+            _ => return Ok(()),
+        }
+        ITreasuryToken::new(base, self.signer.clone())
+            .transfer(cold_wallet, amount)
+            .send()
+            .await?
+            .await?;
+        info!("Treasury swept {} base asset to {:?}", amount, cold_wallet);
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+// src/tx_tracker.rs
+use anyhow::{anyhow, Result};
+use ethers::{
+    prelude::*,
+    types::transaction::eip2718::TypedTransaction,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::rpc_budget::RpcTransport;
+
+pub type SignerClient = SignerMiddleware<Arc<Provider<RpcTransport>>, LocalWallet>;
+
+// Blocks after which a mined execution is no longer re-checked on reorg
+const SETTLEMENT_DEPTH: u64 = 16;
+// Smallest fee increase nodes accept for a same-nonce replacement, in percent
+const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+#[derive(Debug, Clone)]
+pub struct TxTrackerConfig {
+    // Blocks to wait before the first replacement
+    pub stuck_after_blocks: u64,
+    // Speed-ups attempted before giving up and cancelling
+    pub max_speedups: u32,
+    // Fee bump per replacement, in percent (nodes require >= 10)
+    pub fee_bump_percent: u64,
+    pub max_fee_per_gas: U256,
+}
+
+impl Default for TxTrackerConfig {
+    fn default() -> Self {
+        Self {
+            stuck_after_blocks: 3,
+            max_speedups: 2,
+            fee_bump_percent: 15,
+            max_fee_per_gas: U256::from(500_000_000_000u64), // 500 gwei
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TrackedStatus {
+    Pending,
+    Cancelling,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedTx {
+    pub tx: TypedTransaction,
+    pub nonce: U256,
+    // Every hash broadcast for this nonce, latest last
+    pub hashes: Vec<H256>,
+    pub submitted_block: U64,
+    pub speedups: u32,
+    pub status: TrackedStatus,
+}
+
+/// Watches our submitted executions and replaces them when they stall, so a flash
+/// loan can't linger in the mempool and execute later at stale prices.
+pub struct TxTracker {
+    client: Arc<SignerClient>,
+    config: TxTrackerConfig,
+    // nonce -> tracked transaction
+    tracked: Mutex<HashMap<U256, TrackedTx>>,
+    // Settled entries kept until they're deep enough not to be reorged out
+    settled: Mutex<HashMap<U256, (U64, TrackedTx)>>,
+}
+
+impl TxTracker {
+    pub fn new(client: Arc<SignerClient>, config: TxTrackerConfig) -> Self {
+        Self {
+            client,
+            config,
+            tracked: Mutex::new(HashMap::new()),
+            settled: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn submit(&self, mut tx: TypedTransaction) -> Result<H256> {
+        self.client.fill_transaction(&mut tx, None).await?;
+        let nonce = tx.nonce().copied().ok_or_else(|| anyhow!("Transaction has no nonce"))?;
+        let block = self.client.get_block_number().await?;
+
+        let pending = self.client.send_transaction(tx.clone(), None).await?;
+        let hash = pending.tx_hash();
+
+        self.tracked.lock().await.insert(nonce, TrackedTx {
+            tx,
+            nonce,
+            hashes: vec![hash],
+            submitted_block: block,
+            speedups: 0,
+            status: TrackedStatus::Pending,
+        });
+
+        info!("Tracking execution {:?} (nonce {})", hash, nonce);
+        Ok(hash)
+    }
+
+    pub async fn on_block(&self, block: U64) -> Result<()> {
+        let nonces: Vec<U256> = self.tracked.lock().await.keys().copied().collect();
+
+        for nonce in nonces {
+            let entry = match self.tracked.lock().await.get(&nonce) {
+                Some(e) => e.clone(),
+                None => continue,
+            };
+
+            // Any of the replacements may be the one that landed
+            if let Some((mined, mined_block)) = self.find_mined(&entry).await? {
+                let cancelled = entry.status == TrackedStatus::Cancelling;
+                info!("Nonce {} settled in {:?}{}", nonce, mined, if cancelled { " (cancel)" } else { "" });
+                self.tracked.lock().await.remove(&nonce);
+                self.settled.lock().await.insert(nonce, (mined_block, entry));
+                continue;
+            }
+
+            if block < entry.submitted_block + self.config.stuck_after_blocks {
+                continue;
+            }
+
+            let result = if entry.speedups < self.config.max_speedups && entry.status == TrackedStatus::Pending {
+                self.speed_up(entry, block).await
+            } else {
+                self.cancel(entry, block).await
+            };
+            if let Err(e) = result {
+                warn!("Failed to replace nonce {}: {:?}", nonce, e);
+            }
+        }
+
+        // Past the reorg window a settlement is final
+        self.settled
+            .lock()
+            .await
+            .retain(|_, (mined_block, _)| *mined_block + SETTLEMENT_DEPTH > block);
+        Ok(())
+    }
+
+    // Executions mined above the fork point may have been dropped with the
+    // abandoned blocks; those go back to pending so they get replaced or cancelled
+    pub async fn on_reorg(&self, common_ancestor: U64) -> Result<()> {
+        let candidates: Vec<(U256, TrackedTx)> = self
+            .settled
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, (mined_block, _))| *mined_block > common_ancestor)
+            .map(|(nonce, (_, entry))| (*nonce, entry.clone()))
+            .collect();
+
+        for (nonce, entry) in candidates {
+            match self.find_mined(&entry).await? {
+                Some((_, mined_block)) => {
+                    self.settled.lock().await.insert(nonce, (mined_block, entry));
+                }
+                None => {
+                    warn!("Execution nonce {} was dropped by a reorg, tracking again", nonce);
+                    self.settled.lock().await.remove(&nonce);
+                    self.tracked.lock().await.insert(nonce, entry);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Executions still pending at the last shutdown; the next block settles or
+    // replaces them like any other
+    pub async fn restore(&self, entries: Vec<TrackedTx>) {
+        let mut tracked = self.tracked.lock().await;
+        for entry in entries {
+            tracked.entry(entry.nonce).or_insert(entry);
+        }
+    }
+
+    pub async fn tracked(&self) -> Vec<TrackedTx> {
+        self.tracked.lock().await.values().cloned().collect()
+    }
+
+    async fn find_mined(&self, entry: &TrackedTx) -> Result<Option<(H256, U64)>> {
+        for hash in entry.hashes.iter().rev() {
+            if let Some(receipt) = self.client.get_transaction_receipt(*hash).await? {
+                let block = receipt.block_number.unwrap_or_default();
+                return Ok(Some((receipt.transaction_hash, block)));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn speed_up(&self, mut entry: TrackedTx, block: U64) -> Result<()> {
+        let mut tx = entry.tx.clone();
+        // Already priced at the cap: a cancel is the only replacement left
+        if let Err(e) = self.bump_fees(&mut tx) {
+            warn!("Can't speed up nonce {} ({}), cancelling", entry.nonce, e);
+            return self.cancel(entry, block).await;
+        }
+
+        let pending = self.client.send_transaction(tx.clone(), None).await?;
+        let hash = pending.tx_hash();
+        warn!("Execution nonce {} stuck, rebroadcast as {:?}", entry.nonce, hash);
+
+        entry.tx = tx;
+        entry.hashes.push(hash);
+        entry.submitted_block = block;
+        entry.speedups += 1;
+        self.tracked.lock().await.insert(entry.nonce, entry);
+        Ok(())
+    }
+
+    // Zero-value self-transfer at the same nonce, priced above the stuck tx
+    async fn cancel(&self, mut entry: TrackedTx, block: U64) -> Result<()> {
+        if entry.status == TrackedStatus::Cancelling && block < entry.submitted_block + self.config.stuck_after_blocks {
+            return Ok(());
+        }
+
+        let from = self.client.address();
+        let mut tx = entry.tx.clone();
+        tx.set_to(from);
+        tx.set_value(U256::zero());
+        tx.set_data(Bytes::default());
+        tx.set_gas(21_000u64);
+        self.bump_fees(&mut tx)?;
+
+        let pending = self.client.send_transaction(tx.clone(), None).await?;
+        let hash = pending.tx_hash();
+        warn!("Cancelling execution nonce {} with {:?}", entry.nonce, hash);
+
+        entry.tx = tx;
+        entry.hashes.push(hash);
+        entry.submitted_block = block;
+        entry.status = TrackedStatus::Cancelling;
+        self.tracked.lock().await.insert(entry.nonce, entry);
+        Ok(())
+    }
+
+    // Bumps by `fee_bump_percent`, clamped to the cap. Errors only once the cap
+    // leaves no room for a bump the node would accept as a replacement
+    fn bump_fees(&self, tx: &mut TypedTransaction) -> Result<()> {
+        let percent = |fee: U256, pct: u64| fee.saturating_mul(U256::from(100 + pct)) / U256::from(100);
+        let cap = self.config.max_fee_per_gas;
+        let bump = |fee: U256| -> Result<U256> {
+            let bumped = percent(fee, self.config.fee_bump_percent).min(cap);
+            let minimum = percent(fee, MIN_REPLACEMENT_BUMP_PERCENT);
+            if bumped < minimum {
+                return Err(anyhow!("Replacement fee for {} is held at cap {}", fee, cap));
+            }
+            Ok(bumped)
+        };
+
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                let max_fee = bump(inner.max_fee_per_gas.unwrap_or_default())?;
+                let priority = percent(inner.max_priority_fee_per_gas.unwrap_or_default(), self.config.fee_bump_percent);
+                inner.max_fee_per_gas = Some(max_fee);
+                inner.max_priority_fee_per_gas = Some(priority.min(max_fee));
+            }
+            _ => {
+                let gas_price = bump(tx.gas_price().unwrap_or_default())?;
+                tx.set_gas_price(gas_price);
+            }
+        }
+        Ok(())
+    }
+}
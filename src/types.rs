@@ -0,0 +1,60 @@
+// src/types.rs
+use ethers::types::{Address, U256, U64};
+use tracing::{info_span, Span};
+
+use crate::calldata_builder::Hop;
+use crate::flash_loan::{LoanLeg, LoanSource};
+use crate::latency::LatencyTrace;
+use crate::sensitivity::Sensitivity;
+use crate::simulation_engine::SimulationResult;
+
+/// A priced route found by any detection strategy, from queueing through
+/// bundle building. The loan is (token0, amount0) plus (token1, amount1) for a
+/// V3 flash, or `loans` for Balancer and Aave.
+#[derive(Debug, Clone)]
+pub struct ArbitrageOpportunity {
+    pub id: String,
+    pub token0: Address,
+    pub token1: Address,
+    pub amount0: U256,
+    pub amount1: U256,
+    pub loan_source: LoanSource,
+    // More than one leg for strategies that need inventory on both sides
+    pub loans: Vec<LoanLeg>,
+    pub path: Vec<Address>,
+    // Input of each hop, or the amount held after each token
+    pub amounts: Vec<U256>,
+    pub routers: Vec<Address>,
+    // Pools the path swaps through, for exposure limits and batching
+    pub pools: Vec<Address>,
+    // Typed hops when the pathfinder knows its pools (V2 and V3 may mix);
+    // empty for routes that only have path/amounts/routers
+    pub hops: Vec<Hop>,
+    // Simulated profit in token0; the on-chain guard's minimum is a share of it
+    pub expected_profit: U256,
+    // Block the opportunity was priced for; set when it is queued
+    pub target_block: Option<U64>,
+    // Set for mempool opportunities found by the simulation engine
+    pub simulation_result: Option<SimulationResult>,
+    // Profit under price and block slippage; set when it is queued
+    pub sensitivity: Option<Sensitivity>,
+    // Stage timestamps since the triggering tx (or head) arrived
+    pub latency: LatencyTrace,
+    // Opened with the id; queueing, execution and settlement log inside it
+    pub span: Span,
+}
+
+impl ArbitrageOpportunity {
+    /// A fresh opportunity id and the span everything about it is logged under.
+    pub fn open() -> (String, Span) {
+        let id = uuid::Uuid::new_v4().to_string();
+        let span = info_span!("opportunity", id = %id);
+        (id, span)
+    }
+
+    /// Two arbs through the same pool move each other's price, so they can't
+    /// share a batch. Without a pool list nothing can be ruled out.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.pools.is_empty() || other.pools.is_empty() || self.pools.iter().any(|p| other.pools.contains(p))
+    }
+}
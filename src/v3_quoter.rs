@@ -0,0 +1,187 @@
+// src/v3_quoter.rs
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::fee::FeeAmount;
+use crate::pool_state::PoolStateManager;
+use crate::rpc_budget::RpcTransport;
+
+pub const QUOTER_V2: &str = "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+
+abigen!(IQuoterV2, r#"[
+    function quoteExactInput(bytes path, uint256 amountIn) external returns (uint256 amountOut, uint160[] sqrtPriceX96AfterList, uint32[] initializedTicksCrossedList, uint256 gasEstimate)
+]"#);
+
+abigen!(IV3PoolState, r#"[
+    function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+    function liquidity() external view returns (uint128)
+    function token0() external view returns (address)
+]"#);
+
+abigen!(IV3Factory, r#"[
+    function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address)
+]"#);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteSource {
+    OnChain,
+    Local,
+}
+
+#[derive(Debug, Clone)]
+pub struct V3Quote {
+    pub amount_out: U256,
+    pub source: QuoteSource,
+    pub local_amount_out: Option<U256>,
+    pub onchain_amount_out: Option<U256>,
+    // |local - onchain| / onchain, when both were available
+    pub divergence_bps: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct V3Hop {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub fee: FeeAmount,
+}
+
+/// Quotes V3 swaps with the local math and validates against QuoterV2 on chain.
+/// Pools tracked by the PoolStateManager are quoted exactly across ticks; others
+/// fall back to active-tick math, so on-chain results win on divergence.
+pub struct V3Quoter {
+    provider: Arc<Provider<RpcTransport>>,
+    quoter: IQuoterV2<Provider<RpcTransport>>,
+    factory: IV3Factory<Provider<RpcTransport>>,
+    pool_state: Option<Arc<PoolStateManager>>,
+    max_divergence_bps: u64,
+}
+
+// tokenIn (20) | fee (3) | tokenOut (20) | fee (3) | ...
+pub fn encode_path(hops: &[V3Hop]) -> Bytes {
+    let mut path = Vec::with_capacity(20 + hops.len() * 23);
+    for (i, hop) in hops.iter().enumerate() {
+        if i == 0 {
+            path.extend_from_slice(hop.token_in.as_bytes());
+        }
+        path.extend_from_slice(&hop.fee.to_uint24_bytes());
+        path.extend_from_slice(hop.token_out.as_bytes());
+    }
+    Bytes::from(path)
+}
+
+/// Exact-input swap within the current tick: sqrt-price moves along the active
+/// liquidity without crossing initialized ticks. None when an amount overflows.
+pub fn local_amount_out(sqrt_price_x96: U256, liquidity: u128, fee: FeeAmount, amount_in: U256, zero_for_one: bool) -> Option<U256> {
+    let q96 = U256::one() << 96;
+    let liquidity = U256::from(liquidity);
+    if liquidity.is_zero() || sqrt_price_x96.is_zero() {
+        return Some(U256::zero());
+    }
+    let amount_in = amount_in.checked_mul(fee.complement())? / U256::from(1_000_000);
+
+    if zero_for_one {
+        // sqrtP' = L * sqrtP / (L + amountIn * sqrtP / Q96); out = L * (sqrtP - sqrtP') / Q96
+        let denominator = liquidity.checked_add(amount_in.checked_mul(sqrt_price_x96)? / q96)?;
+        let next = liquidity.checked_mul(sqrt_price_x96)? / denominator;
+        Some(liquidity.checked_mul(sqrt_price_x96.checked_sub(next)?)? / q96)
+    } else {
+        // sqrtP' = sqrtP + amountIn * Q96 / L; out = L * Q96 * (sqrtP' - sqrtP) / (sqrtP * sqrtP')
+        let next = sqrt_price_x96.checked_add(amount_in.checked_mul(q96)? / liquidity)?;
+        Some((liquidity.checked_mul(q96)? / sqrt_price_x96).checked_mul(next.checked_sub(sqrt_price_x96)?)? / next)
+    }
+}
+
+impl V3Quoter {
+    pub fn new(provider: Arc<Provider<RpcTransport>>, max_divergence_bps: u64) -> Self {
+        Self {
+            quoter: IQuoterV2::new(QUOTER_V2.parse::<Address>().unwrap(), provider.clone()),
+            factory: IV3Factory::new(UNISWAP_V3_FACTORY.parse::<Address>().unwrap(), provider.clone()),
+            provider,
+            pool_state: None,
+            max_divergence_bps,
+        }
+    }
+
+    pub fn with_pool_state(mut self, pool_state: Arc<PoolStateManager>) -> Self {
+        self.pool_state = Some(pool_state);
+        self
+    }
+
+    pub async fn quote_exact_input(&self, hops: &[V3Hop], amount_in: U256) -> Result<V3Quote> {
+        if hops.is_empty() {
+            return Err(anyhow!("Empty V3 path"));
+        }
+
+        let local = self.local_quote(hops, amount_in).await.ok();
+        let onchain = match self.quoter.quote_exact_input(encode_path(hops), amount_in).call().await {
+            Ok((amount_out, ..)) => Some(amount_out),
+            Err(e) => {
+                debug!("QuoterV2 call failed, using local quote: {:?}", e);
+                None
+            }
+        };
+
+        let divergence_bps = match (local, onchain) {
+            (Some(l), Some(o)) if !o.is_zero() => {
+                let diff = if l > o { l - o } else { o - l };
+                let bps = diff.saturating_mul(U256::from(10_000)) / o;
+                Some(bps.min(U256::from(u64::MAX)).as_u64())
+            }
+            _ => None,
+        };
+
+        if let Some(bps) = divergence_bps {
+            if bps > self.max_divergence_bps {
+                warn!(
+                    "Local V3 quote diverges by {} bps from QuoterV2 ({:?} vs {:?}), using on-chain",
+                    bps, local, onchain
+                );
+            }
+        }
+
+        // Prefer on-chain whenever it's available and local has drifted
+        let (amount_out, source) = match (local, onchain, divergence_bps) {
+            (Some(l), Some(_), Some(bps)) if bps <= self.max_divergence_bps => (l, QuoteSource::Local),
+            (_, Some(o), _) => (o, QuoteSource::OnChain),
+            (Some(l), None, _) => (l, QuoteSource::Local),
+            (None, None, _) => return Err(anyhow!("No V3 quote available")),
+        };
+
+        Ok(V3Quote {
+            amount_out,
+            source,
+            local_amount_out: local,
+            onchain_amount_out: onchain,
+            divergence_bps,
+        })
+    }
+
+    async fn local_quote(&self, hops: &[V3Hop], amount_in: U256) -> Result<U256> {
+        let mut amount = amount_in;
+        for hop in hops {
+            let pool_address = self.factory.get_pool(hop.token_in, hop.token_out, hop.fee.pips()).call().await?;
+            if pool_address == Address::zero() {
+                return Err(anyhow!("No V3 pool for {:?}/{:?} at fee {}", hop.token_in, hop.token_out, hop.fee));
+            }
+
+            if let Some(pool_state) = &self.pool_state {
+                if let Some(out) = pool_state.v3_amount_out(pool_address, hop.token_in, amount).await {
+                    amount = out;
+                    continue;
+                }
+            }
+
+            let pool = IV3PoolState::new(pool_address, self.provider.clone());
+            let (sqrt_price_x96, ..) = pool.slot_0().call().await?;
+            let liquidity = pool.liquidity().call().await?;
+            let token0 = pool.token_0().call().await?;
+
+            amount = local_amount_out(sqrt_price_x96, liquidity, hop.fee, amount, hop.token_in == token0)
+                .ok_or_else(|| anyhow!("V3 quote for {} overflows", amount))?;
+        }
+        Ok(amount)
+    }
+}
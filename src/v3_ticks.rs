@@ -0,0 +1,301 @@
+// src/v3_ticks.rs
+//! Uniswap V3 tick map and exact-input swap math, ported from TickMath,
+//! SqrtPriceMath and SwapMath so local quotes match the pool across ticks.
+use ethers::types::{Address, Log, H256, U256, U512, U64};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::fee::FeeAmount;
+
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+
+fn min_sqrt_ratio() -> U256 {
+    U256::from(4295128739u64)
+}
+
+fn max_sqrt_ratio() -> U256 {
+    U256::from_dec_str("1461446703485210103287273052203988822378723970342").unwrap()
+}
+
+fn q96() -> U256 {
+    U256::one() << 96
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256) -> U256 {
+    let product = a.full_mul(b);
+    U256::try_from(product / U512::from(denominator)).unwrap_or(U256::MAX)
+}
+
+fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> U256 {
+    let product = a.full_mul(b);
+    let denominator = U512::from(denominator);
+    let mut result = product / denominator;
+    if !(product % denominator).is_zero() {
+        result += U512::one();
+    }
+    U256::try_from(result).unwrap_or(U256::MAX)
+}
+
+fn div_rounding_up(a: U256, b: U256) -> U256 {
+    let result = a / b;
+    if (a % b).is_zero() { result } else { result + 1 }
+}
+
+/// TickMath.getSqrtRatioAtTick
+pub fn sqrt_ratio_at_tick(tick: i32) -> U256 {
+    let abs_tick = tick.unsigned_abs();
+    const FACTORS: [(u32, &str); 19] = [
+        (0x2, "fff97272373d413259a46990580e213a"),
+        (0x4, "fff2e50f5f656932ef12357cf3c7fdcc"),
+        (0x8, "ffe5caca7e10e4e61c3624eaa0941cd0"),
+        (0x10, "ffcb9843d60f6159c9db58835c926644"),
+        (0x20, "ff973b41fa98c081472e6896dfb254c0"),
+        (0x40, "ff2ea16466c96a3843ec78b326b52861"),
+        (0x80, "fe5dee046a99a2a811c461f1969c3053"),
+        (0x100, "fcbe86c7900a88aedcffc83b479aa3a4"),
+        (0x200, "f987a7253ac413176f2b074cf7815e54"),
+        (0x400, "f3392b0822b70005940c7a398e4b70f3"),
+        (0x800, "e7159475a2c29b7443b29c7fa6e889d9"),
+        (0x1000, "d097f3bdfd2022b8845ad8f792aa5825"),
+        (0x2000, "a9f746462d870fdf8a65dc1f90e061e5"),
+        (0x4000, "70d869a156d2a1b890bb3df62baf32f7"),
+        (0x8000, "31be135f97d08fd981231505542fcfa6"),
+        (0x10000, "9aa508b5b7a84e1c677de54f3e99bc9"),
+        (0x20000, "5d6af8dedb81196699c329225ee604"),
+        (0x40000, "2216e584f5fa1ea926041bedfe98"),
+        (0x80000, "48a170391f7dc42444e8fa2"),
+    ];
+
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from_str_radix("fffcb933bd6fad37aa2d162d1a594001", 16).unwrap()
+    } else {
+        U256::one() << 128
+    };
+    for (bit, factor) in FACTORS {
+        if abs_tick & bit != 0 {
+            ratio = (ratio * U256::from_str_radix(factor, 16).unwrap()) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Round up so getTickAtSqrtRatio of the result is consistent
+    let remainder = ratio % (U256::one() << 32);
+    (ratio >> 32) + if remainder.is_zero() { U256::zero() } else { U256::one() }
+}
+
+fn amount0_delta(a: U256, b: U256, liquidity: u128, round_up: bool) -> U256 {
+    let (lower, upper) = if a > b { (b, a) } else { (a, b) };
+    let numerator1 = U256::from(liquidity) << 96;
+    let numerator2 = upper - lower;
+    if round_up {
+        div_rounding_up(mul_div_rounding_up(numerator1, numerator2, upper), lower)
+    } else {
+        mul_div(numerator1, numerator2, upper) / lower
+    }
+}
+
+fn amount1_delta(a: U256, b: U256, liquidity: u128, round_up: bool) -> U256 {
+    let (lower, upper) = if a > b { (b, a) } else { (a, b) };
+    if round_up {
+        mul_div_rounding_up(U256::from(liquidity), upper - lower, q96())
+    } else {
+        mul_div(U256::from(liquidity), upper - lower, q96())
+    }
+}
+
+fn next_sqrt_price_from_input(sqrt_price: U256, liquidity: u128, amount_in: U256, zero_for_one: bool) -> U256 {
+    if zero_for_one {
+        // getNextSqrtPriceFromAmount0RoundingUp (add)
+        let numerator1 = U256::from(liquidity) << 96;
+        let (product, overflow) = amount_in.overflowing_mul(sqrt_price);
+        if !overflow {
+            let denominator = numerator1.saturating_add(product);
+            if denominator >= numerator1 && denominator != U256::MAX {
+                return mul_div_rounding_up(numerator1, sqrt_price, denominator);
+            }
+        }
+        div_rounding_up(numerator1, numerator1 / sqrt_price + amount_in)
+    } else {
+        // getNextSqrtPriceFromAmount1RoundingDown (add)
+        let quotient = if amount_in <= (U256::one() << 160) - 1 {
+            (amount_in << 96) / U256::from(liquidity)
+        } else {
+            mul_div(amount_in, q96(), U256::from(liquidity))
+        };
+        sqrt_price + quotient
+    }
+}
+
+/// SwapMath.computeSwapStep for exact input: (next sqrt price, amount in, amount out, fee)
+fn swap_step(current: U256, target: U256, liquidity: u128, remaining: U256, fee_pips: u32) -> (U256, U256, U256, U256) {
+    let zero_for_one = current >= target;
+    let remaining_less_fee = mul_div(remaining, U256::from(1_000_000 - fee_pips), U256::from(1_000_000));
+
+    let max_in = if zero_for_one {
+        amount0_delta(target, current, liquidity, true)
+    } else {
+        amount1_delta(current, target, liquidity, true)
+    };
+    let next = if remaining_less_fee >= max_in {
+        target
+    } else {
+        next_sqrt_price_from_input(current, liquidity, remaining_less_fee, zero_for_one)
+    };
+    let reached = next == target;
+
+    let (amount_in, amount_out) = if zero_for_one {
+        (
+            if reached { max_in } else { amount0_delta(next, current, liquidity, true) },
+            amount1_delta(next, current, liquidity, false),
+        )
+    } else {
+        (
+            if reached { max_in } else { amount1_delta(current, next, liquidity, true) },
+            amount0_delta(current, next, liquidity, false),
+        )
+    };
+    let fee = if !reached {
+        remaining - amount_in
+    } else {
+        mul_div_rounding_up(amount_in, U256::from(fee_pips), U256::from(1_000_000 - fee_pips))
+    };
+    (next, amount_in, amount_out, fee)
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TickInfo {
+    pub liquidity_gross: u128,
+    pub liquidity_net: i128,
+}
+
+/// In-memory copy of a V3 pool's active state and the initialized ticks within
+/// `[range_lower, range_upper]`. Quotes that would leave that range return `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V3PoolState {
+    pub address: Address,
+    pub token0: Address,
+    pub token1: Address,
+    pub fee: FeeAmount,
+    pub tick_spacing: i32,
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    pub liquidity: u128,
+    pub ticks: BTreeMap<i32, TickInfo>,
+    pub range_lower: i32,
+    pub range_upper: i32,
+    pub last_updated_block: U64,
+}
+
+fn topic_i32(topic: &H256) -> i32 {
+    // int24 is sign-extended across the word, the low 4 bytes hold it exactly
+    i32::from_be_bytes(topic.as_bytes()[28..32].try_into().unwrap())
+}
+
+fn word(data: &[u8], index: usize) -> U256 {
+    U256::from_big_endian(&data[index * 32..(index + 1) * 32])
+}
+
+impl V3PoolState {
+    pub fn mint_topic() -> H256 {
+        H256::from(keccak256("Mint(address,address,int24,int24,uint128,uint256,uint256)"))
+    }
+
+    pub fn burn_topic() -> H256 {
+        H256::from(keccak256("Burn(address,int24,int24,uint128,uint256,uint256)"))
+    }
+
+    pub fn swap_topic() -> H256 {
+        H256::from(keccak256("Swap(address,address,int256,int256,uint160,uint128,int24)"))
+    }
+
+    fn update_position(&mut self, lower: i32, upper: i32, delta: i128) {
+        for (tick, net) in [(lower, delta), (upper, -delta)] {
+            let info = self.ticks.entry(tick).or_default();
+            info.liquidity_gross = (info.liquidity_gross as i128 + delta) as u128;
+            info.liquidity_net += net;
+            if info.liquidity_gross == 0 {
+                self.ticks.remove(&tick);
+            }
+        }
+        if lower <= self.tick && self.tick < upper {
+            self.liquidity = (self.liquidity as i128 + delta) as u128;
+        }
+    }
+
+    /// Applies a Mint, Burn or Swap log emitted by this pool.
+    pub fn apply_log(&mut self, log: &Log) {
+        if log.address != self.address || log.topics.is_empty() {
+            return;
+        }
+        let topic = log.topics[0];
+        let data = log.data.as_ref();
+
+        if topic == Self::mint_topic() && log.topics.len() == 4 && data.len() >= 128 {
+            let amount = word(data, 1).as_u128() as i128;
+            self.update_position(topic_i32(&log.topics[2]), topic_i32(&log.topics[3]), amount);
+        } else if topic == Self::burn_topic() && log.topics.len() == 4 && data.len() >= 96 {
+            let amount = word(data, 0).as_u128() as i128;
+            self.update_position(topic_i32(&log.topics[2]), topic_i32(&log.topics[3]), -amount);
+        } else if topic == Self::swap_topic() && data.len() >= 160 {
+            self.sqrt_price_x96 = word(data, 2);
+            self.liquidity = word(data, 3).as_u128();
+            self.tick = i32::from_be_bytes(data[156..160].try_into().unwrap());
+        }
+        if let Some(block) = log.block_number {
+            self.last_updated_block = block;
+        }
+    }
+
+    /// Exact-input swap across initialized ticks, as the pool would execute it.
+    pub fn amount_out(&self, token_in: Address, amount_in: U256) -> Option<U256> {
+        let zero_for_one = token_in == self.token0;
+        let limit = if zero_for_one { min_sqrt_ratio() + 1 } else { max_sqrt_ratio() - 1 };
+
+        let mut remaining = amount_in;
+        let mut amount_out = U256::zero();
+        let mut sqrt_price = self.sqrt_price_x96;
+        let mut tick = self.tick;
+        let mut liquidity = self.liquidity;
+
+        while !remaining.is_zero() && sqrt_price != limit {
+            let next = if zero_for_one {
+                self.ticks.range(..=tick).next_back()
+            } else {
+                self.ticks.range(tick + 1..).next()
+            };
+            let (tick_next, initialized) = match next {
+                Some((t, _)) => (*t, true),
+                // Nothing initialized left in the fetched range; beyond it we're blind
+                None => (if zero_for_one { self.range_lower } else { self.range_upper }, false),
+            };
+            if tick_next < self.range_lower || tick_next > self.range_upper {
+                return None;
+            }
+            let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+
+            let sqrt_next = sqrt_ratio_at_tick(tick_next);
+            let target = if zero_for_one { sqrt_next.max(limit) } else { sqrt_next.min(limit) };
+            let (price, step_in, step_out, fee) = swap_step(sqrt_price, target, liquidity, remaining, self.fee.pips());
+
+            remaining -= step_in + fee;
+            amount_out += step_out;
+            sqrt_price = price;
+
+            if sqrt_price == sqrt_next {
+                if !initialized {
+                    // Ran to the edge of the fetched range with input left over
+                    return None;
+                }
+                let net = self.ticks[&tick_next].liquidity_net;
+                let net = if zero_for_one { -net } else { net };
+                liquidity = (liquidity as i128 + net) as u128;
+                tick = if zero_for_one { tick_next - 1 } else { tick_next };
+            }
+        }
+        Some(amount_out)
+    }
+}
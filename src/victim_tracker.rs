@@ -0,0 +1,131 @@
+// src/victim_tracker.rs
+use anyhow::Result;
+use ethers::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::rpc_budget::RpcTransport;
+
+// Blocks a victim may sit pending before we check whether it still exists
+const DROP_CHECK_AFTER: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VictimStatus {
+    Mined(U64),
+    // Same sender and nonce, higher fee
+    Replaced(H256),
+    // Gone from the mempool, or its nonce was used by a tx we never saw
+    Dropped,
+}
+
+#[derive(Debug, Clone)]
+pub struct VictimEvent {
+    pub tx_hash: H256,
+    pub status: VictimStatus,
+}
+
+#[derive(Debug, Clone)]
+struct Victim {
+    from: Address,
+    nonce: U256,
+    fee: U256,
+    first_seen: U64,
+}
+
+/// Follows the pending txs our opportunities depend on until they are mined,
+/// replaced or dropped, so dependent opportunities can be cancelled.
+pub struct VictimTracker {
+    provider: Arc<Provider<RpcTransport>>,
+    victims: Mutex<HashMap<H256, Victim>>,
+    by_nonce: Mutex<HashMap<(Address, U256), H256>>,
+}
+
+fn fee_of(tx: &Transaction) -> U256 {
+    tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default()
+}
+
+impl VictimTracker {
+    pub fn new(provider: Arc<Provider<RpcTransport>>) -> Self {
+        Self {
+            provider,
+            victims: Mutex::new(HashMap::new()),
+            by_nonce: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn watch(&self, tx: &Transaction, block: U64) {
+        self.victims.lock().await.insert(tx.hash, Victim {
+            from: tx.from,
+            nonce: tx.nonce,
+            fee: fee_of(tx),
+            first_seen: block,
+        });
+        self.by_nonce.lock().await.insert((tx.from, tx.nonce), tx.hash);
+    }
+
+    /// Called for every pending tx: catches replacements of watched victims.
+    pub async fn observe(&self, tx: &Transaction) -> Option<VictimEvent> {
+        let replaced = {
+            let by_nonce = self.by_nonce.lock().await;
+            match by_nonce.get(&(tx.from, tx.nonce)) {
+                Some(hash) if *hash != tx.hash => *hash,
+                _ => return None,
+            }
+        };
+
+        let victim = self.victims.lock().await.get(&replaced).cloned()?;
+        if fee_of(tx) <= victim.fee {
+            // Underpriced replacement; nodes won't accept it
+            return None;
+        }
+
+        info!("Victim {:?} replaced by {:?}", replaced, tx.hash);
+        self.forget(replaced).await;
+        Some(VictimEvent {
+            tx_hash: replaced,
+            status: VictimStatus::Replaced(tx.hash),
+        })
+    }
+
+    pub async fn on_block(&self, block: U64, included: &[H256]) -> Result<Vec<VictimEvent>> {
+        let watched: Vec<(H256, Victim)> = self
+            .victims
+            .lock()
+            .await
+            .iter()
+            .map(|(hash, victim)| (*hash, victim.clone()))
+            .collect();
+
+        let mut events = Vec::new();
+        for (hash, victim) in watched {
+            let status = if included.contains(&hash) {
+                VictimStatus::Mined(block)
+            } else if self.nonce_consumed(&victim).await? {
+                VictimStatus::Dropped
+            } else if block.as_u64() >= victim.first_seen.as_u64() + DROP_CHECK_AFTER
+                && self.provider.get_transaction(hash).await?.is_none()
+            {
+                VictimStatus::Dropped
+            } else {
+                continue;
+            };
+
+            self.forget(hash).await;
+            events.push(VictimEvent { tx_hash: hash, status });
+        }
+        Ok(events)
+    }
+
+    async fn nonce_consumed(&self, victim: &Victim) -> Result<bool> {
+        let next_nonce = self.provider.get_transaction_count(victim.from, None).await?;
+        Ok(next_nonce > victim.nonce)
+    }
+
+    async fn forget(&self, tx_hash: H256) {
+        if let Some(victim) = self.victims.lock().await.remove(&tx_hash) {
+            self.by_nonce.lock().await.remove(&(victim.from, victim.nonce));
+        }
+    }
+}
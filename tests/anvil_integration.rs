@@ -0,0 +1,268 @@
+// tests/anvil_integration.rs
+//
+// End-to-end harness: forks Polygon mainnet in Anvil, deploys FlashLoanArbitrage,
+// runs the bot binary against the fork and injects a whale swap.
+//
+// Requires `anvil` and `solc` on PATH and POLYGON_FORK_URL pointing at an archive
+// capable RPC. Run with: cargo test --test anvil_integration -- --ignored
+use ethers::{
+    prelude::*,
+    solc::Solc,
+    utils::{parse_ether, Anvil, AnvilInstance},
+};
+use polygon_mev_bot::calldata_builder::{self, FlashEntry, Hop, RouterKind};
+use polygon_mev_bot::confidence::ConfidenceModel;
+use polygon_mev_bot::fastlane_integration::FastLaneClient;
+use polygon_mev_bot::flash_loan::LoanLeg;
+use polygon_mev_bot::path_constraints::PathConstraints;
+use polygon_mev_bot::pool_state::PoolStateManager;
+use polygon_mev_bot::retry::RetryPolicy;
+use polygon_mev_bot::rpc_budget::{self, RpcBudget};
+use polygon_mev_bot::simulation_engine::AdvancedSimulationEngine;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+
+const WMATIC: &str = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
+const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+const QUICKSWAP_ROUTER: &str = "0xa5E0829CaCEd8fFDD4De3c43696c57F7D7A678ff";
+const SUSHISWAP_ROUTER: &str = "0x1b02dA8Cb0d097eB8D57A175b88c7D8b47997506";
+const UNISWAP_V3_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+const FASTLANE_CONTRACT: &str = "0x847f82C7E749aBd4E184E3eF65d5bD47b6F49A2D";
+
+abigen!(IWmatic, r#"[
+    function deposit() external payable
+    function approve(address spender, uint256 amount) external returns (bool)
+    function balanceOf(address owner) external view returns (uint256)
+]"#);
+
+abigen!(IQuickswapRouter, r#"[
+    function swapExactTokensForTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external returns (uint256[])
+    function getAmountsOut(uint256 amountIn, address[] path) external view returns (uint256[])
+]"#);
+
+type Client = SignerMiddleware<Provider<Ws>, LocalWallet>;
+
+struct Harness {
+    anvil: AnvilInstance,
+    client: Arc<Client>,
+    executor: Address,
+}
+
+async fn setup(fork_url: &str) -> Harness {
+    let anvil = Anvil::new()
+        .fork(fork_url)
+        .chain_id(137u64)
+        .block_time(2u64)
+        .spawn();
+
+    let provider = Provider::<Ws>::connect(anvil.ws_endpoint()).await.expect("connect to anvil");
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(137u64)));
+
+    let executor = deploy_executor(client.clone()).await;
+    Harness { anvil, client, executor }
+}
+
+async fn deploy_executor(client: Arc<Client>) -> Address {
+    let source = concat!(env!("CARGO_MANIFEST_DIR"), "/contracts/flattened/flashloanarbitrage.sol");
+    let compiled = Solc::default().compile_source(source).expect("compile FlashLoanArbitrage");
+    let (abi, bytecode, _) = compiled
+        .find("FlashLoanArbitrage")
+        .expect("FlashLoanArbitrage artifact")
+        .into_parts_or_default();
+
+    let factory = ContractFactory::new(abi, bytecode, client);
+    let contract = factory
+        .deploy((
+            UNISWAP_V3_ROUTER.parse::<Address>().unwrap(),
+            WMATIC.parse::<Address>().unwrap(),
+            UNISWAP_V3_FACTORY.parse::<Address>().unwrap(),
+        ))
+        .expect("encode constructor")
+        .send()
+        .await
+        .expect("deploy FlashLoanArbitrage");
+    contract.address()
+}
+
+fn spawn_bot(harness: &Harness) -> Child {
+    let key = hex::encode(harness.anvil.keys()[0].to_bytes());
+    Command::new(env!("CARGO_BIN_EXE_polygon-mev-bot"))
+        .env("POLYGON_WS_URL", harness.anvil.ws_endpoint())
+        .env("FLASH_LOAN_CONTRACT", format!("{:?}", harness.executor))
+        .env("ARBITRAGE_EXECUTOR_CONTRACT", format!("{:?}", harness.executor))
+        .env("FASTLANE_RELAY_URL", FASTLANE_CONTRACT)
+        .env("PRIVATE_KEY", key)
+        .env("RUST_LOG", "info")
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("spawn bot binary")
+}
+
+// Wrap native MATIC and dump it into QuickSwap in one large swap
+async fn inject_whale_swap(client: Arc<Client>, amount: U256) -> TxHash {
+    let me = client.address();
+    let wmatic = IWmatic::new(WMATIC.parse::<Address>().unwrap(), client.clone());
+    let router_address: Address = QUICKSWAP_ROUTER.parse().unwrap();
+
+    client
+        .provider()
+        .request::<_, ()>("anvil_setBalance", (me, amount * 2))
+        .await
+        .expect("fund whale");
+    wmatic.deposit().value(amount).send().await.unwrap().await.unwrap();
+    wmatic.approve(router_address, amount).send().await.unwrap().await.unwrap();
+
+    let router = IQuickswapRouter::new(router_address, client);
+    let call = router.swap_exact_tokens_for_tokens(
+        amount,
+        U256::zero(),
+        vec![WMATIC.parse().unwrap(), USDC.parse().unwrap()],
+        me,
+        U256::from(u64::MAX),
+    );
+    let pending = call.send().await.expect("send whale swap");
+    pending.tx_hash()
+}
+
+async fn wait_mined(client: &Client, tx: TxHash) {
+    for _ in 0..30 {
+        if client.get_transaction_receipt(tx).await.unwrap().is_some() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    panic!("{:?} was not mined", tx);
+}
+
+fn v2_hop(router: &str, token_in: &str, token_out: &str, amount_in: U256) -> Hop {
+    Hop {
+        kind: RouterKind::UniswapV2,
+        router: router.parse().unwrap(),
+        pool: Address::zero(),
+        token_in: token_in.parse().unwrap(),
+        token_out: token_out.parse().unwrap(),
+        fee: 3000,
+        amount_in,
+        min_out: U256::zero(),
+    }
+}
+
+async fn wait_for_log(bot: &mut Child, needle: &str, timeout: Duration) -> bool {
+    let stdout = bot.stdout.as_mut().expect("bot stdout");
+    let mut lines = BufReader::new(stdout).lines();
+
+    tokio::time::timeout(timeout, async {
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.contains(needle) {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[tokio::test]
+#[ignore]
+async fn detects_and_executes_whale_swap_backrun() {
+    let fork_url = match std::env::var("POLYGON_FORK_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("POLYGON_FORK_URL not set, skipping");
+            return;
+        }
+    };
+
+    let harness = setup(&fork_url).await;
+    let wmatic = IWmatic::new(WMATIC.parse::<Address>().unwrap(), harness.client.clone());
+    // Profit is paid out to the owner
+    let owner = harness.client.address();
+    let balance_before = wmatic.balance_of(owner).call().await.unwrap();
+
+    let mut bot = spawn_bot(&harness);
+    assert!(
+        wait_for_log(&mut bot, "Starting mempool monitoring", Duration::from_secs(30)).await,
+        "bot did not start"
+    );
+
+    let whale_tx = inject_whale_swap(harness.client.clone(), parse_ether(2_000_000u64).unwrap()).await;
+
+    assert!(
+        wait_for_log(&mut bot, "New arbitrage opportunity found", Duration::from_secs(20)).await,
+        "opportunity from {:?} not detected",
+        whale_tx
+    );
+    assert!(
+        wait_for_log(&mut bot, "Submitted FastLane bundle", Duration::from_secs(20)).await,
+        "opportunity was not executed"
+    );
+
+    // Let the execution get mined
+    tokio::time::sleep(Duration::from_secs(6)).await;
+    let balance_after = wmatic.balance_of(owner).call().await.unwrap();
+    assert!(balance_after > balance_before, "execution was not profitable");
+}
+
+// The preflight has to see what executeGuarded sees: profit paid out to the
+// owner, not left on the executor
+#[tokio::test]
+#[ignore]
+async fn preflight_measures_profit_paid_to_owner() {
+    let fork_url = match std::env::var("POLYGON_FORK_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("POLYGON_FORK_URL not set, skipping");
+            return;
+        }
+    };
+
+    let harness = setup(&fork_url).await;
+    let owner = harness.client.address();
+    // QuickSwap's WMATIC is now cheap against SushiSwap's
+    let whale_tx = inject_whale_swap(harness.client.clone(), parse_ether(2_000_000u64).unwrap()).await;
+    wait_mined(&harness.client, whale_tx).await;
+
+    // Borrow WMATIC, sell it on SushiSwap, buy it back cheaper on QuickSwap
+    let amount_in = parse_ether(5_000u64).unwrap();
+    let sushi = IQuickswapRouter::new(SUSHISWAP_ROUTER.parse::<Address>().unwrap(), harness.client.clone());
+    let usdc_out = sushi
+        .get_amounts_out(amount_in, vec![WMATIC.parse().unwrap(), USDC.parse().unwrap()])
+        .call()
+        .await
+        .unwrap()[1];
+    let hops = [
+        v2_hop(SUSHISWAP_ROUTER, WMATIC, USDC, amount_in),
+        v2_hop(QUICKSWAP_ROUTER, USDC, WMATIC, usdc_out),
+    ];
+    let loans = vec![LoanLeg::new(WMATIC.parse().unwrap(), amount_in)];
+    let execution = calldata_builder::encode(&FlashEntry::Balancer { loans }, &hops).unwrap();
+
+    let provider = Arc::new(
+        rpc_budget::connect(&harness.anvil.ws_endpoint(), RpcBudget::default(), RetryPolicy::default())
+            .await
+            .unwrap(),
+    );
+    let head = provider.get_block_number().await.unwrap();
+    let calldata = FastLaneClient::new(provider.clone(), FASTLANE_CONTRACT.parse().unwrap(), harness.executor)
+        .guarded_calldata(execution, WMATIC.parse().unwrap(), U256::one(), head + 5)
+        .unwrap();
+    let engine = AdvancedSimulationEngine::new(
+        provider.clone(),
+        Arc::new(PoolStateManager::new(provider.clone(), Vec::new())),
+        Arc::new(ConfidenceModel::default()),
+        PathConstraints::default(),
+    );
+
+    let preflight = engine
+        .preflight_execution(owner, harness.executor, calldata, WMATIC.parse().unwrap(), head)
+        .await
+        .expect("profitable path reverted in the preflight");
+    assert!(preflight.delta > I256::zero(), "owner delta {} for a profitable path", preflight.delta);
+}